@@ -2,18 +2,21 @@
 //!
 //! These functions are exposed to the frontend via Tauri's IPC mechanism.
 
+use crate::error::{ErrorCode, ZureshotError};
 use crate::platform;
-use crate::platform::{CaptureRegion, RecordingQuality, StartRecordingConfig};
+use crate::platform::{
+    CaptureRegion, EmptyAudioBehavior, ImageFormat, MicDeniedBehavior, PixelFormat, RecordingQuality, StartRecordingConfig,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
 use tauri::{AppHandle, Emitter, Manager, WebviewWindowBuilder, WebviewUrl};
 
-/// Recording state shared across commands
-pub struct RecordingState {
-    /// Platform-specific recording handle (owns stream, encoder, etc.)
-    pub handle: Option<platform::imp::RecordingHandle>,
+/// One in-progress recording
+pub struct RecordingSession {
+    /// Platform-specific recording handle (owns stream, encoder, etc.).
+    /// Shared so background watchers can block on it without the state lock.
+    pub handle: Option<std::sync::Arc<platform::imp::RecordingHandle>>,
     pub output_path: Option<String>,
-    pub is_recording: bool,
     pub is_paused: bool,
     pub start_time: Option<std::time::Instant>,
     /// Accumulated pause duration (subtracted from wall-clock elapsed)
@@ -24,33 +27,139 @@ pub struct RecordingState {
     pub quality: RecordingQuality,
     /// Output format: "video" (MP4) or "gif" (record MP4, convert to GIF on stop)
     pub output_format: String,
+    /// Chapter markers dropped during recording (written at finalize)
+    pub chapters: Vec<ChapterMarker>,
+    /// Bookmarks (marker + still) dropped during recording
+    pub bookmarks: Vec<Bookmark>,
+    pub cursor_capture: platform::CursorCapture,
+    /// Cursor drawn at all (`show_cursor` setting at start)
+    pub show_cursor: bool,
+    /// Output must be constant frame rate (macOS: re-timed on stop)
+    pub force_cfr: bool,
+    /// Capture frame-rate ceiling (preset capped by `max_fps`), the rate a
+    /// CFR re-time targets
+    pub max_fps: i32,
+    /// GIF conversion settings at start (used when `output_format` is "gif")
+    pub gif_options: GifOptions,
+    /// Earlier recording this one continues (`append_recording`): on stop
+    /// the new segment is joined onto it and it becomes the output
+    pub append_to: Option<String>,
+    /// Seconds cut off the start / end on stop (`trim_head_secs` /
+    /// `trim_tail_secs` at start, 0 = keep everything)
+    pub trim_head_secs: f64,
+    pub trim_tail_secs: f64,
+    /// Target and settings it started with, saved next to the file on stop
+    /// (`<stem>.recording.json`) so `append_recording` can continue it alike
+    pub capture: LastRecording,
+    /// Limit shown in the recording bar (seconds, 0 = unlimited)
+    pub max_duration_secs: f64,
+    /// Recorded time when mouse tracking started: 0, or later for a
+    /// recording promoted to primary when the previous primary stopped
+    pub mouse_track_offset_secs: f64,
 }
 
-impl Default for RecordingState {
-    fn default() -> Self {
-        Self {
-            handle: None,
-            output_path: None,
-            is_recording: false,
-            is_paused: false,
-            start_time: None,
-            pause_accumulated: std::time::Duration::ZERO,
-            pause_start: None,
-            region: None,
-            quality: RecordingQuality::Standard,
-            output_format: "video".to_string(),
-        }
+impl RecordingSession {
+    /// Recorded time so far: wall-clock elapsed minus all pauses.
+    pub fn elapsed_secs(&self) -> f64 {
+        self.start_time
+            .map(|t| {
+                let wall = t.elapsed();
+                let paused = self.pause_accumulated
+                    + self.pause_start.map(|ps| ps.elapsed()).unwrap_or_default();
+                wall.saturating_sub(paused).as_secs_f64()
+            })
+            .unwrap_or(0.0)
     }
 }
 
-// SAFETY: RecordingState contains platform-specific objects that are thread-safe.
+// SAFETY: RecordingSession contains platform-specific objects that are thread-safe.
 // We wrap it in a Mutex for interior mutability.
-unsafe impl Send for RecordingState {}
-unsafe impl Sync for RecordingState {}
+unsafe impl Send for RecordingSession {}
+unsafe impl Sync for RecordingSession {}
+
+/// All in-progress recordings, keyed by capture target (see `recording_id_for`).
+///
+/// Several recordings can run at once on different displays/windows. The
+/// first one started is the *primary* recording: it owns the recording bar,
+/// overlay, tray state, hotkeys and mouse track. Commands that take an
+/// optional `recording_id` operate on the primary recording when it's `None`.
+#[derive(Default)]
+pub struct RecordingState {
+    pub sessions: std::collections::HashMap<String, RecordingSession>,
+    pub primary: Option<String>,
+}
+
+impl RecordingState {
+    /// Whether any recording is in progress.
+    pub fn is_recording(&self) -> bool {
+        !self.sessions.is_empty()
+    }
+
+    /// Resolve `id` (or the primary recording when `None`) to a live session id.
+    pub fn resolve(&self, id: Option<&str>) -> Option<String> {
+        match id {
+            Some(id) => self.sessions.contains_key(id).then(|| id.to_string()),
+            None => self.primary.clone(),
+        }
+    }
+
+    pub fn get(&self, id: Option<&str>) -> Option<&RecordingSession> {
+        self.sessions.get(&self.resolve(id)?)
+    }
+
+    pub fn get_mut(&mut self, id: Option<&str>) -> Option<&mut RecordingSession> {
+        let id = self.resolve(id)?;
+        self.sessions.get_mut(&id)
+    }
+
+    /// The primary recording, if any.
+    pub fn primary(&self) -> Option<&RecordingSession> {
+        self.get(None)
+    }
+}
+
+/// Recording id for a capture target: `window-<id>` or `display-<id>`
+/// (`ALL_DISPLAYS_RECORDING_ID` for whole-desktop recordings). At most one
+/// recording runs per target, so no display means the main display's id —
+/// `display-main` only where displays can't be listed.
+fn recording_id_for(display_id: Option<u32>, window_id: Option<u32>) -> String {
+    let main_display = || {
+        platform::imp::list_displays()
+            .ok()
+            .and_then(|ds| ds.into_iter().find(|d| d.is_main))
+            .map(|d| d.id)
+    };
+    match (window_id, display_id) {
+        (Some(w), _) => format!("window-{}", w),
+        (None, Some(d)) => format!("display-{}", d),
+        (None, None) => match main_display() {
+            Some(d) => format!("display-{}", d),
+            None => "display-main".to_string(),
+        },
+    }
+}
+
+/// Recording id of a whole-desktop (all displays) recording.
+const ALL_DISPLAYS_RECORDING_ID: &str = "all-displays";
+
+/// A chapter marker on the recording timeline
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChapterMarker {
+    pub time_secs: f64,
+    pub label: String,
+}
+
+/// A bookmarked moment: timestamp plus a PNG still of the recorded area
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub time_secs: f64,
+    pub still_path: String,
+}
 
 /// Recording status sent to frontend
 #[derive(Clone, Serialize, Deserialize)]
 pub struct RecordingStatus {
+    pub recording_id: Option<String>,
     pub is_recording: bool,
     pub is_paused: bool,
     pub duration_secs: f64,
@@ -61,19 +170,127 @@ pub struct RecordingStatus {
 /// Result of stopping a recording
 #[derive(Clone, Serialize, Deserialize)]
 pub struct RecordingResult {
+    pub recording_id: String,
     pub path: String,
     pub duration_secs: f64,
     pub file_size_bytes: u64,
+    /// Scene-split parts written next to the full recording (empty if
+    /// scene segmentation is off or no scene change was detected).
+    pub segments: Vec<String>,
+    /// True when every frame has the same duration (CFR); false for the
+    /// variable frame rate ScreenCaptureKit produces natively.
+    pub constant_frame_rate: bool,
+    /// Video frames written to the file
+    pub frame_count: u64,
+    /// Video frames dropped (writer not ready, invalid buffer, append
+    /// failed). Always 0 on Linux: videorate's drop/dup counters only
+    /// reflect frame-rate conversion, not lost frames
+    pub dropped_count: u64,
+    /// Video frames skipped because their timestamp went backwards
+    /// (always 0 on Linux, where videorate smooths these over)
+    pub pts_skip_count: u64,
 }
 
-/// GIF recording constraints (industry standard, matching CleanShot X)
+/// Default GIF recording constraints (industry standard, matching CleanShot X)
 const GIF_MAX_DURATION_SECS: f64 = 30.0;
 const GIF_MAX_WIDTH: usize = 640;
 const GIF_FPS: u32 = 15;
 
+/// GIF conversion settings (persisted as `gif_options`). The defaults are
+/// the original fixed 15 fps / 640 px / 30 s pipeline.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GifOptions {
+    pub fps: u32,
+    /// Frames wider than this are scaled down (never up)
+    pub max_width: usize,
+    /// Recording cap in seconds, which is also the longest GIF written
+    pub max_duration: f64,
+    /// ffmpeg `-loop`: 0 = forever, -1 = play once, N = N extra loops.
+    /// `None` = forever.
+    pub loop_count: Option<i32>,
+    /// Seconds of dead time cut from the start before conversion
+    pub start_offset_secs: Option<f64>,
+    /// Fade to black over the GIF's last N seconds
+    pub fade_out_secs: Option<f64>,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            fps: GIF_FPS,
+            max_width: GIF_MAX_WIDTH,
+            max_duration: GIF_MAX_DURATION_SECS,
+            loop_count: None,
+            start_offset_secs: None,
+            fade_out_secs: None,
+        }
+    }
+}
+
+impl GifOptions {
+    fn validate(&self) -> Result<(), String> {
+        if !(1..=50).contains(&self.fps) {
+            return Err(format!("GIF fps must be between 1 and 50 (got {})", self.fps));
+        }
+        if self.max_width < 16 {
+            return Err(format!("GIF max width must be at least 16 px (got {})", self.max_width));
+        }
+        if !self.max_duration.is_finite() || self.max_duration <= 0.0 {
+            return Err(format!("GIF max duration must be positive (got {})", self.max_duration));
+        }
+        if self.loop_count.is_some_and(|n| n < -1) {
+            return Err("GIF loop count must be -1 (once), 0 (forever) or more".to_string());
+        }
+        for (name, value) in [("start offset", self.start_offset_secs), ("fade-out", self.fade_out_secs)] {
+            if value.is_some_and(|v| !v.is_finite() || v < 0.0) {
+                return Err(format!("GIF {} must be 0 or more seconds", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// `-vf` graph for a GIF `length` seconds long: resample, scale,
+    /// optional fade, then a two-pass palette.
+    fn filter(&self, length: f64) -> String {
+        let mut filters = vec![
+            format!("fps={}", self.fps),
+            format!("scale='min({},iw)':-1:flags=lanczos", self.max_width),
+        ];
+        if let Some(fade) = self.fade_out_secs.filter(|f| *f > 0.0) {
+            let fade = fade.min(length);
+            filters.push(format!("fade=t=out:st={:.3}:d={:.3}", (length - fade).max(0.0), fade));
+        }
+        format!("{},split[s0][s1];[s0]palettegen[p];[s1][p]paletteuse", filters.join(","))
+    }
+}
+
+/// GIF conversion settings for the next GIF recording.
+pub fn gif_options(app: &AppHandle) -> GifOptions {
+    crate::settings::get::<GifOptions>(app, "gif_options")
+        .filter(|o| o.validate().is_ok())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn get_gif_options(app: AppHandle) -> GifOptions {
+    gif_options(&app)
+}
+
+/// Change GIF frame rate, width, length cap, looping, start trim and
+/// fade-out (persisted, applies to the next GIF recording).
+#[tauri::command]
+pub fn set_gif_options(app: AppHandle, options: GifOptions) -> Result<(), ZureshotError> {
+    options.validate()?;
+    crate::settings::set(&app, "gif_options", serde_json::json!(options));
+    println!("[zureshot] GIF options: {:?}", options);
+    Ok(())
+}
+
 /// Payload emitted with `recording-started` event
 #[derive(Clone, Serialize, Deserialize)]
 pub struct RecordingStartedPayload {
+    pub recording_id: String,
     pub path: String,
     pub region: Option<CaptureRegion>,
     pub format: String,
@@ -81,38 +298,198 @@ pub struct RecordingStartedPayload {
     pub max_duration: f64,
 }
 
+/// Camera bubble options remembered with the last recording.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CameraOptions {
+    pub shape: String,
+    pub size: String,
+    pub device_id: Option<String>,
+}
+
+/// Everything needed to start the same recording again
+/// (`repeat_last_recording`), persisted as `last_recording` in settings and
+/// per recording as its `<stem>.recording.json` sidecar (`append_recording`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LastRecording {
+    pub region: Option<CaptureRegion>,
+    pub window_id: Option<u32>,
+    pub display_id: Option<u32>,
+    pub quality: RecordingQuality,
+    pub system_audio: bool,
+    pub microphone: bool,
+    pub output_format: Option<String>,
+    #[serde(default)]
+    pub camera: Option<CameraOptions>,
+    #[serde(default)]
+    pub all_displays: bool,
+}
+
+/// Corner of the recorded area the webcam bubble sits in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebcamCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// Webcam picture-in-picture, persisted as `webcam`: the camera bubble is
+/// opened in `corner` of every recording that doesn't pick its own camera
+/// options. The bubble is a normal window, so the capture composites it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebcamConfig {
+    /// Camera unique ID (`list_native_camera_devices`); `None` = last used
+    #[serde(default)]
+    pub device_id: Option<String>,
+    #[serde(default)]
+    pub corner: WebcamCorner,
+    /// "small", "medium", "large" or "huge"
+    #[serde(default = "default_webcam_size")]
+    pub size: String,
+    /// "circle", "square", "rectangle" or "vertical"
+    #[serde(default = "default_webcam_shape")]
+    pub shape: String,
+}
+
+fn default_webcam_size() -> String {
+    "medium".to_string()
+}
+
+fn default_webcam_shape() -> String {
+    "circle".to_string()
+}
+
+pub fn webcam(app: &AppHandle) -> Option<WebcamConfig> {
+    crate::settings::get(app, "webcam")
+}
+
+#[tauri::command]
+pub fn get_webcam(app: AppHandle) -> Option<WebcamConfig> {
+    webcam(&app)
+}
+
+/// Set (or clear with `None`) the webcam overlay for future recordings.
+#[tauri::command]
+pub fn set_webcam(app: AppHandle, config: Option<WebcamConfig>) -> Result<(), ZureshotError> {
+    if let Some(ref c) = config {
+        if !["small", "medium", "large", "huge"].contains(&c.size.as_str()) {
+            return Err(format!("Invalid webcam size: {}", c.size).into());
+        }
+        if !["circle", "square", "rectangle", "vertical"].contains(&c.shape.as_str()) {
+            return Err(format!("Invalid webcam shape: {}", c.shape).into());
+        }
+    }
+    crate::settings::set(&app, "webcam", serde_json::json!(config));
+    println!("[zureshot] Webcam overlay: {:?}", config);
+    Ok(())
+}
+
+/// Attach the camera bubble options to the remembered last recording.
+fn remember_last_camera(app: &AppHandle, camera: Option<CameraOptions>) {
+    if let Some(mut last) = crate::settings::get::<LastRecording>(app, "last_recording") {
+        last.camera = camera;
+        crate::settings::set(app, "last_recording", serde_json::json!(last));
+    }
+}
+
 /// Core logic to start recording (called from both tray and commands)
+///
+/// Returns the output path. The recording is keyed by its capture target
+/// (`recording_id_for`); starting a second recording on a busy target fails.
 pub fn do_start_recording(
     app: &AppHandle,
     output_path: Option<String>,
+    output_dir: Option<String>,
     region: Option<CaptureRegion>,
+    window_id: Option<u32>,
+    display_id: Option<u32>,
+    all_displays: bool,
     quality: RecordingQuality,
     capture_system_audio: bool,
     capture_microphone: bool,
     output_format: Option<String>,
-) -> Result<String, String> {
+) -> Result<String, ZureshotError> {
+    // Before taking the state lock: this may wait for the permission prompt
+    let capture_microphone = capture_microphone && check_microphone_access(app)?;
+    let audio_only = output_format.as_deref() == Some("audio");
+    if audio_only && !capture_system_audio && !capture_microphone {
+        return Err(ZureshotError::new(
+            ErrorCode::InvalidInput,
+            "Audio-only recording needs system audio or the microphone",
+        ));
+    }
+
     let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
-    let mut recording = state.lock().map_err(|e: std::sync::PoisonError<_>| e.to_string())?;
+    let mut recordings = state.lock().map_err(|e: std::sync::PoisonError<_>| e.to_string())?;
 
-    if recording.is_recording {
-        return Err("Recording already in progress".to_string());
+    let recording_id = if all_displays {
+        ALL_DISPLAYS_RECORDING_ID.to_string()
+    } else {
+        recording_id_for(display_id, window_id)
+    };
+    if recordings.sessions.contains_key(&recording_id) {
+        return Err(ZureshotError::new(ErrorCode::AlreadyRecording, "Recording already in progress"));
+    }
+    let is_primary = recordings.primary.is_none();
+
+    // Reject/clip regions that don't fit the target display (bad sourceRect
+    // makes SCK fail or record garbage)
+    let region = match (region, display_bounds(app, display_id)) {
+        (Some(rgn), Some(bounds)) => {
+            let valid = platform::validate_region(&rgn, &bounds)?;
+            if (valid.x, valid.y, valid.width, valid.height) != (rgn.x, rgn.y, rgn.width, rgn.height) {
+                println!(
+                    "[zureshot] Region {}x{} at ({}, {}) clipped to display: {}x{} at ({}, {})",
+                    rgn.width, rgn.height, rgn.x, rgn.y, valid.width, valid.height, valid.x, valid.y
+                );
+            }
+            Some(valid)
+        }
+        (region, _) => region,
+    };
+    if let Some(ref rgn) = region {
+        let min = min_region_size(app);
+        if rgn.width < min || rgn.height < min {
+            return Err(ZureshotError::new(
+                ErrorCode::InvalidInput,
+                format!(
+                    "Region {}x{} is smaller than the minimum recording size {}x{}; select a larger area",
+                    rgn.width, rgn.height, min, min
+                ),
+            ));
+        }
     }
 
+    let fmt = output_format.unwrap_or_else(|| default_format(app));
+    // The GIF intermediate is always a small HEVC MP4
+    let codec = if fmt == "gif" { platform::VideoCodec::Hevc } else { video_codec(app) };
+
     // Generate output path if not provided
     let path = output_path.unwrap_or_else(|| {
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        let base = dirs::download_dir()
-            .or_else(dirs::home_dir)
-            .unwrap_or_else(|| std::path::PathBuf::from("."));
-        let zureshot_dir = base.join("Zureshot");
-        let _ = std::fs::create_dir_all(&zureshot_dir);
-        zureshot_dir
-            .join(format!("zureshot_{}.mp4", timestamp))
-            .to_string_lossy()
-            .to_string()
+        let zureshot_dir = output_dir_or_default(app, output_dir.as_deref());
+        let ext = if audio_only { "m4a" } else { codec.file_extension() };
+        let stem = capture_file_stem(app, CaptureKind::Recording);
+        let stem = if is_primary { stem } else { format!("{}_{}", stem, recording_id) };
+        unique_capture_path(&zureshot_dir, "", &stem, ext).to_string_lossy().to_string()
     });
 
-    println!("[zureshot] Starting recording to: {}", path);
+    println!("[zureshot] Starting recording {} to: {}", recording_id, path);
+
+    // Standard-size cursor needs the mouse track, which only the primary has
+    // (and it's relative to the main display, not the whole desktop)
+    let cursor_capture = if is_primary && !all_displays {
+        crate::settings::get(app, "cursor_capture").unwrap_or_default()
+    } else {
+        platform::CursorCapture::System
+    };
+    let show_cursor = show_cursor(app);
+
+    let force_cfr = crate::settings::get_bool(app, "force_cfr", false);
+    let gif = gif_options(app);
+    let frame_rate_bounds = frame_rate_bounds(app);
+    let fps = recording_fps(app);
 
     // Delegate all platform-specific setup to the platform layer
     let config = StartRecordingConfig {
@@ -121,39 +498,131 @@ pub fn do_start_recording(
         quality,
         capture_system_audio,
         capture_microphone,
+        hide_menu_bar: crate::settings::get_bool(app, "hide_menu_bar", false),
+        scene_threshold: scene_threshold_setting(app),
+        empty_audio: crate::settings::get(app, "empty_audio_behavior").unwrap_or_default(),
+        window_id,
+        display_id,
+        pixel_format: crate::settings::get(app, "pixel_format").unwrap_or_default(),
+        compatibility_mode: crate::settings::get_bool(app, "compatibility_mode", false),
+        match_display_color: crate::settings::get_bool(app, "match_display_color", false),
+        color_space: crate::settings::get(app, "color_space"),
+        write_timestamps: crate::settings::get_bool(app, "write_timestamps", false),
+        power_mode: crate::settings::get(app, "power_mode").unwrap_or_default(),
+        cursor_capture,
+        show_cursor,
+        // GIFs are scaled to their max width on stop — don't capture more
+        max_width: (fmt == "gif").then_some(gif.max_width),
+        warmup_secs: warmup_secs(app),
+        force_cfr,
+        keyframe_interval_secs: keyframe_interval_secs(app),
+        fps,
+        frame_rate_bounds,
+        bitrate_override_bps: bitrate_override_bps(app),
+        quality_factor: quality_factor(app),
+        rotation: crate::settings::get(app, "rotation"),
+        codec,
+        // GIFs have their own cap (GifOptions::max_duration)
+        max_duration_secs: if fmt == "gif" { None } else { max_duration_secs(app) },
+        // The GIF intermediate is converted on stop; checkpoints don't help it
+        checkpoint_interval_secs: if fmt == "gif" { None } else { checkpoint_interval_secs(app) },
+        microphone_device_id: if capture_microphone { microphone_device(app) } else { None },
+        crash_resilient: crash_resilient(app),
+        audio_only,
+        all_displays,
     };
+    let max_duration = config.max_duration_secs;
+    let checkpoint_interval = config.checkpoint_interval_secs;
     let handle = platform::imp::start_recording(app, config)?;
 
-    // Update state
-    recording.handle = Some(handle);
-    recording.output_path = Some(path.clone());
-    recording.is_recording = true;
-    recording.is_paused = false;
-    recording.start_time = Some(std::time::Instant::now());
-    recording.pause_accumulated = std::time::Duration::ZERO;
-    recording.pause_start = None;
-    recording.region = region.clone();
-    recording.quality = quality;
-    recording.output_format = output_format.unwrap_or_else(|| "video".to_string());
-
-    // Start mouse tracking for editor auto-zoom (macOS only)
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(tracker_state) = app.try_state::<Mutex<platform::macos::mouse_tracker::MouseTrackerState>>() {
-            if let Ok(tracker) = tracker_state.lock() {
-                platform::macos::mouse_tracker::start_mouse_tracking(&tracker);
-                println!("[zureshot] Mouse tracking started for auto-zoom");
+    let capture = LastRecording {
+        region: region.clone(),
+        window_id,
+        display_id,
+        quality,
+        system_audio: capture_system_audio,
+        microphone: capture_microphone,
+        output_format: Some(fmt.clone()),
+        camera: None,
+        all_displays,
+    };
+
+    let max_dur = if fmt == "gif" { gif.max_duration } else { max_duration.unwrap_or(0.0) };
+    recordings.sessions.insert(
+        recording_id.clone(),
+        RecordingSession {
+            handle: Some(std::sync::Arc::new(handle)),
+            output_path: Some(path.clone()),
+            is_paused: false,
+            start_time: Some(std::time::Instant::now()),
+            pause_accumulated: std::time::Duration::ZERO,
+            pause_start: None,
+            region: region.clone(),
+            quality,
+            output_format: fmt.clone(),
+            chapters: Vec::new(),
+            bookmarks: Vec::new(),
+            cursor_capture,
+            show_cursor,
+            force_cfr,
+            max_fps: frame_rate_bounds.max_for(fps.unwrap_or_else(|| quality.fps())),
+            gif_options: gif.clone(),
+            append_to: None,
+            trim_head_secs: trim_secs(app, "trim_head_secs"),
+            trim_tail_secs: trim_secs(app, "trim_tail_secs"),
+            capture: capture.clone(),
+            max_duration_secs: max_dur,
+            mouse_track_offset_secs: 0.0,
+        },
+    );
+
+    // UI, hotkeys and mouse tracking follow the primary recording only
+    if is_primary {
+        recordings.primary = Some(recording_id.clone());
+
+        crate::settings::set(app, "last_recording", serde_json::json!(capture));
+
+        // Start mouse tracking for editor auto-zoom (macOS only)
+        #[cfg(target_os = "macos")]
+        if !audio_only {
+            if let Some(tracker_state) = app.try_state::<Mutex<platform::macos::mouse_tracker::MouseTrackerState>>() {
+                if let Ok(tracker) = tracker_state.lock() {
+                    platform::macos::mouse_tracker::start_mouse_tracking(&tracker);
+                    println!("[zureshot] Mouse tracking started for auto-zoom");
+                }
             }
         }
+
+        register_recording_shortcuts(app);
+
+        if capture_system_audio && mute_ui_sounds(app) {
+            mute_ui_sounds_now(app);
+        }
+    }
+
+    if let Some(id) = window_id {
+        if keep_window_in_front(app) {
+            spawn_keep_window_in_front(app, recording_id.clone(), path.clone(), id);
+        }
+    }
+    #[cfg(target_os = "macos")]
+    if window_id.is_none() && !audio_only {
+        spawn_exclusion_watch(app, recording_id.clone(), path.clone(), display_id);
+    }
+    if let Some(limit) = max_duration {
+        spawn_auto_stop(app, recording_id.clone(), path.clone(), limit);
     }
+    if let Some(interval) = checkpoint_interval {
+        spawn_checkpoints(app, recording_id.clone(), path.clone(), interval);
+    }
+    spawn_recording_stats(app, recording_id.clone(), path.clone());
 
     // Switch tray icon to recording state (red dot + Stop enabled)
     crate::tray::notify_recording_started(app);
 
     // Emit event to frontend with region info and format
-    let fmt = recording.output_format.clone();
-    let max_dur = if fmt == "gif" { GIF_MAX_DURATION_SECS } else { 0.0 };
     let payload = RecordingStartedPayload {
+        recording_id,
         path: path.clone(),
         region,
         format: fmt,
@@ -164,81 +633,295 @@ pub fn do_start_recording(
     Ok(path)
 }
 
-/// Core logic to stop recording (called from both tray and commands)
-pub fn do_stop_recording(app: &AppHandle) -> Result<RecordingResult, String> {
-    // Extract all recording state while holding the mutex, then release it
-    // BEFORE any blocking operations.
-    let (handle, output_path, duration, output_format) = {
-        let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
-        let mut recording = state.lock().map_err(|e: std::sync::PoisonError<_>| e.to_string())?;
+/// Remove a recording's session from `RecordingState` (the primary one
+/// when `recording_id` is None), handing the primary role on if it had it.
+/// Returns the id, the session, whether it was primary and whether other
+/// recordings are still running. The state mutex is released on return, so
+/// the caller can block on stopping the capture.
+fn take_session(
+    app: &AppHandle,
+    recording_id: Option<&str>,
+) -> Result<(String, RecordingSession, bool, bool), ZureshotError> {
+    let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+    let mut recordings = state.lock().map_err(|e: std::sync::PoisonError<_>| e.to_string())?;
+
+    let id = recordings.resolve(recording_id).ok_or_else(ZureshotError::not_recording)?;
+    let session = recordings.sessions.remove(&id).ok_or_else(ZureshotError::not_recording)?;
+
+    let was_primary = recordings.primary.as_deref() == Some(id.as_str());
+    if was_primary {
+        // Remaining recordings (if any) keep running headless; the oldest
+        // takes over (not whichever the HashMap happens to yield first)
+        recordings.primary = recordings
+            .sessions
+            .iter()
+            .min_by_key(|&(id, session)| (session.start_time, id))
+            .map(|(id, _)| id.clone());
+    }
+    Ok((id, session, was_primary, recordings.is_recording()))
+}
 
-        if !recording.is_recording {
-            return Err("No recording in progress".to_string());
+/// Give the recording promoted to primary (`take_session`) what the
+/// primary gets at start: mouse tracking, the recording hotkeys, the bar
+/// and overlays, and a `recording-started` event for them.
+fn attach_primary_controls(app: &AppHandle) {
+    let payload = {
+        let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+        let Ok(mut recordings) = state.lock() else { return };
+        let Some(id) = recordings.primary.clone() else { return };
+        let Some(session) = recordings.sessions.get_mut(&id) else { return };
+        session.mouse_track_offset_secs = session.elapsed_secs();
+        RecordingStartedPayload {
+            recording_id: id,
+            path: session.output_path.clone().unwrap_or_default(),
+            region: session.region.clone(),
+            format: session.output_format.clone(),
+            max_duration: session.max_duration_secs,
         }
+    };
+    println!("[zureshot] Recording {} is now the primary recording", payload.recording_id);
 
-        let duration = recording
-            .start_time
-            .map(|t: std::time::Instant| {
-                let wall = t.elapsed();
-                let paused = recording.pause_accumulated
-                    + recording.pause_start.map(|ps| ps.elapsed()).unwrap_or_default();
-                (wall - paused).as_secs_f64()
-            })
-            .unwrap_or(0.0);
-
-        let handle = recording.handle.take();
-        let output_path = recording.output_path.take().unwrap_or_default();
-        let output_format = std::mem::replace(&mut recording.output_format, "video".to_string());
-        recording.is_recording = false;
-        recording.is_paused = false;
-        recording.start_time = None;
-        recording.pause_accumulated = std::time::Duration::ZERO;
-        recording.pause_start = None;
-        recording.region = None;
-        recording.quality = RecordingQuality::Standard;
-
-        (handle, output_path, duration, output_format)
-    }; // ← mutex released here
-
-    println!("[zureshot] Stopping recording after {:.1}s", duration);
-
-    // Stop mouse tracking and save track data
     #[cfg(target_os = "macos")]
-    {
+    if payload.format != "audio" {
         if let Some(tracker_state) = app.try_state::<Mutex<platform::macos::mouse_tracker::MouseTrackerState>>() {
             if let Ok(tracker) = tracker_state.lock() {
-                let track = platform::macos::mouse_tracker::stop_mouse_tracking(&tracker);
-                if !track.samples.is_empty() {
-                    let _ = platform::macos::mouse_tracker::save_mouse_track(&output_path, &track);
-                }
+                platform::macos::mouse_tracker::start_mouse_tracking(&tracker);
             }
         }
     }
+    register_recording_shortcuts(app);
 
-    // Close the recording bar and dim overlay windows
-    if let Some(win) = app.get_webview_window("recording-bar") {
-        let _ = win.destroy();
+    let capture = primary_capture(app);
+    let (region, display_id) = capture.map(|c| (c.region, c.display_id)).unwrap_or_default();
+    open_recording_windows(app, region.as_ref(), display_id, None);
+    let _ = app.emit("recording-started", &payload);
+}
+
+/// The global logical rect a recording shows, for normalizing its mouse
+/// track and keeping UI off it. `None` for window recordings (the window
+/// moves) and audio-only ones.
+fn capture_rect(capture: &LastRecording) -> Option<CaptureRegion> {
+    if capture.window_id.is_some() || capture.output_format.as_deref() == Some("audio") {
+        return None;
     }
-    if let Some(win) = app.get_webview_window("recording-overlay") {
-        let _ = win.destroy();
+    let displays = platform::imp::list_displays().ok()?;
+    if capture.all_displays {
+        let left = displays.iter().map(|d| d.x).fold(f64::INFINITY, f64::min);
+        let top = displays.iter().map(|d| d.y).fold(f64::INFINITY, f64::min);
+        let right = displays.iter().map(|d| d.x + d.width as f64).fold(f64::NEG_INFINITY, f64::max);
+        let bottom = displays.iter().map(|d| d.y + d.height as f64).fold(f64::NEG_INFINITY, f64::max);
+        return (!displays.is_empty()).then(|| CaptureRegion { x: left, y: top, width: right - left, height: bottom - top });
     }
-    // Close camera bubble if open
-    if let Some(win) = app.get_webview_window("camera-overlay") {
-        let _ = app.emit("camera-overlay-close", ());
-        let _ = win.destroy();
+    let display = displays
+        .iter()
+        .find(|d| capture.display_id.map_or(d.is_main, |id| d.id == id))?;
+    Some(match capture.region {
+        Some(ref r) => CaptureRegion { x: display.x + r.x, y: display.y + r.y, width: r.width, height: r.height },
+        None => CaptureRegion { x: display.x, y: display.y, width: display.width as f64, height: display.height as f64 },
+    })
+}
+
+/// Target and settings the primary recording started with.
+fn primary_capture(app: &AppHandle) -> Option<LastRecording> {
+    let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+    let recordings = state.lock().ok()?;
+    recordings.primary().map(|s| s.capture.clone())
+}
+
+/// `capture_rect` of the primary recording.
+fn primary_capture_rect(app: &AppHandle) -> Option<CaptureRegion> {
+    primary_capture(app).as_ref().and_then(capture_rect)
+}
+
+/// Where on-screen overlays for a recording go: its capture rect, or for a
+/// window recording the display the window is on.
+fn overlay_area(app: &AppHandle, capture: &LastRecording) -> Option<CaptureRegion> {
+    capture_rect(capture).or_else(|| {
+        let window = platform::imp::window_bounds(capture.window_id?).ok()?;
+        screen_containing(app, &window)
+    })
+}
+
+/// Core logic to stop recording (called from both tray and commands).
+/// `recording_id = None` stops the primary recording.
+pub fn do_stop_recording(app: &AppHandle, recording_id: Option<&str>) -> Result<RecordingResult, ZureshotError> {
+    // Remove the session while holding the mutex, then release it
+    // BEFORE any blocking operations.
+    let (recording_id, session, was_primary, others_remain) = take_session(app, recording_id)?;
+
+    let mut duration = session.elapsed_secs();
+    let RecordingSession {
+        handle,
+        output_path,
+        output_format,
+        mut chapters,
+        mut bookmarks,
+        cursor_capture,
+        show_cursor,
+        force_cfr,
+        max_fps,
+        gif_options,
+        append_to,
+        trim_head_secs,
+        trim_tail_secs,
+        capture,
+        mouse_track_offset_secs,
+        ..
+    } = session;
+    #[cfg(not(target_os = "macos"))]
+    let _ = (cursor_capture, show_cursor);
+    let mut output_path = output_path.unwrap_or_default();
+
+    println!("[zureshot] Stopping recording {} after {:.1}s", recording_id, duration);
+
+    if was_primary {
+        unregister_recording_shortcuts(app);
+        BAR_ON_TOP.store(true, std::sync::atomic::Ordering::Relaxed);
+
+        // Stop mouse tracking and save track data
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(tracker_state) = app.try_state::<Mutex<platform::macos::mouse_tracker::MouseTrackerState>>() {
+                if let Ok(tracker) = tracker_state.lock() {
+                    let mut track = platform::macos::mouse_tracker::stop_mouse_tracking(&tracker);
+                    // Promoted mid-recording: the track started late
+                    for sample in &mut track.samples {
+                        sample.time += mouse_track_offset_secs;
+                    }
+                    track.duration_secs += mouse_track_offset_secs;
+                    // A hidden cursor stays hidden: don't have the editor draw one
+                    track.cursor_in_video = !show_cursor || cursor_capture == platform::CursorCapture::System;
+                    track.capture_rect = capture_rect(&capture);
+                    if !track.samples.is_empty() {
+                        let _ = platform::macos::mouse_tracker::save_mouse_track(&output_path, &track);
+                    }
+                }
+            }
+        }
+
+        close_recording_windows(app);
+        if others_remain {
+            attach_primary_controls(app);
+        }
     }
 
     // Stop capture and finalize file (platform-specific)
     if let Some(ref handle) = handle {
         handle.stop_capture();
     }
+    // Nothing is capturing system audio anymore
+    if !others_remain {
+        restore_ui_sounds(app);
+    }
 
     // Brief pause to let the capture pipeline fully drain
     std::thread::sleep(std::time::Duration::from_millis(200));
 
+    // Final counters, before finalize releases the capture
+    let frame_stats = handle.as_ref().map(|h| h.frame_stats()).unwrap_or_default();
+
     // Finalize output file
     if let Some(ref handle) = handle {
-        handle.finalize();
+        if let Some(warning) = handle.finalize() {
+            let _ = app.emit("recording-warning", &warning);
+        }
+    }
+
+    let mut scene_changes = handle.as_ref().map(|h| h.scene_changes()).unwrap_or_default();
+    if let Some(ms) = handle.as_ref().and_then(|h| h.first_frame_latency_ms()) {
+        // Basis for the automatic warm-up duration of the next recording
+        crate::settings::set(app, "measured_first_frame_ms", serde_json::json!(ms));
+    }
+
+    // GIF conversion resamples to GifOptions::fps anyway; audio-only
+    // recordings have no frames to re-time
+    let constant_frame_rate = if output_format == "gif" {
+        true
+    } else if output_format == "audio" {
+        false
+    } else {
+        make_constant_frame_rate(&output_path, max_fps, force_cfr)
+    };
+
+    // Trim-on-stop, before anything is joined or added around this recording
+    if trim_head_secs > 0.0 || trim_tail_secs > 0.0 {
+        match trim_recording(&output_path, duration, trim_head_secs, trim_tail_secs) {
+            Ok(kept) => {
+                let in_kept = |t: &f64| (0.0..=kept).contains(t);
+                for c in &mut chapters {
+                    c.time_secs -= trim_head_secs;
+                }
+                chapters.retain(|c| in_kept(&c.time_secs));
+                for b in &mut bookmarks {
+                    b.time_secs -= trim_head_secs;
+                }
+                bookmarks.retain(|b| in_kept(&b.time_secs));
+                for t in &mut scene_changes {
+                    *t -= trim_head_secs;
+                }
+                scene_changes.retain(in_kept);
+                shift_sidecar_times(&output_path, -trim_head_secs, Some(kept));
+                duration = kept;
+            }
+            Err(e) => {
+                eprintln!("[zureshot] {}", e);
+                let _ = app.emit("recording-warning", &e);
+            }
+        }
+    }
+
+    // Continuing an earlier recording: join this segment onto it. On a
+    // mismatch the segment is kept as its own file.
+    if let Some(existing) = append_to.as_deref() {
+        match append_segment(existing, &output_path) {
+            Ok(offset) => {
+                for c in &mut chapters {
+                    c.time_secs += offset;
+                }
+                for b in &mut bookmarks {
+                    b.time_secs += offset;
+                }
+                for t in &mut scene_changes {
+                    *t += offset;
+                }
+                duration += offset;
+                output_path = existing.to_string();
+            }
+            Err(e) => {
+                eprintln!("[zureshot] {}", e);
+                let _ = app.emit("recording-warning", &e);
+            }
+        }
+    }
+
+    // Title/end cards (MP4 only — GIFs are capped at a few seconds). An
+    // appended recording already has them from its first part.
+    let cards: platform::IntroOutroConfig = crate::settings::get(app, "intro_outro").unwrap_or_default();
+    if cards.is_enabled() && output_format != "gif" && output_format != "audio" && append_to.is_none() {
+        let (intro_secs, outro_secs) = apply_intro_outro(app, &output_path, &cards);
+        if intro_secs > 0.0 {
+            for c in &mut chapters {
+                c.time_secs += intro_secs;
+            }
+            for b in &mut bookmarks {
+                b.time_secs += intro_secs;
+            }
+            for t in &mut scene_changes {
+                *t += intro_secs;
+            }
+        }
+        duration += intro_secs + outro_secs;
+    }
+
+    if !chapters.is_empty() && output_format != "gif" {
+        write_chapters(&output_path, &chapters, duration);
+    }
+    if !bookmarks.is_empty() {
+        write_bookmarks_index(&output_path, &bookmarks);
+    }
+    // An appended recording keeps the settings of its first part
+    if output_format == "video" && append_to.as_deref() != Some(output_path.as_str()) {
+        write_capture_settings(&output_path, &capture);
     }
 
     // If format is GIF, convert MP4 → GIF using ffmpeg with palette optimization
@@ -247,23 +930,28 @@ pub fn do_stop_recording(app: &AppHandle) -> Result<RecordingResult, String> {
         println!("[zureshot] Converting MP4 to GIF: {} → {}", output_path, gif_path);
 
         // Two-pass palette-optimized GIF for high quality:
-        // - Cap width at 640px (scale down large regions for reasonable file size)
-        // - fps=15 balances file size and smoothness
+        // - Cap width (scale down large regions for reasonable file size)
+        // - fps (15 by default) balances file size and smoothness
         // - lanczos scaling for sharpness
         // - palettegen+paletteuse for optimal color dithering
-        let vf = format!(
-            "fps={},scale='min({},iw)':-1:flags=lanczos,split[s0][s1];[s0]palettegen[p];[s1][p]paletteuse",
-            GIF_FPS, GIF_MAX_WIDTH
-        );
-        let ffmpeg_result = std::process::Command::new("ffmpeg")
-            .args([
-                "-i", &output_path,
-                "-t", &format!("{}", GIF_MAX_DURATION_SECS),
-                "-vf", &vf,
-                "-y",
-                &gif_path,
-            ])
-            .output();
+        // The start offset is an input seek, so the GIF's clock starts at 0
+        let offset = gif_options.start_offset_secs.unwrap_or(0.0).min(duration);
+        let length = (duration - offset).min(gif_options.max_duration);
+        let vf = gif_options.filter(length);
+        let mut args: Vec<String> = Vec::new();
+        if offset > 0.0 {
+            args.extend(["-ss".to_string(), format!("{:.3}", offset)]);
+        }
+        args.extend([
+            "-i".to_string(), output_path.clone(),
+            "-t".to_string(), format!("{}", gif_options.max_duration),
+            "-vf".to_string(), vf,
+        ]);
+        if let Some(n) = gif_options.loop_count {
+            args.extend(["-loop".to_string(), n.to_string()]);
+        }
+        args.extend(["-y".to_string(), gif_path.clone()]);
+        let ffmpeg_result = crate::ffmpeg::command().args(&args).output();
 
         match ffmpeg_result {
             Ok(output) if output.status.success() => {
@@ -291,28 +979,66 @@ pub fn do_stop_recording(app: &AppHandle) -> Result<RecordingResult, String> {
 
     let file_size = std::fs::metadata(&final_path).map(|m| m.len()).unwrap_or(0);
 
+    // Split into scene segments (full recording is kept for the editor)
+    let segments = if output_format != "gif" && !scene_changes.is_empty() {
+        split_at_scene_changes(&final_path, &scene_changes)
+    } else {
+        Vec::new()
+    };
+
     let result = RecordingResult {
+        recording_id,
         path: final_path.clone(),
         duration_secs: duration,
         file_size_bytes: file_size,
+        segments,
+        constant_frame_rate,
+        frame_count: frame_stats.frames,
+        dropped_count: frame_stats.dropped,
+        pts_skip_count: frame_stats.pts_skips,
     };
 
     // Emit event to frontend with result
     let _ = app.emit("recording-stopped", &result);
 
+    if flush_on_stop(app) {
+        match flush_to_disk(&final_path) {
+            Ok(size) => {
+                println!("[zureshot] Recording flushed to disk: {} ({} bytes)", final_path, size);
+                let _ = app.emit(
+                    "recording-flushed",
+                    serde_json::json!({ "recording_id": result.recording_id, "path": final_path, "file_size_bytes": size }),
+                );
+            }
+            Err(e) => eprintln!("[zureshot] Failed to flush recording: {}", e),
+        }
+    }
+
     println!(
-        "[zureshot] Recording complete: {} ({:.1}s, {:.1} MB)",
+        "[zureshot] Recording complete: {} ({:.1}s, {:.1} MB, {} frames, {} dropped, {} out of order)",
         final_path,
         duration,
-        file_size as f64 / 1_048_576.0
+        file_size as f64 / 1_048_576.0,
+        frame_stats.frames,
+        frame_stats.dropped,
+        frame_stats.pts_skips
     );
 
     // Update tray menu to reflect stopped state
     // (handles case where stop was triggered from recording bar, not tray)
-    crate::tray::notify_recording_stopped(app);
+    if others_remain {
+        crate::tray::notify_recording_started(app);
+    } else {
+        crate::tray::notify_recording_stopped(app);
+    }
+
+    // Vertical copy needs the cursor track, which only the primary has
+    if was_primary && output_format != "gif" && output_format != "audio" {
+        spawn_auto_vertical_export(app, &final_path);
+    }
 
-    // Auto-open video editor for MP4 recordings (not GIF)
-    if output_format != "gif" {
+    // Auto-open video editor for MP4 recordings (not GIF or audio-only)
+    if was_primary && output_format != "gif" && output_format != "audio" {
         let app_clone = app.clone();
         let path_clone = final_path.clone();
         // Slight delay to let the UI settle before opening editor
@@ -327,458 +1053,5033 @@ pub fn do_stop_recording(app: &AppHandle) -> Result<RecordingResult, String> {
     Ok(result)
 }
 
-/// Start screen recording (Tauri command - called from frontend)
-#[tauri::command]
-pub async fn start_recording(
-    app: AppHandle,
-    _state: tauri::State<'_, Mutex<RecordingState>>,
-    output_path: Option<String>,
-) -> Result<(), String> {
-    // CRITICAL: Must run on a dedicated OS thread, not the Tokio async runtime.
-    // do_start_recording() blocks on GCD completion handlers via mpsc::channel.
-    // Running this on a Tokio worker thread can deadlock because GCD may not
-    // deliver callbacks to Tokio-managed threads on macOS.
-    let app_clone = app.clone();
-    tokio::task::spawn_blocking(move || {
-        do_start_recording(&app_clone, output_path, None, RecordingQuality::Standard, false, false, None).map(|_| ())
-    })
-    .await
-    .map_err(|e| format!("Task join error: {e}"))?
+/// Close the recording bar and the overlays that belong to the primary
+/// recording.
+fn close_recording_windows(app: &AppHandle) {
+    // Close the recording bar and dim overlay windows
+    if let Some(win) = app.get_webview_window("recording-bar") {
+        let _ = win.destroy();
+    }
+    if let Some(win) = app.get_webview_window("recording-overlay") {
+        let _ = win.destroy();
+    }
+    close_keystroke_overlay(app);
+    close_click_overlay(app);
+    // Close camera bubble if open
+    if let Some(win) = app.get_webview_window("camera-overlay") {
+        let _ = app.emit("camera-overlay-close", ());
+        let _ = win.destroy();
+    }
 }
 
-/// Stop screen recording (Tauri command - called from frontend)
-#[tauri::command]
-pub async fn stop_recording(
-    app: AppHandle,
-    _state: tauri::State<'_, Mutex<RecordingState>>,
-) -> Result<RecordingResult, String> {
-    // CRITICAL: Must run on a dedicated OS thread, not the Tokio async runtime.
-    // do_stop_recording() blocks on GCD completion handlers (finishWriting,
-    // stopCapture) via mpsc::channel. If this runs on a Tokio worker thread,
-    // GCD may not deliver callbacks → deadlock → moov atom never written
-    // → unplayable MP4.
-    let app_clone = app.clone();
-    tokio::task::spawn_blocking(move || do_stop_recording(&app_clone))
-        .await
-        .map_err(|e| format!("Task join error: {e}"))?
-}
+/// Core logic to cancel a recording: stop it like `do_stop_recording`,
+/// then delete the output and its sidecars instead of post-processing it.
+/// The file is still finalized first so nothing is left half-written if
+/// the delete fails. When appending, only the new segment is discarded.
+/// Returns the cancelled recording's id.
+pub fn do_cancel_recording(app: &AppHandle, recording_id: Option<&str>) -> Result<String, ZureshotError> {
+    // Same discipline as do_stop_recording: take the session out, release
+    // the mutex, then block
+    let (recording_id, session, was_primary, others_remain) = take_session(app, recording_id)?;
+
+    let handle = session.handle;
+    let output_path = session.output_path.unwrap_or_default();
+
+    println!("[zureshot] Cancelling recording {}", recording_id);
+
+    if was_primary {
+        unregister_recording_shortcuts(app);
+        BAR_ON_TOP.store(true, std::sync::atomic::Ordering::Relaxed);
+
+        // Stop mouse tracking; the track is discarded with the recording
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(tracker_state) = app.try_state::<Mutex<platform::macos::mouse_tracker::MouseTrackerState>>() {
+                if let Ok(tracker) = tracker_state.lock() {
+                    let _ = platform::macos::mouse_tracker::stop_mouse_tracking(&tracker);
+                }
+            }
+        }
 
-/// Get current recording status
-#[tauri::command]
-pub fn get_recording_status(
-    state: tauri::State<'_, Mutex<RecordingState>>,
-) -> Result<RecordingStatus, String> {
-    let recording = state.lock().map_err(|e| e.to_string())?;
-
-    Ok(RecordingStatus {
-        is_recording: recording.is_recording,
-        is_paused: recording.is_paused,
-        duration_secs: recording
-            .start_time
-            .map(|t| {
-                let wall = t.elapsed();
-                let paused = recording.pause_accumulated
-                    + recording.pause_start.map(|ps| ps.elapsed()).unwrap_or_default();
-                (wall - paused).as_secs_f64()
-            })
-            .unwrap_or(0.0),
-        output_path: recording.output_path.clone(),
-        quality: format!("{:?}", recording.quality),
-    })
-}
+        close_recording_windows(app);
+    }
 
-/// Open the recorded file in the system file manager
-#[tauri::command]
-pub async fn reveal_in_finder(path: String) -> Result<(), String> {
-    platform::imp::reveal_file(&path)
-}
+    if let Some(ref handle) = handle {
+        handle.stop_capture();
+    }
+    if !others_remain {
+        restore_ui_sounds(app);
+    }
 
-/// Get the default recordings directory
-#[tauri::command]
-pub fn get_recordings_dir() -> String {
-    let base = dirs::download_dir()
-        .or_else(dirs::home_dir)
-        .unwrap_or_else(|| std::path::PathBuf::from("."));
-    let zureshot_dir = base.join("Zureshot");
-    let _ = std::fs::create_dir_all(&zureshot_dir);
-    zureshot_dir.to_string_lossy().to_string()
-}
+    // Brief pause to let the capture pipeline fully drain
+    std::thread::sleep(std::time::Duration::from_millis(200));
 
-/// Core logic to open the region selector overlay (callable from both tray and commands)
-pub fn do_start_region_selection(app: &AppHandle) -> Result<(), String> {
-    // Check if already recording
-    {
-        let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
-        let recording = state.lock().map_err(|e| e.to_string())?;
-        if recording.is_recording {
-            return Err("Recording already in progress".to_string());
+    if let Some(ref handle) = handle {
+        if let Some(warning) = handle.finalize() {
+            eprintln!("[zureshot] Cancelled recording finalized with a warning: {}", warning);
         }
     }
 
-    // If a region-selector window already exists, just show + focus it
-    if let Some(win) = app.get_webview_window("region-selector") {
-        let _ = win.show();
-        let _ = win.set_focus();
-        return Ok(());
+    if !output_path.is_empty() {
+        for sidecar in recording_sidecars(&output_path) {
+            if let Err(e) = std::fs::remove_file(&sidecar) {
+                eprintln!("[zureshot] Failed to delete {}: {}", sidecar.display(), e);
+            }
+        }
+        // Stills saved by bookmarks during the recording
+        let stills = bookmarks_dir(&output_path);
+        if stills.is_dir() {
+            if let Err(e) = std::fs::remove_dir_all(&stills) {
+                eprintln!("[zureshot] Failed to delete {}: {}", stills.display(), e);
+            }
+        }
+        match std::fs::remove_file(&output_path) {
+            Ok(()) => println!("[zureshot] Deleted cancelled recording: {}", output_path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => eprintln!("[zureshot] Failed to delete {}: {}", output_path, e),
+        }
     }
 
-    // Get display info for sizing the overlay
-    let monitor = app
-        .primary_monitor()
-        .map_err(|e| format!("Failed to get monitor: {}", e))?
-        .ok_or("No primary monitor found")?;
-    let phys_size = monitor.size();
-    let scale = monitor.scale_factor();
-    let position = monitor.position();
-
-    // Tauri's inner_size() expects LOGICAL pixels, not physical.
-    // On a Retina display (scale=2.0), physical 2880×1800 → logical 1440×900.
-    let logical_w = phys_size.width as f64 / scale;
-    let logical_h = phys_size.height as f64 / scale;
-
-    println!(
-        "[zureshot] Region selector: physical={}×{}, scale={}, logical={:.0}×{:.0}, pos=({},{})",
-        phys_size.width, phys_size.height, scale, logical_w, logical_h,
-        position.x, position.y
+    let _ = app.emit(
+        "recording-cancelled",
+        serde_json::json!({ "recording_id": recording_id, "path": output_path }),
     );
 
-    // Create fullscreen transparent overlay window.
-    // transparent(true) is safe now that the Svelte component mounts correctly
-    // (the earlier invisibility was caused by an SSR resolution bug, not by
-    // the transparency itself).
-    let window = WebviewWindowBuilder::new(
-        app,
-        "region-selector",
-        WebviewUrl::App("region-selector.html".into()),
-    )
-    .title("Region Selector")
-    .inner_size(logical_w, logical_h)
-    .position(position.x as f64 / scale, position.y as f64 / scale)
-    .transparent(true)
-    .decorations(false)
-    .always_on_top(true)
-    .skip_taskbar(true)
-    .resizable(false)
-    .build()
-    .map_err(|e| format!("Failed to create region selector window: {}", e))?;
+    if others_remain {
+        crate::tray::notify_recording_started(app);
+    } else {
+        crate::tray::notify_recording_stopped(app);
+    }
 
-    // Explicitly show and focus — tray-only macOS apps may not auto-focus new windows
-    let _ = window.show();
-    let _ = window.set_focus();
+    Ok(recording_id)
+}
 
-    println!("[zureshot] Region selector window created and focused");
+/// Apply the `force_cfr` option to a finished MP4. Returns whether the
+/// output is constant frame rate.
+fn make_constant_frame_rate(path: &str, fps: i32, force_cfr: bool) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        if !force_cfr {
+            return false;
+        }
+        match platform::macos::editor::convert_to_cfr(path, fps) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("[zureshot] CFR conversion failed, keeping VFR recording: {}", e);
+                false
+            }
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        // videorate in the GStreamer pipeline always emits a fixed rate
+        let _ = (path, fps, force_cfr);
+        true
+    }
+}
 
-    Ok(())
+/// Why a stream copy of `segment` onto `existing` would break, if it would:
+/// the video codec, frame size and frame rate, and every audio track's
+/// codec and layout, must match.
+fn append_mismatch(existing: &crate::ffmpeg::MediaInfo, segment: &crate::ffmpeg::MediaInfo) -> Option<String> {
+    let (Some(before), Some(added)) = (&existing.video, &segment.video) else {
+        return Some("one of the files has no video".to_string());
+    };
+    if before.codec != added.codec {
+        return Some(format!("the codec changed from {} to {}", before.codec, added.codec));
+    }
+    if (before.width, before.height) != (added.width, added.height) {
+        return Some(format!(
+            "the size changed from {}x{} to {}x{}",
+            before.width, before.height, added.width, added.height
+        ));
+    }
+    if before.fps > 0.0 && added.fps > 0.0 && (before.fps - added.fps).abs() > 0.5 {
+        return Some(format!("the frame rate changed from {:.0} to {:.0} fps", before.fps, added.fps));
+    }
+    if existing.audio.len() != segment.audio.len() {
+        return Some(format!(
+            "the recording has {} audio track(s) but the new part has {}",
+            existing.audio.len(),
+            segment.audio.len()
+        ));
+    }
+    existing
+        .audio
+        .iter()
+        .zip(&segment.audio)
+        .position(|(a, b)| a != b)
+        .map(|i| format!("audio track {} changed format", i + 1))
 }
 
-/// Open the region selector overlay window (Tauri command)
-#[tauri::command]
-pub async fn start_region_selection(app: AppHandle) -> Result<(), String> {
-    do_start_region_selection(&app)
+/// Join `segment` onto the end of `existing` in place (stream copy via the
+/// concat demuxer) and delete the segment and its sidecars. Returns the
+/// duration of `existing` before the join, i.e. where the segment now
+/// starts. Refuses to join files whose streams differ (`append_mismatch`)
+/// — a stream copy would produce a file that breaks halfway through.
+fn append_segment(existing: &str, segment: &str) -> Result<f64, String> {
+    let before = crate::ffmpeg::media_info(existing)?;
+    let added = crate::ffmpeg::media_info(segment)?;
+    if let Some(why) = append_mismatch(&before, &added) {
+        return Err(format!("Can't append to {}: {}; kept the new recording as {}", existing, why, segment));
+    }
+
+    let source = std::path::Path::new(existing);
+    let ext = source.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+    let list_path = source.with_extension("concat.txt");
+    let tmp_path = source.with_extension(format!("appending.{}", ext));
+    let quote = |p: &str| format!("file '{}'\n", p.replace('\'', "'\\''"));
+    std::fs::write(&list_path, format!("{}{}", quote(existing), quote(segment)))
+        .map_err(|e| format!("Failed to write concat list: {}", e))?;
+
+    println!("[zureshot] Appending {} to {}", segment, existing);
+    let result = crate::ffmpeg::command()
+        .args([
+            "-f", "concat",
+            "-safe", "0",
+            "-i", &list_path.to_string_lossy(),
+            "-map", "0",
+            "-c", "copy",
+            "-movflags", "+faststart",
+            "-y",
+            &tmp_path.to_string_lossy(),
+        ])
+        .output();
+    let _ = std::fs::remove_file(&list_path);
+
+    match result {
+        Ok(o) if o.status.success() => {
+            std::fs::rename(&tmp_path, existing).map_err(|e| format!("Failed to replace {}: {}", existing, e))?;
+            // Its timing/cursor sidecars describe a file that's gone now
+            for sidecar in recording_sidecars(segment) {
+                let _ = std::fs::remove_file(sidecar);
+            }
+            let _ = std::fs::remove_file(segment);
+            println!(
+                "[zureshot] Appended {:.1}s onto {} ({:.1}s total)",
+                added.duration_secs,
+                existing,
+                before.duration_secs + added.duration_secs
+            );
+            Ok(before.duration_secs)
+        }
+        Ok(o) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            Err(format!("Appending failed, kept the new recording as {}: {}", segment, String::from_utf8_lossy(&o.stderr)))
+        }
+        Err(e) => Err(format!("ffmpeg not found or failed to run: {}", e)),
+    }
 }
 
-/// Confirm region selection and start recording with the selected region
-#[tauri::command]
-pub fn confirm_region_selection(
-    app: AppHandle,
-    x: f64,
-    y: f64,
-    width: f64,
-    height: f64,
-    quality: Option<String>,
-    system_audio: Option<bool>,
-    microphone: Option<bool>,
-    format: Option<String>,
-    camera: Option<bool>,
-    camera_device_id: Option<String>,
-    camera_shape: Option<String>,
-    camera_size: Option<String>,
-) -> Result<(), String> {
-    // Hide the region selector (don't destroy — we're inside its IPC call).
-    if let Some(win) = app.get_webview_window("region-selector") {
-        let _ = win.hide();
+/// Shortest recording a trim may leave (seconds).
+const MIN_TRIMMED_SECS: f64 = 1.0;
+
+/// Cut `head` seconds off the start and `tail` off the end of a finished
+/// recording in place (ffmpeg stream copy, no re-encode, so the start
+/// lands on the keyframe at or before `head`). Returns the new length. The
+/// file is left untrimmed, with an error saying why, when ffmpeg is missing
+/// or less than `MIN_TRIMMED_SECS` would be left.
+fn trim_recording(path: &str, recorded_secs: f64, head: f64, tail: f64) -> Result<f64, String> {
+    if !crate::ffmpeg::probe(false).available {
+        return Err("ffmpeg not found — the recording was kept untrimmed. Install ffmpeg to trim recordings on stop.".into());
+    }
+    // The file's own length, or the recorded time if it can't be probed
+    let length = crate::ffmpeg::media_info(path)
+        .map(|i| i.duration_secs)
+        .ok()
+        .filter(|d| *d > 0.0)
+        .unwrap_or(recorded_secs);
+    let kept = length - head - tail;
+    if kept < MIN_TRIMMED_SECS {
+        return Err(format!(
+            "The recording is only {:.1}s long, too short to trim {:.1}s off — kept it untrimmed",
+            length,
+            head + tail
+        ));
     }
 
-    let region = CaptureRegion {
-        x,
-        y,
-        width,
-        height,
-    };
+    let source = std::path::Path::new(path);
+    let ext = source.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+    let tmp_path = source.with_extension(format!("trimming.{}", ext));
+    let mut args: Vec<String> = Vec::new();
+    if head > 0.0 {
+        args.extend(["-ss".to_string(), format!("{:.3}", head)]);
+    }
+    args.extend([
+        "-i".to_string(), path.to_string(),
+        "-t".to_string(), format!("{:.3}", kept),
+        "-map".to_string(), "0".to_string(),
+        "-c".to_string(), "copy".to_string(),
+        "-movflags".to_string(), "+faststart".to_string(),
+        "-y".to_string(), tmp_path.to_string_lossy().to_string(),
+    ]);
+
+    match crate::ffmpeg::command().args(&args).output() {
+        Ok(o) if o.status.success() => {
+            std::fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace {}: {}", path, e))?;
+            println!(
+                "[zureshot] Trimmed {:.1}s from the start and {:.1}s from the end ({:.1}s kept)",
+                head, tail, kept
+            );
+            Ok(kept)
+        }
+        Ok(o) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            Err(format!("Trimming failed, kept the recording untrimmed: {}", String::from_utf8_lossy(&o.stderr)))
+        }
+        Err(e) => Err(format!("ffmpeg not found or failed to run: {}", e)),
+    }
+}
 
-    let q = match quality.as_deref() {
-        Some("high") => RecordingQuality::High,
-        _ => RecordingQuality::Standard,
-    };
+/// Move the mouse track (`.mousetrack.json`) and frame timestamps
+/// (`.timestamps.json`) of `path` by `delta` seconds after its video was
+/// re-timed in place. With `keep_until`, entries that end up outside
+/// `0..=keep_until` are dropped and that becomes the track's duration
+/// (trim); without it everything is kept (intro card).
+fn shift_sidecar_times(path: &str, delta: f64, keep_until: Option<f64>) {
+    let kept = |t: f64| keep_until.map_or(true, |end| (0.0..=end).contains(&t));
 
-    let sys_audio = system_audio.unwrap_or(false);
-    let mic = microphone.unwrap_or(false);
-    let output_format = format.unwrap_or_else(|| "video".to_string());
-    let camera_enabled = camera.unwrap_or(false);
-    let cam_device_id = camera_device_id.clone();
-    let cam_shape = camera_shape.unwrap_or_else(|| "circle".to_string());
-    let cam_size = camera_size.unwrap_or_else(|| "medium".to_string());
-
-    let region_for_bar = region.clone();
-    let region_for_overlay = region.clone();
-    let app_clone = app.clone();
-    std::thread::spawn(move || {
-        // Small delay to let the region selector fully disappear
-        std::thread::sleep(std::time::Duration::from_millis(300));
+    #[cfg(target_os = "macos")]
+    if let Ok(mut track) = platform::macos::mouse_tracker::load_mouse_track(path) {
+        for sample in &mut track.samples {
+            sample.time += delta;
+        }
+        track.samples.retain(|s| kept(s.time));
+        track.duration_secs = keep_until.unwrap_or(track.duration_secs + delta);
+        let _ = platform::macos::mouse_tracker::save_mouse_track(path, &track);
+    }
 
-        // Now safe to destroy region-selector
-        if let Some(win) = app_clone.get_webview_window("region-selector") {
-            let _ = win.destroy();
+    let timestamps_path = std::path::Path::new(path).with_extension("timestamps.json");
+    let Some(mut timestamps) = std::fs::read_to_string(&timestamps_path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+    else {
+        return;
+    };
+    if let Some(frames) = timestamps["frames"].as_array_mut() {
+        for frame in frames.iter_mut() {
+            if let Some(t) = frame["video_time_secs"].as_f64() {
+                frame["video_time_secs"] = serde_json::json!(t + delta);
+            }
         }
+        frames.retain(|f| f["video_time_secs"].as_f64().is_some_and(kept));
+        let count = frames.len();
+        timestamps["frame_count"] = serde_json::json!(count);
+    }
+    if let Err(e) = std::fs::write(&timestamps_path, serde_json::to_string(&timestamps).unwrap_or_default()) {
+        eprintln!("[zureshot] Failed to update {}: {}", timestamps_path.display(), e);
+    }
+}
 
-        match do_start_recording(&app_clone, None, Some(region), q, sys_audio, mic, Some(output_format)) {
-            Ok(_) => {
-                // Open the dim overlay and floating control bar
-                let _ = do_open_recording_overlay(&app_clone, &region_for_overlay);
-                let _ = do_open_recording_bar(&app_clone, Some(&region_for_bar));
-
-                // Open camera bubble if user enabled it
-                if camera_enabled {
-                    let _ = do_open_camera_overlay_with_options(
-                        &app_clone,
-                        &cam_shape,
-                        &cam_size,
-                        cam_device_id.as_deref(),
-                        Some(&region_for_overlay),
-                    );
+/// Add the configured intro/outro cards to a finished recording in place.
+/// Returns the seconds inserted before and after the original content
+/// (0 on failure, in which case the recording is left untouched).
+fn apply_intro_outro(app: &AppHandle, path: &str, cards: &platform::IntroOutroConfig) -> (f64, f64) {
+    #[cfg(target_os = "macos")]
+    {
+        match platform::macos::editor::add_intro_outro(path, cards, &letterbox_background(app)) {
+            Ok((intro_secs, outro_secs)) => {
+                // Keep the editor's cursor/zoom data aligned with the new timeline
+                if intro_secs > 0.0 {
+                    shift_sidecar_times(path, intro_secs, None);
                 }
-
-                // Brief delay for windows to register with WindowServer,
-                // then refresh the stream filter to exclude them from capture
-                std::thread::sleep(std::time::Duration::from_millis(150));
-                let _ = refresh_stream_exclusion(&app_clone);
-
-                // Send region coordinates to the overlay for the dim effect
-                let _ = app_clone.emit("recording-region", &region_for_overlay);
+                (intro_secs, outro_secs)
+            }
+            Err(e) => {
+                eprintln!("[zureshot] Intro/outro cards skipped: {}", e);
+                (0.0, 0.0)
             }
-            Err(e) => eprintln!("[zureshot] Start error: {}", e),
         }
-    });
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, path, cards);
+        println!("[zureshot] Intro/outro cards ignored (not supported on this platform yet)");
+        (0.0, 0.0)
+    }
+}
 
-    Ok(())
+/// Write recordings as fragmented MP4 so a crash or kill before finalize
+/// still leaves a playable file (persisted as `crash_resilient`).
+pub fn crash_resilient(app: &AppHandle) -> bool {
+    crate::settings::get_bool(app, "crash_resilient", false)
 }
 
-/// Cancel region selection without starting recording
 #[tauri::command]
-pub async fn cancel_region_selection(app: AppHandle) -> Result<(), String> {
-    if let Some(win) = app.get_webview_window("region-selector") {
-        let _ = win.destroy();
-    }
-    // Clean up frozen screen preview if exists
-    let preview_path = std::env::temp_dir().join("zureshot_screen_preview.png");
-    let _ = std::fs::remove_file(&preview_path);
-    Ok(())
+pub fn get_crash_resilient(app: AppHandle) -> bool {
+    crash_resilient(&app)
 }
 
-/// Pause the current recording (frames will be dropped, timer pauses)
+/// Turn crash-resilient (fragmented) recording on or off (persisted,
+/// applies to the next recording). Linux recordings are always fragmented.
 #[tauri::command]
-pub fn pause_recording(
-    state: tauri::State<'_, Mutex<RecordingState>>,
-) -> Result<(), String> {
-    let mut recording = state.lock().map_err(|e| e.to_string())?;
+pub fn set_crash_resilient(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "crash_resilient", serde_json::json!(enabled));
+    println!("[zureshot] Crash-resilient recording: {}", enabled);
+}
+
+/// fsync the finished recording before reporting it (persisted as
+/// `flush_on_stop`).
+pub fn flush_on_stop(app: &AppHandle) -> bool {
+    crate::settings::get_bool(app, "flush_on_stop", false)
+}
+
+#[tauri::command]
+pub fn get_flush_on_stop(app: AppHandle) -> bool {
+    flush_on_stop(&app)
+}
+
+/// Flush each finished recording to disk and emit `recording-flushed` once
+/// it is durable (persisted).
+#[tauri::command]
+pub fn set_flush_on_stop(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "flush_on_stop", serde_json::json!(enabled));
+    println!("[zureshot] Flush recordings on stop: {}", enabled);
+}
 
-    if !recording.is_recording {
-        return Err("No recording in progress".to_string());
+/// fsync `path` and its directory (the intro/outro pass renames into
+/// place), returning the final size. On macOS `sync_all` issues
+/// `F_FULLFSYNC`, which also flushes the drive's write cache.
+fn flush_to_disk(path: &str) -> Result<u64, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    file.sync_all().map_err(|e| format!("fsync failed for {}: {}", path, e))?;
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        if let Ok(dir) = std::fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
     }
-    if recording.is_paused {
-        return Err("Recording is already paused".to_string());
+    file.metadata()
+        .map(|m| m.len())
+        .map_err(|e| format!("Failed to read size of {}: {}", path, e))
+}
+
+/// Sensitivity used for scene detection, or `None` when segmentation is off.
+fn scene_threshold_setting(app: &AppHandle) -> Option<f64> {
+    if !crate::settings::get_bool(app, "scene_segmentation", false) {
+        return None;
     }
+    Some(crate::settings::get_f64(app, "scene_threshold", DEFAULT_SCENE_THRESHOLD))
+}
 
-    // Set the atomic flag so the capture delegate drops frames
-    if let Some(ref handle) = recording.handle {
-        handle.pause();
+/// Default scene-change sensitivity (mean luma difference, 0.0 – 1.0)
+const DEFAULT_SCENE_THRESHOLD: f64 = 0.3;
+
+/// Split a finished recording at scene-change timestamps into
+/// `<stem>_scene01.mp4`, `<stem>_scene02.mp4`, … (stream copy, no re-encode;
+/// ProRes `.mov` recordings keep their extension).
+///
+/// Cuts snap to the nearest keyframe, so boundaries may shift by up to one
+/// keyframe interval. Returns the written parts, or an empty list on failure.
+fn split_at_scene_changes(path: &str, scene_changes: &[f64]) -> Vec<String> {
+    let source = std::path::Path::new(path);
+    let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let dir = source.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+    let ext = source.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+    let pattern = dir.join(format!("{}_scene%02d.{}", stem, ext));
+    let times = scene_changes
+        .iter()
+        .map(|t| format!("{:.3}", t))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    println!("[zureshot] Splitting {} at scene changes: {}", path, times);
+
+    let output = crate::ffmpeg::command()
+        .args([
+            "-i", path,
+            "-map", "0",
+            "-c", "copy",
+            "-f", "segment",
+            "-segment_times", &times,
+            "-segment_start_number", "1",
+            "-reset_timestamps", "1",
+            "-y",
+            &pattern.to_string_lossy(),
+        ])
+        .output();
+
+    match output {
+        Ok(o) if o.status.success() => {}
+        Ok(o) => {
+            eprintln!("[zureshot] Scene split failed: {}", String::from_utf8_lossy(&o.stderr));
+            return Vec::new();
+        }
+        Err(e) => {
+            eprintln!("[zureshot] ffmpeg not found or failed to run: {}", e);
+            return Vec::new();
+        }
     }
 
-    recording.is_paused = true;
-    recording.pause_start = Some(std::time::Instant::now());
-    println!("[zureshot] Recording paused");
-    Ok(())
+    (1..=scene_changes.len() + 1)
+        .map(|i| dir.join(format!("{}_scene{:02}.{}", stem, i, ext)))
+        .filter(|p| p.exists())
+        .map(|p| p.to_string_lossy().to_string())
+        .collect()
 }
 
-/// Resume a paused recording
+/// Choose what happens when an enabled audio source records no samples:
+/// `"drop"` (default) removes the empty track, `"warn"` keeps it and emits
+/// a `recording-warning` event.
 #[tauri::command]
-pub fn resume_recording(
-    state: tauri::State<'_, Mutex<RecordingState>>,
-) -> Result<(), String> {
-    let mut recording = state.lock().map_err(|e| e.to_string())?;
+pub fn set_empty_audio_behavior(app: AppHandle, behavior: EmptyAudioBehavior) {
+    crate::settings::set(&app, "empty_audio_behavior", serde_json::json!(behavior));
+    println!("[zureshot] Empty audio track behavior: {:?}", behavior);
+}
 
-    if !recording.is_recording {
-        return Err("No recording in progress".to_string());
+/// Whether a recording that asked for the microphone can have it. Denied
+/// access either drops the mic (emitting `mic-permission-denied`) or
+/// aborts, per `mic_denied_behavior`.
+fn check_microphone_access(app: &AppHandle) -> Result<bool, ZureshotError> {
+    if platform::imp::microphone_access() {
+        return Ok(true);
     }
-    if !recording.is_paused {
-        return Err("Recording is not paused".to_string());
+    let msg = "Microphone permission denied. \
+               → System Settings > Privacy & Security > Microphone → Enable Zureshot.";
+    let behavior: MicDeniedBehavior = crate::settings::get(app, "mic_denied_behavior").unwrap_or_default();
+    if behavior == MicDeniedBehavior::Abort {
+        return Err(ZureshotError::new(ErrorCode::PermissionDenied, msg));
     }
+    eprintln!("[zureshot] WARNING: {} Recording without the microphone.", msg);
+    let _ = app.emit("mic-permission-denied", msg);
+    Ok(false)
+}
 
-    // Clear the atomic flag so frames start being written again
-    if let Some(ref handle) = recording.handle {
-        handle.resume();
-    }
+/// Choose what happens when the microphone is requested but access is
+/// denied: `"continue"` (default) records without it, `"abort"` refuses to
+/// start the recording.
+#[tauri::command]
+pub fn set_mic_denied_behavior(app: AppHandle, behavior: MicDeniedBehavior) {
+    crate::settings::set(&app, "mic_denied_behavior", serde_json::json!(behavior));
+    println!("[zureshot] Mic permission denied behavior: {:?}", behavior);
+}
 
-    // Accumulate this pause duration
-    if let Some(ps) = recording.pause_start.take() {
-        recording.pause_accumulated += ps.elapsed();
+/// Microphones the recording can use, with the system default flagged.
+#[tauri::command]
+pub fn list_microphones() -> Result<Vec<platform::MicrophoneInfo>, ZureshotError> {
+    Ok(platform::imp::list_microphones()?)
+}
+
+#[tauri::command]
+pub fn get_microphone_device(app: AppHandle) -> Option<String> {
+    crate::settings::get(&app, "microphone_device_id")
+}
+
+/// Record from the microphone with this `MicrophoneInfo::id`, or `None`
+/// for the system default (persisted, applies to the next recording).
+#[tauri::command]
+pub fn set_microphone_device(app: AppHandle, device_id: Option<String>) -> Result<(), ZureshotError> {
+    if let Some(ref id) = device_id {
+        if !platform::imp::list_microphones()?.iter().any(|m| &m.id == id) {
+            return Err(format!("Microphone {} not found", id).into());
+        }
     }
-    recording.is_paused = false;
-    println!("[zureshot] Recording resumed");
+    crate::settings::set(&app, "microphone_device_id", serde_json::json!(device_id));
+    println!("[zureshot] Microphone device: {}", device_id.as_deref().unwrap_or("system default"));
     Ok(())
 }
 
-/// Open the floating recording control bar.
-/// Called after recording starts to give the user stop/pause controls.
-pub fn do_open_recording_bar(app: &AppHandle, region: Option<&CaptureRegion>) -> Result<(), String> {
-    // If already open, just focus it
-    if let Some(win) = app.get_webview_window("recording-bar") {
-        let _ = win.show();
+/// Payload emitted with `microphone-device-missing`.
+#[derive(Clone, Serialize)]
+struct MicrophoneDeviceMissingPayload {
+    device_id: String,
+    message: String,
+}
+
+/// The chosen microphone for a new recording. A device that is no longer
+/// connected falls back to the system default with a
+/// `microphone-device-missing` warning instead of failing the recording.
+fn microphone_device(app: &AppHandle) -> Option<String> {
+    let id: String = crate::settings::get(app, "microphone_device_id")?;
+    let available = match platform::imp::list_microphones() {
+        Ok(mics) => mics.iter().any(|m| m.id == id),
+        Err(e) => {
+            eprintln!("[zureshot] Could not list microphones: {}", e);
+            false
+        }
+    };
+    if available {
+        return Some(id);
+    }
+    let message = format!("Microphone {} is not connected — recording from the default input", id);
+    eprintln!("[zureshot] WARNING: {}", message);
+    let _ = app.emit("microphone-device-missing", &MicrophoneDeviceMissingPayload { device_id: id, message });
+    None
+}
+
+/// Advanced: override the capture pixel format (`"420v"` default, `"420f"`,
+/// `"BGRA"`, `"l10r"`, `"x420"`) for reproducing color/compatibility bug reports.
+/// BGRA is known to fail after a few seconds — diagnostic captures only.
+#[tauri::command]
+pub fn set_pixel_format(app: AppHandle, format: PixelFormat) {
+    if format == PixelFormat::Bgra {
+        println!("[zureshot] WARNING: BGRA capture is unstable — keep test recordings short");
+    }
+    crate::settings::set(&app, "pixel_format", serde_json::json!(format));
+    println!("[zureshot] Capture pixel format: {:?}", format);
+}
+
+/// Get scene segmentation settings: (enabled, threshold)
+#[tauri::command]
+pub fn get_scene_detection(app: AppHandle) -> (bool, f64) {
+    (
+        crate::settings::get_bool(&app, "scene_segmentation", false),
+        crate::settings::get_f64(&app, "scene_threshold", DEFAULT_SCENE_THRESHOLD),
+    )
+}
+
+/// Enable/disable splitting recordings at detected scene changes.
+/// `threshold` (0.05 – 1.0) is the mean luma difference counted as a cut;
+/// lower = more sensitive. Applies from the next recording.
+#[tauri::command]
+pub fn set_scene_detection(app: AppHandle, enabled: bool, threshold: Option<f64>) {
+    crate::settings::set(&app, "scene_segmentation", serde_json::json!(enabled));
+    if let Some(t) = threshold {
+        crate::settings::set(&app, "scene_threshold", serde_json::json!(t.clamp(0.05, 1.0)));
+    }
+    println!("[zureshot] Scene segmentation: enabled={}, threshold={:?}", enabled, threshold);
+}
+
+/// Get the automatic update check schedule: (delay after launch in seconds,
+/// minimum hours between checks). The check itself is toggled from the tray.
+#[tauri::command]
+pub fn get_update_schedule(app: AppHandle) -> (u64, f64) {
+    (
+        crate::tray::get_update_check_delay_secs(&app),
+        crate::tray::get_update_check_interval_hours(&app),
+    )
+}
+
+/// Configure the automatic update check schedule. Applies from next launch;
+/// manual "Check for Updates…" is unaffected.
+#[tauri::command]
+pub fn set_update_schedule(app: AppHandle, delay_secs: Option<u64>, interval_hours: Option<f64>) {
+    if let Some(d) = delay_secs {
+        crate::settings::set(&app, "update_check_delay_secs", serde_json::json!(d.min(3600)));
+    }
+    if let Some(h) = interval_hours {
+        crate::settings::set(&app, "update_check_interval_hours", serde_json::json!(h.max(0.0)));
+    }
+    println!(
+        "[zureshot] Update schedule: delay={:?}s, interval={:?}h",
+        delay_secs, interval_hours
+    );
+}
+
+/// Start screen recording (Tauri command - called from frontend).
+///
+/// `display_id` selects the display (see `list_displays`); recordings on
+/// different displays run independently. `output_dir` saves this recording
+/// somewhere other than the recordings folder. Returns the recording id.
+#[tauri::command]
+pub async fn start_recording(
+    app: AppHandle,
+    _state: tauri::State<'_, Mutex<RecordingState>>,
+    output_path: Option<String>,
+    output_dir: Option<String>,
+    display_id: Option<u32>,
+) -> Result<String, ZureshotError> {
+    // CRITICAL: Must run on a dedicated OS thread, not the Tokio async runtime.
+    // do_start_recording() blocks on GCD completion handlers via mpsc::channel.
+    // Running this on a Tokio worker thread can deadlock because GCD may not
+    // deliver callbacks to Tokio-managed threads on macOS.
+    let app_clone = app.clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        do_start_recording(&app_clone, output_path, output_dir, None, None, display_id, false, RecordingQuality::Standard, false, false, None)
+            .map(|_| recording_id_for(display_id, None))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+/// Continue an earlier recording: records a new segment with the same
+/// target, region, quality and audio tracks as `existing_path` (from its
+/// `.recording.json` sidecar) and, on stop, joins it onto the file without
+/// re-encoding. Recordings without saved settings are continued full
+/// screen on `display_id` with no audio. Stop fails to merge (keeping the
+/// segment as its own file and emitting `recording-warning`) if the
+/// streams don't match. Returns the recording id.
+#[tauri::command]
+pub async fn append_recording(
+    app: AppHandle,
+    existing_path: String,
+    display_id: Option<u32>,
+) -> Result<String, ZureshotError> {
+    let existing = std::path::Path::new(&existing_path);
+    if !existing.is_file() {
+        return Err(format!("Recording not found: {}", existing_path).into());
+    }
+    let ext = existing.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let codec_ext = video_codec(&app).file_extension();
+    if ext != codec_ext {
+        return Err(format!(
+            "Can't append to a .{} recording with the current codec, which records .{}",
+            ext, codec_ext
+        )
+        .into());
+    }
+    let capture = read_capture_settings(&existing_path).unwrap_or_else(|| LastRecording {
+        region: None,
+        window_id: None,
+        display_id,
+        quality: RecordingQuality::Standard,
+        system_audio: false,
+        microphone: false,
+        output_format: None,
+        camera: None,
+        all_displays: false,
+    });
+    if let Some(id) = capture.window_id {
+        if !platform::imp::window_exists(id) {
+            return Err("The window this recording was made of is gone — can't continue it".into());
+        }
+    }
+    let stem = existing.file_stem().and_then(|s| s.to_str()).unwrap_or("recording");
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let segment = existing
+        .with_file_name(format!("{}_append_{}.{}", stem, timestamp, ext))
+        .to_string_lossy()
+        .to_string();
+
+    // Same threading constraint as start_recording
+    let app_clone = app.clone();
+    Ok(tokio::task::spawn_blocking(move || {
+        do_start_recording(
+            &app_clone,
+            Some(segment),
+            None,
+            capture.region.clone(),
+            capture.window_id,
+            capture.display_id,
+            capture.all_displays,
+            capture.quality,
+            capture.system_audio,
+            capture.microphone,
+            Some("video".to_string()),
+        )?;
+        let id = if capture.all_displays {
+            ALL_DISPLAYS_RECORDING_ID.to_string()
+        } else {
+            recording_id_for(capture.display_id, capture.window_id)
+        };
+        let state: tauri::State<'_, Mutex<RecordingState>> = app_clone.state();
+        let mut recordings = state.lock().map_err(|e| e.to_string())?;
+        if let Some(session) = recordings.sessions.get_mut(&id) {
+            println!("[zureshot] Recording {} will be appended to {}", id, existing_path);
+            session.append_to = Some(existing_path);
+        }
+        Ok::<_, ZureshotError>(id)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+/// Stop screen recording (Tauri command - called from frontend)
+#[tauri::command]
+pub async fn stop_recording(
+    app: AppHandle,
+    _state: tauri::State<'_, Mutex<RecordingState>>,
+    recording_id: Option<String>,
+) -> Result<RecordingResult, ZureshotError> {
+    // CRITICAL: Must run on a dedicated OS thread, not the Tokio async runtime.
+    // do_stop_recording() blocks on GCD completion handlers (finishWriting,
+    // stopCapture) via mpsc::channel. If this runs on a Tokio worker thread,
+    // GCD may not deliver callbacks → deadlock → moov atom never written
+    // → unplayable MP4.
+    let app_clone = app.clone();
+    Ok(tokio::task::spawn_blocking(move || do_stop_recording(&app_clone, recording_id.as_deref()))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+/// Cancel a recording and delete its file (Tauri command - called from
+/// frontend). Returns the cancelled recording's id.
+#[tauri::command]
+pub async fn cancel_recording(
+    app: AppHandle,
+    recording_id: Option<String>,
+) -> Result<String, ZureshotError> {
+    // Blocks on GCD completion handlers like stop_recording: keep it off
+    // the Tokio workers
+    let app_clone = app.clone();
+    Ok(tokio::task::spawn_blocking(move || do_cancel_recording(&app_clone, recording_id.as_deref()))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+fn session_status(id: &str, session: &RecordingSession) -> RecordingStatus {
+    RecordingStatus {
+        recording_id: Some(id.to_string()),
+        is_recording: true,
+        is_paused: session.is_paused,
+        duration_secs: session.elapsed_secs(),
+        output_path: session.output_path.clone(),
+        quality: format!("{:?}", session.quality),
+    }
+}
+
+/// Get recording status (primary recording if `recording_id` is omitted)
+#[tauri::command]
+pub fn get_recording_status(
+    state: tauri::State<'_, Mutex<RecordingState>>,
+    recording_id: Option<String>,
+) -> Result<RecordingStatus, ZureshotError> {
+    let recordings = state.lock().map_err(|e| e.to_string())?;
+
+    match recordings.resolve(recording_id.as_deref()) {
+        Some(id) => Ok(session_status(&id, &recordings.sessions[&id])),
+        None => Ok(RecordingStatus {
+            recording_id: None,
+            is_recording: false,
+            is_paused: false,
+            duration_secs: 0.0,
+            output_path: None,
+            quality: format!("{:?}", RecordingQuality::Standard),
+        }),
+    }
+}
+
+/// Status of every in-progress recording
+#[tauri::command]
+pub fn list_recordings(
+    state: tauri::State<'_, Mutex<RecordingState>>,
+) -> Result<Vec<RecordingStatus>, ZureshotError> {
+    let recordings = state.lock().map_err(|e| e.to_string())?;
+    Ok(recordings
+        .sessions
+        .iter()
+        .map(|(id, session)| session_status(id, session))
+        .collect())
+}
+
+/// Full resolved configuration of a running recording
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActiveCaptureConfig {
+    pub recording_id: String,
+    pub is_primary: bool,
+    pub output_path: Option<String>,
+    pub output_format: String,
+    pub quality: RecordingQuality,
+    /// Region as requested (after display clipping), logical points
+    pub region: Option<CaptureRegion>,
+    pub cursor_capture: platform::CursorCapture,
+    pub force_cfr: bool,
+    /// Cursor track recorded for the editor's auto-zoom (primary, macOS)
+    pub mouse_tracking: bool,
+    /// What the platform pipeline actually applied
+    pub applied: Option<platform::AppliedCaptureConfig>,
+}
+
+/// Resolved configuration of the given (or primary) recording: output
+/// size, fps, codec, bitrate and audio tracks as actually applied, for the
+/// UI and for bug reports.
+#[tauri::command]
+pub fn get_active_capture_config(
+    state: tauri::State<'_, Mutex<RecordingState>>,
+    recording_id: Option<String>,
+) -> Result<ActiveCaptureConfig, ZureshotError> {
+    let recordings = state.lock().map_err(|e| e.to_string())?;
+    let id = recordings
+        .resolve(recording_id.as_deref())
+        .ok_or_else(ZureshotError::not_recording)?;
+    let session = &recordings.sessions[&id];
+    let is_primary = recordings.primary.as_deref() == Some(id.as_str());
+    Ok(ActiveCaptureConfig {
+        recording_id: id.clone(),
+        is_primary,
+        output_path: session.output_path.clone(),
+        output_format: session.output_format.clone(),
+        quality: session.quality,
+        region: session.region.clone(),
+        cursor_capture: session.cursor_capture,
+        force_cfr: session.force_cfr,
+        mouse_tracking: cfg!(target_os = "macos") && is_primary,
+        applied: session.handle.as_ref().map(|h| h.applied_config()),
+    })
+}
+
+/// Estimate the file size of a recording so the UI can warn before a long
+/// capture ("~1.2 GB for 10 minutes"). `width`/`height` are output pixels;
+/// omit them for a full-screen recording of the main display. Codec, frame
+/// rate, bitrate override, quality factor and power mode come from the
+/// settings a recording started now would use.
+#[tauri::command]
+pub fn estimate_size(
+    app: AppHandle,
+    width: Option<u32>,
+    height: Option<u32>,
+    quality: Option<String>,
+    system_audio: Option<bool>,
+    microphone: Option<bool>,
+    duration_secs: f64,
+) -> Result<platform::SizeEstimate, ZureshotError> {
+    let q = match quality.as_deref() {
+        Some("high") => RecordingQuality::High,
+        _ => RecordingQuality::Standard,
+    };
+    let audio_tracks = system_audio.unwrap_or(false) as usize + microphone.unwrap_or(false) as usize;
+    let settings = platform::EncodingSettings {
+        quality: q,
+        codec: video_codec(&app),
+        fps: recording_fps(&app),
+        frame_rate_bounds: frame_rate_bounds(&app),
+        bitrate_override_bps: bitrate_override_bps(&app),
+        quality_factor: quality_factor(&app),
+        power_mode: crate::settings::get(&app, "power_mode").unwrap_or_default(),
+    };
+    let size = width.zip(height);
+    Ok(platform::imp::estimate_size(size, &settings, audio_tracks, duration_secs)?)
+}
+
+/// Displays available for recording (pass an `id` as `display_id`)
+#[tauri::command]
+pub fn list_displays() -> Result<Vec<platform::DisplayInfo>, ZureshotError> {
+    Ok(platform::imp::list_displays()?)
+}
+
+/// Windows available for recording (pass an `id` to `record_window`)
+#[tauri::command]
+pub async fn list_windows() -> Result<Vec<platform::WindowInfo>, ZureshotError> {
+    // Blocks on the SCShareableContent completion handler
+    Ok(tokio::task::spawn_blocking(platform::imp::list_windows)
+        .await
+        .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+/// Open the recorded file in the system file manager
+#[tauri::command]
+pub async fn reveal_in_finder(path: String) -> Result<(), ZureshotError> {
+    Ok(platform::imp::reveal_file(&path)?)
+}
+
+/// Folder for a new recording: the `output_dir` override when it's usable,
+/// else the recordings folder (with a `recording-warning`).
+fn output_dir_or_default(app: &AppHandle, output_dir: Option<&str>) -> std::path::PathBuf {
+    if let Some(dir) = output_dir.map(str::trim).filter(|d| !d.is_empty()) {
+        let dir = std::path::PathBuf::from(dir);
+        match crate::settings::check_writable_dir(&dir) {
+            Ok(()) => return dir,
+            Err(e) => {
+                let msg = format!("Can't save to the chosen folder ({}) — saving to the recordings folder instead.", e);
+                println!("[zureshot] {}", msg);
+                let _ = app.emit("recording-warning", &msg);
+            }
+        }
+    }
+    crate::settings::recordings_dir(app)
+}
+
+/// Get the current recordings directory
+#[tauri::command]
+pub fn get_recordings_dir(app: AppHandle) -> String {
+    let zureshot_dir = crate::settings::recordings_dir(&app);
+    zureshot_dir.to_string_lossy().to_string()
+}
+
+/// Change the recordings directory (persisted).
+///
+/// Takes effect from the next recording or screenshot. A recording that is
+/// already in progress keeps writing to the path it was started with.
+/// Pass an empty string to go back to the default `~/Downloads/Zureshot`.
+#[tauri::command]
+pub fn set_recordings_dir(app: AppHandle, path: String) -> Result<String, ZureshotError> {
+    Ok(do_set_recordings_dir(&app, &path)?)
+}
+
+/// Core logic of `set_recordings_dir` (also used by the tray's folder picker).
+pub fn do_set_recordings_dir(app: &AppHandle, path: &str) -> Result<String, String> {
+    let path = path.trim().to_string();
+    if !path.is_empty() {
+        crate::settings::check_writable_dir(std::path::Path::new(&path))
+            .map_err(|e| format!("Cannot use {} as recordings folder: {}", path, e))?;
+    }
+    crate::settings::set(app, "recordings_dir", serde_json::json!(path));
+    let dir = crate::settings::recordings_dir(app).to_string_lossy().to_string();
+
+    let state = app.state::<Mutex<RecordingState>>();
+    let recordings = state.lock().map_err(|e| e.to_string())?;
+    if let Some(recording) = recordings.primary() {
+        println!(
+            "[zureshot] Recordings dir changed to {} — current recording stays at {}",
+            dir,
+            recording.output_path.as_deref().unwrap_or("?")
+        );
+    } else {
+        println!("[zureshot] Recordings dir changed to {}", dir);
+    }
+    Ok(dir)
+}
+
+/// Whether strict playback compatibility is on (see `set_compatibility_mode`)
+#[tauri::command]
+pub fn get_compatibility_mode(app: AppHandle) -> bool {
+    crate::settings::get_bool(&app, "compatibility_mode", false)
+}
+
+/// Prefer universally playable recordings over smaller files (persisted).
+/// On Linux, an HEVC stream that Safari/QuickTime can't play is replaced
+/// by H.264 at recording start.
+#[tauri::command]
+pub fn set_compatibility_mode(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "compatibility_mode", serde_json::json!(enabled));
+    println!("[zureshot] Compatibility mode: {}", enabled);
+}
+
+/// Whether recordings are tagged with the display's color space
+#[tauri::command]
+pub fn get_match_display_color(app: AppHandle) -> bool {
+    crate::settings::get_bool(&app, "match_display_color", false)
+}
+
+/// Tag recordings with the captured display's color space (Display P3 on
+/// wide-gamut screens) instead of BT.709, and save the display's ICC
+/// profile next to the file (persisted, macOS only).
+#[tauri::command]
+pub fn set_match_display_color(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "match_display_color", serde_json::json!(enabled));
+    println!("[zureshot] Match display color: {}", enabled);
+}
+
+#[tauri::command]
+pub fn get_color_space(app: AppHandle) -> Option<platform::ColorSpace> {
+    crate::settings::get(&app, "color_space")
+}
+
+/// Force the capture/tag color space (`None` = BT.709 or, with
+/// `match_display_color`, the display's). Display P3 records 10-bit with
+/// HEVC or ProRes (persisted, macOS only).
+#[tauri::command]
+pub fn set_color_space(app: AppHandle, space: Option<platform::ColorSpace>) {
+    crate::settings::set(&app, "color_space", serde_json::json!(space));
+    println!("[zureshot] Color space: {:?}", space);
+}
+
+/// How the cursor is captured (`system` or `standard`)
+#[tauri::command]
+pub fn get_cursor_capture(app: AppHandle) -> platform::CursorCapture {
+    crate::settings::get(&app, "cursor_capture").unwrap_or_default()
+}
+
+/// `standard` hides the (possibly accessibility-enlarged) system cursor and
+/// has the editor draw a standard-size one from the mouse track; `system`
+/// keeps the cursor exactly as the user sees it (persisted).
+#[tauri::command]
+pub fn set_cursor_capture(app: AppHandle, mode: platform::CursorCapture) {
+    crate::settings::set(&app, "cursor_capture", serde_json::json!(mode));
+    println!("[zureshot] Cursor capture: {:?}", mode);
+}
+
+/// Whether recordings show the cursor at all (`show_cursor`, default on).
+pub fn show_cursor(app: &AppHandle) -> bool {
+    crate::settings::get_bool(app, "show_cursor", true)
+}
+
+#[tauri::command]
+pub fn get_show_cursor(app: AppHandle) -> bool {
+    show_cursor(&app)
+}
+
+/// Off hides the cursor from new recordings entirely, for demos that
+/// shouldn't show it (persisted). `cursor_capture` only applies when on.
+#[tauri::command]
+pub fn set_show_cursor(app: AppHandle, show: bool) {
+    crate::settings::set(&app, "show_cursor", serde_json::json!(show));
+    println!("[zureshot] Show cursor: {}", show);
+}
+
+/// Current battery behavior (`auto` or `max_quality`)
+#[tauri::command]
+pub fn get_power_mode(app: AppHandle) -> platform::PowerMode {
+    crate::settings::get(&app, "power_mode").unwrap_or_default()
+}
+
+/// Choose battery behavior (persisted; `max_quality` unless set). `auto`
+/// trades quality for battery life while unplugged; applies to running
+/// recordings immediately.
+#[tauri::command]
+pub fn set_power_mode(app: AppHandle, mode: platform::PowerMode) {
+    crate::settings::set(&app, "power_mode", serde_json::json!(mode));
+    println!("[zureshot] Power mode: {:?}", mode);
+    #[cfg(target_os = "macos")]
+    apply_power_state(&app, platform::macos::power::on_battery());
+}
+
+/// React to an AC ⇄ battery change: cap or restore the frame rate of running
+/// recordings and the camera preview rate, per the power mode.
+#[cfg(target_os = "macos")]
+pub(crate) fn apply_power_state(app: &AppHandle, on_battery: bool) {
+    let mode: platform::PowerMode = crate::settings::get(app, "power_mode").unwrap_or_default();
+    let saver = mode == platform::PowerMode::Auto && on_battery;
+    platform::macos::power::set_reduced_preview(saver);
+
+    let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+    if let Ok(recordings) = state.lock() {
+        for handle in recordings.sessions.values().filter_map(|s| s.handle.as_ref()) {
+            handle.set_battery_saver(saver);
+        }
+    }
+    let _ = app.emit(
+        "power-source-changed",
+        serde_json::json!({ "on_battery": on_battery, "battery_saver": saver }),
+    );
+}
+
+/// Whether recordings get a `.timestamps.json` frame timing sidecar
+#[tauri::command]
+pub fn get_write_timestamps(app: AppHandle) -> bool {
+    crate::settings::get_bool(&app, "write_timestamps", false)
+}
+
+/// Write per-frame PTS + wall-clock times next to each recording, for
+/// aligning video with logs or sensor data (persisted, macOS only).
+#[tauri::command]
+pub fn set_write_timestamps(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "write_timestamps", serde_json::json!(enabled));
+    println!("[zureshot] Frame timestamps sidecar: {}", enabled);
+}
+
+/// Whether recordings are made constant frame rate
+#[tauri::command]
+pub fn get_force_cfr(app: AppHandle) -> bool {
+    crate::settings::get_bool(&app, "force_cfr", false)
+}
+
+/// Force constant frame rate output for editors/uploaders that mishandle
+/// VFR (persisted). macOS re-times the file when recording stops.
+#[tauri::command]
+pub fn set_force_cfr(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "force_cfr", serde_json::json!(enabled));
+    println!("[zureshot] Force constant frame rate: {}", enabled);
+}
+
+/// Frame-rate bounds for new recordings (persisted as `frame_rate_bounds`;
+/// invalid stored values are ignored).
+pub fn frame_rate_bounds(app: &AppHandle) -> platform::FrameRateBounds {
+    crate::settings::get::<platform::FrameRateBounds>(app, "frame_rate_bounds")
+        .filter(|b| b.validate().is_ok())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn get_frame_rate_bounds(app: AppHandle) -> platform::FrameRateBounds {
+    frame_rate_bounds(&app)
+}
+
+/// Bound the capture frame rate independently of the quality preset, e.g.
+/// `max_fps: 5` for slide-style recordings (persisted, applies to the next
+/// recording).
+#[tauri::command]
+pub fn set_frame_rate_bounds(app: AppHandle, bounds: platform::FrameRateBounds) -> Result<(), ZureshotError> {
+    bounds.validate()?;
+    crate::settings::set(&app, "frame_rate_bounds", serde_json::json!(bounds));
+    println!(
+        "[zureshot] Frame rate bounds: max={}, min={}",
+        bounds.max_fps.map_or("preset".to_string(), |f| format!("{}fps", f)),
+        bounds.min_fps.map_or("none".to_string(), |f| format!("{}fps", f))
+    );
+    Ok(())
+}
+
+/// Explicit frame rate for new recordings (persisted as `recording_fps`;
+/// `None` = the quality preset's 30/60).
+pub fn recording_fps(app: &AppHandle) -> Option<i32> {
+    crate::settings::get::<i32>(app, "recording_fps").filter(|&f| platform::validate_fps(f).is_ok())
+}
+
+#[tauri::command]
+pub fn get_recording_fps(app: AppHandle) -> Option<i32> {
+    recording_fps(&app)
+}
+
+/// Record at e.g. 24, 50 or 120fps instead of the Standard/High preset
+/// rate. Above the display's refresh rate the capture is capped to it.
+#[tauri::command]
+pub fn set_recording_fps(app: AppHandle, fps: Option<i32>) -> Result<(), ZureshotError> {
+    if let Some(fps) = fps {
+        platform::validate_fps(fps)?;
+    }
+    crate::settings::set(&app, "recording_fps", serde_json::json!(fps));
+    println!(
+        "[zureshot] Recording frame rate: {}",
+        fps.map_or("preset".to_string(), |f| format!("{}fps", f))
+    );
+    Ok(())
+}
+
+/// Fixed video bitrate for new recordings (persisted as
+/// `bitrate_override_bps`; `None` = pick from resolution and quality).
+pub fn bitrate_override_bps(app: &AppHandle) -> Option<i64> {
+    crate::settings::get::<i64>(app, "bitrate_override_bps")
+        .filter(|bps| *bps >= platform::MIN_BITRATE_OVERRIDE_BPS)
+}
+
+#[tauri::command]
+pub fn get_bitrate_override_bps(app: AppHandle) -> Option<i64> {
+    bitrate_override_bps(&app)
+}
+
+/// Record at exactly `bps` bits/s instead of the automatic bitrate, or pass
+/// `None` to go back to automatic (persisted, applies to the next recording).
+#[tauri::command]
+pub fn set_bitrate_override_bps(app: AppHandle, bps: Option<i64>) -> Result<(), ZureshotError> {
+    if let Some(bps) = bps {
+        if bps < platform::MIN_BITRATE_OVERRIDE_BPS {
+            return Err(format!(
+                "Bitrate override must be at least {} bps, got {}",
+                platform::MIN_BITRATE_OVERRIDE_BPS, bps
+            ).into());
+        }
+    }
+    crate::settings::set(&app, "bitrate_override_bps", serde_json::json!(bps));
+    println!(
+        "[zureshot] Bitrate override: {}",
+        bps.map_or("automatic".to_string(), |b| format!("{} bps", b))
+    );
+    Ok(())
+}
+
+/// Encoder quality for new recordings (persisted as `quality_factor`;
+/// `None` = the quality preset's).
+pub fn quality_factor(app: &AppHandle) -> Option<f64> {
+    crate::settings::get::<f64>(app, "quality_factor")
+        .filter(|q| q.is_finite() && (0.0..=1.0).contains(q))
+}
+
+#[tauri::command]
+pub fn get_quality_factor(app: AppHandle) -> Option<f64> {
+    quality_factor(&app)
+}
+
+/// Encode at `factor` (0.0 – 1.0) instead of the Standard/High preset's
+/// quality, or pass `None` to follow the preset again (persisted, applies
+/// to the next recording).
+#[tauri::command]
+pub fn set_quality_factor(app: AppHandle, factor: Option<f64>) -> Result<(), ZureshotError> {
+    if let Some(q) = factor {
+        if !q.is_finite() || !(0.0..=1.0).contains(&q) {
+            return Err(format!("Quality factor must be between 0.0 and 1.0, got {}", q).into());
+        }
+    }
+    crate::settings::set(&app, "quality_factor", serde_json::json!(factor));
+    println!(
+        "[zureshot] Quality factor: {}",
+        factor.map_or("preset".to_string(), |q| format!("{:.2}", q))
+    );
+    Ok(())
+}
+
+/// Output rotation for new recordings (`None` = follow the display).
+#[tauri::command]
+pub fn get_rotation(app: AppHandle) -> Option<platform::Rotation> {
+    crate::settings::get(&app, "rotation")
+}
+
+/// Rotate new recordings for a sideways-mounted display, or pass `None` to
+/// follow the display's detected orientation (persisted).
+#[tauri::command]
+pub fn set_rotation(app: AppHandle, rotation: Option<platform::Rotation>) {
+    crate::settings::set(&app, "rotation", serde_json::json!(rotation));
+    println!("[zureshot] Output rotation: {:?}", rotation);
+}
+
+/// Longest gap between keyframes in new recordings (persisted as
+/// `keyframe_interval_secs`, clamped to `KEYFRAME_INTERVAL_RANGE`).
+pub fn keyframe_interval_secs(app: &AppHandle) -> f64 {
+    platform::clamp_keyframe_interval(crate::settings::get_f64(
+        app,
+        "keyframe_interval_secs",
+        platform::DEFAULT_KEYFRAME_INTERVAL_SECS,
+    ))
+}
+
+#[tauri::command]
+pub fn get_keyframe_interval_secs(app: AppHandle) -> f64 {
+    keyframe_interval_secs(&app)
+}
+
+/// Set the keyframe interval for the next recording (persisted). Out-of-range
+/// values are clamped; returns the interval that will be used.
+#[tauri::command]
+pub fn set_keyframe_interval_secs(app: AppHandle, secs: f64) -> f64 {
+    let secs = platform::clamp_keyframe_interval(secs);
+    crate::settings::set(&app, "keyframe_interval_secs", serde_json::json!(secs));
+    println!("[zureshot] Keyframe interval: {}s", secs);
+    secs
+}
+
+/// How often a display recording checks whether the windows it must
+/// exclude changed
+#[cfg(target_os = "macos")]
+const EXCLUSION_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Keep a display recording's content filter current. It excludes a
+/// snapshot of windows, so a window opened later by an app in
+/// `privacy_exclusions` (or a new window of ours) would otherwise be
+/// recorded. Stops with the recording.
+#[cfg(target_os = "macos")]
+fn spawn_exclusion_watch(app: &AppHandle, recording_id: String, path: String, display_id: Option<u32>) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut excluded = platform::macos::excluded_window_ids(&app, display_id).unwrap_or_default();
+        loop {
+            std::thread::sleep(EXCLUSION_WATCH_INTERVAL);
+            let Ok(current) = platform::macos::excluded_window_ids(&app, display_id) else { continue };
+            if current == excluded {
+                continue;
+            }
+            // Clone the handle and release the mutex: the refresh blocks on
+            // SCK completion handlers
+            let handle = {
+                let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+                let Ok(recordings) = state.lock() else { return };
+                match recordings.sessions.get(&recording_id) {
+                    Some(s) if s.output_path.as_deref() == Some(path.as_str()) => s.handle.clone(),
+                    _ => return,
+                }
+            };
+            if let Some(handle) = handle {
+                match handle.refresh_exclusion(&app) {
+                    Ok(()) => excluded = current,
+                    // Retried next interval (a new window may not be listed yet)
+                    Err(e) => eprintln!("[zureshot] Exclusion refresh failed: {}", e),
+                }
+            }
+        }
+    });
+}
+
+/// How often a window recording's target is brought back to the front
+const KEEP_IN_FRONT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Whether recordings with system audio turn off UI sound effects (Trash,
+/// screenshot shutter, alert sounds…) while they run. Physical keyboard
+/// clicks picked up by the microphone are not affected.
+pub fn mute_ui_sounds(app: &AppHandle) -> bool {
+    crate::settings::get_bool(app, "mute_ui_sounds", false)
+}
+
+#[tauri::command]
+pub fn get_mute_ui_sounds(app: AppHandle) -> bool {
+    mute_ui_sounds(&app)
+}
+
+/// Mute UI sound effects during recordings with system audio (persisted,
+/// applies to the next recording).
+#[tauri::command]
+pub fn set_mute_ui_sounds(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "mute_ui_sounds", serde_json::json!(enabled));
+    println!("[zureshot] Mute UI sounds while recording: {}", enabled);
+}
+
+/// Turn the system's UI sounds off for a recording. Only touches them when
+/// they were on, and remembers that in settings (`ui_sounds_muted`) so a
+/// crash mid-recording is undone on the next launch.
+fn mute_ui_sounds_now(app: &AppHandle) {
+    if platform::imp::ui_sounds_enabled() != Some(true) {
+        return;
+    }
+    match platform::imp::set_ui_sounds_enabled(false) {
+        Ok(()) => {
+            crate::settings::set(app, "ui_sounds_muted", serde_json::json!(true));
+            println!("[zureshot] UI sounds muted for the recording");
+        }
+        Err(e) => eprintln!("[zureshot] {}", e),
+    }
+}
+
+/// Turn UI sounds back on if `mute_ui_sounds_now` turned them off.
+pub fn restore_ui_sounds(app: &AppHandle) {
+    if !crate::settings::get_bool(app, "ui_sounds_muted", false) {
+        return;
+    }
+    match platform::imp::set_ui_sounds_enabled(true) {
+        Ok(()) => println!("[zureshot] UI sounds restored"),
+        Err(e) => eprintln!("[zureshot] {}", e),
+    }
+    crate::settings::set(app, "ui_sounds_muted", serde_json::json!(false));
+}
+
+/// Whether window recordings periodically raise their target window
+/// (opt-in: it takes focus away from whatever covered the window).
+pub fn keep_window_in_front(app: &AppHandle) -> bool {
+    crate::settings::get_bool(app, "keep_window_in_front", false)
+}
+
+/// Raise `window_id` every `KEEP_IN_FRONT_INTERVAL` until the recording
+/// (identified by id + output path) stops or the window goes away.
+/// Skipped while paused.
+fn spawn_keep_window_in_front(app: &AppHandle, recording_id: String, path: String, window_id: u32) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(KEEP_IN_FRONT_INTERVAL);
+        let paused = {
+            let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+            let Ok(recordings) = state.lock() else { return };
+            match recordings.sessions.get(&recording_id) {
+                Some(s) if s.output_path.as_deref() == Some(path.as_str()) => s.is_paused,
+                _ => return,
+            }
+        };
+        if paused {
+            continue;
+        }
+        if let Err(e) = platform::imp::raise_window(window_id) {
+            eprintln!("[zureshot] Keep window in front stopped: {}", e);
+            return;
+        }
+    });
+}
+
+/// How often the auto-stop timer re-checks the recorded time. Pauses can
+/// extend the deadline at any point, so it polls instead of sleeping once.
+const AUTO_STOP_POLL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Maximum recording length for new (non-GIF) recordings, in seconds
+/// (persisted as `max_duration_secs`; `None` = unlimited).
+pub fn max_duration_secs(app: &AppHandle) -> Option<f64> {
+    crate::settings::get::<f64>(app, "max_duration_secs").filter(|s| s.is_finite() && *s > 0.0)
+}
+
+#[tauri::command]
+pub fn get_max_duration_secs(app: AppHandle) -> Option<f64> {
+    max_duration_secs(&app)
+}
+
+/// Stop recordings automatically after `secs` of recorded time, or pass
+/// `None` for unlimited (persisted, applies to the next recording).
+#[tauri::command]
+pub fn set_max_duration_secs(app: AppHandle, secs: Option<f64>) -> Result<(), ZureshotError> {
+    if let Some(secs) = secs {
+        if !secs.is_finite() || secs < 1.0 {
+            return Err(format!("Max duration must be at least 1 second (got {})", secs).into());
+        }
+    }
+    crate::settings::set(&app, "max_duration_secs", serde_json::json!(secs));
+    println!(
+        "[zureshot] Max recording duration: {}",
+        secs.map_or("unlimited".to_string(), |s| format!("{:.0}s", s))
+    );
+    Ok(())
+}
+
+/// Longest `trim_head_secs` / `trim_tail_secs`.
+const MAX_TRIM_SECS: f64 = 60.0;
+
+/// Trim-on-stop setting `key` in seconds (0 = off).
+fn trim_secs(app: &AppHandle, key: &str) -> f64 {
+    crate::settings::get_f64(app, key, 0.0).clamp(0.0, MAX_TRIM_SECS)
+}
+
+fn set_trim_secs(app: &AppHandle, key: &str, secs: f64) -> Result<(), String> {
+    if !(0.0..=MAX_TRIM_SECS).contains(&secs) {
+        return Err(format!("Trim must be between 0 and {} seconds (got {})", MAX_TRIM_SECS, secs));
+    }
+    crate::settings::set(app, key, serde_json::json!(secs));
+    println!("[zureshot] {}: {:.1}s", key, secs);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_trim_head_secs(app: AppHandle) -> f64 {
+    trim_secs(&app, "trim_head_secs")
+}
+
+/// Cut `secs` off the start of every recording when it stops, e.g. the
+/// moment after a countdown (0 = off; persisted, applies to the next
+/// recording). Needs ffmpeg; without it recordings are kept untrimmed.
+#[tauri::command]
+pub fn set_trim_head_secs(app: AppHandle, secs: f64) -> Result<(), ZureshotError> {
+    Ok(set_trim_secs(&app, "trim_head_secs", secs)?)
+}
+
+#[tauri::command]
+pub fn get_trim_tail_secs(app: AppHandle) -> f64 {
+    trim_secs(&app, "trim_tail_secs")
+}
+
+/// Cut `secs` off the end of every recording when it stops — the reach
+/// for the stop button (0 = off; persisted, applies to the next
+/// recording). Needs ffmpeg; without it recordings are kept untrimmed.
+#[tauri::command]
+pub fn set_trim_tail_secs(app: AppHandle, secs: f64) -> Result<(), ZureshotError> {
+    Ok(set_trim_secs(&app, "trim_tail_secs", secs)?)
+}
+
+/// Payload emitted with `recording-paused` on pause and resume.
+#[derive(Clone, Serialize)]
+struct RecordingPausedPayload {
+    recording_id: String,
+    paused: bool,
+    /// The recording that owns the bar, overlay and tray state
+    is_primary: bool,
+}
+
+/// Tell the UI a recording was paused or resumed: the overlay shows its
+/// watermark and the tray tooltip follows the primary recording.
+fn notify_pause_state(app: &AppHandle, recording_id: String, paused: bool, is_primary: bool) {
+    let _ = app.emit("recording-paused", RecordingPausedPayload { recording_id, paused, is_primary });
+    if is_primary {
+        if paused {
+            crate::tray::notify_recording_paused(app);
+        } else {
+            crate::tray::notify_recording_resumed(app);
+        }
+    }
+}
+
+/// Payload emitted with `recording-auto-stopped`.
+#[derive(Clone, Serialize)]
+struct RecordingAutoStoppedPayload {
+    recording_id: String,
+    path: String,
+    max_duration_secs: f64,
+}
+
+/// Stop the recording writing `path` once it has `limit` seconds of
+/// unpaused time. Exits quietly when the recording is stopped (or replaced
+/// by another one with the same ID) before the limit.
+fn spawn_auto_stop(app: &AppHandle, recording_id: String, path: String, limit: f64) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(AUTO_STOP_POLL);
+            let elapsed = {
+                let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+                let Ok(recordings) = state.lock() else { return };
+                match recordings.sessions.get(&recording_id) {
+                    Some(s) if s.output_path.as_deref() == Some(path.as_str()) => s.elapsed_secs(),
+                    _ => return,
+                }
+            };
+            if elapsed >= limit {
+                break;
+            }
+        }
+
+        println!("[zureshot] Recording {} reached its {:.0}s limit, stopping", recording_id, limit);
+        let _ = app.emit(
+            "recording-auto-stopped",
+            &RecordingAutoStoppedPayload {
+                recording_id: recording_id.clone(),
+                path: path.clone(),
+                max_duration_secs: limit,
+            },
+        );
+        // A manual stop may have won the race since the last check
+        match do_stop_recording(&app, Some(&recording_id)) {
+            Ok(result) => println!(
+                "[zureshot] Auto-stopped recording saved: {} ({:.1}s)",
+                result.path, result.duration_secs
+            ),
+            Err(e) => eprintln!("[zureshot] Auto-stop failed: {}", e),
+        }
+    });
+}
+
+/// Fragment/checkpoint interval for new recordings, in seconds (persisted as
+/// `checkpoint_interval_secs`; `None` = off). Costs one small fragment header
+/// and a disk sync per interval.
+pub fn checkpoint_interval_secs(app: &AppHandle) -> Option<f64> {
+    crate::settings::get::<f64>(app, "checkpoint_interval_secs")
+        .and_then(platform::clamp_checkpoint_interval)
+}
+
+#[tauri::command]
+pub fn get_checkpoint_interval_secs(app: AppHandle) -> Option<f64> {
+    checkpoint_interval_secs(&app)
+}
+
+/// Write new recordings as fragmented MP4 with a durable checkpoint every
+/// `secs` (clamped to `CHECKPOINT_INTERVAL_RANGE`), or pass `None` to turn
+/// checkpoints off. Returns the interval that will be used.
+#[tauri::command]
+pub fn set_checkpoint_interval_secs(app: AppHandle, secs: Option<f64>) -> Option<f64> {
+    let secs = secs.and_then(platform::clamp_checkpoint_interval);
+    crate::settings::set(&app, "checkpoint_interval_secs", serde_json::json!(secs));
+    println!(
+        "[zureshot] Checkpoint interval: {}",
+        secs.map_or("off".to_string(), |s| format!("{}s", s))
+    );
+    secs
+}
+
+/// Payload emitted with `recording-checkpoint`.
+#[derive(Clone, Serialize)]
+struct RecordingCheckpointPayload {
+    recording_id: String,
+    path: String,
+    /// Recorded time up to which the file is complete on disk
+    durable_secs: f64,
+    /// Bytes of the file that hold complete fragments
+    bytes: u64,
+}
+
+/// Emit `recording-checkpoint` each time another fragment of the recording
+/// writing `path` is complete on disk (synced, with its `moof` and `mdat`
+/// both whole). The writer cuts a fragment every `interval` seconds, so `k`
+/// complete fragments hold the first `k * interval` seconds. Exits when the
+/// recording stops (or is replaced by another one with the same ID).
+fn spawn_checkpoints(app: &AppHandle, recording_id: String, path: String, interval: f64) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut reported = 0usize;
+        loop {
+            std::thread::sleep(AUTO_STOP_POLL);
+            {
+                let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+                let Ok(recordings) = state.lock() else { return };
+                match recordings.sessions.get(&recording_id) {
+                    Some(s) if s.output_path.as_deref() == Some(path.as_str()) => {}
+                    _ => return,
+                }
+            }
+            // Make the fragments durable, not just handed to the page cache
+            let synced = std::fs::File::open(&path).and_then(|f| f.sync_data());
+            let progress = synced.and_then(|_| complete_fragments(std::path::Path::new(&path)));
+            let (fragments, bytes) = match progress {
+                Ok(progress) => progress,
+                Err(e) => {
+                    eprintln!("[zureshot] Checkpoint sync failed for {}: {}", path, e);
+                    continue;
+                }
+            };
+            if fragments <= reported {
+                continue;
+            }
+            reported = fragments;
+            let _ = app.emit(
+                "recording-checkpoint",
+                &RecordingCheckpointPayload {
+                    recording_id: recording_id.clone(),
+                    path: path.clone(),
+                    durable_secs: fragments as f64 * interval,
+                    bytes,
+                },
+            );
+        }
+    });
+}
+
+/// How often `recording-stats` is emitted
+const RECORDING_STATS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Payload emitted with `recording-stats`.
+#[derive(Clone, Serialize)]
+struct RecordingStatsPayload {
+    recording_id: String,
+    frames: u64,
+    dropped: u64,
+    /// Frames written per second over the last interval
+    fps: f64,
+    pts_skip_count: u64,
+}
+
+/// Emit `recording-stats` once a second while the recording writing `path`
+/// runs (not while paused), so the bar can show dropped frames live.
+fn spawn_recording_stats(app: &AppHandle, recording_id: String, path: String) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut last: Option<(u64, std::time::Instant)> = None;
+        loop {
+            std::thread::sleep(RECORDING_STATS_INTERVAL);
+            let stats = {
+                let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+                let Ok(recordings) = state.lock() else { return };
+                match recordings.sessions.get(&recording_id) {
+                    Some(s) if s.output_path.as_deref() == Some(path.as_str()) => {
+                        if s.is_paused {
+                            last = None;
+                            continue;
+                        }
+                        match s.handle {
+                            Some(ref h) => h.frame_stats(),
+                            None => continue,
+                        }
+                    }
+                    _ => return,
+                }
+            };
+            let now = std::time::Instant::now();
+            let fps = match last {
+                Some((frames, at)) => {
+                    stats.frames.saturating_sub(frames) as f64 / now.duration_since(at).as_secs_f64()
+                }
+                None => 0.0,
+            };
+            last = Some((stats.frames, now));
+            let _ = app.emit(
+                "recording-stats",
+                &RecordingStatsPayload {
+                    recording_id: recording_id.clone(),
+                    frames: stats.frames,
+                    dropped: stats.dropped,
+                    fps,
+                    pts_skip_count: stats.pts_skips,
+                },
+            );
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_keep_window_in_front(app: AppHandle) -> bool {
+    keep_window_in_front(&app)
+}
+
+/// Keep the recorded window in front during window recordings (persisted,
+/// applies to the next window recording).
+#[tauri::command]
+pub fn set_keep_window_in_front(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "keep_window_in_front", serde_json::json!(enabled));
+    println!("[zureshot] Keep recorded window in front: {}", enabled);
+}
+
+/// Formats a recording can be started with
+const OUTPUT_FORMATS: [&str; 2] = ["video", "gif"];
+
+/// Output format used when a recording is started without one (persisted
+/// as `default_format`).
+pub fn default_format(app: &AppHandle) -> String {
+    crate::settings::get::<String>(app, "default_format")
+        .filter(|f| OUTPUT_FORMATS.contains(&f.as_str()))
+        .unwrap_or_else(|| "video".to_string())
+}
+
+/// Core logic to change the default output format. Running recordings keep
+/// the format they started with.
+pub fn do_set_default_format(app: &AppHandle, format: &str) -> Result<(), String> {
+    if !OUTPUT_FORMATS.contains(&format) {
+        return Err(format!(
+            "Unknown output format '{}' (expected one of: {})",
+            format,
+            OUTPUT_FORMATS.join(", ")
+        ));
+    }
+    crate::settings::set(app, "default_format", serde_json::json!(format));
+    println!("[zureshot] Default output format: {}", format);
+    let _ = app.emit("default-format-changed", format);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_default_format(app: AppHandle) -> String {
+    default_format(&app)
+}
+
+/// Set the output format for subsequent recordings ("video" or "gif").
+#[tauri::command]
+pub fn set_default_format(app: AppHandle, format: String) -> Result<(), ZureshotError> {
+    do_set_default_format(&app, &format)?;
+    crate::tray::refresh_menu(&app);
+    Ok(())
+}
+
+/// Video codec for new recordings (persisted as `video_codec`).
+pub fn video_codec(app: &AppHandle) -> platform::VideoCodec {
+    let codec = crate::settings::get(app, "video_codec").unwrap_or_default();
+    // ProRes needs AVFoundation; fall back to the default elsewhere
+    if cfg!(not(target_os = "macos")) && codec == platform::VideoCodec::ProRes422 {
+        return platform::VideoCodec::default();
+    }
+    codec
+}
+
+/// Core logic to change the codec. Running recordings keep theirs.
+pub fn do_set_video_codec(app: &AppHandle, codec: platform::VideoCodec) {
+    crate::settings::set(app, "video_codec", serde_json::json!(codec));
+    println!("[zureshot] Video codec: {:?}", codec);
+}
+
+#[tauri::command]
+pub fn get_video_codec(app: AppHandle) -> platform::VideoCodec {
+    video_codec(&app)
+}
+
+/// Record HEVC (smaller), H.264 (plays everywhere) or ProRes 422 (editing,
+/// macOS only) from the next recording on.
+#[tauri::command]
+pub fn set_video_codec(app: AppHandle, codec: platform::VideoCodec) {
+    do_set_video_codec(&app, codec);
+    crate::tray::refresh_menu(&app);
+}
+
+/// Title/end cards added to new recordings
+#[tauri::command]
+pub fn get_intro_outro(app: AppHandle) -> platform::IntroOutroConfig {
+    crate::settings::get(&app, "intro_outro").unwrap_or_default()
+}
+
+/// Set the title/end card images (persisted, applies when the next
+/// recording stops). Clear both images to turn cards off.
+#[tauri::command]
+pub fn set_intro_outro(app: AppHandle, config: platform::IntroOutroConfig) -> Result<(), ZureshotError> {
+    if config.is_enabled() {
+        config.validate()?;
+    }
+    crate::settings::set(&app, "intro_outro", serde_json::json!(config));
+    println!("[zureshot] Intro/outro cards: {:?}", config);
+    Ok(())
+}
+
+/// Tauri command: which ffmpeg filters/encoders are available (cached;
+/// `refresh` re-runs the probe)
+#[tauri::command]
+pub async fn probe_ffmpeg(refresh: Option<bool>) -> Result<crate::ffmpeg::FfmpegCapabilities, ZureshotError> {
+    Ok(tokio::task::spawn_blocking(move || crate::ffmpeg::probe(refresh.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("ffmpeg probe failed: {}", e))?)
+}
+
+/// Configured ffmpeg binary (empty = `ffmpeg` from PATH)
+#[tauri::command]
+pub fn get_ffmpeg_path(app: AppHandle) -> String {
+    crate::settings::get(&app, "ffmpeg_path").unwrap_or_default()
+}
+
+/// Use a specific ffmpeg binary for all conversions (persisted). Returns
+/// the fresh probe of the new binary.
+#[tauri::command]
+pub async fn set_ffmpeg_path(app: AppHandle, path: String) -> Result<crate::ffmpeg::FfmpegCapabilities, ZureshotError> {
+    let path = path.trim().to_string();
+    if !path.is_empty() && !std::path::Path::new(&path).is_file() {
+        return Err(format!("ffmpeg not found at {}", path).into());
+    }
+    crate::ffmpeg::set_path(&app, &path);
+    println!("[zureshot] ffmpeg path: {}", if path.is_empty() { "(PATH)" } else { &path });
+    probe_ffmpeg(Some(true)).await
+}
+
+/// Recording warm-up: the stream runs briefly before the writer session
+/// starts so the first second isn't soft/stuttering (persisted)
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct WarmupSettings {
+    pub enabled: bool,
+    /// Fixed warm-up length; `None` = automatic (measured first-frame latency)
+    #[serde(default)]
+    pub duration_ms: Option<f64>,
+}
+
+/// Automatic warm-up bounds and fallback before anything was measured
+const WARMUP_MIN_MS: f64 = 100.0;
+const WARMUP_MAX_MS: f64 = 1000.0;
+const WARMUP_DEFAULT_MS: f64 = 250.0;
+
+/// Warm-up to apply to the next recording, in seconds (0 = off).
+fn warmup_secs(app: &AppHandle) -> f64 {
+    let settings = get_recording_warmup(app.clone());
+    if !settings.enabled {
+        return 0.0;
+    }
+    let ms = settings.duration_ms.unwrap_or_else(|| {
+        // The pipeline ramps up for about as long as it takes to deliver a frame
+        crate::settings::get_f64(app, "measured_first_frame_ms", WARMUP_DEFAULT_MS)
+            .clamp(WARMUP_MIN_MS, WARMUP_MAX_MS)
+    });
+    ms / 1000.0
+}
+
+/// Current warm-up settings
+#[tauri::command]
+pub fn get_recording_warmup(app: AppHandle) -> WarmupSettings {
+    WarmupSettings {
+        enabled: crate::settings::get_bool(&app, "warmup_enabled", false),
+        duration_ms: crate::settings::get(&app, "warmup_ms"),
+    }
+}
+
+/// Enable/disable the recording warm-up and optionally fix its length
+/// (applies from the next recording)
+#[tauri::command]
+pub fn set_recording_warmup(app: AppHandle, settings: WarmupSettings) -> Result<(), ZureshotError> {
+    if let Some(ms) = settings.duration_ms {
+        if !ms.is_finite() || !(0.0..=WARMUP_MAX_MS).contains(&ms) {
+            return Err(format!("Warm-up must be between 0 and {}ms (got {})", WARMUP_MAX_MS, ms).into());
+        }
+    }
+    crate::settings::set(&app, "warmup_enabled", serde_json::json!(settings.enabled));
+    crate::settings::set(&app, "warmup_ms", serde_json::json!(settings.duration_ms));
+    println!("[zureshot] Recording warm-up: {:?}", settings);
+    Ok(())
+}
+
+/// On-screen keystroke overlay for tutorials (persisted as
+/// `show_keystrokes` / `keystroke_fade_secs`)
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct KeystrokeOverlaySettings {
+    pub show_keystrokes: bool,
+    /// How long a key stays on screen before fading out
+    pub fade_secs: f64,
+}
+
+const KEYSTROKE_FADE_DEFAULT_SECS: f64 = 1.5;
+const KEYSTROKE_FADE_LIMITS: (f64, f64) = (0.3, 10.0);
+
+#[tauri::command]
+pub fn get_keystroke_overlay(app: AppHandle) -> KeystrokeOverlaySettings {
+    KeystrokeOverlaySettings {
+        show_keystrokes: crate::settings::get_bool(&app, "show_keystrokes", false),
+        fade_secs: crate::settings::get_f64(&app, "keystroke_fade_secs", KEYSTROKE_FADE_DEFAULT_SECS)
+            .clamp(KEYSTROKE_FADE_LIMITS.0, KEYSTROKE_FADE_LIMITS.1),
+    }
+}
+
+/// Show pressed keys on screen during recordings (macOS; needs the
+/// Accessibility permission). Applies from the next recording.
+#[tauri::command]
+pub fn set_keystroke_overlay(app: AppHandle, settings: KeystrokeOverlaySettings) -> Result<(), ZureshotError> {
+    let (lo, hi) = KEYSTROKE_FADE_LIMITS;
+    if !settings.fade_secs.is_finite() || !(lo..=hi).contains(&settings.fade_secs) {
+        return Err(format!("Keystroke fade must be between {}s and {}s (got {})", lo, hi, settings.fade_secs).into());
+    }
+    crate::settings::set(&app, "show_keystrokes", serde_json::json!(settings.show_keystrokes));
+    crate::settings::set(&app, "keystroke_fade_secs", serde_json::json!(settings.fade_secs));
+    println!("[zureshot] Keystroke overlay: {:?}", settings);
+    Ok(())
+}
+
+/// Ripple drawn at each click during recordings (persisted as
+/// `click_highlight` / `click_highlight_color` / `click_highlight_radius`)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClickHighlightSettings {
+    pub click_highlight: bool,
+    /// `#RRGGBB`
+    pub color: String,
+    /// Ripple radius at its largest (logical points)
+    pub radius: f64,
+}
+
+const CLICK_HIGHLIGHT_DEFAULT_COLOR: &str = "#ffd60a";
+const CLICK_HIGHLIGHT_DEFAULT_RADIUS: f64 = 36.0;
+const CLICK_HIGHLIGHT_RADIUS_LIMITS: (f64, f64) = (10.0, 120.0);
+
+fn is_hex_color(s: &str) -> bool {
+    s.len() == 7 && s.starts_with('#') && s[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[tauri::command]
+pub fn get_click_highlight(app: AppHandle) -> ClickHighlightSettings {
+    let color = crate::settings::get::<String>(&app, "click_highlight_color")
+        .filter(|c| is_hex_color(c))
+        .unwrap_or_else(|| CLICK_HIGHLIGHT_DEFAULT_COLOR.into());
+    ClickHighlightSettings {
+        click_highlight: crate::settings::get_bool(&app, "click_highlight", false),
+        color,
+        radius: crate::settings::get_f64(&app, "click_highlight_radius", CLICK_HIGHLIGHT_DEFAULT_RADIUS)
+            .clamp(CLICK_HIGHLIGHT_RADIUS_LIMITS.0, CLICK_HIGHLIGHT_RADIUS_LIMITS.1),
+    }
+}
+
+/// Draw an expanding circle at every left click, baked into the recording
+/// (macOS; needs the Accessibility permission). Applies from the next
+/// recording.
+#[tauri::command]
+pub fn set_click_highlight(app: AppHandle, settings: ClickHighlightSettings) -> Result<(), ZureshotError> {
+    if !is_hex_color(&settings.color) {
+        return Err(format!("Click highlight color must be #RRGGBB (got {:?})", settings.color).into());
+    }
+    let (lo, hi) = CLICK_HIGHLIGHT_RADIUS_LIMITS;
+    if !settings.radius.is_finite() || !(lo..=hi).contains(&settings.radius) {
+        return Err(format!("Click highlight radius must be between {} and {} (got {})", lo, hi, settings.radius).into());
+    }
+    crate::settings::set(&app, "click_highlight", serde_json::json!(settings.click_highlight));
+    crate::settings::set(&app, "click_highlight_color", serde_json::json!(settings.color));
+    crate::settings::set(&app, "click_highlight_radius", serde_json::json!(settings.radius));
+    println!("[zureshot] Click highlight: {:?}", settings);
+    Ok(())
+}
+
+/// Whether full-screen recordings crop out the menu bar (clock, status icons)
+#[tauri::command]
+pub fn get_hide_menu_bar(app: AppHandle) -> bool {
+    crate::settings::get_bool(&app, "hide_menu_bar", false)
+}
+
+/// Toggle menu bar cropping for full-screen recordings (persisted, applies
+/// from the next recording)
+#[tauri::command]
+pub fn set_hide_menu_bar(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "hide_menu_bar", serde_json::json!(enabled));
+    println!("[zureshot] Hide menu bar in full-screen recordings: {}", enabled);
+}
+
+/// What "Record Region" does while the region selector is already open
+/// (persisted as `region_retrigger`).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionRetrigger {
+    /// Bring the selector to the front
+    #[default]
+    Focus,
+    /// Record the current selection, so one hotkey both selects and starts
+    Confirm,
+    /// Close the selector
+    Toggle,
+}
+
+#[tauri::command]
+pub fn get_region_retrigger(app: AppHandle) -> RegionRetrigger {
+    crate::settings::get(&app, "region_retrigger").unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn set_region_retrigger(app: AppHandle, behavior: RegionRetrigger) {
+    crate::settings::set(&app, "region_retrigger", serde_json::json!(behavior));
+    println!("[zureshot] Second Record Region trigger: {:?}", behavior);
+}
+
+/// Core logic to open the region selector overlay (callable from both tray and commands)
+pub fn do_start_region_selection(app: &AppHandle) -> Result<(), String> {
+    // Check if already recording
+    {
+        let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+        let recordings = state.lock().map_err(|e| e.to_string())?;
+        if recordings.is_recording() {
+            return Err("Recording already in progress".to_string());
+        }
+    }
+
+    // Selector already open: focus it, record its selection, or close it
+    if let Some(win) = app.get_webview_window("region-selector") {
+        // Screenshot selectors share the label (`?mode=…`) — never confirm those
+        let records = win
+            .url()
+            .is_ok_and(|u| !u.query().is_some_and(|q| q.contains("mode=")));
+        match get_region_retrigger(app.clone()) {
+            RegionRetrigger::Confirm if records => {
+                println!("[zureshot] Record Region triggered again — confirming the selection");
+                // Every display's selector gets it; only the one holding the selection confirms
+                let _ = app.emit("region-selection-confirm", ());
+            }
+            RegionRetrigger::Toggle => {
+                println!("[zureshot] Record Region triggered again — closing the selector");
+                do_cancel_region_selection(app);
+            }
+            _ => {
+                let _ = win.show();
+                let _ = win.set_focus();
+            }
+        }
+        return Ok(());
+    }
+
+    let primary = app
+        .primary_monitor()
+        .map_err(|e| format!("Failed to get monitor: {}", e))?
+        .ok_or("No primary monitor found")?;
+    // One selector per display — only where a selection can be mapped back
+    // to its display (see `display_id_at`); elsewhere just the primary
+    let mut monitors = vec![primary.clone()];
+    if platform::imp::list_displays().is_ok() {
+        if let Ok(all) = app.available_monitors() {
+            monitors.extend(all.into_iter().filter(|m| m.position() != primary.position()));
+        }
+    }
+
+    for (i, monitor) in monitors.iter().enumerate() {
+        let phys_size = monitor.size();
+        let scale = monitor.scale_factor();
+        let position = monitor.position();
+
+        // Tauri's inner_size() expects LOGICAL pixels, not physical.
+        // On a Retina display (scale=2.0), physical 2880×1800 → logical 1440×900.
+        let logical_w = phys_size.width as f64 / scale;
+        let logical_h = phys_size.height as f64 / scale;
+
+        println!(
+            "[zureshot] Region selector {}: physical={}×{}, scale={}, logical={:.0}×{:.0}, pos=({},{})",
+            i, phys_size.width, phys_size.height, scale, logical_w, logical_h,
+            position.x, position.y
+        );
+
+        // The primary keeps the plain label everything else looks up
+        let label = if i == 0 {
+            "region-selector".to_string()
+        } else {
+            format!("{}{}", EXTRA_REGION_SELECTOR_PREFIX, i)
+        };
+
+        // Create fullscreen transparent overlay window.
+        // transparent(true) is safe now that the Svelte component mounts correctly
+        // (the earlier invisibility was caused by an SSR resolution bug, not by
+        // the transparency itself).
+        let window = WebviewWindowBuilder::new(
+            app,
+            &label,
+            WebviewUrl::App("region-selector.html".into()),
+        )
+        .title("Region Selector")
+        .inner_size(logical_w, logical_h)
+        .position(position.x as f64 / scale, position.y as f64 / scale)
+        .transparent(true)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .build()
+        .map_err(|e| format!("Failed to create region selector window: {}", e))?;
+
+        // Explicitly show — tray-only macOS apps may not auto-focus new windows
+        let _ = window.show();
+        if i == 0 {
+            let _ = window.set_focus();
+        }
+    }
+
+    println!("[zureshot] Region selector opened on {} display(s)", monitors.len());
+
+    Ok(())
+}
+
+/// Labels of the selectors opened on non-primary displays: `region-selector-<n>`
+const EXTRA_REGION_SELECTOR_PREFIX: &str = "region-selector-";
+
+/// The selectors on non-primary displays (the primary's is `region-selector`).
+fn extra_region_selectors(app: &AppHandle) -> Vec<tauri::WebviewWindow> {
+    app.webview_windows()
+        .into_iter()
+        .filter(|(label, _)| label.starts_with(EXTRA_REGION_SELECTOR_PREFIX))
+        .map(|(_, win)| win)
+        .collect()
+}
+
+/// Platform display whose top-left corner is at the global logical point
+/// `(x, y)` — how a Tauri monitor is matched to a capturable display.
+fn display_id_at(x: f64, y: f64) -> Option<u32> {
+    platform::imp::list_displays()
+        .ok()?
+        .into_iter()
+        .find(|d| (d.x - x).abs() < 1.0 && (d.y - y).abs() < 1.0)
+        .map(|d| d.id)
+}
+
+/// Open the region selector overlay window (Tauri command)
+#[tauri::command]
+pub async fn start_region_selection(app: AppHandle) -> Result<(), ZureshotError> {
+    Ok(do_start_region_selection(&app)?)
+}
+
+/// Logical bounds of a display (`None` = primary), origin at its top-left.
+fn display_bounds(app: &AppHandle, display_id: Option<u32>) -> Option<CaptureRegion> {
+    match display_id {
+        Some(id) => platform::imp::list_displays()
+            .ok()?
+            .into_iter()
+            .find(|d| d.id == id)
+            .map(|d| CaptureRegion {
+                x: 0.0,
+                y: 0.0,
+                width: d.width as f64,
+                height: d.height as f64,
+            }),
+        None => primary_display_bounds(app),
+    }
+}
+
+/// Logical bounds of the primary display (region-selector coordinate space).
+fn primary_display_bounds(app: &AppHandle) -> Option<CaptureRegion> {
+    let monitor = app.primary_monitor().ok()??;
+    let scale = monitor.scale_factor();
+    Some(CaptureRegion {
+        x: 0.0,
+        y: 0.0,
+        width: monitor.size().width as f64 / scale,
+        height: monitor.size().height as f64 / scale,
+    })
+}
+
+/// Snap a region to an aspect ratio (e.g. 16/9 while Shift is held).
+#[tauri::command]
+pub fn snap_region_to_aspect(region: CaptureRegion, ratio: f64) -> CaptureRegion {
+    platform::snap_region_to_aspect(&region, ratio)
+}
+
+/// Fit a region inside display bounds (defaults to the primary display).
+#[tauri::command]
+pub fn clamp_region_to_display(
+    app: AppHandle,
+    region: CaptureRegion,
+    display_bounds: Option<CaptureRegion>,
+) -> Result<CaptureRegion, ZureshotError> {
+    let bounds = display_bounds
+        .or_else(|| primary_display_bounds(&app))
+        .ok_or("No primary monitor found")?;
+    Ok(platform::clamp_region_to_display(&region, &bounds))
+}
+
+/// Smallest region (logical points) a recording accepts; 0 turns the
+/// check off (persisted as `min_region_size`).
+pub fn min_region_size(app: &AppHandle) -> f64 {
+    crate::settings::get_f64(app, "min_region_size", platform::DEFAULT_MIN_REGION_SIZE).max(0.0)
+}
+
+#[tauri::command]
+pub fn get_min_region_size(app: AppHandle) -> f64 {
+    min_region_size(&app)
+}
+
+#[tauri::command]
+pub fn set_min_region_size(app: AppHandle, size: f64) -> Result<(), ZureshotError> {
+    if !size.is_finite() || size < 0.0 {
+        return Err(format!("Minimum region size must be 0 or more (got {})", size).into());
+    }
+    crate::settings::set(&app, "min_region_size", serde_json::json!(size));
+    println!("[zureshot] Minimum region size: {}x{}", size, size);
+    Ok(())
+}
+
+/// Grow a selection to the minimum region size (centered) and fit it inside
+/// the display (defaults to the primary display).
+#[tauri::command]
+pub fn enforce_min_region_size(
+    app: AppHandle,
+    region: CaptureRegion,
+    display_bounds: Option<CaptureRegion>,
+) -> Result<CaptureRegion, ZureshotError> {
+    let bounds = display_bounds
+        .or_else(|| primary_display_bounds(&app))
+        .ok_or("No primary monitor found")?;
+    Ok(platform::expand_region_to_min(&region, min_region_size(&app), &bounds))
+}
+
+/// Confirm region selection and start recording with the selected region
+/// (into `output_dir` instead of the recordings folder, if given)
+#[tauri::command]
+pub fn confirm_region_selection(
+    app: AppHandle,
+    window: tauri::WebviewWindow,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    quality: Option<String>,
+    system_audio: Option<bool>,
+    microphone: Option<bool>,
+    format: Option<String>,
+    camera: Option<bool>,
+    camera_device_id: Option<String>,
+    camera_shape: Option<String>,
+    camera_size: Option<String>,
+    output_dir: Option<String>,
+) -> Result<(), ZureshotError> {
+    // Hide the region selectors (don't destroy — we're inside an IPC call).
+    if let Some(win) = app.get_webview_window("region-selector") {
+        let _ = win.hide();
+    }
+    for win in extra_region_selectors(&app) {
+        let _ = win.hide();
+    }
+
+    // The selection is local to the display of the selector it was drawn
+    // in; anything but the primary records that display by ID
+    let primary = app.primary_monitor().ok().flatten();
+    let secondary = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .filter(|m| primary.as_ref().is_some_and(|p| p.position() != m.position()));
+    let (display_id, display_origin, bounds) = match secondary {
+        Some(m) => {
+            let scale = m.scale_factor();
+            let (ox, oy) = (m.position().x as f64 / scale, m.position().y as f64 / scale);
+            let id = display_id_at(ox, oy).ok_or_else(|| format!("No display found at ({}, {})", ox, oy))?;
+            let bounds = CaptureRegion {
+                x: 0.0,
+                y: 0.0,
+                width: m.size().width as f64 / scale,
+                height: m.size().height as f64 / scale,
+            };
+            (Some(id), (ox, oy), Some(bounds))
+        }
+        None => (None, (0.0, 0.0), primary_display_bounds(&app)),
+    };
+
+    let region = CaptureRegion {
+        x,
+        y,
+        width,
+        height,
+    };
+    // Same rounding/clamping the selector uses, so drawn == captured;
+    // sub-minimum selections grow instead of being rejected at start
+    let region = match bounds {
+        Some(bounds) => {
+            let min = min_region_size(&app);
+            if region.width < min || region.height < min {
+                println!(
+                    "[zureshot] Region {}x{} is below the {}x{} minimum; expanding",
+                    region.width, region.height, min, min
+                );
+            }
+            platform::expand_region_to_min(&region, min, &bounds)
+        }
+        None => region,
+    };
+
+    let q = match quality.as_deref() {
+        Some("high") => RecordingQuality::High,
+        _ => RecordingQuality::Standard,
+    };
+
+    let sys_audio = system_audio.unwrap_or(false);
+    let mic = microphone.unwrap_or(false);
+    let output_format = format.unwrap_or_else(|| default_format(&app));
+    let camera = camera.unwrap_or(false).then(|| CameraOptions {
+        shape: camera_shape.unwrap_or_else(|| "circle".to_string()),
+        size: camera_size.unwrap_or_else(|| "medium".to_string()),
+        device_id: camera_device_id,
+    });
+
+    // The countdown is placed in global coordinates
+    let region_on_screen = CaptureRegion {
+        x: region.x + display_origin.0,
+        y: region.y + display_origin.1,
+        ..region.clone()
+    };
+    let region_for_windows = region.clone();
+    let app_clone = app.clone();
+    std::thread::spawn(move || {
+        // Small delay to let the region selector fully disappear
+        std::thread::sleep(std::time::Duration::from_millis(300));
+
+        // Now safe to destroy the region selectors
+        if let Some(win) = app_clone.get_webview_window("region-selector") {
+            let _ = win.destroy();
+        }
+        for win in extra_region_selectors(&app_clone) {
+            let _ = win.destroy();
+        }
+
+        if !run_countdown(&app_clone, countdown_secs(&app_clone), Some(&region_on_screen)) {
+            return;
+        }
+
+        let started = do_start_recording(&app_clone, None, output_dir, Some(region), None, display_id, false, q, sys_audio, mic, Some(output_format));
+        // Kept up until capture runs so the screen never looks idle; the
+        // stream was created excluding it along with our other windows
+        close_countdown_window(&app_clone);
+        match started {
+            Ok(_) => {
+                remember_last_camera(&app_clone, camera.clone());
+                open_recording_windows(&app_clone, Some(&region_for_windows), display_id, camera.as_ref());
+            }
+            Err(e) => eprintln!("[zureshot] Start error: {}", e),
+        }
+    });
+
+    Ok(())
+}
+
+/// Countdown before a region recording starts unless configured (seconds).
+const DEFAULT_COUNTDOWN_SECS: u32 = 3;
+/// Longest accepted countdown (seconds).
+const MAX_COUNTDOWN_SECS: u32 = 10;
+/// Countdown window size (logical pixels).
+const COUNTDOWN_WINDOW_SIZE: f64 = 160.0;
+/// Gap between the countdown window and the region it counts down for.
+const COUNTDOWN_WINDOW_GAP: f64 = 16.0;
+/// Cancels a running countdown (the window never takes focus).
+pub const COUNTDOWN_CANCEL_SHORTCUT: &str = "Escape";
+
+/// Set while a countdown runs; cleared by `cancel_countdown`.
+static COUNTDOWN_ACTIVE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Seconds counted down after confirming a region (persisted as
+/// `countdown_secs`, 0 = start immediately).
+pub fn countdown_secs(app: &AppHandle) -> u32 {
+    crate::settings::get::<u32>(app, "countdown_secs")
+        .unwrap_or(DEFAULT_COUNTDOWN_SECS)
+        .min(MAX_COUNTDOWN_SECS)
+}
+
+#[tauri::command]
+pub fn get_countdown_secs(app: AppHandle) -> u32 {
+    countdown_secs(&app)
+}
+
+/// Set the countdown before region recordings (clamped to 10 s; returns
+/// the stored value).
+#[tauri::command]
+pub fn set_countdown_secs(app: AppHandle, secs: u32) -> u32 {
+    let secs = secs.min(MAX_COUNTDOWN_SECS);
+    crate::settings::set(&app, "countdown_secs", serde_json::json!(secs));
+    println!("[zureshot] Countdown: {}s", secs);
+    secs
+}
+
+/// Show the countdown window next to `region` (or centered on the screen)
+/// and block until it runs out. Returns false if it was cancelled with Escape.
+fn run_countdown(app: &AppHandle, secs: u32, region: Option<&CaptureRegion>) -> bool {
+    if secs == 0 {
+        return true;
+    }
+    COUNTDOWN_ACTIVE.store(true, std::sync::atomic::Ordering::SeqCst);
+    register_countdown_shortcut(app);
+    if let Err(e) = open_countdown_window(app, secs, region) {
+        // Still wait, so the user gets the time they asked for
+        eprintln!("[zureshot] {}", e);
+    }
+    println!("[zureshot] Countdown: {}s", secs);
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(secs as u64);
+    while std::time::Instant::now() < deadline {
+        if !COUNTDOWN_ACTIVE.load(std::sync::atomic::Ordering::SeqCst) {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    {
+        use tauri_plugin_global_shortcut::GlobalShortcutExt;
+        let _ = app.global_shortcut().unregister(COUNTDOWN_CANCEL_SHORTCUT);
+    }
+    if COUNTDOWN_ACTIVE.swap(false, std::sync::atomic::Ordering::SeqCst) {
+        return true;
+    }
+
+    // Nothing was started, so the tray has nothing to reset
+    println!("[zureshot] Countdown cancelled");
+    close_countdown_window(app);
+    false
+}
+
+/// Escape cancels the countdown for as long as it runs.
+fn register_countdown_shortcut(app: &AppHandle) {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+    let result = app
+        .global_shortcut()
+        .on_shortcut(COUNTDOWN_CANCEL_SHORTCUT, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                cancel_countdown(app.clone());
+            }
+        });
+    if let Err(e) = result {
+        eprintln!("[zureshot] Failed to register {}: {}", COUNTDOWN_CANCEL_SHORTCUT, e);
+    }
+}
+
+/// Where the countdown window goes: centered below `region`, else above it,
+/// else beside it on the display it's on, so it never covers what's about
+/// to be captured. A region filling its display gets the window centered
+/// (it's excluded from the capture anyway).
+fn countdown_position(region: &CaptureRegion, screen: &CaptureRegion) -> (f64, f64) {
+    let size = COUNTDOWN_WINDOW_SIZE;
+    let gap = COUNTDOWN_WINDOW_GAP;
+    let center_x = (region.x + (region.width - size) / 2.0).clamp(screen.x, screen.x + screen.width - size);
+    let center_y = (region.y + (region.height - size) / 2.0).clamp(screen.y, screen.y + screen.height - size);
+    let below = region.y + region.height + gap;
+    let above = region.y - gap - size;
+    let right = region.x + region.width + gap;
+    let left = region.x - gap - size;
+    if below + size <= screen.y + screen.height {
+        (center_x, below)
+    } else if above >= screen.y {
+        (center_x, above)
+    } else if right + size <= screen.x + screen.width {
+        (right, center_y)
+    } else if left >= screen.x {
+        (left, center_y)
+    } else {
+        (region.x + (region.width - size) / 2.0, region.y + (region.height - size) / 2.0)
+    }
+}
+
+/// Global logical bounds of the display containing the center of `area`.
+fn screen_containing(app: &AppHandle, area: &CaptureRegion) -> Option<CaptureRegion> {
+    let (cx, cy) = (area.x + area.width / 2.0, area.y + area.height / 2.0);
+    platform::imp::list_displays()
+        .ok()?
+        .into_iter()
+        .map(|d| CaptureRegion { x: d.x, y: d.y, width: d.width as f64, height: d.height as f64 })
+        .find(|d| cx >= d.x && cx < d.x + d.width && cy >= d.y && cy < d.y + d.height)
+        .or_else(|| primary_display_bounds(app))
+}
+
+fn open_countdown_window(app: &AppHandle, secs: u32, region: Option<&CaptureRegion>) -> Result<(), String> {
+    close_countdown_window(app);
+    let (pos_x, pos_y) = match region {
+        Some(region) => match screen_containing(app, region) {
+            Some(screen) => countdown_position(region, &screen),
+            None => countdown_position(region, region),
+        },
+        None => {
+            let screen = primary_display_bounds(app).ok_or("No primary monitor found")?;
+            countdown_position(&screen, &screen)
+        }
+    };
+
+    let window = WebviewWindowBuilder::new(app, "countdown", WebviewUrl::App("countdown.html".into()))
+        .title("Countdown")
+        .initialization_script(&format!("window.__COUNTDOWN_SECS__ = {};", secs))
+        .inner_size(COUNTDOWN_WINDOW_SIZE, COUNTDOWN_WINDOW_SIZE)
+        .position(pos_x, pos_y)
+        .transparent(true)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .focused(false)
+        .build()
+        .map_err(|e| format!("Failed to create countdown window: {}", e))?;
+    apply_bar_spaces(app, &window);
+    // Never takes focus or clicks away from the app about to be recorded
+    let _ = window.set_ignore_cursor_events(true);
+    let _ = window.show();
+    Ok(())
+}
+
+fn close_countdown_window(app: &AppHandle) {
+    if let Some(win) = app.get_webview_window("countdown") {
+        let _ = win.destroy();
+    }
+}
+
+/// Cancel a running countdown (the Escape shortcut); the recording is not
+/// started.
+#[tauri::command]
+pub fn cancel_countdown(app: AppHandle) {
+    // Hide only — the countdown thread destroys it
+    if let Some(win) = app.get_webview_window("countdown") {
+        let _ = win.hide();
+    }
+    COUNTDOWN_ACTIVE.store(false, std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Open the recording UI for a freshly started primary recording: dim
+/// overlay (region only), control bar and optional camera bubble, then
+/// re-exclude the new windows from the capture. `region` is local to
+/// `display_id` (`None` = primary).
+fn open_recording_windows(
+    app: &AppHandle,
+    region: Option<&CaptureRegion>,
+    display_id: Option<u32>,
+    camera: Option<&CameraOptions>,
+) {
+    // The dim overlay and bar placement only know the primary display: on
+    // other displays record without the overlay, with the bar centered on
+    // the primary (where it stays out of the recording)
+    let primary_region = region.filter(|_| display_id.is_none());
+    if let Some(region) = primary_region {
+        let _ = do_open_recording_overlay(app, region);
+    }
+    let _ = do_open_recording_bar(app, primary_region);
+
+    // The camera bubble and overlays go on whatever is recorded, on any display
+    let capture = primary_capture(app);
+    let area = capture.as_ref().and_then(capture_rect);
+    let overlays = capture.as_ref().and_then(|c| overlay_area(app, c));
+    if crate::settings::get_bool(app, "show_keystrokes", false) {
+        if let Err(e) = open_keystroke_overlay(app, overlays.as_ref()) {
+            eprintln!("[zureshot] {}", e);
+            let _ = app.emit("recording-warning", &e);
+        }
+    }
+    if crate::settings::get_bool(app, "click_highlight", false) {
+        if let Err(e) = open_click_overlay(app, overlays.as_ref()) {
+            eprintln!("[zureshot] {}", e);
+            let _ = app.emit("recording-warning", &e);
+        }
+    }
+
+    // Open camera bubble if user enabled it, otherwise the configured
+    // webcam picture-in-picture. A window recording captures only that
+    // window, so a bubble floating over it would never be in the video.
+    if capture.as_ref().is_some_and(|c| c.window_id.is_some()) {
+        if camera.is_some() || webcam(app).is_some() {
+            let msg = "The webcam overlay isn't captured in window recordings — record a region or the full screen to include it.";
+            println!("[zureshot] {}", msg);
+            let _ = app.emit("recording-warning", msg);
+        }
+    } else if let Some(cam) = camera {
+        let _ = do_open_camera_overlay_with_options(
+            app,
+            &cam.shape,
+            &cam.size,
+            cam.device_id.as_deref(),
+            area.as_ref(),
+            WebcamCorner::BottomRight,
+        );
+    } else if let Some(cam) = webcam(app) {
+        if platform::imp::camera_access() {
+            let _ = do_open_camera_overlay_with_options(
+                app,
+                &cam.shape,
+                &cam.size,
+                cam.device_id.as_deref(),
+                area.as_ref(),
+                cam.corner,
+            );
+        } else {
+            println!("[zureshot] Camera access not granted — recording without the webcam overlay");
+        }
+    }
+
+    // Exclude the new windows from capture once WindowServer lists them
+    refresh_stream_exclusion_with_retry(app);
+
+    // Send region coordinates to the overlay for the dim effect
+    if let Some(region) = primary_region {
+        let _ = app.emit("recording-region", region);
+    }
+}
+
+/// Start a recording with exactly the last-used configuration (target,
+/// region, quality, audio, format, camera bubble). If the window or display
+/// is gone, falls back to full screen on the main display and emits a
+/// `recording-warning`.
+pub fn do_repeat_last_recording(app: &AppHandle) -> Result<String, String> {
+    let mut last: LastRecording = crate::settings::get(app, "last_recording")
+        .ok_or("No previous recording to repeat")?;
+
+    if let Some(id) = last.display_id {
+        let connected = platform::imp::list_displays()
+            .map(|ds| ds.iter().any(|d| d.id == id))
+            .unwrap_or(false);
+        if !connected {
+            let msg = format!("Display {} is no longer connected — recording the main display instead.", id);
+            println!("[zureshot] {}", msg);
+            let _ = app.emit("recording-warning", &msg);
+            last.display_id = None;
+            last.region = None;
+        }
+    }
+    if let Some(id) = last.window_id {
+        if !platform::imp::window_exists(id) {
+            let msg = "The window from the last recording is gone — recording full screen instead.".to_string();
+            println!("[zureshot] {} (window {})", msg, id);
+            let _ = app.emit("recording-warning", &msg);
+            last.window_id = None;
+        }
+    }
+
+    let path = do_start_recording(
+        app,
+        None,
+        None,
+        last.region.clone(),
+        last.window_id,
+        last.display_id,
+        last.all_displays,
+        last.quality,
+        last.system_audio,
+        last.microphone,
+        last.output_format.clone(),
+    )?;
+    remember_last_camera(app, last.camera.clone());
+    open_recording_windows(app, last.region.as_ref(), last.display_id, last.camera.as_ref());
+    Ok(path)
+}
+
+/// "Do it again exactly": repeat the last recording's configuration.
+#[tauri::command]
+pub async fn repeat_last_recording(app: AppHandle) -> Result<String, ZureshotError> {
+    // Blocks on GCD completion handlers — keep off the async runtime
+    Ok(tokio::task::spawn_blocking(move || do_repeat_last_recording(&app))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+/// Core logic to record the window that currently has keyboard focus.
+///
+/// The window is resolved via Accessibility; if permission is denied or the
+/// window can't be matched, falls back to a full-screen recording.
+pub fn do_record_focused_window(
+    app: &AppHandle,
+    quality: RecordingQuality,
+    capture_system_audio: bool,
+    capture_microphone: bool,
+) -> Result<String, String> {
+    let window_id = platform::imp::focused_window_id();
+    if window_id.is_none() {
+        println!("[zureshot] No focused window resolved — recording full screen");
+    }
+    do_record_window(app, window_id, quality, capture_system_audio, capture_microphone)
+}
+
+/// Core logic to record a single window (`None` = full screen). The capture
+/// follows the window and ignores everything around it; if the window
+/// closes mid-recording, the recording is stopped and saved.
+pub fn do_record_window(
+    app: &AppHandle,
+    window_id: Option<u32>,
+    quality: RecordingQuality,
+    capture_system_audio: bool,
+    capture_microphone: bool,
+) -> Result<String, String> {
+    let path = do_start_recording(
+        app,
+        None,
+        None,
+        None,
+        window_id,
+        None,
+        false,
+        quality,
+        capture_system_audio,
+        capture_microphone,
+        None,
+    )?;
+    open_recording_windows(app, None, None, None);
+    Ok(path)
+}
+
+/// Quality, system audio and microphone of the last recording, for the
+/// one-click starts (full screen, all displays, a window from the tray).
+/// Standard quality with no audio before the first recording.
+pub fn last_recording_settings(app: &AppHandle) -> (RecordingQuality, bool, bool) {
+    let last: Option<LastRecording> = crate::settings::get(app, "last_recording");
+    last.map(|l| (l.quality, l.system_audio, l.microphone))
+        .unwrap_or((RecordingQuality::Standard, false, false))
+}
+
+/// Core logic to record a whole display straight away, without the region
+/// selector or dim overlay. Quality and audio follow the last recording's
+/// settings, the format follows `default_format`.
+pub fn do_start_fullscreen_recording(app: &AppHandle, display_id: Option<u32>) -> Result<String, String> {
+    let (quality, system_audio, microphone) = last_recording_settings(app);
+    let path = do_start_recording(
+        app,
+        None,
+        None,
+        None,
+        None,
+        display_id,
+        false,
+        quality,
+        system_audio,
+        microphone,
+        Some(default_format(app)),
+    )?;
+    // No region: the bar goes bottom-center
+    open_recording_windows(app, None, None, None);
+    Ok(path)
+}
+
+/// Core logic to record every display at once into one video (macOS; the
+/// displays need matching scale factors). Quality, audio and format follow
+/// `do_start_fullscreen_recording`.
+pub fn do_start_all_displays_recording(app: &AppHandle) -> Result<String, String> {
+    let (quality, system_audio, microphone) = last_recording_settings(app);
+    let path = do_start_recording(
+        app,
+        None,
+        None,
+        None,
+        None,
+        None,
+        true,
+        quality,
+        system_audio,
+        microphone,
+        Some(default_format(app)),
+    )?;
+    open_recording_windows(app, None, None, None);
+    Ok(path)
+}
+
+/// Record system audio and/or the microphone to an `.m4a` with no video
+/// (an audio memo). Only the recording bar opens, for pause and stop.
+pub fn do_start_audio_recording(app: &AppHandle, system_audio: bool, microphone: bool) -> Result<String, String> {
+    let path = do_start_recording(
+        app,
+        None,
+        None,
+        None,
+        None,
+        None,
+        false,
+        RecordingQuality::Standard,
+        system_audio,
+        microphone,
+        Some("audio".to_string()),
+    )?;
+    let _ = do_open_recording_bar(app, None);
+    Ok(path)
+}
+
+/// Start an audio-only recording (Tauri command). Defaults to system audio
+/// only. Returns the output path.
+#[tauri::command]
+pub async fn start_audio_recording(
+    app: AppHandle,
+    system_audio: Option<bool>,
+    microphone: Option<bool>,
+) -> Result<String, ZureshotError> {
+    let system_audio = system_audio.unwrap_or(true);
+    let microphone = microphone.unwrap_or(false);
+    // Blocks on GCD completion handlers — keep off the async runtime
+    Ok(tokio::task::spawn_blocking(move || do_start_audio_recording(&app, system_audio, microphone))
+        .await
+        .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+/// Record a whole display immediately (Tauri command). Returns the
+/// recording id.
+#[tauri::command]
+pub async fn start_fullscreen_recording(app: AppHandle, display_id: Option<u32>) -> Result<String, ZureshotError> {
+    // Blocks on GCD completion handlers — keep off the async runtime
+    Ok(tokio::task::spawn_blocking(move || {
+        do_start_fullscreen_recording(&app, display_id).map(|_| recording_id_for(display_id, None))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+/// Record every display at once into one video (Tauri command). Returns
+/// the recording id.
+#[tauri::command]
+pub async fn start_all_displays_recording(app: AppHandle) -> Result<String, ZureshotError> {
+    // Blocks on GCD completion handlers — keep off the async runtime
+    Ok(tokio::task::spawn_blocking(move || {
+        do_start_all_displays_recording(&app).map(|_| ALL_DISPLAYS_RECORDING_ID.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+/// Record the focused window (Tauri command).
+#[tauri::command]
+pub async fn record_focused_window(
+    app: AppHandle,
+    quality: Option<String>,
+    system_audio: Option<bool>,
+    microphone: Option<bool>,
+) -> Result<String, ZureshotError> {
+    let q = match quality.as_deref() {
+        Some("high") => RecordingQuality::High,
+        _ => RecordingQuality::Standard,
+    };
+    // Blocks on GCD completion handlers — keep off the async runtime
+    Ok(tokio::task::spawn_blocking(move || {
+        do_record_focused_window(&app, q, system_audio.unwrap_or(false), microphone.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+/// Record one window, picked from `list_windows` (Tauri command).
+#[tauri::command]
+pub async fn record_window(
+    app: AppHandle,
+    window_id: u32,
+    quality: Option<String>,
+    system_audio: Option<bool>,
+    microphone: Option<bool>,
+) -> Result<String, ZureshotError> {
+    let q = match quality.as_deref() {
+        Some("high") => RecordingQuality::High,
+        _ => RecordingQuality::Standard,
+    };
+    if !platform::imp::window_exists(window_id) {
+        return Err(format!("Window {} is no longer on screen", window_id).into());
+    }
+    // Blocks on GCD completion handlers — keep off the async runtime
+    Ok(tokio::task::spawn_blocking(move || {
+        do_record_window(&app, Some(window_id), q, system_audio.unwrap_or(false), microphone.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {e}"))??)
+}
+
+/// The capture stream writing `output_path` was stopped by the system —
+/// the recorded window closed or the display went away. Stop that
+/// recording normally so the MP4 is finalized instead of left without a
+/// moov atom, and tell the frontend why it ended.
+#[cfg(target_os = "macos")]
+pub(crate) fn handle_capture_interrupted(app: &AppHandle, output_path: &str, reason: &str) {
+    let recording_id = {
+        let state = app.state::<Mutex<RecordingState>>();
+        let Ok(recordings) = state.lock() else { return };
+        recordings
+            .sessions
+            .iter()
+            .find(|(_, s)| s.output_path.as_deref() == Some(output_path))
+            .map(|(id, _)| id.clone())
+    };
+    // Already being stopped, or not a recording (instant replay)
+    let Some(recording_id) = recording_id else { return };
+
+    let msg = if recording_id.starts_with("window-") {
+        "The recorded window was closed — the recording was stopped and saved."
+    } else {
+        "Screen capture was interrupted — the recording was stopped and saved."
+    };
+    println!("[zureshot] {} ({}: {})", msg, recording_id, reason);
+    let _ = app.emit("recording-warning", msg);
+
+    // Called on the capture queue; stopping blocks on GCD completion handlers
+    let app = app.clone();
+    std::thread::spawn(move || match do_stop_recording(&app, Some(&recording_id)) {
+        Ok(result) => println!(
+            "[zureshot] Interrupted recording saved: {} ({:.1}s)",
+            result.path, result.duration_secs
+        ),
+        Err(e) => eprintln!("[zureshot] Failed to finalize interrupted recording: {}", e),
+    });
+}
+
+/// Payload of the `recording-failed` event.
+#[cfg(target_os = "macos")]
+#[derive(Clone, Serialize)]
+pub struct RecordingFailedEvent {
+    pub recording_id: String,
+    pub path: String,
+    pub error: String,
+}
+
+/// The writer of a running recording entered the failed state. Tell the UI
+/// and stop the recording so whatever was written before the failure is
+/// finalized (the writer only keeps samples it accepted).
+#[cfg(target_os = "macos")]
+pub(crate) fn handle_writer_failed(app: &AppHandle, output_path: &str, error: &str) {
+    let recording_id = {
+        let state = app.state::<Mutex<RecordingState>>();
+        let Ok(recordings) = state.lock() else { return };
+        recordings
+            .sessions
+            .iter()
+            .find(|(_, s)| s.output_path.as_deref() == Some(output_path))
+            .map(|(id, _)| id.clone())
+    };
+    // Already being stopped, or not a recording (instant replay)
+    let Some(recording_id) = recording_id else { return };
+
+    eprintln!("[zureshot] Recording {} failed: {}", recording_id, error);
+    let _ = app.emit(
+        "recording-failed",
+        RecordingFailedEvent {
+            recording_id: recording_id.clone(),
+            path: output_path.to_string(),
+            error: error.to_string(),
+        },
+    );
+
+    // Called on the capture queue; stopping blocks on GCD completion handlers
+    let app = app.clone();
+    std::thread::spawn(move || match do_stop_recording(&app, Some(&recording_id)) {
+        Ok(result) => println!(
+            "[zureshot] Failed recording stopped: {} ({:.1}s)",
+            result.path, result.duration_secs
+        ),
+        Err(e) => eprintln!("[zureshot] Failed to stop failed recording: {}", e),
+    });
+}
+
+/// Cancel region selection without starting recording
+#[tauri::command]
+pub async fn cancel_region_selection(app: AppHandle) -> Result<(), ZureshotError> {
+    do_cancel_region_selection(&app);
+    Ok(())
+}
+
+/// Close the region selector and drop its frozen screen preview.
+fn do_cancel_region_selection(app: &AppHandle) {
+    if let Some(win) = app.get_webview_window("region-selector") {
+        let _ = win.destroy();
+    }
+    for win in extra_region_selectors(app) {
+        let _ = win.destroy();
+    }
+    // Clean up frozen screen preview if exists
+    let preview_path = std::env::temp_dir().join("zureshot_screen_preview.png");
+    let _ = std::fs::remove_file(&preview_path);
+}
+
+/// Pause the current recording (frames will be dropped, timer pauses)
+#[tauri::command]
+pub fn pause_recording(
+    app: AppHandle,
+    state: tauri::State<'_, Mutex<RecordingState>>,
+    recording_id: Option<String>,
+) -> Result<(), ZureshotError> {
+    let mut recordings = state.lock().map_err(|e| e.to_string())?;
+    let id = recordings
+        .resolve(recording_id.as_deref())
+        .ok_or_else(ZureshotError::not_recording)?;
+    // Only the primary recording has a mouse track
+    let is_primary = recordings.primary.as_deref() == Some(id.as_str());
+    let recording = recordings
+        .get_mut(recording_id.as_deref())
+        .ok_or_else(ZureshotError::not_recording)?;
+
+    if recording.is_paused {
+        return Err("Recording is already paused".into());
+    }
+
+    // Set the atomic flag so the capture delegate drops frames
+    if let Some(ref handle) = recording.handle {
+        handle.pause();
+    }
+
+    recording.is_paused = true;
+    recording.pause_start = Some(std::time::Instant::now());
+
+    #[cfg(target_os = "macos")]
+    if is_primary {
+        if let Some(tracker_state) = app.try_state::<Mutex<platform::macos::mouse_tracker::MouseTrackerState>>() {
+            if let Ok(tracker) = tracker_state.lock() {
+                platform::macos::mouse_tracker::pause_mouse_tracking(&tracker);
+            }
+        }
+    }
+    drop(recordings);
+    notify_pause_state(&app, id, true, is_primary);
+
+    println!("[zureshot] Recording paused");
+    Ok(())
+}
+
+/// Resume a paused recording
+#[tauri::command]
+pub fn resume_recording(
+    app: AppHandle,
+    state: tauri::State<'_, Mutex<RecordingState>>,
+    recording_id: Option<String>,
+) -> Result<(), ZureshotError> {
+    let mut recordings = state.lock().map_err(|e| e.to_string())?;
+    let id = recordings
+        .resolve(recording_id.as_deref())
+        .ok_or_else(ZureshotError::not_recording)?;
+    // Only the primary recording has a mouse track
+    let is_primary = recordings.primary.as_deref() == Some(id.as_str());
+    let recording = recordings
+        .get_mut(recording_id.as_deref())
+        .ok_or_else(ZureshotError::not_recording)?;
+
+    if !recording.is_paused {
+        return Err("Recording is not paused".into());
+    }
+
+    // Clear the atomic flag so frames start being written again
+    if let Some(ref handle) = recording.handle {
+        handle.resume();
+    }
+
+    // Accumulate this pause duration
+    if let Some(ps) = recording.pause_start.take() {
+        recording.pause_accumulated += ps.elapsed();
+    }
+    recording.is_paused = false;
+
+    #[cfg(target_os = "macos")]
+    if is_primary {
+        if let Some(tracker_state) = app.try_state::<Mutex<platform::macos::mouse_tracker::MouseTrackerState>>() {
+            if let Ok(tracker) = tracker_state.lock() {
+                platform::macos::mouse_tracker::resume_mouse_tracking(&tracker);
+            }
+        }
+    }
+    drop(recordings);
+    notify_pause_state(&app, id, false, is_primary);
+
+    println!("[zureshot] Recording resumed");
+    Ok(())
+}
+
+/// Force the next encoded frame to be a keyframe, e.g. right before a
+/// scene change so the cut lands on a clean seek point. Targets the given
+/// (or primary) recording; with no id and no recording running, macOS
+/// targets the instant replay buffer instead, whose encoder we own
+/// (AVAssetWriter keeps its session private).
+#[tauri::command]
+pub fn request_keyframe(
+    app: AppHandle,
+    state: tauri::State<'_, Mutex<RecordingState>>,
+    recording_id: Option<String>,
+) -> Result<(), ZureshotError> {
+    let recording_result = {
+        let recordings = state.lock().map_err(|e| e.to_string())?;
+        recordings
+            .get(recording_id.as_deref())
+            .and_then(|r| r.handle.as_ref())
+            .map(|handle| handle.request_keyframe())
+    };
+    if recording_id.is_some() || recording_result.is_some() {
+        return Ok(recording_result.ok_or_else(ZureshotError::not_recording)??);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let replay_state: tauri::State<'_, Mutex<platform::macos::replay::InstantReplayState>> = app.state();
+        let replay = replay_state.lock().map_err(|e| e.to_string())?;
+        if let Some(ref session) = replay.session {
+            session.request_keyframe();
+            println!("[zureshot] Keyframe requested (instant replay)");
+            return Ok(());
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = app;
+
+    Err(ZureshotError::not_recording())
+}
+
+/// Global hotkey for dropping a chapter marker while recording
+pub const CHAPTER_MARKER_SHORTCUT: &str = "CmdOrCtrl+Shift+M";
+
+/// Core logic to add a chapter marker at the current recording time
+pub fn do_add_chapter_marker(
+    app: &AppHandle,
+    recording_id: Option<&str>,
+    label: Option<String>,
+) -> Result<ChapterMarker, String> {
+    let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+    let mut recordings = state.lock().map_err(|e| e.to_string())?;
+    let recording = recordings
+        .get_mut(recording_id)
+        .ok_or_else(ZureshotError::not_recording)?;
+
+    let time_secs = recording.elapsed_secs();
+    let label = label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| format!("Chapter {}", recording.chapters.len() + 1));
+    let marker = ChapterMarker { time_secs, label };
+    recording.chapters.push(marker.clone());
+    println!("[zureshot] Chapter marker at {:.1}s: {}", marker.time_secs, marker.label);
+
+    let _ = app.emit("chapter-added", &marker);
+    Ok(marker)
+}
+
+/// Drop a chapter marker at the current timestamp (also bound to
+/// `CHAPTER_MARKER_SHORTCUT` while recording)
+#[tauri::command]
+pub fn add_chapter_marker(
+    app: AppHandle,
+    recording_id: Option<String>,
+    label: Option<String>,
+) -> Result<ChapterMarker, ZureshotError> {
+    Ok(do_add_chapter_marker(&app, recording_id.as_deref(), label)?)
+}
+
+/// Global hotkey for bookmarking the current moment (marker + still) while recording
+pub const BOOKMARK_SHORTCUT: &str = "CmdOrCtrl+Shift+B";
+
+/// Folder holding a recording's bookmark stills: `<stem>_bookmarks/`
+fn bookmarks_dir(output_path: &str) -> std::path::PathBuf {
+    let source = std::path::Path::new(output_path);
+    let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("recording");
+    source.with_file_name(format!("{}_bookmarks", stem))
+}
+
+/// Core logic to bookmark the current moment: saves a PNG still of the
+/// recorded area into `<stem>_bookmarks/` and records its timestamp.
+/// Blocks while the still is captured — call off the main thread.
+pub fn do_add_bookmark(app: &AppHandle, recording_id: Option<&str>) -> Result<Bookmark, String> {
+    let (id, time_secs, output_path, region, index) = {
+        let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+        let recordings = state.lock().map_err(|e| e.to_string())?;
+        let id = recordings
+            .resolve(recording_id)
+            .ok_or_else(ZureshotError::not_recording)?;
+        let recording = &recordings.sessions[&id];
+        (
+            id.clone(),
+            recording.elapsed_secs(),
+            recording.output_path.clone().unwrap_or_default(),
+            recording.region.clone(),
+            recording.bookmarks.len() + 1,
+        )
+    }; // ← mutex released before capturing the still
+
+    let region = match region {
+        Some(r) => r,
+        None => {
+            let monitor = app
+                .primary_monitor()
+                .map_err(|e| e.to_string())?
+                .ok_or("No primary monitor")?;
+            let scale = monitor.scale_factor();
+            CaptureRegion {
+                x: 0.0,
+                y: 0.0,
+                width: monitor.size().width as f64 / scale,
+                height: monitor.size().height as f64 / scale,
+            }
+        }
+    };
+
+    let dir = bookmarks_dir(&output_path);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create bookmarks folder: {}", e))?;
+    let total = time_secs as u64;
+    let still_path = dir
+        .join(format!("bookmark_{:02}_{:02}m{:02}s.png", index, total / 60, total % 60))
+        .to_string_lossy()
+        .to_string();
+    platform::imp::take_screenshot_region(
+        region.x,
+        region.y,
+        region.width,
+        region.height,
+        &still_path,
+        ImageFormat::Png,
+    )?;
+
+    let bookmark = Bookmark { time_secs, still_path };
+    {
+        let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+        let mut recordings = state.lock().map_err(|e| e.to_string())?;
+        // Recording may have stopped while the still was being captured
+        let recording = recordings
+            .sessions
+            .get_mut(&id)
+            .filter(|r| r.output_path.as_deref() == Some(output_path.as_str()))
+            .ok_or_else(|| "Recording stopped before the bookmark was saved".to_string())?;
+        recording.bookmarks.push(bookmark.clone());
+    }
+    println!("[zureshot] Bookmark at {:.1}s → {}", bookmark.time_secs, bookmark.still_path);
+
+    let _ = app.emit("bookmark-added", &bookmark);
+    Ok(bookmark)
+}
+
+/// Bookmark the current moment (also bound to `BOOKMARK_SHORTCUT` while recording)
+#[tauri::command]
+pub async fn add_bookmark(app: AppHandle, recording_id: Option<String>) -> Result<Bookmark, ZureshotError> {
+    Ok(tokio::task::spawn_blocking(move || do_add_bookmark(&app, recording_id.as_deref()))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??)
+}
+
+/// `<stem>.bookmarks.json`: the recording and its bookmarks' still paths
+#[derive(Serialize, Deserialize)]
+struct BookmarksIndex {
+    recording: String,
+    bookmarks: Vec<Bookmark>,
+}
+
+/// Write `<stem>.bookmarks.json` mapping timestamps to still paths.
+fn write_bookmarks_index(output_path: &str, bookmarks: &[Bookmark]) {
+    let index_path = std::path::Path::new(output_path).with_extension("bookmarks.json");
+    let index = BookmarksIndex {
+        recording: output_path.to_string(),
+        bookmarks: bookmarks.to_vec(),
+    };
+    match std::fs::write(&index_path, serde_json::to_string_pretty(&index).unwrap_or_default()) {
+        Ok(()) => println!("[zureshot] {} bookmark(s) written: {}", bookmarks.len(), index_path.display()),
+        Err(e) => eprintln!("[zureshot] Failed to write bookmarks index: {}", e),
+    }
+}
+
+/// Save the capture settings a recording started with as its
+/// `<stem>.recording.json` sidecar.
+fn write_capture_settings(output_path: &str, capture: &LastRecording) {
+    let path = std::path::Path::new(output_path).with_extension("recording.json");
+    if let Err(e) = std::fs::write(&path, serde_json::to_string_pretty(capture).unwrap_or_default()) {
+        eprintln!("[zureshot] Failed to write recording settings: {}", e);
+    }
+}
+
+/// The settings saved by `write_capture_settings` (None for recordings
+/// made before they were kept, or by another app).
+fn read_capture_settings(output_path: &str) -> Option<LastRecording> {
+    let path = std::path::Path::new(output_path).with_extension("recording.json");
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Register the chapter-marker and bookmark hotkeys for the duration of a recording.
+fn register_recording_shortcuts(app: &AppHandle) {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+    let result = app
+        .global_shortcut()
+        .on_shortcut(CHAPTER_MARKER_SHORTCUT, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                if let Err(e) = do_add_chapter_marker(app, None, None) {
+                    eprintln!("[zureshot] Chapter marker error: {}", e);
+                }
+            }
+        });
+    if let Err(e) = result {
+        eprintln!("[zureshot] Failed to register {}: {}", CHAPTER_MARKER_SHORTCUT, e);
+    }
+
+    let result = app
+        .global_shortcut()
+        .on_shortcut(BOOKMARK_SHORTCUT, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                // Still capture blocks on GCD — keep off the main thread
+                let app = app.clone();
+                std::thread::spawn(move || {
+                    if let Err(e) = do_add_bookmark(&app, None) {
+                        eprintln!("[zureshot] Bookmark error: {}", e);
+                    }
+                });
+            }
+        });
+    if let Err(e) = result {
+        eprintln!("[zureshot] Failed to register {}: {}", BOOKMARK_SHORTCUT, e);
+    }
+
+    let result = app
+        .global_shortcut()
+        .on_shortcut(BAR_ON_TOP_SHORTCUT, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                do_set_bar_always_on_top(app, !bar_on_top());
+            }
+        });
+    if let Err(e) = result {
+        eprintln!("[zureshot] Failed to register {}: {}", BAR_ON_TOP_SHORTCUT, e);
+    }
+
+    if let Err(e) = register_stop_hotkey(app, &global_hotkeys(app).stop) {
+        eprintln!("[zureshot] {}", e);
+    }
+}
+
+fn unregister_recording_shortcuts(app: &AppHandle) {
+    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+    let _ = app.global_shortcut().unregister(CHAPTER_MARKER_SHORTCUT);
+    let _ = app.global_shortcut().unregister(BOOKMARK_SHORTCUT);
+    let _ = app.global_shortcut().unregister(BAR_ON_TOP_SHORTCUT);
+    unregister_hotkey(app, &global_hotkeys(app).stop);
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Global hotkeys (start region / screenshot / stop, from settings.json)
+// ═══════════════════════════════════════════════════════════════════════
+
+/// System-wide key combos, persisted as `global_hotkeys`. All are off
+/// (empty) until the user picks a combo: a system-wide grab takes the keys
+/// from every other app, so it has to be opted into. The stop hotkey is only
+/// registered while recording so it doesn't take the combo from other apps
+/// otherwise.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GlobalHotkeys {
+    #[serde(default)]
+    pub start_region: String,
+    #[serde(default)]
+    pub screenshot: String,
+    #[serde(default)]
+    pub stop: String,
+}
+
+pub fn global_hotkeys(app: &AppHandle) -> GlobalHotkeys {
+    crate::settings::get(app, "global_hotkeys").unwrap_or_default()
+}
+
+fn unregister_hotkey(app: &AppHandle, hotkey: &str) {
+    use tauri_plugin_global_shortcut::GlobalShortcutExt;
+    if !hotkey.is_empty() {
+        let _ = app.global_shortcut().unregister(hotkey);
+    }
+}
+
+/// Register the start-region hotkey (no-op when empty).
+fn register_start_region_hotkey(app: &AppHandle, hotkey: &str) -> Result<(), String> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+    if hotkey.is_empty() {
+        return Ok(());
+    }
+    app.global_shortcut()
+        .on_shortcut(hotkey, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                match do_start_region_selection(app) {
+                    Ok(()) => println!("[zureshot] Region selector opened via hotkey"),
+                    Err(e) => eprintln!("[zureshot] Region selection error: {}", e),
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to register {}: {}", hotkey, e))
+}
+
+/// Register the screenshot hotkey (no-op when empty).
+fn register_screenshot_hotkey(app: &AppHandle, hotkey: &str) -> Result<(), String> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+    if hotkey.is_empty() {
+        return Ok(());
+    }
+    app.global_shortcut()
+        .on_shortcut(hotkey, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                match do_start_screenshot_selection(app) {
+                    Ok(()) => println!("[zureshot] Screenshot region selector opened via hotkey"),
+                    Err(e) => eprintln!("[zureshot] Screenshot region selection error: {}", e),
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to register {}: {}", hotkey, e))
+}
+
+/// Register the always-on hotkeys (start region, screenshot). Called once
+/// at startup; a combo another app holds is logged and skipped.
+pub fn register_global_hotkeys(app: &AppHandle) {
+    let hotkeys = global_hotkeys(app);
+    for result in [
+        register_start_region_hotkey(app, &hotkeys.start_region),
+        register_screenshot_hotkey(app, &hotkeys.screenshot),
+    ] {
+        if let Err(e) = result {
+            eprintln!("[zureshot] {}", e);
+        }
+    }
+}
+
+/// Register the stop hotkey for the duration of a recording.
+fn register_stop_hotkey(app: &AppHandle, hotkey: &str) -> Result<(), String> {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+    if hotkey.is_empty() {
+        return Ok(());
+    }
+    app.global_shortcut()
+        .on_shortcut(hotkey, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                // Same as the tray "stop" item: finalizing blocks on GCD
+                // completion handlers that may need the main thread, so never
+                // stop from the hotkey callback itself
+                let app = app.clone();
+                std::thread::spawn(move || match do_stop_recording(&app, None) {
+                    Ok(result) => println!(
+                        "[zureshot] Stopped via hotkey: {} ({:.1}s, {:.1} MB)",
+                        result.path,
+                        result.duration_secs,
+                        result.file_size_bytes as f64 / 1_048_576.0
+                    ),
+                    Err(e) => eprintln!("[zureshot] Stop error: {}", e),
+                });
+            }
+        })
+        .map_err(|e| format!("Failed to register {}: {}", hotkey, e))
+}
+
+/// Register every hotkey of `hotkeys` that is active right now (stop only
+/// while `recording`), all or nothing: on failure the ones already
+/// registered are released again.
+fn register_hotkey_set(app: &AppHandle, hotkeys: &GlobalHotkeys, recording: bool) -> Result<(), String> {
+    type Register = fn(&AppHandle, &str) -> Result<(), String>;
+    let mut steps: Vec<(&str, Register)> = vec![
+        (hotkeys.start_region.as_str(), register_start_region_hotkey as Register),
+        (hotkeys.screenshot.as_str(), register_screenshot_hotkey as Register),
+    ];
+    if recording {
+        steps.push((hotkeys.stop.as_str(), register_stop_hotkey as Register));
+    }
+    for (i, (combo, register)) in steps.iter().enumerate() {
+        if let Err(e) = register(app, combo) {
+            for (done, _) in &steps[..i] {
+                unregister_hotkey(app, done);
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_global_hotkeys(app: AppHandle) -> GlobalHotkeys {
+    global_hotkeys(&app)
+}
+
+/// Change the global hotkeys (persisted, applied immediately). Each combo
+/// uses accelerator syntax (e.g. `CmdOrCtrl+Shift+R`); empty disables it.
+/// Saved only once every combo is registered; if one is taken, the
+/// previous set is restored and the error says which.
+#[tauri::command]
+pub fn set_global_hotkeys(app: AppHandle, hotkeys: GlobalHotkeys) -> Result<(), ZureshotError> {
+    use tauri_plugin_global_shortcut::Shortcut;
+    let reserved = [CHAPTER_MARKER_SHORTCUT, BOOKMARK_SHORTCUT, BAR_ON_TOP_SHORTCUT]
+        .map(|s| s.parse::<Shortcut>().ok());
+    let mut parsed: Vec<Shortcut> = Vec::new();
+    for combo in [&hotkeys.start_region, &hotkeys.screenshot, &hotkeys.stop] {
+        if combo.is_empty() {
+            continue;
+        }
+        let shortcut = combo
+            .parse::<Shortcut>()
+            .map_err(|e| format!("Invalid hotkey '{}': {}", combo, e))?;
+        if parsed.contains(&shortcut) || reserved.contains(&Some(shortcut)) {
+            return Err(format!("Invalid hotkey '{}': already used by another action", combo).into());
+        }
+        parsed.push(shortcut);
+    }
+
+    let old = global_hotkeys(&app);
+    let recording = {
+        let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+        let recordings = state.lock().map_err(|e| e.to_string())?;
+        recordings.primary.is_some()
+    };
+    let release = |keys: &GlobalHotkeys| {
+        unregister_hotkey(&app, &keys.start_region);
+        unregister_hotkey(&app, &keys.screenshot);
+        if recording {
+            unregister_hotkey(&app, &keys.stop);
+        }
+    };
+
+    // The new set may reuse some of the old combos, so the old ones are
+    // released first and restored if the new set can't be registered
+    release(&old);
+    if let Err(e) = register_hotkey_set(&app, &hotkeys, recording) {
+        if let Err(restore) = register_hotkey_set(&app, &old, recording) {
+            eprintln!("[zureshot] Couldn't restore the previous hotkeys: {}", restore);
+        }
+        return Err(format!("{} — kept the previous hotkeys", e).into());
+    }
+    crate::settings::set(&app, "global_hotkeys", serde_json::json!(hotkeys));
+    println!(
+        "[zureshot] Global hotkeys: region={:?} screenshot={:?} stop={:?}",
+        hotkeys.start_region, hotkeys.screenshot, hotkeys.stop
+    );
+    Ok(())
+}
+
+/// Write chapter markers for a finished MP4.
+///
+/// Always writes a WebVTT sidecar (`<stem>.chapters.vtt`) and additionally
+/// embeds the chapters into the MP4 via an ffmpeg stream-copy remux, so
+/// players that read QuickTime/MP4 chapters show them natively.
+fn write_chapters(path: &str, chapters: &[ChapterMarker], duration: f64) {
+    // Each marker starts a chapter that runs until the next one
+    let mut spans: Vec<(f64, f64, String)> = Vec::new();
+    if chapters.first().map(|c| c.time_secs > 0.5).unwrap_or(false) {
+        spans.push((0.0, chapters[0].time_secs, "Start".to_string()));
+    }
+    for (i, c) in chapters.iter().enumerate() {
+        let end = chapters.get(i + 1).map(|n| n.time_secs).unwrap_or(duration).max(c.time_secs);
+        spans.push((c.time_secs, end, c.label.clone()));
+    }
+
+    fn vtt_time(t: f64) -> String {
+        let ms = (t.max(0.0) * 1000.0).round() as u64;
+        format!("{:02}:{:02}:{:02}.{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+    }
+
+    let source = std::path::Path::new(path);
+    let vtt_path = source.with_extension("chapters.vtt");
+    let mut vtt = String::from("WEBVTT\n");
+    for (i, (start, end, label)) in spans.iter().enumerate() {
+        vtt.push_str(&format!("\n{}\n{} --> {}\n{}\n", i + 1, vtt_time(*start), vtt_time(*end), label));
+    }
+    match std::fs::write(&vtt_path, vtt) {
+        Ok(()) => println!("[zureshot] Chapters written: {}", vtt_path.display()),
+        Err(e) => eprintln!("[zureshot] Failed to write chapters sidecar: {}", e),
+    }
+
+    // Embed as MP4 chapters (ffmetadata → -map_chapters)
+    let meta_path = source.with_extension("ffmeta");
+    let mut meta = String::from(";FFMETADATA1\n");
+    for (start, end, label) in &spans {
+        let escaped = label
+            .replace('\\', "\\\\")
+            .replace('=', "\\=")
+            .replace(';', "\\;")
+            .replace('#', "\\#");
+        meta.push_str(&format!(
+            "[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            (start * 1000.0) as u64,
+            (end * 1000.0) as u64,
+            escaped
+        ));
+    }
+    if std::fs::write(&meta_path, meta).is_err() {
+        return;
+    }
+    let ext = source.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+    let tmp_path = source.with_extension(format!("chapters.{}", ext));
+    let result = crate::ffmpeg::command()
+        .args([
+            "-i", path,
+            "-i", &meta_path.to_string_lossy(),
+            "-map", "0",
+            "-map_chapters", "1",
+            "-c", "copy",
+        ])
+        .args(platform::VideoCodec::of_file(path).stream_tag_args())
+        .args(["-y", &tmp_path.to_string_lossy()])
+        .output();
+    let _ = std::fs::remove_file(&meta_path);
+
+    match result {
+        Ok(o) if o.status.success() => {
+            if std::fs::rename(&tmp_path, path).is_ok() {
+                println!("[zureshot] {} chapter(s) embedded into {}", spans.len(), path);
+            }
+        }
+        Ok(o) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            eprintln!("[zureshot] Chapter embed failed: {}", String::from_utf8_lossy(&o.stderr));
+        }
+        Err(e) => eprintln!("[zureshot] ffmpeg not found, chapters kept as sidecar only: {}", e),
+    }
+}
+
+/// Open the floating recording control bar.
+/// Called after recording starts to give the user stop/pause controls.
+pub fn do_open_recording_bar(app: &AppHandle, region: Option<&CaptureRegion>) -> Result<(), String> {
+    // If already open, just focus it
+    if let Some(win) = app.get_webview_window("recording-bar") {
+        let _ = win.show();
         let _ = win.set_focus();
         return Ok(());
     }
 
-    // Bar dimensions (logical pixels)
-    let bar_width = 220.0;
-    let bar_height = 48.0;
+    // Bar dimensions (logical pixels)
+    let bar_width = 220.0;
+    let bar_height = 48.0;
+
+    // Position: smart placement based on region vs screen
+    let monitor = app
+        .primary_monitor()
+        .map_err(|e| format!("Failed to get monitor: {}", e))?
+        .ok_or("No primary monitor found")?;
+    let scale = monitor.scale_factor();
+    let phys_size = monitor.size();
+    let screen_w = phys_size.width as f64 / scale;
+    let screen_h = phys_size.height as f64 / scale;
+
+    let (pos_x, pos_y) = if let Some(rgn) = region {
+        // Check if region is effectively fullscreen (covers >90% of screen)
+        let is_fullscreen = rgn.width >= screen_w * 0.9 && rgn.height >= screen_h * 0.9;
+
+        if is_fullscreen {
+            // Fullscreen: bottom-center, above the Dock area
+            ((screen_w - bar_width) / 2.0, screen_h - bar_height - 80.0)
+        } else {
+            // Custom region: find the side with the most available space
+            let space_below = screen_h - (rgn.y + rgn.height);
+            let space_above = rgn.y;
+            let space_right = screen_w - (rgn.x + rgn.width);
+            let space_left = rgn.x;
+
+            let gap = 16.0;
+            let cx = rgn.x + rgn.width / 2.0 - bar_width / 2.0;
+
+            if space_below >= bar_height + gap + 20.0 {
+                // Below the region (preferred)
+                let x = cx.max(8.0).min(screen_w - bar_width - 8.0);
+                (x, rgn.y + rgn.height + gap)
+            } else if space_above >= bar_height + gap + 20.0 {
+                // Above the region
+                let x = cx.max(8.0).min(screen_w - bar_width - 8.0);
+                (x, rgn.y - bar_height - gap)
+            } else if space_right >= bar_width + gap + 20.0 {
+                // Right of the region
+                let y = (rgn.y + rgn.height / 2.0 - bar_height / 2.0)
+                    .max(8.0).min(screen_h - bar_height - 8.0);
+                (rgn.x + rgn.width + gap, y)
+            } else if space_left >= bar_width + gap + 20.0 {
+                // Left of the region
+                let y = (rgn.y + rgn.height / 2.0 - bar_height / 2.0)
+                    .max(8.0).min(screen_h - bar_height - 8.0);
+                (rgn.x - bar_width - gap, y)
+            } else {
+                // No good space outside: place inside at bottom-center of region
+                let x = cx.max(8.0).min(screen_w - bar_width - 8.0);
+                let y = (rgn.y + rgn.height - bar_height - gap)
+                    .max(8.0).min(screen_h - bar_height - 8.0);
+                (x, y)
+            }
+        }
+    } else {
+        // No region at all: bottom-center
+        ((screen_w - bar_width) / 2.0, screen_h - bar_height - 80.0)
+    };
+
+    // The bar is excluded from the capture either way; with
+    // `keep_ui_out_of_region` it also shouldn't sit over what's being
+    // recorded (a region or a whole display) — move it to a display that
+    // isn't recorded. With none, it stays put: still out of the video
+    let (pos_x, pos_y) = match primary_capture_rect(app) {
+        Some(ref rect) if keep_ui_out_of_region(app) && overlaps_region(rect, pos_x, pos_y, bar_width, bar_height) => {
+            bar_position_off_capture(app, rect, bar_width, bar_height).unwrap_or((pos_x, pos_y))
+        }
+        _ => (pos_x, pos_y),
+    };
+
+    let window = WebviewWindowBuilder::new(
+        app,
+        "recording-bar",
+        WebviewUrl::App("recording-bar.html".into()),
+    )
+    .title("Recording")
+    .inner_size(bar_width, bar_height)
+    .position(pos_x, pos_y)
+    .transparent(true)
+    .decorations(false)
+    .always_on_top(bar_on_top())
+    .skip_taskbar(true)
+    .resizable(false)
+    .build()
+    .map_err(|e| format!("Failed to create recording bar: {}", e))?;
+    apply_bar_spaces(app, &window);
+
+    let _ = window.show();
+    let _ = window.set_focus();
+
+    println!("[zureshot] Recording bar opened at ({:.0}, {:.0})", pos_x, pos_y);
+    Ok(())
+}
+
+/// Whether a `w`×`h` window at (`x`, `y`) would cover part of `region`.
+fn overlaps_region(region: &CaptureRegion, x: f64, y: f64, w: f64, h: f64) -> bool {
+    x < region.x + region.width && x + w > region.x && y < region.y + region.height && y + h > region.y
+}
+
+/// Bottom-center of the first display the bar placed there wouldn't put
+/// over `recorded` (global logical coordinates).
+fn bar_position_off_capture(
+    app: &AppHandle,
+    recorded: &CaptureRegion,
+    bar_width: f64,
+    bar_height: f64,
+) -> Option<(f64, f64)> {
+    let position = platform::imp::list_displays()
+        .ok()?
+        .into_iter()
+        .map(|d| (d.x + (d.width as f64 - bar_width) / 2.0, d.y + d.height as f64 - bar_height - 80.0))
+        .find(|&(x, y)| !overlaps_region(recorded, x, y, bar_width, bar_height));
+    if position.is_some() {
+        println!("[zureshot] Recording bar moved to another display to keep it out of the recording");
+    } else {
+        println!("[zureshot] No display outside the recording; the bar stays, excluded from the video");
+    }
+    position
+}
+
+/// Whether the recording bar is kept from covering the recorded region
+#[tauri::command]
+pub fn get_keep_ui_out_of_region(app: AppHandle) -> bool {
+    keep_ui_out_of_region(&app)
+}
+
+/// Keep the recording bar off the recorded region or display when there is
+/// no room around it: it moves to a display that isn't recorded, if any
+/// (persisted, applies to the next recording). It's excluded from the video
+/// either way.
+#[tauri::command]
+pub fn set_keep_ui_out_of_region(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "keep_ui_out_of_region", serde_json::json!(enabled));
+    println!("[zureshot] Keep UI out of recorded region: {}", enabled);
+}
+
+fn keep_ui_out_of_region(app: &AppHandle) -> bool {
+    crate::settings::get_bool(app, "keep_ui_out_of_region", false)
+}
+
+/// Open a fullscreen transparent dim overlay that darkens the non-recorded area.
+/// The overlay is click-through (ignores cursor events) so the user can still
+/// interact with apps underneath. Only used for region recording.
+pub fn do_open_recording_overlay(app: &AppHandle, region: &CaptureRegion) -> Result<(), String> {
+    // If already open, just show it
+    if let Some(win) = app.get_webview_window("recording-overlay") {
+        let _ = win.show();
+        return Ok(());
+    }
+
+    let monitor = app
+        .primary_monitor()
+        .map_err(|e| format!("Failed to get monitor: {}", e))?
+        .ok_or("No primary monitor found")?;
+    let scale = monitor.scale_factor();
+    let phys_size = monitor.size();
+    let position = monitor.position();
+    let logical_w = phys_size.width as f64 / scale;
+    let logical_h = phys_size.height as f64 / scale;
+
+    let window = WebviewWindowBuilder::new(
+        app,
+        "recording-overlay",
+        WebviewUrl::App("recording-overlay.html".into()),
+    )
+    .title("Recording Overlay")
+    .inner_size(logical_w, logical_h)
+    .position(position.x as f64 / scale, position.y as f64 / scale)
+    .transparent(true)
+    .decorations(false)
+    .always_on_top(bar_on_top())
+    .skip_taskbar(true)
+    .resizable(false)
+    .build()
+    .map_err(|e| format!("Failed to create recording overlay: {}", e))?;
+    apply_bar_spaces(app, &window);
+
+    // Make overlay completely click-through so it doesn't intercept mouse events
+    let _ = window.set_ignore_cursor_events(true);
+    let _ = window.show();
+
+    println!(
+        "[zureshot] Recording overlay opened for region ({:.0},{:.0} {:.0}x{:.0})",
+        region.x, region.y, region.width, region.height
+    );
+    Ok(())
+}
+
+/// Tap feeding the keystroke overlay while the primary recording runs
+#[cfg(target_os = "macos")]
+static KEYSTROKE_MONITOR: Mutex<Option<platform::macos::keystrokes::KeystrokeMonitor>> = Mutex::new(None);
+
+/// Open the click-through keystroke overlay over the bottom of the
+/// recorded area and start forwarding key presses to it as `keystroke`
+/// events. The overlay is kept in the video (see
+/// `collect_app_windows_to_exclude`).
+fn open_keystroke_overlay(app: &AppHandle, region: Option<&CaptureRegion>) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        // Overlay size (logical points) and its gap to the bottom of the
+        // recorded area, leaving room for the recording bar
+        const KEYSTROKE_OVERLAY_WIDTH: f64 = 640.0;
+        const KEYSTROKE_OVERLAY_HEIGHT: f64 = 96.0;
+        const KEYSTROKE_OVERLAY_MARGIN: f64 = 120.0;
+
+        let settings = get_keystroke_overlay(app.clone());
+        let area = region.cloned().or_else(|| primary_display_bounds(app)).ok_or("No primary monitor found")?;
+        let width = KEYSTROKE_OVERLAY_WIDTH.min(area.width);
+        let pos_x = area.x + (area.width - width) / 2.0;
+        let pos_y = area.y + (area.height - KEYSTROKE_OVERLAY_HEIGHT - KEYSTROKE_OVERLAY_MARGIN).max(0.0);
+
+        let emitter = app.clone();
+        let monitor = platform::macos::keystrokes::KeystrokeMonitor::start(move |key| {
+            let _ = emitter.emit_to("keystroke-overlay", "keystroke", &key);
+        })?;
+
+        if let Some(win) = app.get_webview_window("keystroke-overlay") {
+            let _ = win.destroy();
+        }
+        let window = WebviewWindowBuilder::new(
+            app,
+            "keystroke-overlay",
+            WebviewUrl::App("keystroke-overlay.html".into()),
+        )
+        .title("Keystrokes")
+        .initialization_script(&format!("window.__KEYSTROKE_FADE_SECS__ = {};", settings.fade_secs))
+        .inner_size(width, KEYSTROKE_OVERLAY_HEIGHT)
+        .position(pos_x, pos_y)
+        .transparent(true)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .focused(false)
+        .build()
+        .map_err(|e| format!("Failed to create keystroke overlay: {}", e))?;
+        apply_bar_spaces(app, &window);
+        let _ = window.set_ignore_cursor_events(true);
+        let _ = window.show();
+
+        if let Ok(mut slot) = KEYSTROKE_MONITOR.lock() {
+            *slot = Some(monitor);
+        }
+        println!("[zureshot] Keystroke overlay opened (fade {:.1}s)", settings.fade_secs);
+        Ok(())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, region);
+        Err("The keystroke overlay is only available on macOS".into())
+    }
+}
+
+/// Remove the keystroke tap and close its overlay.
+fn close_keystroke_overlay(app: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    if let Some(monitor) = KEYSTROKE_MONITOR.lock().ok().and_then(|mut m| m.take()) {
+        drop(monitor);
+    }
+    if let Some(win) = app.get_webview_window("keystroke-overlay") {
+        let _ = win.destroy();
+    }
+}
+
+/// Tap feeding the click highlight overlay while the primary recording runs
+#[cfg(target_os = "macos")]
+static CLICK_MONITOR: Mutex<Option<platform::macos::clicks::ClickMonitor>> = Mutex::new(None);
+
+/// Open the click-through click highlight overlay over the recorded area
+/// and forward left clicks inside it as `click` events (window-relative
+/// points). Kept in the video like the keystroke overlay.
+fn open_click_overlay(app: &AppHandle, region: Option<&CaptureRegion>) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let settings = get_click_highlight(app.clone());
+        let area = region.cloned().or_else(|| primary_display_bounds(app)).ok_or("No primary monitor found")?;
+
+        let emitter = app.clone();
+        let bounds = area.clone();
+        let monitor = platform::macos::clicks::ClickMonitor::start(move |click| {
+            let (x, y) = (click.x - bounds.x, click.y - bounds.y);
+            if x >= 0.0 && y >= 0.0 && x <= bounds.width && y <= bounds.height {
+                let _ = emitter.emit_to("click-overlay", "click", platform::macos::clicks::Click { x, y });
+            }
+        })?;
+
+        if let Some(win) = app.get_webview_window("click-overlay") {
+            let _ = win.destroy();
+        }
+        let window = WebviewWindowBuilder::new(app, "click-overlay", WebviewUrl::App("click-overlay.html".into()))
+            .title("Clicks")
+            .initialization_script(&format!(
+                "window.__CLICK_HIGHLIGHT_COLOR__ = {}; window.__CLICK_HIGHLIGHT_RADIUS__ = {};",
+                serde_json::json!(settings.color),
+                settings.radius
+            ))
+            .inner_size(area.width, area.height)
+            .position(area.x, area.y)
+            .transparent(true)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .resizable(false)
+            .focused(false)
+            .build()
+            .map_err(|e| format!("Failed to create click overlay: {}", e))?;
+        apply_bar_spaces(app, &window);
+        let _ = window.set_ignore_cursor_events(true);
+        let _ = window.show();
+
+        if let Ok(mut slot) = CLICK_MONITOR.lock() {
+            *slot = Some(monitor);
+        }
+        println!("[zureshot] Click overlay opened ({} r={:.0})", settings.color, settings.radius);
+        Ok(())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, region);
+        Err("The click highlight is only available on macOS".into())
+    }
+}
+
+/// Remove the click tap and close its overlay.
+fn close_click_overlay(app: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    if let Some(monitor) = CLICK_MONITOR.lock().ok().and_then(|mut m| m.take()) {
+        drop(monitor);
+    }
+    if let Some(win) = app.get_webview_window("click-overlay") {
+        let _ = win.destroy();
+    }
+}
+
+/// Whether the recording bar and dim overlay float above other windows.
+/// Reset to on-top whenever the primary recording stops.
+static BAR_ON_TOP: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(true);
+
+/// Global hotkey that toggles the recording bar between floating and behind
+/// other windows while recording
+pub const BAR_ON_TOP_SHORTCUT: &str = "CmdOrCtrl+Shift+H";
+
+fn bar_on_top() -> bool {
+    BAR_ON_TOP.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Core logic to raise/lower the recording bar and overlay
+pub fn do_set_bar_always_on_top(app: &AppHandle, on_top: bool) {
+    BAR_ON_TOP.store(on_top, std::sync::atomic::Ordering::Relaxed);
+    for label in ["recording-bar", "recording-overlay"] {
+        if let Some(win) = app.get_webview_window(label) {
+            let _ = win.set_always_on_top(on_top);
+        }
+    }
+    let _ = app.emit("bar-always-on-top-changed", on_top);
+    println!(
+        "[zureshot] Recording bar {}",
+        if on_top { "floating above other windows" } else { "lowered behind other windows" }
+    );
+}
+
+/// Tauri command: keep the recording bar/overlay above other windows, or
+/// let them fall behind the user's work (`BAR_ON_TOP_SHORTCUT` toggles back)
+#[tauri::command]
+pub fn set_bar_always_on_top(app: AppHandle, on_top: bool) {
+    do_set_bar_always_on_top(&app, on_top);
+}
+
+/// Tauri command: whether the recording bar currently floats on top
+#[tauri::command]
+pub fn get_bar_always_on_top() -> bool {
+    bar_on_top()
+}
+
+/// Spaces behavior of the recording bar and dim overlay
+#[tauri::command]
+pub fn get_bar_spaces(app: AppHandle) -> platform::BarSpacesConfig {
+    crate::settings::get(&app, "bar_spaces").unwrap_or_default()
+}
+
+/// Choose whether the recording bar/overlay follow you to other Spaces
+/// (including fullscreen apps) and stay put during Space switches
+/// (persisted, applied to open windows immediately).
+#[tauri::command]
+pub fn set_bar_spaces(app: AppHandle, config: platform::BarSpacesConfig) {
+    crate::settings::set(&app, "bar_spaces", serde_json::json!(config));
+    println!(
+        "[zureshot] Recording bar Spaces: join_all_spaces={}, stationary={}",
+        config.join_all_spaces, config.stationary
+    );
+    for label in ["recording-bar", "recording-overlay"] {
+        if let Some(win) = app.get_webview_window(label) {
+            apply_bar_spaces(&app, &win);
+        }
+    }
+}
+
+/// Apply the `bar_spaces` setting to the recording bar or dim overlay.
+fn apply_bar_spaces(app: &AppHandle, window: &tauri::WebviewWindow) {
+    #[cfg(target_os = "macos")]
+    {
+        let config = get_bar_spaces(app.clone());
+        if let Err(e) = platform::macos::apply_spaces_behavior(window, &config) {
+            eprintln!("[zureshot] Failed to set Spaces behavior for {}: {}", window.label(), e);
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (app, window);
+}
+
+/// Attempts (`EXCLUSION_RETRY_DELAY` apart) to get newly opened windows
+/// out of the capture
+const EXCLUSION_ATTEMPTS: u32 = 5;
+const EXCLUSION_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// Refresh the stream exclusion after opening recording windows, retrying
+/// until WindowServer lists them (a window SCK doesn't know about yet can't
+/// be excluded and would show up in the video).
+fn refresh_stream_exclusion_with_retry(app: &AppHandle) {
+    for attempt in 1..=EXCLUSION_ATTEMPTS {
+        std::thread::sleep(EXCLUSION_RETRY_DELAY);
+        match refresh_stream_exclusion(app) {
+            Ok(()) => return,
+            Err(e) if attempt == EXCLUSION_ATTEMPTS => {
+                eprintln!("[zureshot] WARNING: recording windows may be visible in the capture: {}", e);
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+pub fn privacy_exclusions(app: &AppHandle) -> platform::PrivacyExclusions {
+    crate::settings::get(app, "privacy_exclusions").unwrap_or_default()
+}
+
+/// Save the privacy exclusion list and apply it to a running recording.
+pub fn do_set_privacy_exclusions(app: &AppHandle, exclusions: platform::PrivacyExclusions) {
+    println!(
+        "[zureshot] Privacy exclusions: {} app(s), {} title(s)",
+        exclusions.bundle_ids.len(),
+        exclusions.window_titles.len()
+    );
+    crate::settings::set(app, "privacy_exclusions", serde_json::json!(exclusions));
+    let recording = {
+        let state = app.state::<Mutex<RecordingState>>();
+        state.lock().map(|r| r.is_recording()).unwrap_or(false)
+    };
+    if recording {
+        if let Err(e) = refresh_stream_exclusion(app) {
+            eprintln!("[zureshot] Failed to apply privacy exclusions: {}", e);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_privacy_exclusions(app: AppHandle) -> platform::PrivacyExclusions {
+    privacy_exclusions(&app)
+}
+
+/// Hide other apps' windows (by bundle id or title) from display
+/// recordings. macOS only; window recordings capture just their window.
+#[tauri::command]
+pub fn set_privacy_exclusions(app: AppHandle, exclusions: platform::PrivacyExclusions) {
+    do_set_privacy_exclusions(&app, exclusions);
+}
+
+/// Refresh the stream content filter to exclude our app windows from capture.
+/// Each platform handles this differently (macOS: SCStream filter, Linux: no-op).
+pub fn refresh_stream_exclusion(app: &AppHandle) -> Result<(), String> {
+    let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+    let recordings = state.lock().map_err(|e| e.to_string())?;
+    if let Some(handle) = recordings.primary().and_then(|r| r.handle.as_ref()) {
+        handle.refresh_exclusion(app)
+    } else {
+        Err("No active recording to update".into())
+    }
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  Broken recording cleanup
+// ════════════════════════════════════════════════════════════════════════
+
+/// Anything smaller can't hold a playable video frame plus headers
+const MIN_RECORDING_BYTES: u64 = 16 * 1024;
+/// Shorter (non-fragmented) files are left over from failed starts
+const MIN_RECORDING_SECS: f64 = 0.5;
+
+/// A recording that can't be played back
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BrokenRecording {
+    pub path: String,
+    pub file_size_bytes: u64,
+    /// "empty", "too_small", "no_moov" or "too_short"
+    pub reason: String,
+}
+
+/// Outcome of `cleanup_broken_recordings`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CleanupResult {
+    pub found: Vec<BrokenRecording>,
+    /// Files actually moved to the Trash (empty for a dry run or if declined)
+    pub trashed: Vec<String>,
+}
+
+/// Top-level MP4 structure, enough to tell whether a file is playable.
+struct Mp4Summary {
+    has_moov: bool,
+    /// `moof` boxes present (fragmented MP4, e.g. the Linux pipeline)
+    fragmented: bool,
+    /// `mvhd` duration, when the header could be read
+    duration_secs: Option<f64>,
+}
+
+/// Header of a top-level MP4 box.
+struct BoxHeader {
+    kind: [u8; 4],
+    /// Whole box, header included
+    size: u64,
+    header_len: u64,
+    /// Size 0 in the file: the box runs to the end (may still be growing)
+    to_end: bool,
+}
+
+/// The complete top-level box at `pos` of a `len`-byte file; `None` past
+/// the end or for a truncated box.
+fn read_box_header(file: &mut std::fs::File, pos: u64, len: u64) -> std::io::Result<Option<BoxHeader>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    if pos + 8 > len {
+        return Ok(None);
+    }
+    file.seek(SeekFrom::Start(pos))?;
+    let mut header = [0u8; 8];
+    file.read_exact(&mut header)?;
+    let mut size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+    let kind = [header[4], header[5], header[6], header[7]];
+    let mut header_len = 8;
+    let to_end = size == 0;
+    if size == 1 {
+        let mut large = [0u8; 8];
+        file.read_exact(&mut large)?;
+        size = u64::from_be_bytes(large);
+        header_len = 16;
+    } else if to_end {
+        size = len - pos;
+    }
+    if size < header_len || pos + size > len {
+        return Ok(None); // truncated — typical of a writer that never finalized
+    }
+    Ok(Some(BoxHeader { kind, size, header_len, to_end }))
+}
+
+/// Walk the top-level boxes of an MP4 without reading the media data.
+fn scan_mp4(path: &std::path::Path) -> std::io::Result<Mp4Summary> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut summary = Mp4Summary { has_moov: false, fragmented: false, duration_secs: None };
+    let mut pos = 0u64;
+
+    while let Some(b) = read_box_header(&mut file, pos, len)? {
+        match &b.kind {
+            b"moov" => {
+                summary.has_moov = true;
+                summary.duration_secs = read_mvhd_duration(&mut file, pos + b.header_len, b.size - b.header_len);
+            }
+            b"moof" => summary.fragmented = true,
+            _ => {}
+        }
+        pos += b.size;
+    }
+    Ok(summary)
+}
+
+/// Fragments of a fragmented MP4 that are whole on disk (a `moof` and the
+/// `mdat` after it), and the byte offset where the last one ends. An `mdat`
+/// still being written (size 0, "to end of file") isn't counted.
+fn complete_fragments(path: &std::path::Path) -> std::io::Result<(usize, u64)> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let (mut fragments, mut end) = (0usize, 0u64);
+    let mut in_fragment = false;
+    let mut pos = 0u64;
+
+    while let Some(b) = read_box_header(&mut file, pos, len)? {
+        match &b.kind {
+            b"moof" => in_fragment = true,
+            b"mdat" if in_fragment && !b.to_end => {
+                fragments += 1;
+                end = pos + b.size;
+                in_fragment = false;
+            }
+            _ => {}
+        }
+        pos += b.size;
+    }
+    Ok((fragments, end))
+}
+
+/// Duration from the `mvhd` box inside a `moov` payload.
+fn read_mvhd_duration(file: &mut std::fs::File, start: u64, len: u64) -> Option<f64> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut pos = start;
+    while pos + 8 <= start + len {
+        file.seek(SeekFrom::Start(pos)).ok()?;
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header).ok()?;
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        if &header[4..8] == b"mvhd" {
+            let mut body = [0u8; 32];
+            file.read_exact(&mut body).ok()?;
+            // v0: ctime(4) mtime(4) timescale(4) duration(4); v1: 8/8/4/8
+            let (timescale, duration) = if body[0] == 1 {
+                (
+                    u32::from_be_bytes(body[20..24].try_into().ok()?) as f64,
+                    u64::from_be_bytes(body[24..32].try_into().ok()?) as f64,
+                )
+            } else {
+                (
+                    u32::from_be_bytes(body[12..16].try_into().ok()?) as f64,
+                    u32::from_be_bytes(body[16..20].try_into().ok()?) as f64,
+                )
+            };
+            return (timescale > 0.0).then_some(duration / timescale);
+        }
+        if size < 8 {
+            return None;
+        }
+        pos += size;
+    }
+    None
+}
+
+/// Why `path` is unplayable, or `None` if it looks fine.
+fn broken_reason(path: &std::path::Path, size: u64) -> Option<&'static str> {
+    if size == 0 {
+        return Some("empty");
+    }
+    if size < MIN_RECORDING_BYTES {
+        return Some("too_small");
+    }
+    let summary = scan_mp4(path).ok()?;
+    if !summary.has_moov {
+        return Some("no_moov");
+    }
+    match summary.duration_secs {
+        // Fragmented files may legitimately report 0 in the moov header
+        Some(d) if d < MIN_RECORDING_SECS && !summary.fragmented => Some("too_short"),
+        _ => None,
+    }
+}
+
+/// Output files of the recordings currently in progress.
+fn active_output_paths(app: &AppHandle) -> Vec<String> {
+    let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
+    state
+        .lock()
+        .map(|r| r.sessions.values().filter_map(|s| s.output_path.clone()).collect())
+        .unwrap_or_default()
+}
+
+/// Broken MP4/MOV recordings in the recordings folder, skipping any
+/// recording in progress and the temp files remuxes write next to one.
+fn find_broken_recordings(app: &AppHandle) -> Vec<BrokenRecording> {
+    let active = active_output_paths(app);
+
+    let dir = crate::settings::recordings_dir(app);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut found: Vec<BrokenRecording> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .map(|x| x.eq_ignore_ascii_case("mp4") || x.eq_ignore_ascii_case("mov"))
+                .unwrap_or(false)
+        })
+        .filter(|p| !active.iter().any(|a| std::path::Path::new(a) == p.as_path()))
+        .filter(|p| !platform::is_remux_temp(p))
+        .filter_map(|p| {
+            let size = std::fs::metadata(&p).ok()?.len();
+            let reason = broken_reason(&p, size)?;
+            Some(BrokenRecording {
+                path: p.to_string_lossy().to_string(),
+                file_size_bytes: size,
+                reason: reason.to_string(),
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    found
+}
+
+/// Find broken recordings and, after a native confirmation dialog, move
+/// them to the Trash. Blocks on the dialog — call off the main thread.
+pub fn do_cleanup_broken_recordings(app: &AppHandle, dry_run: bool) -> CleanupResult {
+    let found = find_broken_recordings(app);
+    if found.is_empty() || dry_run {
+        return CleanupResult { found, trashed: Vec::new() };
+    }
+    let trashed = confirm_and_trash_broken(&found).unwrap_or_default();
+    CleanupResult { found, trashed }
+}
+
+/// Ask before moving `found` to the Trash. `None` if the user chose to
+/// keep them, else the paths actually trashed.
+fn confirm_and_trash_broken(found: &[BrokenRecording]) -> Option<Vec<String>> {
+    println!("[zureshot] Found {} broken recording(s)", found.len());
+    let names: Vec<String> = found
+        .iter()
+        .take(5)
+        .map(|b| {
+            std::path::Path::new(&b.path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+        .collect();
+    let more = if found.len() > names.len() { format!("\n…and {} more", found.len() - names.len()) } else { String::new() };
+    let message = format!(
+        "{} recording(s) in your recordings folder are empty or unplayable:\n\n{}{}\n\nMove them to the Trash?",
+        found.len(),
+        names.join("\n"),
+        more
+    );
+    if !platform::imp::show_confirm_dialog("Zureshot", &message, "Move to Trash", "Keep") {
+        println!("[zureshot] Broken recordings kept");
+        return None;
+    }
+
+    let trashed = found
+        .iter()
+        .filter_map(|b| match trash_recording(&b.path, true) {
+            Ok(_) => Some(b.path.clone()),
+            Err(e) => {
+                eprintln!("[zureshot] {}", e);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    println!("[zureshot] Moved {} broken recording(s) to Trash", trashed.len());
+    Some(trashed)
+}
+
+/// Whether launch checks the recordings folder for broken recordings.
+pub fn cleanup_on_launch(app: &AppHandle) -> bool {
+    crate::settings::get_bool(app, "cleanup_on_launch", true)
+}
+
+#[tauri::command]
+pub fn get_cleanup_on_launch(app: AppHandle) -> bool {
+    cleanup_on_launch(&app)
+}
+
+/// Offer to trash broken recordings at launch (persisted). The manual
+/// `cleanup_broken_recordings` command is not affected.
+#[tauri::command]
+pub fn set_cleanup_on_launch(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "cleanup_on_launch", serde_json::json!(enabled));
+    println!("[zureshot] Broken recording check on launch: {}", enabled);
+}
+
+/// Launch-time cleanup: like `do_cleanup_broken_recordings`, but skips
+/// recordings the user already chose to keep (`cleanup_declined`) so
+/// "Keep" doesn't bring the dialog back on every launch.
+pub fn cleanup_broken_recordings_on_launch(app: &AppHandle) {
+    if !cleanup_on_launch(app) {
+        return;
+    }
+    let declined: Vec<String> = crate::settings::get(app, "cleanup_declined").unwrap_or_default();
+    let found: Vec<BrokenRecording> =
+        find_broken_recordings(app).into_iter().filter(|b| !declined.contains(&b.path)).collect();
+    if found.is_empty() {
+        return;
+    }
+    if confirm_and_trash_broken(&found).is_none() {
+        // Forget files that are gone so the list doesn't grow forever
+        crate::settings::update(app, "cleanup_declined", |current| {
+            let mut paths: Vec<String> = serde_json::from_value(current.clone()).unwrap_or_default();
+            paths.extend(found.iter().map(|b| b.path.clone()));
+            paths.retain(|p| std::path::Path::new(p).exists());
+            paths.sort();
+            paths.dedup();
+            serde_json::json!(paths)
+        });
+    }
+}
+
+/// Tauri command: list broken recordings (`dry_run`) or move them to the
+/// Trash after confirmation
+#[tauri::command]
+pub async fn cleanup_broken_recordings(app: AppHandle, dry_run: Option<bool>) -> Result<CleanupResult, ZureshotError> {
+    Ok(tokio::task::spawn_blocking(move || do_cleanup_broken_recordings(&app, dry_run.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("Cleanup failed: {}", e))?)
+}
 
-    // Position: smart placement based on region vs screen
-    let monitor = app
-        .primary_monitor()
-        .map_err(|e| format!("Failed to get monitor: {}", e))?
-        .ok_or("No primary monitor found")?;
-    let scale = monitor.scale_factor();
-    let phys_size = monitor.size();
-    let screen_w = phys_size.width as f64 / scale;
-    let screen_h = phys_size.height as f64 / scale;
+// ════════════════════════════════════════════════════════════════════════
+//  File naming (filename_template setting)
+// ════════════════════════════════════════════════════════════════════════
 
-    let (pos_x, pos_y) = if let Some(rgn) = region {
-        // Check if region is effectively fullscreen (covers >90% of screen)
-        let is_fullscreen = rgn.width >= screen_w * 0.9 && rgn.height >= screen_h * 0.9;
+/// What a capture is, for the `{type}` token and the built-in names.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CaptureKind {
+    Recording,
+    Screenshot,
+    /// Screenshot saved straight to the clipboard (and kept on disk)
+    ClipboardScreenshot,
+    /// Annotated screenshot saved from the editor
+    AnnotatedScreenshot,
+    ScrollScreenshot,
+    Replay,
+}
 
-        if is_fullscreen {
-            // Fullscreen: bottom-center, above the Dock area
-            ((screen_w - bar_width) / 2.0, screen_h - bar_height - 80.0)
-        } else {
-            // Custom region: find the side with the most available space
-            let space_below = screen_h - (rgn.y + rgn.height);
-            let space_above = rgn.y;
-            let space_right = screen_w - (rgn.x + rgn.width);
-            let space_left = rgn.x;
+impl CaptureKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Recording => "recording",
+            Self::Screenshot | Self::ClipboardScreenshot | Self::AnnotatedScreenshot => "screenshot",
+            Self::ScrollScreenshot => "scroll_screenshot",
+            Self::Replay => "replay",
+        }
+    }
 
-            let gap = 16.0;
-            let cx = rgn.x + rgn.width / 2.0 - bar_width / 2.0;
+    /// Name used when no `filename_template` is set
+    fn default_template(self) -> &'static str {
+        match self {
+            Self::Recording => "zureshot_{date}_{time}",
+            Self::Screenshot => "zureshot_screenshot_{date}_{time}",
+            Self::ClipboardScreenshot => "zureshot_{epoch}",
+            Self::AnnotatedScreenshot => "screenshot_{date}_{time}",
+            Self::ScrollScreenshot => "scroll_screenshot_{date}_{time}",
+            Self::Replay => "zureshot_replay_{date}_{time}",
+        }
+    }
+}
 
-            if space_below >= bar_height + gap + 20.0 {
-                // Below the region (preferred)
-                let x = cx.max(8.0).min(screen_w - bar_width - 8.0);
-                (x, rgn.y + rgn.height + gap)
-            } else if space_above >= bar_height + gap + 20.0 {
-                // Above the region
-                let x = cx.max(8.0).min(screen_w - bar_width - 8.0);
-                (x, rgn.y - bar_height - gap)
-            } else if space_right >= bar_width + gap + 20.0 {
-                // Right of the region
-                let y = (rgn.y + rgn.height / 2.0 - bar_height / 2.0)
-                    .max(8.0).min(screen_h - bar_height - 8.0);
-                (rgn.x + rgn.width + gap, y)
-            } else if space_left >= bar_width + gap + 20.0 {
-                // Left of the region
-                let y = (rgn.y + rgn.height / 2.0 - bar_height / 2.0)
-                    .max(8.0).min(screen_h - bar_height - 8.0);
-                (rgn.x - bar_width - gap, y)
+/// Expand a filename template into a file stem (no extension).
+///
+/// Tokens: `{app}` (zureshot), `{date}` (20240131), `{time}` (142501),
+/// `{epoch}` (Unix seconds), `{counter}` (the given sequence number),
+/// `{type}` (`CaptureKind::as_str`); the date and time are `now`'s.
+/// Unknown tokens are kept as written; path separators become `_`.
+pub fn render_filename(
+    template: &str,
+    kind: CaptureKind,
+    counter: u64,
+    now: chrono::DateTime<chrono::Local>,
+) -> String {
+    let stem = template
+        .replace("{app}", "zureshot")
+        .replace("{date}", &now.format("%Y%m%d").to_string())
+        .replace("{time}", &now.format("%H%M%S").to_string())
+        .replace("{epoch}", &now.timestamp().to_string())
+        .replace("{counter}", &counter.to_string())
+        .replace("{type}", kind.as_str())
+        .replace(['/', '\\', ':'], "_");
+    let stem = stem.trim();
+    if stem.is_empty() { "zureshot".to_string() } else { stem.to_string() }
+}
+
+/// The `filename_template` setting, if one is set.
+fn filename_template(app: &AppHandle) -> Option<String> {
+    crate::settings::get::<String>(app, "filename_template").filter(|t| !t.trim().is_empty())
+}
+
+/// File stem for a new capture, from `filename_template` (else the kind's
+/// built-in name). `{counter}` advances a persisted sequence number.
+pub fn capture_file_stem(app: &AppHandle, kind: CaptureKind) -> String {
+    file_stem_from(filename_template(app).as_deref(), kind, chrono::Local::now(), || {
+        crate::settings::update(app, "filename_counter", |n| serde_json::json!(n.as_u64().unwrap_or(0) + 1))
+            .as_u64()
+            .unwrap_or(0)
+    })
+}
+
+/// `capture_file_stem` without the settings: `next_counter` is only called
+/// (advancing the sequence) when the template uses `{counter}`.
+fn file_stem_from(
+    template: Option<&str>,
+    kind: CaptureKind,
+    now: chrono::DateTime<chrono::Local>,
+    next_counter: impl FnOnce() -> u64,
+) -> String {
+    let template = template.unwrap_or(kind.default_template());
+    let counter = if template.contains("{counter}") { next_counter() } else { 0 };
+    render_filename(template, kind, counter, now)
+}
+
+/// `dir/<stem>.<ext>`, or `dir/<stem>-1.<ext>`, `-2`… if that's taken.
+/// `prefix` is prepended to every candidate (hidden temp files).
+pub fn unique_capture_path(dir: &std::path::Path, prefix: &str, stem: &str, ext: &str) -> std::path::PathBuf {
+    let mut path = dir.join(format!("{}{}.{}", prefix, stem, ext));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{}{}-{}.{}", prefix, stem, n, ext));
+        n += 1;
+    }
+    path
+}
+
+/// Path for a new capture in `dir`, named by `capture_file_stem`.
+pub fn new_capture_path(app: &AppHandle, dir: &std::path::Path, kind: CaptureKind, ext: &str) -> String {
+    let stem = capture_file_stem(app, kind);
+    unique_capture_path(dir, "", &stem, ext).to_string_lossy().to_string()
+}
+
+#[tauri::command]
+pub fn get_filename_template(app: AppHandle) -> Option<String> {
+    filename_template(&app)
+}
+
+/// Name new recordings and screenshots from a template such as
+/// `{app}_{date}_{time}` (see `render_filename` for the tokens); `None` or
+/// an empty template goes back to the built-in names (persisted).
+#[tauri::command]
+pub fn set_filename_template(app: AppHandle, template: Option<String>) -> Result<(), ZureshotError> {
+    let template = template.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    if let Some(ref t) = template {
+        if t.starts_with('.') {
+            return Err("Filename template can't start with '.' (that would hide the files)".into());
+        }
+    }
+    crate::settings::set(&app, "filename_template", serde_json::json!(template));
+    println!("[zureshot] Filename template: {:?}", template);
+    Ok(())
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  Recording files (sidecars travel with the video)
+// ════════════════════════════════════════════════════════════════════════
+
+/// Sidecar suffixes written as `<stem><suffix>` next to a recording
+const SIDECAR_SUFFIXES: &[&str] = &[
+    ".timestamps.json",
+    ".recording.json",
+    ".bookmarks.json",
+    ".chapters.vtt",
+    ".icc",
+    ".mousetrack.json", // legacy, before the track was hidden
+];
+
+/// Existing sidecars of the recording at `path`: timing, capture
+/// settings, bookmarks, chapters, ICC profile, the hidden
+/// `.<stem>.mousetrack.json` and the `<stem>_bookmarks/` stills folder.
+fn recording_sidecars(path: &str) -> Vec<std::path::PathBuf> {
+    let video = std::path::Path::new(path);
+    let dir = video.parent().unwrap_or(std::path::Path::new("."));
+    let Some(stem) = video.file_stem().and_then(|s| s.to_str()) else {
+        return Vec::new();
+    };
+    let stills = bookmarks_dir(path);
+    SIDECAR_SUFFIXES
+        .iter()
+        .map(|suffix| dir.join(format!("{}{}", stem, suffix)))
+        .chain(std::iter::once(dir.join(format!(".{}.mousetrack.json", stem))))
+        .filter(|p| p.is_file())
+        .chain(stills.is_dir().then_some(stills))
+        .collect()
+}
+
+/// Move a file or folder, copying and then deleting it when `rename` can't
+/// cross filesystems (EXDEV, e.g. onto an external drive).
+fn move_path(src: &std::path::Path, dest: &std::path::Path) -> std::io::Result<()> {
+    match std::fs::rename(src, dest) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_path(src, dest) {
+                // Don't leave half a copy behind
+                let _ = if dest.is_dir() { std::fs::remove_dir_all(dest) } else { std::fs::remove_file(dest) };
+                return Err(e);
+            }
+            if src.is_dir() {
+                std::fs::remove_dir_all(src)
             } else {
-                // No good space outside: place inside at bottom-center of region
-                let x = cx.max(8.0).min(screen_w - bar_width - 8.0);
-                let y = (rgn.y + rgn.height - bar_height - gap)
-                    .max(8.0).min(screen_h - bar_height - 8.0);
-                (x, y)
+                std::fs::remove_file(src)
             }
         }
-    } else {
-        // No region at all: bottom-center
-        ((screen_w - bar_width) / 2.0, screen_h - bar_height - 80.0)
+        result => result,
+    }
+}
+
+fn copy_path(src: &std::path::Path, dest: &std::path::Path) -> std::io::Result<()> {
+    if !src.is_dir() {
+        return std::fs::copy(src, dest).map(|_| ());
+    }
+    std::fs::create_dir(dest)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        copy_path(&entry.path(), &dest.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// Point a moved recording's `.bookmarks.json` at its new path and at the
+/// stills in its moved `<stem>_bookmarks/` folder.
+fn retarget_bookmarks_index(old_path: &str, new_path: &str) {
+    let index_path = std::path::Path::new(new_path).with_extension("bookmarks.json");
+    let Ok(json) = std::fs::read_to_string(&index_path) else {
+        return;
+    };
+    let mut index: BookmarksIndex = match serde_json::from_str(&json) {
+        Ok(index) => index,
+        Err(e) => {
+            eprintln!("[zureshot] Can't update {}: {}", index_path.display(), e);
+            return;
+        }
     };
+    let (old_stills, new_stills) = (bookmarks_dir(old_path), bookmarks_dir(new_path));
+    for bookmark in &mut index.bookmarks {
+        if let Ok(rel) = std::path::Path::new(&bookmark.still_path).strip_prefix(&old_stills) {
+            bookmark.still_path = new_stills.join(rel).to_string_lossy().to_string();
+        }
+    }
+    write_bookmarks_index(new_path, &index.bookmarks);
+}
 
-    let window = WebviewWindowBuilder::new(
-        app,
-        "recording-bar",
-        WebviewUrl::App("recording-bar.html".into()),
-    )
-    .title("Recording")
-    .inner_size(bar_width, bar_height)
-    .position(pos_x, pos_y)
-    .transparent(true)
-    .decorations(false)
-    .always_on_top(true)
-    .skip_taskbar(true)
-    .resizable(false)
-    .build()
-    .map_err(|e| format!("Failed to create recording bar: {}", e))?;
+/// Move a recording to the Trash, and its sidecars too when
+/// `with_sidecars`. Returns the number of sidecars trashed; a sidecar that
+/// can't be trashed is logged, not fatal.
+fn trash_recording(path: &str, with_sidecars: bool) -> Result<usize, String> {
+    platform::imp::move_to_trash(path)?;
+    if !with_sidecars {
+        return Ok(0);
+    }
+    let mut trashed = 0;
+    for sidecar in recording_sidecars(path) {
+        match platform::imp::move_to_trash(&sidecar.to_string_lossy()) {
+            Ok(()) => trashed += 1,
+            Err(e) => eprintln!("[zureshot] {}", e),
+        }
+    }
+    Ok(trashed)
+}
 
-    let _ = window.show();
-    let _ = window.set_focus();
+fn is_in_progress(app: &AppHandle, path: &str) -> bool {
+    active_output_paths(app)
+        .iter()
+        .any(|a| std::path::Path::new(a) == std::path::Path::new(path))
+}
 
-    println!("[zureshot] Recording bar opened at ({:.0}, {:.0})", pos_x, pos_y);
+/// Whether deleting a recording also deletes its sidecars
+#[tauri::command]
+pub fn get_delete_sidecars(app: AppHandle) -> bool {
+    crate::settings::get_bool(&app, "delete_sidecars", true)
+}
+
+/// Delete sidecar files together with their recording (persisted).
+#[tauri::command]
+pub fn set_delete_sidecars(app: AppHandle, enabled: bool) {
+    crate::settings::set(&app, "delete_sidecars", serde_json::json!(enabled));
+    println!("[zureshot] Delete sidecars with recordings: {}", enabled);
+}
+
+/// Move a recording to the Trash. `delete_sidecars` overrides the
+/// `delete_sidecars` setting for this call.
+#[tauri::command]
+pub async fn delete_recording(
+    app: AppHandle,
+    path: String,
+    delete_sidecars: Option<bool>,
+) -> Result<(), ZureshotError> {
+    if !std::path::Path::new(&path).is_file() {
+        return Err(format!("Recording not found: {}", path).into());
+    }
+    if is_in_progress(&app, &path) {
+        return Err("Can't delete a recording that is still in progress".into());
+    }
+    let with_sidecars = delete_sidecars.unwrap_or_else(|| get_delete_sidecars(app.clone()));
+    let target = path.clone();
+    let sidecars = tokio::task::spawn_blocking(move || trash_recording(&target, with_sidecars))
+        .await
+        .map_err(|e| format!("Delete failed: {}", e))??;
+    println!("[zureshot] Deleted recording {} ({} sidecar(s))", path, sidecars);
     Ok(())
 }
 
-/// Open a fullscreen transparent dim overlay that darkens the non-recorded area.
-/// The overlay is click-through (ignores cursor events) so the user can still
-/// interact with apps underneath. Only used for region recording.
-pub fn do_open_recording_overlay(app: &AppHandle, region: &CaptureRegion) -> Result<(), String> {
-    // If already open, just show it
-    if let Some(win) = app.get_webview_window("recording-overlay") {
-        let _ = win.show();
-        return Ok(());
+/// Move a recording into `dest_dir`, taking its sidecars along so they keep
+/// matching it. Returns the new recording path.
+#[tauri::command]
+pub async fn move_recording(app: AppHandle, path: String, dest_dir: String) -> Result<String, ZureshotError> {
+    let src = std::path::Path::new(&path);
+    let dest_dir = std::path::Path::new(&dest_dir);
+    if !src.is_file() {
+        return Err(format!("Recording not found: {}", path).into());
+    }
+    if !dest_dir.is_dir() {
+        return Err(format!("Not a folder: {}", dest_dir.display()).into());
+    }
+    if is_in_progress(&app, &path) {
+        return Err("Can't move a recording that is still in progress".into());
+    }
+    let file_name = src.file_name().ok_or("Invalid recording path")?;
+    let dest = dest_dir.join(file_name);
+    if dest.exists() {
+        return Err(format!("{} already exists", dest.display()).into());
     }
 
-    let monitor = app
-        .primary_monitor()
-        .map_err(|e| format!("Failed to get monitor: {}", e))?
-        .ok_or("No primary monitor found")?;
-    let scale = monitor.scale_factor();
-    let phys_size = monitor.size();
-    let position = monitor.position();
-    let logical_w = phys_size.width as f64 / scale;
-    let logical_h = phys_size.height as f64 / scale;
+    // Collect before the video moves (lookup is relative to its folder)
+    let sidecars = recording_sidecars(&path);
+    let (src, moved) = (src.to_path_buf(), dest.clone());
+    tokio::task::spawn_blocking(move || -> Result<(), String> {
+        move_path(&src, &moved).map_err(|e| format!("Failed to move recording: {}", e))?;
+        for sidecar in sidecars {
+            let Some(name) = sidecar.file_name() else { continue };
+            let Some(folder) = moved.parent() else { continue };
+            if let Err(e) = move_path(&sidecar, &folder.join(name)) {
+                eprintln!("[zureshot] Failed to move {}: {}", sidecar.display(), e);
+            }
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Move failed: {}", e))??;
+
+    let dest_str = dest.to_string_lossy().to_string();
+    retarget_bookmarks_index(&path, &dest_str);
+    println!("[zureshot] Moved recording {} -> {}", path, dest_str);
+    Ok(dest_str)
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  Recent recordings
+// ════════════════════════════════════════════════════════════════════════
+
+/// A finished recording in the recordings folder
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecentRecording {
+    pub path: String,
+    pub file_size_bytes: u64,
+    /// From the MP4 header, or ffmpeg when the header has none (fragmented
+    /// files, GIFs). `None` if neither could read it.
+    pub duration_secs: Option<f64>,
+    /// Last modification, milliseconds since the Unix epoch
+    pub modified_ms: u64,
+    /// A `.cursor.json` (or hidden `.mousetrack.json`) sidecar exists, so
+    /// the recording can be zoom-edited
+    pub has_cursor_json: bool,
+}
+
+/// Duration probed from one recording, valid while the file's size and
+/// mtime stay the same (appending, trimming or re-timing in place changes
+/// them even though the folder itself doesn't change)
+struct ProbedRecording {
+    file_size_bytes: u64,
+    modified: std::time::SystemTime,
+    duration_secs: Option<f64>,
+}
+
+static RECENT_RECORDINGS_CACHE: Mutex<Option<std::collections::HashMap<std::path::PathBuf, ProbedRecording>>> =
+    Mutex::new(None);
+
+/// Recordings shown by default in `list_recent_recordings`
+const DEFAULT_RECENT_LIMIT: usize = 10;
+
+/// What `recent_recordings` lists and `open_recording` opens
+const RECORDING_EXTENSIONS: [&str; 3] = ["mp4", "mov", "gif"];
+
+fn is_recording_file(path: &std::path::Path) -> bool {
+    path.extension()
+        .map(|x| RECORDING_EXTENSIONS.iter().any(|ext| x.eq_ignore_ascii_case(ext)))
+        .unwrap_or(false)
+}
+
+fn probe_duration(path: &std::path::Path) -> Option<f64> {
+    scan_mp4(path)
+        .ok()
+        .and_then(|s| s.duration_secs)
+        .filter(|&d| d > 0.0)
+        .or_else(|| crate::ffmpeg::media_info(&path.to_string_lossy()).ok().map(|i| i.duration_secs))
+}
+
+/// The newest `limit` recordings in the recordings folder, skipping any in
+/// progress. Every file is stat'ed to order them, but only those `limit`
+/// are probed for their duration, and a probe is reused until the file's
+/// size or mtime changes. Blocks on ffprobe for new files: keep it off the
+/// main thread.
+pub fn recent_recordings(app: &AppHandle, limit: usize) -> Vec<RecentRecording> {
+    let dir = crate::settings::recordings_dir(app);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let active = active_output_paths(app);
+    let mut files: Vec<(std::path::PathBuf, std::fs::Metadata)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| is_recording_file(p))
+        // Hidden files are in-progress temporaries (append, CFR re-time, …)
+        .filter(|p| !p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with('.')))
+        .filter(|p| !active.iter().any(|a| std::path::Path::new(a) == p.as_path()))
+        .filter_map(|p| {
+            let meta = std::fs::metadata(&p).ok().filter(|m| m.is_file())?;
+            Some((p, meta))
+        })
+        .collect();
+    files.sort_by_key(|(_, meta)| std::cmp::Reverse(meta.modified().unwrap_or(std::time::UNIX_EPOCH)));
+
+    let Ok(mut cache) = RECENT_RECORDINGS_CACHE.lock() else {
+        return Vec::new();
+    };
+    let cache = cache.get_or_insert_with(std::collections::HashMap::new);
+    // Forget files that were deleted, moved or renamed
+    cache.retain(|path, _| files.iter().any(|(p, _)| p == path));
+
+    let mut probed = 0;
+    let recordings = files
+        .into_iter()
+        .take(limit)
+        .map(|(p, meta)| {
+            let modified = meta.modified().unwrap_or(std::time::UNIX_EPOCH);
+            let duration_secs = match cache.get(&p) {
+                Some(c) if c.file_size_bytes == meta.len() && c.modified == modified => c.duration_secs,
+                _ => {
+                    probed += 1;
+                    let duration_secs = probe_duration(&p);
+                    cache.insert(
+                        p.clone(),
+                        ProbedRecording { file_size_bytes: meta.len(), modified, duration_secs },
+                    );
+                    duration_secs
+                }
+            };
+            let modified_ms = modified
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64);
+            let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+            let has_cursor_json = p.with_file_name(format!("{}.cursor.json", stem)).is_file()
+                || p.with_file_name(format!(".{}.mousetrack.json", stem)).is_file();
+            RecentRecording {
+                path: p.to_string_lossy().to_string(),
+                file_size_bytes: meta.len(),
+                duration_secs,
+                modified_ms,
+                has_cursor_json,
+            }
+        })
+        .collect();
+    if probed > 0 {
+        println!("[zureshot] Probed {} recording(s) in {}", probed, dir.display());
+    }
+    recordings
+}
+
+/// Tauri command: newest-first recordings (default 10) with size,
+/// duration and whether they have cursor data for the zoom editor
+#[tauri::command]
+pub async fn list_recent_recordings(app: AppHandle, limit: Option<usize>) -> Result<Vec<RecentRecording>, ZureshotError> {
+    let limit = limit.unwrap_or(DEFAULT_RECENT_LIMIT);
+    Ok(tokio::task::spawn_blocking(move || recent_recordings(&app, limit))
+        .await
+        .map_err(|e| format!("Listing recordings failed: {}", e))?)
+}
+
+/// Open a recording in the system's default player. Only videos and GIFs
+/// directly in the recordings folder are opened, so this can't be used to
+/// launch arbitrary files.
+pub fn do_open_recording(app: &AppHandle, path: &str) -> Result<(), ZureshotError> {
+    let file = std::path::Path::new(path);
+    if !file.is_file() {
+        return Err(ZureshotError::new(ErrorCode::NotFound, format!("Recording not found: {}", path)));
+    }
+    let dir = crate::settings::recordings_dir(app);
+    let in_recordings_dir = match (file.canonicalize(), dir.canonicalize()) {
+        (Ok(file), Ok(dir)) => file.parent() == Some(dir.as_path()),
+        _ => false,
+    };
+    if !in_recordings_dir || !is_recording_file(file) {
+        return Err(ZureshotError::new(
+            ErrorCode::InvalidInput,
+            format!("Not a recording in {}: {}", dir.display(), path),
+        ));
+    }
+    Ok(platform::imp::open_file(path)?)
+}
+
+/// Tauri command: open a recording in the system's default player
+#[tauri::command]
+pub async fn open_recording(app: AppHandle, path: String) -> Result<(), ZureshotError> {
+    do_open_recording(&app, &path)
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  Instant Replay
+// ════════════════════════════════════════════════════════════════════════
+
+/// Start keeping the last `secs` (default 30) of a display in memory.
+/// Nothing is written to disk until `save_instant_replay`.
+#[tauri::command]
+pub async fn start_instant_replay(
+    app: AppHandle,
+    secs: Option<f64>,
+    display_id: Option<u32>,
+    quality: Option<String>,
+) -> Result<f64, ZureshotError> {
+    #[cfg(target_os = "macos")]
+    {
+        use platform::macos::replay;
+        let capacity = secs
+            .unwrap_or(replay::DEFAULT_REPLAY_SECS)
+            .clamp(1.0, replay::MAX_REPLAY_SECS);
+        let q = match quality.as_deref() {
+            Some("high") => RecordingQuality::High,
+            _ => RecordingQuality::Standard,
+        };
+
+        Ok(tokio::task::spawn_blocking(move || {
+            let state: tauri::State<'_, Mutex<replay::InstantReplayState>> = app.state();
+            if state.lock().map_err(|e| e.to_string())?.session.is_some() {
+                return Err(ZureshotError::new(ErrorCode::AlreadyRecording, "Instant replay is already running"));
+            }
+            let session = platform::macos::start_instant_replay(&app, display_id, q, capacity)?;
+            state.lock().map_err(|e| e.to_string())?.session = Some(session);
+            Ok(capacity)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, secs, display_id, quality);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Instant replay is not supported on this platform yet"))
+    }
+}
+
+/// Stop the replay buffer and discard its contents.
+#[tauri::command]
+pub async fn stop_instant_replay(app: AppHandle) -> Result<(), ZureshotError> {
+    #[cfg(target_os = "macos")]
+    {
+        Ok(tokio::task::spawn_blocking(move || {
+            let state: tauri::State<'_, Mutex<platform::macos::replay::InstantReplayState>> = app.state();
+            let session = state.lock().map_err(|e| e.to_string())?.session.take();
+            match session {
+                Some(session) => {
+                    session.stop();
+                    Ok(())
+                }
+                None => Err("Instant replay is not running".to_string()),
+            }
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Instant replay is not supported on this platform yet"))
+    }
+}
 
-    let window = WebviewWindowBuilder::new(
-        app,
-        "recording-overlay",
-        WebviewUrl::App("recording-overlay.html".into()),
-    )
-    .title("Recording Overlay")
-    .inner_size(logical_w, logical_h)
-    .position(position.x as f64 / scale, position.y as f64 / scale)
-    .transparent(true)
-    .decorations(false)
-    .always_on_top(true)
-    .skip_taskbar(true)
-    .resizable(false)
-    .build()
-    .map_err(|e| format!("Failed to create recording overlay: {}", e))?;
+/// Save the last `secs` (default: whole buffer) of the replay buffer as an
+/// MP4. The buffer keeps running. Returns the saved file path.
+#[tauri::command]
+pub async fn save_instant_replay(
+    app: AppHandle,
+    secs: Option<f64>,
+    output_path: Option<String>,
+) -> Result<String, ZureshotError> {
+    #[cfg(target_os = "macos")]
+    {
+        Ok(tokio::task::spawn_blocking(move || -> Result<String, String> {
+            let state: tauri::State<'_, Mutex<platform::macos::replay::InstantReplayState>> = app.state();
+            // Snapshot under the lock (retains only), write after releasing it
+            let clip = {
+                let replay = state.lock().map_err(|e| e.to_string())?;
+                let session = replay.session.as_ref().ok_or("Instant replay is not running")?;
+                session.snapshot(secs.unwrap_or(session.capacity_secs()))
+            };
+
+            let out = output_path.unwrap_or_else(|| {
+                new_capture_path(&app, &crate::settings::recordings_dir(&app), CaptureKind::Replay, "mp4")
+            });
+            let duration = clip.write(&out)?;
+            println!("[zureshot] Instant replay saved: {} ({:.1}s)", out, duration);
+            let _ = app.emit(
+                "instant-replay-saved",
+                serde_json::json!({ "path": out, "duration_secs": duration }),
+            );
+            Ok(out)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, secs, output_path);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Instant replay is not supported on this platform yet"))
+    }
+}
 
-    // Make overlay completely click-through so it doesn't intercept mouse events
-    let _ = window.set_ignore_cursor_events(true);
-    let _ = window.show();
+// ════════════════════════════════════════════════════════════════════════
+//  Live streaming (Linux: GStreamer flvmux → rtmpsink / mpegtsmux → srtsink)
+// ════════════════════════════════════════════════════════════════════════
 
-    println!(
-        "[zureshot] Recording overlay opened for region ({:.0},{:.0} {:.0}x{:.0})",
-        region.x, region.y, region.width, region.height
-    );
-    Ok(())
+/// Start streaming the screen to an RTMP/SRT ingest (Twitch, YouTube, a
+/// custom server). The user picks the monitor in the portal dialog.
+#[tauri::command]
+pub async fn start_streaming(
+    app: AppHandle,
+    target: platform::StreamTarget,
+    quality: Option<RecordingQuality>,
+    capture_system_audio: Option<bool>,
+    capture_microphone: Option<bool>,
+) -> Result<(), ZureshotError> {
+    target.validate()?;
+    println!("[zureshot] start_streaming: {}", target.redacted());
+    #[cfg(target_os = "linux")]
+    {
+        use platform::linux::LiveStreamState;
+        Ok(tokio::task::spawn_blocking(move || {
+            let state: tauri::State<'_, Mutex<LiveStreamState>> = app.state();
+            if state.lock().map_err(|e| e.to_string())?.session.is_some() {
+                return Err(ZureshotError::new(ErrorCode::AlreadyRecording, "A live stream is already running"));
+            }
+            let handle = platform::linux::start_streaming(
+                &target,
+                quality.unwrap_or_default(),
+                capture_system_audio.unwrap_or(true),
+                capture_microphone.unwrap_or(false),
+            )?;
+            let mut stream = state.lock().map_err(|e| e.to_string())?;
+            stream.session = Some(handle);
+            stream.target = Some(target.clone());
+            println!("[zureshot] Live stream started: {}", target.redacted());
+            let _ = app.emit("streaming-started", serde_json::json!({ "protocol": target.protocol }));
+            Ok(())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (app, target, quality, capture_system_audio, capture_microphone);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Live streaming is not supported on this platform yet"))
+    }
 }
 
-/// Refresh the stream content filter to exclude our app windows from capture.
-/// Each platform handles this differently (macOS: SCStream filter, Linux: no-op).
-pub fn refresh_stream_exclusion(app: &AppHandle) -> Result<(), String> {
-    let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
-    let recording = state.lock().map_err(|e| e.to_string())?;
-    if let Some(ref handle) = recording.handle {
-        handle.refresh_exclusion(app)
-    } else {
-        Err("No active recording to update".into())
+/// Stop the live stream (sends EOS so the ingest sees a clean end).
+#[tauri::command]
+pub async fn stop_streaming(app: AppHandle) -> Result<(), ZureshotError> {
+    #[cfg(target_os = "linux")]
+    {
+        use platform::linux::LiveStreamState;
+        Ok(tokio::task::spawn_blocking(move || -> Result<(), String> {
+            let state: tauri::State<'_, Mutex<LiveStreamState>> = app.state();
+            let (handle, target) = {
+                let mut stream = state.lock().map_err(|e| e.to_string())?;
+                (stream.session.take(), stream.target.take())
+            };
+            let handle = handle.ok_or("No live stream is running")?;
+            handle.stop_capture();
+            handle.finalize();
+            if let Some(target) = target {
+                println!("[zureshot] Live stream stopped: {}", target.redacted());
+            }
+            let _ = app.emit("streaming-stopped", ());
+            Ok(())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = app;
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Live streaming is not supported on this platform yet"))
     }
 }
 
@@ -814,7 +6115,7 @@ pub async fn move_camera_overlay(
     bound_y: f64,
     bound_w: f64,
     bound_h: f64,
-) -> Result<(), String> {
+) -> Result<(), ZureshotError> {
     if let Some(win) = app.get_webview_window("camera-overlay") {
         let scale = win.scale_factor().unwrap_or(1.0);
         let pos = win.outer_position().map_err(|e| e.to_string())?;
@@ -839,7 +6140,7 @@ pub async fn move_camera_overlay(
 
 /// List camera devices via native AVFoundation (works with Continuity Camera)
 #[tauri::command]
-pub async fn list_native_camera_devices() -> Result<Vec<serde_json::Value>, String> {
+pub async fn list_native_camera_devices() -> Result<Vec<serde_json::Value>, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         Ok(platform::macos::camera::list_camera_devices()
@@ -848,7 +6149,7 @@ pub async fn list_native_camera_devices() -> Result<Vec<serde_json::Value>, Stri
             .collect())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Native camera is not supported on this platform yet".into())
+    Err(ZureshotError::new(ErrorCode::Unsupported, "Native camera is not supported on this platform yet"))
 }
 
 /// Start native camera capture (ffmpeg-based, emits JPEG frames as events)
@@ -856,17 +6157,19 @@ pub async fn list_native_camera_devices() -> Result<Vec<serde_json::Value>, Stri
 pub async fn start_native_camera(
     app: AppHandle,
     device_id: String,
-) -> Result<(), String> {
+) -> Result<(), ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         let state: tauri::State<'_, Mutex<platform::macos::camera::NativeCameraState>> = app.state();
         let camera_state = state.lock().map_err(|e| e.to_string())?;
-        platform::macos::camera::start_native_camera_stream(&app, &device_id, &camera_state)
+        platform::macos::camera::start_native_camera_stream(&app, &device_id, &camera_state)?;
+        crate::settings::set(&app, "camera_device_id", serde_json::json!(device_id));
+        Ok(())
     }
     #[cfg(not(target_os = "macos"))]
     {
         let _ = (app, device_id);
-        Err("Native camera is not supported on this platform yet".into())
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Native camera is not supported on this platform yet"))
     }
 }
 
@@ -874,7 +6177,7 @@ pub async fn start_native_camera(
 #[tauri::command]
 pub async fn stop_native_camera(
     app: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         let state: tauri::State<'_, Mutex<platform::macos::camera::NativeCameraState>> = app.state();
@@ -885,10 +6188,44 @@ pub async fn stop_native_camera(
     #[cfg(not(target_os = "macos"))]
     {
         let _ = app;
-        Err("Native camera is not supported on this platform yet".into())
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Native camera is not supported on this platform yet"))
+    }
+}
+
+/// List cameras (uid, name, model, front/back position) for the webcam overlay.
+#[tauri::command]
+pub async fn list_cameras() -> Result<Vec<serde_json::Value>, ZureshotError> {
+    list_native_camera_devices().await
+}
+
+/// Switch the webcam overlay to another camera. If the overlay stream is
+/// running it is restarted on the new device immediately; the choice is
+/// remembered as the default for the next overlay.
+#[tauri::command]
+pub async fn set_camera(app: AppHandle, uid: String) -> Result<(), ZureshotError> {
+    #[cfg(target_os = "macos")]
+    {
+        let state: tauri::State<'_, Mutex<platform::macos::camera::NativeCameraState>> = app.state();
+        let mut camera_state = state.lock().map_err(|e| e.to_string())?;
+        platform::macos::camera::switch_native_camera_stream(&app, &uid, &mut camera_state)?;
+        drop(camera_state);
+
+        crate::settings::set(&app, "camera_device_id", serde_json::json!(uid));
+        let _ = app.emit("camera-changed", &uid);
+        Ok(())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, uid);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Native camera is not supported on this platform yet"))
     }
 }
 
+/// Last camera used by the overlay (`set_camera` / `start_native_camera`).
+fn last_camera_device_id(app: &AppHandle) -> Option<String> {
+    crate::settings::get::<String>(app, "camera_device_id").filter(|s| !s.is_empty())
+}
+
 /// Payload emitted with `camera-overlay-settings` event
 #[derive(Clone, Serialize, Deserialize)]
 pub struct CameraOverlaySettings {
@@ -908,7 +6245,13 @@ pub fn do_open_camera_overlay_with_options(
     size: &str,
     device_id: Option<&str>,
     bound_region: Option<&CaptureRegion>,
+    corner: WebcamCorner,
 ) -> Result<(), String> {
+    let device_id = device_id
+        .map(|s| s.to_string())
+        .or_else(|| last_camera_device_id(app));
+    let device_id = device_id.as_deref();
+
     // If already open, just emit new settings and refocus
     if let Some(win) = app.get_webview_window("camera-overlay") {
         let bounds = bound_region.map(|r| (r.x, r.y, r.width, r.height));
@@ -932,7 +6275,7 @@ pub fn do_open_camera_overlay_with_options(
     let win_w = content_w + padding;
     let win_h = content_h + padding;
 
-    // Position: bottom-right corner with margin
+    // Position: the requested corner (bottom-right by default) with margin
     let monitor = app
         .primary_monitor()
         .map_err(|e| format!("Failed to get monitor: {}", e))?
@@ -953,8 +6296,18 @@ pub fn do_open_camera_overlay_with_options(
     let min_y = bounds_y + 4.0;
     let max_x = (bounds_x + bounds_w - win_w - 4.0).max(min_x);
     let max_y = (bounds_y + bounds_h - win_h - 4.0).max(min_y);
-    let pos_x = (bounds_x + bounds_w - win_w - margin).max(min_x).min(max_x);
-    let pos_y = (bounds_y + bounds_h - win_h - margin).max(min_y).min(max_y);
+    let pos_x = match corner {
+        WebcamCorner::TopLeft | WebcamCorner::BottomLeft => bounds_x + margin,
+        WebcamCorner::TopRight | WebcamCorner::BottomRight => bounds_x + bounds_w - win_w - margin,
+    }
+    .max(min_x)
+    .min(max_x);
+    let pos_y = match corner {
+        WebcamCorner::TopLeft | WebcamCorner::TopRight => bounds_y + margin,
+        WebcamCorner::BottomLeft | WebcamCorner::BottomRight => bounds_y + bounds_h - win_h - margin,
+    }
+    .max(min_y)
+    .min(max_y);
 
     // Pass initial settings via URL query params
     let url = format!(
@@ -1016,7 +6369,7 @@ pub fn do_open_camera_overlay_with_options(
     // If currently recording, refresh stream exclusion
     {
         let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
-        let is_recording = state.lock().map(|r| r.is_recording).unwrap_or(false);
+        let is_recording = state.lock().map(|r| r.is_recording()).unwrap_or(false);
         if is_recording {
             std::thread::sleep(std::time::Duration::from_millis(150));
             let _ = refresh_stream_exclusion(app);
@@ -1030,9 +6383,9 @@ pub fn do_open_camera_overlay_with_options(
 pub fn do_open_camera_overlay(app: &AppHandle) -> Result<(), String> {
     let region = {
         let state: tauri::State<'_, Mutex<RecordingState>> = app.state();
-        state.lock().ok().and_then(|r| r.region.clone())
+        state.lock().ok().and_then(|r| r.primary().and_then(|p| p.region.clone()))
     };
-    do_open_camera_overlay_with_options(app, "circle", "medium", None, region.as_ref())
+    do_open_camera_overlay_with_options(app, "circle", "medium", None, region.as_ref(), WebcamCorner::BottomRight)
 }
 
 /// Close the camera bubble overlay window.
@@ -1059,8 +6412,8 @@ pub fn do_close_camera_overlay(app: &AppHandle) -> Result<(), String> {
 
 /// Tauri command: open camera bubble
 #[tauri::command]
-pub async fn open_camera_overlay(app: AppHandle) -> Result<(), String> {
-    do_open_camera_overlay(&app)
+pub async fn open_camera_overlay(app: AppHandle) -> Result<(), ZureshotError> {
+    Ok(do_open_camera_overlay(&app)?)
 }
 
 /// Tauri command: open camera bubble with options (shape, size, device)
@@ -1074,7 +6427,7 @@ pub async fn open_camera_overlay_with_options(
     bound_y: Option<f64>,
     bound_w: Option<f64>,
     bound_h: Option<f64>,
-) -> Result<(), String> {
+) -> Result<(), ZureshotError> {
     let bounds_region = match (bound_x, bound_y, bound_w, bound_h) {
         (Some(x), Some(y), Some(width), Some(height)) if width > 0.0 && height > 0.0 => {
             Some(CaptureRegion { x, y, width, height })
@@ -1091,24 +6444,25 @@ pub async fn open_camera_overlay_with_options(
             .as_ref()
             .map(|b| format!("{:.0},{:.0} {:.0}x{:.0}", b.x, b.y, b.width, b.height))
     );
-    do_open_camera_overlay_with_options(
+    Ok(do_open_camera_overlay_with_options(
         &app,
         &shape.unwrap_or_else(|| "circle".to_string()),
         &size.unwrap_or_else(|| "medium".to_string()),
         device_id.as_deref(),
         bounds_region.as_ref(),
-    )
+        WebcamCorner::BottomRight,
+    )?)
 }
 
 /// Tauri command: close camera bubble
 #[tauri::command]
-pub async fn close_camera_overlay(app: AppHandle) -> Result<(), String> {
-    do_close_camera_overlay(&app)
+pub async fn close_camera_overlay(app: AppHandle) -> Result<(), ZureshotError> {
+    Ok(do_close_camera_overlay(&app)?)
 }
 
 /// Tauri command: toggle camera bubble (open if closed, close if open)
 #[tauri::command]
-pub async fn toggle_camera_overlay(app: AppHandle) -> Result<bool, String> {
+pub async fn toggle_camera_overlay(app: AppHandle) -> Result<bool, ZureshotError> {
     if app.get_webview_window("camera-overlay").is_some() {
         do_close_camera_overlay(&app)?;
         Ok(false) // now closed
@@ -1129,10 +6483,41 @@ pub struct ScreenshotResult {
     pub width: usize,
     pub height: usize,
     pub file_size_bytes: u64,
+    /// File extension without the dot: `"png"` or `"jpg"`
+    #[serde(default)]
+    pub extension: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub image_base64: String,
 }
 
+/// What the preview does when its auto-dismiss timer runs out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenshotDismissAction {
+    /// Discard the screenshot (same as the preview's close button)
+    #[default]
+    Close,
+    /// Keep it and copy it to the clipboard (same as the copy button)
+    Copy,
+}
+
+/// Preview auto-dismiss/auto-copy behavior (persisted)
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ScreenshotPreviewSettings {
+    /// Copy every screenshot to the clipboard as soon as it is captured
+    pub auto_copy: bool,
+    /// Dismiss the preview after this many seconds (0 = stay until the user acts)
+    pub auto_dismiss_secs: f64,
+    pub auto_dismiss_action: ScreenshotDismissAction,
+}
+
+/// Matches the preview's original fixed auto-hide delay
+const DEFAULT_SCREENSHOT_DISMISS_SECS: f64 = 6.0;
+
+/// Bumped for every new screenshot and whenever the user cancels the timer,
+/// so a pending auto-dismiss only fires for the preview it was started for.
+static SCREENSHOT_DISMISS_GEN: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
 /// Open the region selector in screenshot mode.
 pub fn do_start_screenshot_selection(app: &AppHandle) -> Result<(), String> {
     // If a region-selector window already exists, just show + focus it
@@ -1157,7 +6542,7 @@ pub fn do_start_screenshot_selection(app: &AppHandle) -> Result<(), String> {
         .join("zureshot_screen_preview.png")
         .to_string_lossy()
         .to_string();
-    let _ = platform::imp::take_screenshot_region(0.0, 0.0, logical_w, logical_h, &preview_path);
+    let _ = platform::imp::take_screenshot_region(0.0, 0.0, logical_w, logical_h, &preview_path, ImageFormat::Png);
 
     // Pass mode + preview path via URL query parameters
     let url = format!(
@@ -1190,11 +6575,14 @@ pub fn do_start_screenshot_selection(app: &AppHandle) -> Result<(), String> {
 
 /// Tauri command: open screenshot region selector
 #[tauri::command]
-pub async fn start_screenshot_selection(app: AppHandle) -> Result<(), String> {
-    do_start_screenshot_selection(&app)
+pub async fn start_screenshot_selection(app: AppHandle) -> Result<(), ZureshotError> {
+    Ok(do_start_screenshot_selection(&app)?)
 }
 
-/// Tauri command: take a screenshot of the selected region
+/// Tauri command: take a screenshot of the selected region, as PNG unless
+/// `format` asks for JPEG. `delay_secs` (at most 10) waits with a countdown
+/// beside the region first, e.g. to open a menu that closes on focus loss;
+/// Escape cancels.
 #[tauri::command]
 pub async fn take_screenshot(
     app: AppHandle,
@@ -1202,7 +6590,11 @@ pub async fn take_screenshot(
     y: f64,
     width: f64,
     height: f64,
-) -> Result<ScreenshotResult, String> {
+    format: Option<ImageFormat>,
+    delay_secs: Option<u32>,
+) -> Result<ScreenshotResult, ZureshotError> {
+    let format = format.unwrap_or_default();
+    let delay_secs = delay_secs.unwrap_or(0).min(MAX_COUNTDOWN_SECS);
     // Close region selector immediately
     if let Some(win) = app.get_webview_window("region-selector") {
         let _ = win.hide();
@@ -1219,20 +6611,34 @@ pub async fn take_screenshot(
     // Small delay to ensure region-selector is fully hidden
     tokio::time::sleep(std::time::Duration::from_millis(200)).await;
 
+    if delay_secs > 0 {
+        println!("[zureshot] Screenshot in {}s", delay_secs);
+        let region = CaptureRegion { x, y, width, height };
+        let app2 = app.clone();
+        let finished = tokio::task::spawn_blocking(move || {
+            let finished = run_countdown(&app2, delay_secs, Some(&region));
+            close_countdown_window(&app2);
+            finished
+        })
+        .await
+        .map_err(|e| format!("Task join error: {e}"))?;
+        if !finished {
+            return Err("Screenshot cancelled".into());
+        }
+        // Same as above, for the countdown window
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    }
+
     // Generate temp file path
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    let base = dirs::download_dir()
-        .or_else(dirs::home_dir)
-        .unwrap_or_else(|| std::path::PathBuf::from("."));
-    let zureshot_dir = base.join("Zureshot");
-    let _ = std::fs::create_dir_all(&zureshot_dir);
-    let temp_path = zureshot_dir
-        .join(format!(".zureshot_screenshot_{}.png", timestamp))
+    let zureshot_dir = crate::settings::recordings_dir(&app);
+    let stem = capture_file_stem(&app, CaptureKind::Screenshot);
+    let temp_path = unique_capture_path(&zureshot_dir, TEMP_SCREENSHOT_PREFIX, &stem, format.extension())
         .to_string_lossy()
         .to_string();
 
     // Capture
-    let (img_w, img_h, file_size) = platform::imp::take_screenshot_region(x, y, width, height, &temp_path)?;
+    let (img_w, img_h, file_size) =
+        platform::imp::take_screenshot_region(x, y, width, height, &temp_path, format)?;
 
     // Read file and encode as base64 for preview
     let file_bytes = std::fs::read(&temp_path)
@@ -1244,6 +6650,7 @@ pub async fn take_screenshot(
         width: img_w,
         height: img_h,
         file_size_bytes: file_size,
+        extension: format.extension().to_string(),
         image_base64: image_b64,
     };
 
@@ -1254,6 +6661,14 @@ pub async fn take_screenshot(
         temp_path
     );
 
+    let preview = get_screenshot_preview_settings(app.clone());
+    if preview.auto_copy {
+        match platform::imp::copy_image_to_clipboard(&temp_path) {
+            Ok(()) => println!("[zureshot] Screenshot auto-copied to clipboard"),
+            Err(e) => eprintln!("[zureshot] Screenshot auto-copy failed: {}", e),
+        }
+    }
+
     // Show screenshot preview window
     let is_new = do_open_screenshot_preview(&app).unwrap_or(false);
 
@@ -1265,9 +6680,223 @@ pub async fn take_screenshot(
     // Emit event to the preview window
     let _ = app.emit("screenshot-taken", &result);
 
+    schedule_screenshot_auto_dismiss(&app, &temp_path, preview);
+
     Ok(result)
 }
 
+/// Upper bound on shots per burst
+const MAX_BURST_COUNT: u32 = 100;
+/// Shortest interval between burst shots
+const MIN_BURST_INTERVAL_SECS: f64 = 0.1;
+
+/// Set while a screenshot burst runs; cleared by `cancel_screenshot_burst`.
+static BURST_ACTIVE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Tauri command: capture `count` screenshots of `region`, `interval_secs`
+/// apart, for step-by-step guides (perform the next step between shots).
+/// Files are numbered `_01`, `_02`, … and open in the preview as a gallery.
+/// Returns the paths taken — fewer than `count` if the burst was cancelled.
+#[tauri::command]
+pub async fn screenshot_burst(
+    app: AppHandle,
+    region: CaptureRegion,
+    count: u32,
+    interval_secs: f64,
+) -> Result<Vec<String>, ZureshotError> {
+    use std::sync::atomic::Ordering;
+
+    if count == 0 || count > MAX_BURST_COUNT {
+        return Err(format!("Burst count must be between 1 and {} (got {})", MAX_BURST_COUNT, count).into());
+    }
+    if !interval_secs.is_finite() || interval_secs < MIN_BURST_INTERVAL_SECS {
+        return Err(format!(
+            "Burst interval must be at least {}s (got {})",
+            MIN_BURST_INTERVAL_SECS, interval_secs
+        ).into());
+    }
+    if BURST_ACTIVE.swap(true, Ordering::SeqCst) {
+        return Err(ZureshotError::new(ErrorCode::AlreadyRecording, "Screenshot burst already in progress"));
+    }
+
+    // Same as take_screenshot: get the selector out of the first shot
+    if let Some(win) = app.get_webview_window("region-selector") {
+        let _ = win.destroy();
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    let zureshot_dir = crate::settings::recordings_dir(&app);
+    let stem = capture_file_stem(&app, CaptureKind::Screenshot);
+    let interval = std::time::Duration::from_secs_f64(interval_secs);
+    let mut shots: Vec<ScreenshotResult> = Vec::new();
+    for i in 0..count {
+        if i > 0 {
+            let deadline = std::time::Instant::now() + interval;
+            while std::time::Instant::now() < deadline && BURST_ACTIVE.load(Ordering::SeqCst) {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+        }
+        if !BURST_ACTIVE.load(Ordering::SeqCst) {
+            println!("[zureshot] Screenshot burst cancelled after {} of {} shots", shots.len(), count);
+            break;
+        }
+
+        // Hidden temp files, like single screenshots, until saved from the preview
+        let path = unique_capture_path(&zureshot_dir, TEMP_SCREENSHOT_PREFIX, &format!("{}_{:02}", stem, i + 1), "png")
+            .to_string_lossy()
+            .to_string();
+        let (width, height, file_size_bytes) =
+            match platform::imp::take_screenshot_region(
+                region.x,
+                region.y,
+                region.width,
+                region.height,
+                &path,
+                ImageFormat::Png,
+            ) {
+                Ok(r) => r,
+                Err(e) => {
+                    // The caller only gets the error: don't leave the hidden
+                    // temp files of the shots already taken behind
+                    BURST_ACTIVE.store(false, Ordering::SeqCst);
+                    for shot in &shots {
+                        let _ = std::fs::remove_file(&shot.path);
+                    }
+                    let _ = std::fs::remove_file(&path);
+                    return Err(e.into());
+                }
+            };
+        let image_base64 = std::fs::read(&path)
+            .map(|bytes| base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &bytes))
+            .unwrap_or_default();
+        let _ = app.emit(
+            "screenshot-burst-progress",
+            serde_json::json!({ "index": i + 1, "count": count, "path": path }),
+        );
+        shots.push(ScreenshotResult {
+            path,
+            width,
+            height,
+            file_size_bytes,
+            extension: "png".to_string(),
+            image_base64,
+        });
+    }
+    BURST_ACTIVE.store(false, Ordering::SeqCst);
+
+    let paths: Vec<String> = shots.iter().map(|s| s.path.clone()).collect();
+    println!("[zureshot] Screenshot burst: {} shots of {}x{}", paths.len(), region.width, region.height);
+    if shots.is_empty() {
+        return Ok(paths);
+    }
+
+    // Gallery in the preview window; bursts never auto-dismiss, and a
+    // pending timer from an earlier screenshot must not hide the gallery
+    cancel_screenshot_auto_dismiss();
+    let is_new = do_open_screenshot_preview(&app).unwrap_or(false);
+    if is_new {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+    let _ = app.emit("screenshot-burst-taken", &shots);
+
+    Ok(paths)
+}
+
+/// Tauri command: stop a running screenshot burst (shots already taken are kept)
+#[tauri::command]
+pub fn cancel_screenshot_burst() {
+    BURST_ACTIVE.store(false, std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Start the preview's auto-dismiss timer (if enabled). Any newer screenshot
+/// or a `cancel_screenshot_auto_dismiss` call invalidates it.
+fn schedule_screenshot_auto_dismiss(app: &AppHandle, path: &str, preview: ScreenshotPreviewSettings) {
+    use std::sync::atomic::Ordering;
+
+    let generation = SCREENSHOT_DISMISS_GEN.fetch_add(1, Ordering::SeqCst) + 1;
+    if !preview.auto_dismiss_secs.is_finite() || preview.auto_dismiss_secs <= 0.0 {
+        return;
+    }
+
+    let app = app.clone();
+    let path = path.to_string();
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_secs_f64(preview.auto_dismiss_secs));
+        // Superseded, cancelled, or already handled by the user (copy/save/pin
+        // move the temp file, close deletes it)
+        if SCREENSHOT_DISMISS_GEN.load(Ordering::SeqCst) != generation
+            || !std::path::Path::new(&path).exists()
+        {
+            return;
+        }
+
+        let saved = match preview.auto_dismiss_action {
+            ScreenshotDismissAction::Copy => match do_copy_screenshot(&path) {
+                Ok(dest) => Some(dest),
+                Err(e) => {
+                    eprintln!("[zureshot] Auto-dismiss copy failed: {}", e);
+                    return;
+                }
+            },
+            ScreenshotDismissAction::Close => {
+                let _ = std::fs::remove_file(&path);
+                None
+            }
+        };
+
+        if let Some(win) = app.get_webview_window("screenshot-preview") {
+            let _ = win.hide();
+        }
+        let _ = app.emit(
+            "screenshot-auto-dismissed",
+            serde_json::json!({
+                "path": path,
+                "action": preview.auto_dismiss_action,
+                "saved_path": saved,
+            }),
+        );
+        println!(
+            "[zureshot] Screenshot preview auto-dismissed after {:.1}s ({:?})",
+            preview.auto_dismiss_secs, preview.auto_dismiss_action
+        );
+    });
+}
+
+/// Tauri command: stop the current preview's auto-dismiss timer
+/// (called by the preview when the pointer hovers it)
+#[tauri::command]
+pub fn cancel_screenshot_auto_dismiss() {
+    SCREENSHOT_DISMISS_GEN.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Current screenshot preview auto-copy / auto-dismiss settings
+#[tauri::command]
+pub fn get_screenshot_preview_settings(app: AppHandle) -> ScreenshotPreviewSettings {
+    ScreenshotPreviewSettings {
+        auto_copy: crate::settings::get_bool(&app, "screenshot_auto_copy", false),
+        auto_dismiss_secs: crate::settings::get_f64(&app, "screenshot_auto_dismiss_secs", DEFAULT_SCREENSHOT_DISMISS_SECS),
+        auto_dismiss_action: crate::settings::get(&app, "screenshot_auto_dismiss_action")
+            .unwrap_or_default(),
+    }
+}
+
+/// Configure the screenshot preview (persisted, applies from the next
+/// screenshot). `auto_dismiss_secs` of 0 keeps the preview until the user acts.
+#[tauri::command]
+pub fn set_screenshot_preview_settings(
+    app: AppHandle,
+    settings: ScreenshotPreviewSettings,
+) -> Result<(), ZureshotError> {
+    if !settings.auto_dismiss_secs.is_finite() || settings.auto_dismiss_secs < 0.0 {
+        return Err(format!("Invalid auto-dismiss delay: {}", settings.auto_dismiss_secs).into());
+    }
+    crate::settings::set(&app, "screenshot_auto_copy", serde_json::json!(settings.auto_copy));
+    crate::settings::set(&app, "screenshot_auto_dismiss_secs", serde_json::json!(settings.auto_dismiss_secs));
+    crate::settings::set(&app, "screenshot_auto_dismiss_action", serde_json::json!(settings.auto_dismiss_action));
+    println!("[zureshot] Screenshot preview settings: {:?}", settings);
+    Ok(())
+}
+
 /// Tauri command: capture a region and copy directly to clipboard (no preview window)
 #[tauri::command]
 pub async fn screenshot_to_clipboard(
@@ -1276,7 +6905,7 @@ pub async fn screenshot_to_clipboard(
     y: f64,
     width: f64,
     height: f64,
-) -> Result<(), String> {
+) -> Result<(), ZureshotError> {
     // Hide region selector
     if let Some(win) = app.get_webview_window("region-selector") {
         let _ = win.hide();
@@ -1284,21 +6913,10 @@ pub async fn screenshot_to_clipboard(
     tokio::time::sleep(std::time::Duration::from_millis(200)).await;
 
     // Save to permanent location so the path can be pasted in terminals
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
-    let base = dirs::download_dir()
-        .or_else(dirs::home_dir)
-        .unwrap_or_else(|| std::path::PathBuf::from("."));
-    let save_dir = base.join("Zureshot");
-    let _ = std::fs::create_dir_all(&save_dir);
-    let save_path = save_dir
-        .join(format!("zureshot_{}.png", timestamp))
-        .to_string_lossy()
-        .to_string();
+    let save_path =
+        new_capture_path(&app, &crate::settings::recordings_dir(&app), CaptureKind::ClipboardScreenshot, "png");
 
-    platform::imp::take_screenshot_region(x, y, width, height, &save_path)?;
+    platform::imp::take_screenshot_region(x, y, width, height, &save_path, ImageFormat::Png)?;
 
     // Copy image + shell-escaped path to clipboard
     #[cfg(target_os = "macos")]
@@ -1337,7 +6955,7 @@ fn shell_escape_path(path: &str) -> String {
 
 /// Tauri command: copy base64-encoded PNG image data to clipboard
 #[tauri::command]
-pub async fn copy_image_data_to_clipboard(app: AppHandle, data: String) -> Result<(), String> {
+pub async fn copy_image_data_to_clipboard(app: AppHandle, data: String) -> Result<(), ZureshotError> {
     use base64::Engine;
     let bytes = base64::engine::general_purpose::STANDARD
         .decode(&data)
@@ -1369,7 +6987,7 @@ pub async fn copy_image_data_to_clipboard(app: AppHandle, data: String) -> Resul
 
 /// Tauri command: close a pinned screenshot window by its label
 #[tauri::command]
-pub async fn close_pin_window(app: AppHandle, label: String) -> Result<(), String> {
+pub async fn close_pin_window(app: AppHandle, label: String) -> Result<(), ZureshotError> {
     if let Some(win) = app.get_webview_window(&label) {
         let _ = win.destroy();
         println!("[zureshot] Pin window closed: {}", label);
@@ -1386,22 +7004,14 @@ pub async fn save_annotated_and_pin(
     y: f64,
     width: f64,
     height: f64,
-) -> Result<String, String> {
+) -> Result<String, ZureshotError> {
     use base64::Engine;
     let bytes = base64::engine::general_purpose::STANDARD
         .decode(&data)
         .map_err(|e| format!("Invalid base64: {}", e))?;
 
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    let base = dirs::download_dir()
-        .or_else(dirs::home_dir)
-        .unwrap_or_else(|| std::path::PathBuf::from("."));
-    let zureshot_dir = base.join("Zureshot");
-    let _ = std::fs::create_dir_all(&zureshot_dir);
-    let save_path = zureshot_dir
-        .join(format!("screenshot_{}.png", timestamp))
-        .to_string_lossy()
-        .to_string();
+    let save_path =
+        new_capture_path(&app, &crate::settings::recordings_dir(&app), CaptureKind::AnnotatedScreenshot, "png");
 
     std::fs::write(&save_path, &bytes)
         .map_err(|e| format!("Failed to write screenshot: {}", e))?;
@@ -1446,22 +7056,31 @@ pub async fn save_annotated_and_pin(
     Ok(window_label)
 }
 
+/// Screenshots wait under this prefix (hidden) until saved from the preview
+const TEMP_SCREENSHOT_PREFIX: &str = ".zureshot_screenshot_";
+
+/// Permanent path for a temp screenshot: the prefix dropped, with `-1`, `-2`…
+/// if that name is taken by now. `pinned_default` keeps the built-in name
+/// pins have always had (`screenshot_<date>_<time>`).
+fn saved_screenshot_path(temp: &std::path::Path, pinned_default: bool) -> std::path::PathBuf {
+    let dir = temp.parent().unwrap_or_else(|| std::path::Path::new("."));
+    let stem = temp.file_stem().unwrap_or_default().to_string_lossy();
+    let stem = stem.strip_prefix(TEMP_SCREENSHOT_PREFIX).unwrap_or(stem.trim_start_matches('.'));
+    let stem = if pinned_default { stem.strip_prefix("zureshot_").unwrap_or(stem) } else { stem };
+    let ext = temp.extension().unwrap_or_default().to_string_lossy();
+    unique_capture_path(dir, "", stem, &ext)
+}
+
 /// Tauri command: save screenshot to permanent location (move from temp)
 #[tauri::command]
-pub async fn save_screenshot(path: String) -> Result<String, String> {
+pub async fn save_screenshot(path: String) -> Result<String, ZureshotError> {
     let src = std::path::Path::new(&path);
     if !src.exists() {
         return Err("Screenshot file not found".into());
     }
 
     // Rename from hidden temp file (.zureshot_screenshot_...) to final name
-    let filename = src
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
-    let final_name = filename.trim_start_matches('.');
-    let dest = src.parent().unwrap().join(final_name);
+    let dest = saved_screenshot_path(src, false);
 
     std::fs::rename(&path, &dest).map_err(|e| format!("Failed to save screenshot: {}", e))?;
 
@@ -1473,17 +7092,22 @@ pub async fn save_screenshot(path: String) -> Result<String, String> {
 /// Tauri command: copy screenshot to clipboard
 /// Also saves the file to its permanent name so the path can be pasted in terminals.
 #[tauri::command]
-pub async fn copy_screenshot(path: String) -> Result<(), String> {
-    let src = std::path::Path::new(&path);
+pub async fn copy_screenshot(path: String) -> Result<(), ZureshotError> {
+    do_copy_screenshot(&path)?;
+    Ok(())
+}
+
+/// Move the temp screenshot to its permanent name and copy it to the
+/// clipboard. Returns the permanent path.
+fn do_copy_screenshot(path: &str) -> Result<String, String> {
+    let src = std::path::Path::new(path);
     if !src.exists() {
         return Err("Screenshot file not found".into());
     }
 
-    // Save to permanent name (strip the leading dot from the hidden temp file)
-    let filename = src.file_name().unwrap_or_default().to_string_lossy().to_string();
-    let final_name = filename.trim_start_matches('.');
-    let dest = src.parent().unwrap().join(final_name);
-    let _ = std::fs::rename(&path, &dest);
+    // Save to permanent name (strip the hidden temp file's prefix)
+    let dest = saved_screenshot_path(src, false);
+    let _ = std::fs::rename(path, &dest);
 
     let dest_str = dest.to_string_lossy().to_string();
 
@@ -1496,17 +7120,114 @@ pub async fn copy_screenshot(path: String) -> Result<(), String> {
     platform::imp::copy_image_to_clipboard(&dest_str)?;
 
     println!("[zureshot] Screenshot copied to clipboard: {}", dest_str);
-    Ok(())
+    Ok(dest_str)
 }
 
 /// Tauri command: dismiss screenshot (delete temp file)
 #[tauri::command]
-pub async fn dismiss_screenshot(path: String) -> Result<(), String> {
+pub async fn dismiss_screenshot(path: String) -> Result<(), ZureshotError> {
     let _ = std::fs::remove_file(&path);
     println!("[zureshot] Screenshot dismissed, temp file removed");
     Ok(())
 }
 
+/// Paste-ready reference formats for an uploaded screenshot
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenshotLinkFormat {
+    /// `![](url)`
+    Markdown,
+    /// `<img src="url" alt="...">`
+    Html,
+    /// The bare URL
+    Url,
+}
+
+/// Shell command that uploads a file (persisted, see `set_upload_command`)
+#[tauri::command]
+pub fn get_upload_command(app: AppHandle) -> String {
+    crate::settings::get(&app, "upload_command").unwrap_or_default()
+}
+
+/// Set the upload hook: a shell command run with the file path as its
+/// argument (`$1`) that prints the uploaded URL as its last line of output.
+/// Empty string turns uploading off.
+#[tauri::command]
+pub fn set_upload_command(app: AppHandle, command: String) {
+    let command = command.trim().to_string();
+    crate::settings::set(&app, "upload_command", serde_json::json!(command));
+    println!("[zureshot] Upload command: {}", if command.is_empty() { "(none)" } else { &command });
+}
+
+/// Run the upload hook for `path` and return the URL it printed.
+fn run_upload_hook(app: &AppHandle, path: &str) -> Result<String, String> {
+    let command = get_upload_command(app.clone());
+    if command.is_empty() {
+        return Err("No upload command configured".into());
+    }
+
+    println!("[zureshot] Uploading {} via: {}", path, command);
+    // `sh -c '<cmd> "$1"' zureshot <path>` — the path is never interpolated
+    let output = std::process::Command::new("sh")
+        .args(["-c", &format!("{} \"$1\"", command), "zureshot", path])
+        .output()
+        .map_err(|e| format!("Failed to run upload command: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Upload command failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let url = stdout.lines().map(str::trim).rev().find(|l| !l.is_empty()).unwrap_or_default();
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(format!("Upload command did not print a URL (got {:?})", url));
+    }
+    Ok(url.to_string())
+}
+
+/// Tauri command: upload a screenshot via the upload hook and copy a
+/// reference to it (`markdown`, `html` or `url`) to the clipboard.
+/// A temp screenshot is saved to its permanent name first. Returns the
+/// copied snippet.
+#[tauri::command]
+pub async fn copy_screenshot_as(
+    app: AppHandle,
+    path: String,
+    format: ScreenshotLinkFormat,
+) -> Result<String, ZureshotError> {
+    let path = if path.contains(TEMP_SCREENSHOT_PREFIX) {
+        save_screenshot(path).await?
+    } else {
+        path
+    };
+
+    let app2 = app.clone();
+    let path2 = path.clone();
+    let url = tokio::task::spawn_blocking(move || run_upload_hook(&app2, &path2))
+        .await
+        .map_err(|e| format!("Upload task failed: {}", e))??;
+
+    let snippet = match format {
+        ScreenshotLinkFormat::Markdown => format!("![]({})", url),
+        ScreenshotLinkFormat::Html => {
+            let name = std::path::Path::new(&path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let escape = |s: &str| s.replace('&', "&amp;").replace('"', "&quot;");
+            format!("<img src=\"{}\" alt=\"{}\">", escape(&url), escape(&name))
+        }
+        ScreenshotLinkFormat::Url => url,
+    };
+
+    platform::imp::copy_text_to_clipboard(&snippet)?;
+    println!("[zureshot] Screenshot reference copied ({:?}): {}", format, snippet);
+    Ok(snippet)
+}
+
 /// Open the screenshot preview window (bottom-left floating)
 fn do_open_screenshot_preview(app: &AppHandle) -> Result<bool, String> {
     if let Some(win) = app.get_webview_window("screenshot-preview") {
@@ -1546,20 +7267,15 @@ static PIN_COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32:
 
 /// Pin a screenshot to the desktop as an always-on-top floating window.
 #[tauri::command]
-pub async fn pin_screenshot(app: AppHandle, path: String) -> Result<String, String> {
+pub async fn pin_screenshot(app: AppHandle, path: String) -> Result<String, ZureshotError> {
     // First save the screenshot to a permanent location if it's a temp file
-    let permanent_path = if path.contains(".zureshot_screenshot_") {
+    let permanent_path = if path.contains(TEMP_SCREENSHOT_PREFIX) {
         // It's a temp file — save it permanently first
         let src = std::path::Path::new(&path);
         if !src.exists() {
             return Err("Screenshot file not found".into());
         }
-        let filename = src
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .replace(".zureshot_screenshot_", "screenshot_");
-        let dest = src.parent().unwrap().join(&filename);
+        let dest = saved_screenshot_path(src, filename_template(&app).is_none());
         std::fs::copy(&path, &dest)
             .map_err(|e| format!("Failed to copy screenshot: {}", e))?;
         dest.to_string_lossy().to_string()
@@ -1612,7 +7328,7 @@ pub struct OcrResponse {
 
 /// Recognize text in a screenshot image.
 #[tauri::command]
-pub async fn ocr_screenshot(_path: String) -> Result<OcrResponse, String> {
+pub async fn ocr_screenshot(_path: String) -> Result<OcrResponse, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         let result = tokio::task::spawn_blocking(move || {
@@ -1628,7 +7344,7 @@ pub async fn ocr_screenshot(_path: String) -> Result<OcrResponse, String> {
     }
 
     #[cfg(not(target_os = "macos"))]
-    Err("OCR is not supported on this platform yet".into())
+    Err(ZureshotError::new(ErrorCode::Unsupported, "OCR is not supported on this platform yet"))
 }
 
 // ════════════════════════════════════════════════════════════════════════
@@ -1637,10 +7353,7 @@ pub async fn ocr_screenshot(_path: String) -> Result<OcrResponse, String> {
 
 /// State for the scroll capture session (managed by Tauri)
 pub struct ScrollCaptureStateWrapper {
-    #[cfg(target_os = "macos")]
-    pub session: Option<crate::platform::macos::scroll_capture::ScrollCaptureSession>,
-    #[cfg(not(target_os = "macos"))]
-    pub session: Option<()>,
+    pub session: Option<platform::imp::ScrollCaptureSession>,
 }
 
 impl Default for ScrollCaptureStateWrapper {
@@ -1695,113 +7408,157 @@ pub fn do_start_scroll_screenshot_selection(app: &AppHandle) -> Result<(), Strin
     .build()
     .map_err(|e| format!("Failed to create region selector window: {}", e))?;
 
-    let _ = window.show();
-    let _ = window.set_focus();
+    let _ = window.show();
+    let _ = window.set_focus();
+
+    println!("[zureshot] Scroll screenshot region selector opened");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn start_scroll_screenshot_selection(app: AppHandle) -> Result<(), ZureshotError> {
+    Ok(do_start_scroll_screenshot_selection(&app)?)
+}
+
+/// Start a scroll capture session: capture the first frame and open the control bar.
+#[tauri::command]
+pub async fn start_scroll_capture(
+    app: AppHandle,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) -> Result<ScrollCaptureStatus, ZureshotError> {
+    // Close region selector
+    if let Some(win) = app.get_webview_window("region-selector") {
+        let _ = win.destroy();
+    }
+
+    let session = platform::imp::ScrollCaptureSession::new(x, y, width, height)?;
+    let status = ScrollCaptureStatus {
+        frame_count: session.frame_count(),
+        total_height: session.total_height(),
+        width: session.width(),
+        new_content: true,
+    };
+
+    let state: tauri::State<'_, Mutex<ScrollCaptureStateWrapper>> = app.state();
+    let mut guard = state.lock().map_err(|e| e.to_string())?;
+    guard.session = Some(session);
+
+    // Open scroll capture bar
+    do_open_scroll_capture_bar(&app)?;
 
-    println!("[zureshot] Scroll screenshot region selector opened");
-    Ok(())
+    Ok(status)
 }
 
+/// Capture one frame and stitch if new content detected.
 #[tauri::command]
-pub async fn start_scroll_screenshot_selection(app: AppHandle) -> Result<(), String> {
-    do_start_scroll_screenshot_selection(&app)
+pub async fn scroll_capture_tick(
+    app: AppHandle,
+) -> Result<ScrollCaptureStatus, ZureshotError> {
+    let state: tauri::State<'_, Mutex<ScrollCaptureStateWrapper>> = app.state();
+    let mut guard = state.lock().map_err(|e| e.to_string())?;
+    let session = guard.session.as_mut().ok_or("No scroll capture session active")?;
+
+    let new_content = session.capture_frame()?;
+
+    Ok(ScrollCaptureStatus {
+        frame_count: session.frame_count(),
+        total_height: session.total_height(),
+        width: session.width(),
+        new_content,
+    })
 }
 
-/// Start a scroll capture session: capture the first frame and open the control bar.
+/// Finish scroll capture: stitch and save as PNG.
 #[tauri::command]
-pub async fn start_scroll_capture(
+pub async fn finish_scroll_capture(
     app: AppHandle,
-    _x: f64,
-    _y: f64,
-    _width: f64,
-    _height: f64,
-) -> Result<ScrollCaptureStatus, String> {
-    // Close region selector
-    if let Some(win) = app.get_webview_window("region-selector") {
+) -> Result<ScreenshotResult, ZureshotError> {
+    // Close the scroll capture bar
+    if let Some(win) = app.get_webview_window("scroll-capture-bar") {
         let _ = win.destroy();
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        use crate::platform::macos::scroll_capture::ScrollCaptureSession;
-
-        let session = ScrollCaptureSession::new(_x, _y, _width, _height)?;
-        let status = ScrollCaptureStatus {
-            frame_count: session.frame_count(),
-            total_height: session.total_height(),
-            width: session.width(),
-            new_content: true,
-        };
+    let state: tauri::State<'_, Mutex<ScrollCaptureStateWrapper>> = app.state();
+    let mut guard = state.lock().map_err(|e| e.to_string())?;
+    let session = guard.session.take().ok_or("No scroll capture session active")?;
 
-        let state: tauri::State<'_, Mutex<ScrollCaptureStateWrapper>> = app.state();
-        let mut guard = state.lock().map_err(|e| e.to_string())?;
-        guard.session = Some(session);
+    let output_path =
+        new_capture_path(&app, &crate::settings::recordings_dir(&app), CaptureKind::ScrollScreenshot, "png");
 
-        // Open scroll capture bar
-        do_open_scroll_capture_bar(&app)?;
+    let (width, height, file_size) = session.finish(&output_path)?;
 
-        Ok(status)
-    }
+    // Read file for base64 preview
+    let image_base64 = std::fs::read(&output_path)
+        .map(|bytes| {
+            use base64::Engine;
+            base64::engine::general_purpose::STANDARD.encode(&bytes)
+        })
+        .unwrap_or_default();
 
-    #[cfg(not(target_os = "macos"))]
-    Err("Scroll capture is not supported on this platform yet".into())
-}
+    let result = ScreenshotResult {
+        path: output_path.clone(),
+        width,
+        height,
+        file_size_bytes: file_size,
+        extension: "png".to_string(),
+        image_base64,
+    };
 
-/// Capture one frame and stitch if new content detected.
-#[tauri::command]
-pub async fn scroll_capture_tick(
-    _app: AppHandle,
-) -> Result<ScrollCaptureStatus, String> {
-    #[cfg(target_os = "macos")]
-    {
-        let state: tauri::State<'_, Mutex<ScrollCaptureStateWrapper>> = _app.state();
-        let mut guard = state.lock().map_err(|e| e.to_string())?;
-        let session = guard.session.as_mut().ok_or("No scroll capture session active")?;
+    println!(
+        "[zureshot] Scroll screenshot saved: {}x{} ({:.1} KB) -> {}",
+        width, height, file_size as f64 / 1024.0, output_path
+    );
 
-        let new_content = session.capture_frame()?;
+    // Emit event for screenshot preview
+    let _ = app.emit("screenshot-taken", &result);
 
-        Ok(ScrollCaptureStatus {
-            frame_count: session.frame_count(),
-            total_height: session.total_height(),
-            width: session.width(),
-            new_content,
-        })
-    }
+    // Open screenshot preview
+    let _ = do_open_screenshot_preview(&app);
 
-    #[cfg(not(target_os = "macos"))]
-    Err("Scroll capture is not supported on this platform yet".into())
+    Ok(result)
 }
 
-/// Finish scroll capture: stitch and save as PNG.
+/// Cancel scroll capture without saving.
 #[tauri::command]
-pub async fn finish_scroll_capture(
+pub async fn cancel_scroll_capture(
     app: AppHandle,
-) -> Result<ScreenshotResult, String> {
-    // Close the scroll capture bar
+) -> Result<(), ZureshotError> {
     if let Some(win) = app.get_webview_window("scroll-capture-bar") {
         let _ = win.destroy();
     }
 
+    let state: tauri::State<'_, Mutex<ScrollCaptureStateWrapper>> = app.state();
+    let mut guard = state.lock().map_err(|e| e.to_string())?;
+    guard.session = None;
+
+    println!("[zureshot] Scroll capture cancelled");
+    Ok(())
+}
+
+/// Scrolling screenshot of one window without the manual capture bar: the
+/// window is raised and scrolled to the end automatically, and the
+/// viewports are stitched into one tall PNG. Needs Accessibility permission
+/// (for the synthetic scroll events).
+#[tauri::command]
+pub async fn scrolling_screenshot(
+    app: AppHandle,
+    window_id: u32,
+) -> Result<ScreenshotResult, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
-        let state: tauri::State<'_, Mutex<ScrollCaptureStateWrapper>> = app.state();
-        let mut guard = state.lock().map_err(|e| e.to_string())?;
-        let session = guard.session.take().ok_or("No scroll capture session active")?;
-
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        let base = dirs::download_dir()
-            .or_else(dirs::home_dir)
-            .unwrap_or_else(|| std::path::PathBuf::from("."));
-        let zureshot_dir = base.join("Zureshot");
-        let _ = std::fs::create_dir_all(&zureshot_dir);
-        let output_path = zureshot_dir
-            .join(format!("scroll_screenshot_{}.png", timestamp))
-            .to_string_lossy()
-            .to_string();
+        let output_path =
+            new_capture_path(&app, &crate::settings::recordings_dir(&app), CaptureKind::ScrollScreenshot, "png");
 
-        let (width, height, file_size) = session.finish(&output_path)?;
+        let path = output_path.clone();
+        let (width, height, file_size, viewports) = tokio::task::spawn_blocking(move || {
+            platform::imp::scrolling_window_screenshot(window_id, &path)
+        })
+        .await
+        .map_err(|e| format!("Scrolling screenshot task failed: {}", e))??;
 
-        // Read file for base64 preview
         let image_base64 = std::fs::read(&output_path)
             .map(|bytes| {
                 use base64::Engine;
@@ -1814,42 +7571,26 @@ pub async fn finish_scroll_capture(
             width,
             height,
             file_size_bytes: file_size,
+            extension: "png".to_string(),
             image_base64,
         };
 
         println!(
-            "[zureshot] Scroll screenshot saved: {}x{} ({:.1} KB) -> {}",
-            width, height, file_size as f64 / 1024.0, output_path
+            "[zureshot] Scrolling screenshot of window {} saved: {}x{} from {} viewports ({:.1} KB) -> {}",
+            window_id, width, height, viewports, file_size as f64 / 1024.0, output_path
         );
 
-        // Emit event for screenshot preview
         let _ = app.emit("screenshot-taken", &result);
-
-        // Open screenshot preview
         let _ = do_open_screenshot_preview(&app);
 
         Ok(result)
     }
 
     #[cfg(not(target_os = "macos"))]
-    Err("Scroll capture is not supported on this platform yet".into())
-}
-
-/// Cancel scroll capture without saving.
-#[tauri::command]
-pub async fn cancel_scroll_capture(
-    app: AppHandle,
-) -> Result<(), String> {
-    if let Some(win) = app.get_webview_window("scroll-capture-bar") {
-        let _ = win.destroy();
+    {
+        let _ = (app, window_id);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Scrolling screenshots are not supported on this platform yet"))
     }
-
-    let state: tauri::State<'_, Mutex<ScrollCaptureStateWrapper>> = app.state();
-    let mut guard = state.lock().map_err(|e| e.to_string())?;
-    guard.session = None;
-
-    println!("[zureshot] Scroll capture cancelled");
-    Ok(())
 }
 
 /// Open the scroll capture floating control bar (bottom-center of screen)
@@ -1901,8 +7642,8 @@ fn do_open_scroll_capture_bar(app: &AppHandle) -> Result<(), String> {
 
 /// Open the video editor window for a recorded video.
 #[tauri::command]
-pub async fn open_video_editor(app: AppHandle, path: String) -> Result<(), String> {
-    do_open_video_editor(&app, &path)
+pub async fn open_video_editor(app: AppHandle, path: String) -> Result<(), ZureshotError> {
+    Ok(do_open_video_editor(&app, &path)?)
 }
 
 /// Core logic to open the video editor window.
@@ -1946,16 +7687,16 @@ pub fn do_open_video_editor(app: &AppHandle, video_path: &str) -> Result<(), Str
 
 /// Get video metadata (duration, resolution, codec, etc.)
 #[tauri::command]
-pub async fn get_video_metadata(path: String) -> Result<serde_json::Value, String> {
+pub async fn get_video_metadata(path: String) -> Result<serde_json::Value, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         let meta = platform::macos::editor::get_video_metadata(&path)?;
-        serde_json::to_value(meta).map_err(|e| e.to_string())
+        Ok(serde_json::to_value(meta).map_err(|e| e.to_string())?)
     }
     #[cfg(not(target_os = "macos"))]
     {
         let _ = path;
-        Err("Video editor is not supported on this platform yet".into())
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Video editor is not supported on this platform yet"))
     }
 }
 
@@ -1965,22 +7706,22 @@ pub async fn generate_timeline_thumbnails(
     path: String,
     count: Option<usize>,
     thumb_height: Option<u32>,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<Vec<serde_json::Value>, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         let count = count.unwrap_or(20);
         let height = thumb_height.unwrap_or(60);
-        tokio::task::spawn_blocking(move || {
+        Ok(tokio::task::spawn_blocking(move || {
             platform::macos::editor::generate_timeline_thumbnails(&path, count, height)
-                .map(|v| v.into_iter().map(|t| serde_json::to_value(t).unwrap_or_default()).collect())
+                .map(|v| v.into_iter().map(|t| serde_json::to_value(t).unwrap_or_default()).collect::<Vec<_>>())
         })
         .await
-        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| format!("Task join error: {}", e))??)
     }
     #[cfg(not(target_os = "macos"))]
     {
         let _ = (path, count, thumb_height);
-        Err("Video editor is not supported on this platform yet".into())
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Video editor is not supported on this platform yet"))
     }
 }
 
@@ -1989,57 +7730,166 @@ pub async fn generate_timeline_thumbnails(
 pub async fn generate_waveform(
     path: String,
     num_samples: Option<usize>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         let samples = num_samples.unwrap_or(200);
-        tokio::task::spawn_blocking(move || {
+        Ok(tokio::task::spawn_blocking(move || {
             platform::macos::editor::generate_waveform(&path, samples)
                 .and_then(|w| serde_json::to_value(w).map_err(|e| e.to_string()))
         })
         .await
-        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| format!("Task join error: {}", e))??)
     }
     #[cfg(not(target_os = "macos"))]
     {
         let _ = (path, num_samples);
-        Err("Video editor is not supported on this platform yet".into())
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Video editor is not supported on this platform yet"))
     }
 }
 
 /// Trim video (stream copy — near-instant, no re-encode)
 #[tauri::command]
 pub async fn trim_video(
+    app: AppHandle,
     input_path: String,
     start_secs: f64,
     end_secs: f64,
     output_path: Option<String>,
-) -> Result<String, String> {
+) -> Result<String, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         let out = output_path.unwrap_or_else(|| {
             let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-            let base = dirs::download_dir()
-                .or_else(dirs::home_dir)
-                .unwrap_or_else(|| std::path::PathBuf::from("."));
-            let zureshot_dir = base.join("Zureshot");
-            let _ = std::fs::create_dir_all(&zureshot_dir);
+            let zureshot_dir = crate::settings::recordings_dir(&app);
             zureshot_dir
                 .join(format!("zureshot_trimmed_{}.mp4", timestamp))
                 .to_string_lossy()
                 .to_string()
         });
 
-        tokio::task::spawn_blocking(move || {
+        Ok(tokio::task::spawn_blocking(move || {
             platform::macos::editor::trim_video(&input_path, start_secs, end_secs, &out)
         })
         .await
-        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| format!("Task join error: {}", e))??)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, input_path, start_secs, end_secs, output_path);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Video editor is not supported on this platform yet"))
+    }
+}
+
+/// Export just the audio of a recording as m4a (default), wav or mp3.
+/// `track` is "system", "mic" or "mixed" (default) for two-track
+/// recordings. `output_path` defaults to `<recordings>/<stem>.<format>`
+/// (`<stem>_audio.<format>` when the recording already has that extension).
+#[tauri::command]
+pub async fn extract_audio(
+    app: AppHandle,
+    video_path: String,
+    output_path: Option<String>,
+    format: Option<String>,
+    track: Option<String>,
+) -> Result<String, ZureshotError> {
+    #[cfg(target_os = "macos")]
+    {
+        let format = format.unwrap_or_else(|| "m4a".into());
+        let out = output_path.unwrap_or_else(|| {
+            let source = std::path::Path::new(&video_path);
+            let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("recording");
+            let ext = format.to_ascii_lowercase();
+            let dir = crate::settings::recordings_dir(&app);
+            // An audio-only .m4a recording would otherwise name itself
+            let same_ext = source.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase) == Some(ext.clone());
+            let name = if same_ext { format!("{}_audio.{}", stem, ext) } else { format!("{}.{}", stem, ext) };
+            dir.join(name).to_string_lossy().to_string()
+        });
+        let track = track.unwrap_or_else(|| "mixed".into());
+
+        Ok(tokio::task::spawn_blocking(move || {
+            platform::macos::editor::extract_audio(&video_path, &out, &format, &track)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, video_path, output_path, format, track);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Audio extraction is not supported on this platform yet"))
+    }
+}
+
+/// Extract numbered still frames from a video (storyboard / animation reference).
+/// `output_dir` defaults to `<recordings>/<stem>_frames/`.
+#[tauri::command]
+pub async fn export_frames(
+    app: AppHandle,
+    input: String,
+    fps: Option<f64>,
+    output_dir: Option<String>,
+    format: Option<String>,
+) -> Result<serde_json::Value, ZureshotError> {
+    #[cfg(target_os = "macos")]
+    {
+        let out = output_dir.unwrap_or_else(|| {
+            let stem = std::path::Path::new(&input)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("recording")
+                .to_string();
+            crate::settings::recordings_dir(&app)
+                .join(format!("{}_frames", stem))
+                .to_string_lossy()
+                .to_string()
+        });
+        let format = format.unwrap_or_else(|| "png".into());
+
+        let result = tokio::task::spawn_blocking(move || {
+            platform::macos::editor::export_frames(&input, fps, &out, &format)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+        Ok(serde_json::to_value(result).map_err(|e| e.to_string())?)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, input, fps, output_dir, format);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Frame export is not supported on this platform yet"))
+    }
+}
+
+/// Concatenate recordings in order (stream copy when compatible, otherwise
+/// re-encoded). `output_path` defaults to `zureshot_merged_<ts>.mp4`.
+#[tauri::command]
+pub async fn merge_recordings(
+    app: AppHandle,
+    paths: Vec<String>,
+    output_path: Option<String>,
+) -> Result<serde_json::Value, ZureshotError> {
+    #[cfg(target_os = "macos")]
+    {
+        let out = output_path.unwrap_or_else(|| {
+            let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+            crate::settings::recordings_dir(&app)
+                .join(format!("zureshot_merged_{}.mp4", timestamp))
+                .to_string_lossy()
+                .to_string()
+        });
+
+        let background = letterbox_background(&app);
+        let result = tokio::task::spawn_blocking(move || {
+            platform::macos::editor::merge_recordings(&paths, &out, &background)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+        Ok(serde_json::to_value(result).map_err(|e| e.to_string())?)
     }
     #[cfg(not(target_os = "macos"))]
     {
-        let _ = (input_path, start_secs, end_secs, output_path);
-        Err("Video editor is not supported on this platform yet".into())
+        let _ = (app, paths, output_path);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Merging recordings is not supported on this platform yet"))
     }
 }
 
@@ -2050,23 +7900,23 @@ pub async fn render_preview_frame(
     time_secs: f64,
     width: Option<u32>,
     height: Option<u32>,
-) -> Result<String, String> {
+) -> Result<String, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         let w = width.unwrap_or(640);
         let h = height.unwrap_or(360);
         let bg = platform::macos::editor::Background::Transparent;
 
-        tokio::task::spawn_blocking(move || {
+        Ok(tokio::task::spawn_blocking(move || {
             platform::macos::editor::render_preview_frame(&path, time_secs, w, h, 0.0, 0.0, &bg)
         })
         .await
-        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| format!("Task join error: {}", e))??)
     }
     #[cfg(not(target_os = "macos"))]
     {
         let _ = (path, time_secs, width, height);
-        Err("Video editor is not supported on this platform yet".into())
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Video editor is not supported on this platform yet"))
     }
 }
 
@@ -2075,21 +7925,21 @@ pub async fn render_preview_frame(
 pub async fn start_export(
     app: AppHandle,
     project: serde_json::Value,
-) -> Result<String, String> {
+) -> Result<String, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         let project: platform::macos::editor::VideoEditProject =
             serde_json::from_value(project).map_err(|e| format!("Invalid project: {}", e))?;
-        tokio::task::spawn_blocking(move || {
+        Ok(tokio::task::spawn_blocking(move || {
             platform::macos::editor::export_video(&project, &app)
         })
         .await
-        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| format!("Task join error: {}", e))??)
     }
     #[cfg(not(target_os = "macos"))]
     {
         let _ = (app, project);
-        Err("Video editor is not supported on this platform yet".into())
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Video editor is not supported on this platform yet"))
     }
 }
 
@@ -2097,16 +7947,16 @@ pub async fn start_export(
 #[tauri::command]
 pub async fn get_mouse_track(
     video_path: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
         let track = platform::macos::mouse_tracker::load_mouse_track(&video_path)?;
-        serde_json::to_value(track).map_err(|e| e.to_string())
+        Ok(serde_json::to_value(track).map_err(|e| e.to_string())?)
     }
     #[cfg(not(target_os = "macos"))]
     {
         let _ = video_path;
-        Err("Mouse tracking is not supported on this platform yet".into())
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Mouse tracking is not supported on this platform yet"))
     }
 }
 
@@ -2116,20 +7966,387 @@ pub fn log_debug(msg: String) {
     println!("{}", msg);
 }
 
-/// Get auto-zoom keyframe suggestions based on mouse tracking data
+/// Get auto-zoom keyframe suggestions based on mouse tracking data.
+/// `config` is a `ZoomConfig`; only the click-zoom fields are used here.
 #[tauri::command]
 pub async fn suggest_zoom_keyframes(
     video_path: String,
-) -> Result<Vec<serde_json::Value>, String> {
+    config: Option<serde_json::Value>,
+) -> Result<Vec<serde_json::Value>, ZureshotError> {
     #[cfg(target_os = "macos")]
     {
+        let config: platform::macos::editor::ZoomConfig = match config {
+            Some(v) => serde_json::from_value(v).map_err(|e| format!("Invalid zoom config: {}", e))?,
+            None => Default::default(),
+        };
         let track = platform::macos::mouse_tracker::load_mouse_track(&video_path)?;
-        let kfs = platform::macos::mouse_tracker::suggest_zoom_keyframes(&track);
+        let kfs = platform::macos::mouse_tracker::suggest_zoom_keyframes(&track, config.click_zoom_hold());
         Ok(kfs.into_iter().map(|k| serde_json::to_value(k).unwrap_or_default()).collect())
     }
     #[cfg(not(target_os = "macos"))]
     {
-        let _ = video_path;
-        Err("Zoom suggestions are not supported on this platform yet".into())
+        let _ = (video_path, config);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Zoom suggestions are not supported on this platform yet"))
+    }
+}
+
+/// Render a zoomed copy of a recording from its cursor track (non-destructive).
+/// `cursor_json` defaults to the recording's `.mousetrack.json` sidecar.
+#[tauri::command]
+pub async fn apply_zoom(
+    app: AppHandle,
+    video_path: String,
+    cursor_json: Option<String>,
+    config: Option<serde_json::Value>,
+    output_path: Option<String>,
+) -> Result<String, ZureshotError> {
+    #[cfg(target_os = "macos")]
+    {
+        let config: platform::macos::editor::ZoomConfig = match config {
+            Some(v) => serde_json::from_value(v).map_err(|e| format!("Invalid zoom config: {}", e))?,
+            None => Default::default(),
+        };
+        let video = std::path::Path::new(&video_path);
+        let stem = video.file_stem().and_then(|s| s.to_str()).unwrap_or("recording").to_string();
+        let cursor_json = cursor_json.unwrap_or_else(|| {
+            video
+                .with_file_name(format!(".{}.mousetrack.json", stem))
+                .to_string_lossy()
+                .to_string()
+        });
+        let out = output_path.unwrap_or_else(|| {
+            crate::settings::recordings_dir(&app)
+                .join(format!("{}_zoomed.mp4", stem))
+                .to_string_lossy()
+                .to_string()
+        });
+
+        Ok(tokio::task::spawn_blocking(move || {
+            platform::macos::editor::apply_zoom(&video_path, &cursor_json, &config, &out)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, video_path, cursor_json, config, output_path);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Post-hoc zoom is not supported on this platform yet"))
+    }
+}
+
+/// Render a 9:16 vertical copy of a recording (non-destructive), panning
+/// along the cursor track when `follow_cursor` is set.
+#[tauri::command]
+pub async fn export_vertical(
+    app: AppHandle,
+    video_path: String,
+    config: Option<serde_json::Value>,
+    output_path: Option<String>,
+) -> Result<String, ZureshotError> {
+    #[cfg(target_os = "macos")]
+    {
+        let config: platform::macos::editor::VerticalExportConfig = match config {
+            Some(v) => serde_json::from_value(v).map_err(|e| format!("Invalid vertical export config: {}", e))?,
+            None => Default::default(),
+        };
+        let out = output_path.unwrap_or_else(|| vertical_output_path(&app, &video_path));
+        Ok(tokio::task::spawn_blocking(move || {
+            platform::macos::editor::export_vertical(&video_path, &config, &out)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, video_path, config, output_path);
+        Err(ZureshotError::new(ErrorCode::Unsupported, "Vertical export is not supported on this platform yet"))
+    }
+}
+
+/// `<recordings>/<stem>_vertical.mp4`
+#[cfg(target_os = "macos")]
+fn vertical_output_path(app: &AppHandle, video_path: &str) -> String {
+    let stem = std::path::Path::new(video_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("recording");
+    crate::settings::recordings_dir(app)
+        .join(format!("{}_vertical.mp4", stem))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Vertical copy produced automatically after each recording, if any
+#[tauri::command]
+pub fn get_vertical_export(app: AppHandle) -> Option<serde_json::Value> {
+    crate::settings::get(&app, "vertical_export")
+}
+
+/// Also produce a vertical copy of every new recording (persisted). `None`
+/// turns it off.
+#[tauri::command]
+pub fn set_vertical_export(app: AppHandle, config: Option<serde_json::Value>) -> Result<(), ZureshotError> {
+    #[cfg(target_os = "macos")]
+    if let Some(ref v) = config {
+        serde_json::from_value::<platform::macos::editor::VerticalExportConfig>(v.clone())
+            .map_err(|e| format!("Invalid vertical export config: {}", e))?;
+    }
+    crate::settings::set(&app, "vertical_export", config.clone().unwrap_or(serde_json::Value::Null));
+    println!("[zureshot] Automatic vertical export: {}", if config.is_some() { "on" } else { "off" });
+    Ok(())
+}
+
+/// Fill used when padding to a different aspect (merges, intro/outro
+/// cards), or `None` for the default black bars
+#[tauri::command]
+pub fn get_letterbox_background(app: AppHandle) -> Option<serde_json::Value> {
+    crate::settings::get(&app, "letterbox_background")
+}
+
+/// Set the letterbox fill (persisted): `{ "type": "color", "rgba": "#RRGGBBAA" }`,
+/// `{ "type": "blurred_source" }` or `{ "type": "image", "path": … }`.
+/// `None` restores black bars.
+#[tauri::command]
+pub fn set_letterbox_background(app: AppHandle, background: Option<serde_json::Value>) -> Result<(), ZureshotError> {
+    #[cfg(target_os = "macos")]
+    if let Some(ref v) = background {
+        serde_json::from_value::<platform::macos::editor::LetterboxBackground>(v.clone())
+            .map_err(|e| format!("Invalid letterbox background: {}", e))?
+            .validate()?;
+    }
+    crate::settings::set(&app, "letterbox_background", background.clone().unwrap_or(serde_json::Value::Null));
+    println!(
+        "[zureshot] Letterbox background: {}",
+        background.as_ref().and_then(|v| v.get("type")).and_then(|t| t.as_str()).unwrap_or("default")
+    );
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn letterbox_background(app: &AppHandle) -> platform::macos::editor::LetterboxBackground {
+    crate::settings::get(app, "letterbox_background").unwrap_or_default()
+}
+
+/// Render the automatic vertical copy in the background; emits
+/// `vertical-export-ready` with the new path.
+fn spawn_auto_vertical_export(app: &AppHandle, video_path: &str) {
+    #[cfg(target_os = "macos")]
+    {
+        let Some(config) = crate::settings::get::<platform::macos::editor::VerticalExportConfig>(app, "vertical_export") else {
+            return;
+        };
+        let app = app.clone();
+        let video_path = video_path.to_string();
+        std::thread::spawn(move || {
+            let out = vertical_output_path(&app, &video_path);
+            match platform::macos::editor::export_vertical(&video_path, &config, &out) {
+                Ok(path) => {
+                    let _ = app.emit(
+                        "vertical-export-ready",
+                        serde_json::json!({ "source": video_path, "path": path }),
+                    );
+                }
+                Err(e) => eprintln!("[zureshot] Automatic vertical export failed: {}", e),
+            }
+        });
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (app, video_path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn jan_31() -> chrono::DateTime<chrono::Local> {
+        chrono::Local.with_ymd_and_hms(2024, 1, 31, 14, 25, 1).unwrap()
+    }
+
+    /// An empty directory of its own under the system temp dir.
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("zureshot_test_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn render_filename_expands_every_token() {
+        let now = jan_31();
+        assert_eq!(render_filename("{app}", CaptureKind::Recording, 0, now), "zureshot");
+        assert_eq!(render_filename("{date}", CaptureKind::Recording, 0, now), "20240131");
+        assert_eq!(render_filename("{time}", CaptureKind::Recording, 0, now), "142501");
+        assert_eq!(render_filename("{epoch}", CaptureKind::Recording, 0, now), now.timestamp().to_string());
+        assert_eq!(render_filename("{counter}", CaptureKind::Recording, 42, now), "42");
+        assert_eq!(render_filename("{type}", CaptureKind::ScrollScreenshot, 0, now), "scroll_screenshot");
+        assert_eq!(
+            render_filename("{app}-{type}-{date}_{time}-{counter}", CaptureKind::Replay, 7, now),
+            "zureshot-replay-20240131_142501-7"
+        );
+    }
+
+    #[test]
+    fn render_filename_keeps_unknown_tokens_and_strips_separators() {
+        let now = jan_31();
+        assert_eq!(render_filename("{nope}_{date}", CaptureKind::Screenshot, 0, now), "{nope}_20240131");
+        assert_eq!(render_filename("a/b\\c:d", CaptureKind::Screenshot, 0, now), "a_b_c_d");
+        assert_eq!(render_filename("   ", CaptureKind::Screenshot, 0, now), "zureshot");
+    }
+
+    #[test]
+    fn file_stem_defaults_match_the_built_in_names() {
+        let now = jan_31();
+        let stem = |kind| file_stem_from(None, kind, now, || unreachable!());
+        assert_eq!(stem(CaptureKind::Recording), "zureshot_20240131_142501");
+        assert_eq!(stem(CaptureKind::Screenshot), "zureshot_screenshot_20240131_142501");
+        assert_eq!(stem(CaptureKind::ClipboardScreenshot), format!("zureshot_{}", now.timestamp()));
+        assert_eq!(stem(CaptureKind::AnnotatedScreenshot), "screenshot_20240131_142501");
+        assert_eq!(stem(CaptureKind::ScrollScreenshot), "scroll_screenshot_20240131_142501");
+        assert_eq!(stem(CaptureKind::Replay), "zureshot_replay_20240131_142501");
+    }
+
+    #[test]
+    fn file_stem_advances_the_counter_only_when_used() {
+        let now = jan_31();
+        let calls = std::cell::Cell::new(0);
+        let next = || {
+            calls.set(calls.get() + 1);
+            calls.get()
+        };
+        assert_eq!(file_stem_from(Some("shot_{counter}"), CaptureKind::Screenshot, now, next), "shot_1");
+        assert_eq!(file_stem_from(Some("shot_{date}"), CaptureKind::Screenshot, now, next), "shot_20240131");
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn unique_capture_path_adds_a_suffix_per_collision() {
+        let dir = scratch_dir("unique_path");
+        let first = unique_capture_path(&dir, "", "shot", "png");
+        assert_eq!(first, dir.join("shot.png"));
+        std::fs::write(&first, b"").unwrap();
+
+        let second = unique_capture_path(&dir, "", "shot", "png");
+        assert_eq!(second, dir.join("shot-1.png"));
+        std::fs::write(&second, b"").unwrap();
+        assert_eq!(unique_capture_path(&dir, "", "shot", "png"), dir.join("shot-2.png"));
+
+        // Another extension or prefix is another name
+        assert_eq!(unique_capture_path(&dir, "", "shot", "jpg"), dir.join("shot.jpg"));
+        assert_eq!(unique_capture_path(&dir, ".tmp_", "shot", "png"), dir.join(".tmp_shot.png"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// A top-level MP4 box with `payload` zero bytes; `open_ended` writes
+    /// size 0 ("runs to the end of the file").
+    fn mp4_box(kind: &[u8; 4], payload: usize, open_ended: bool) -> Vec<u8> {
+        let size = if open_ended { 0 } else { (8 + payload) as u32 };
+        let mut b = size.to_be_bytes().to_vec();
+        b.extend_from_slice(kind);
+        b.resize(8 + payload, 0);
+        b
+    }
+
+    #[test]
+    fn complete_fragments_counts_whole_moof_mdat_pairs() {
+        let dir = scratch_dir("fragments");
+        let path = dir.join("rec.mp4");
+        let mut file = [mp4_box(b"ftyp", 16, false), mp4_box(b"moov", 64, false)].concat();
+        for _ in 0..2 {
+            file.extend(mp4_box(b"moof", 32, false));
+            file.extend(mp4_box(b"mdat", 100, false));
+        }
+        let two_fragments = file.len() as u64;
+
+        // Growing fragment: open-ended mdat
+        file.extend(mp4_box(b"moof", 32, false));
+        file.extend(mp4_box(b"mdat", 50, true));
+        std::fs::write(&path, &file).unwrap();
+        assert_eq!(complete_fragments(&path).unwrap(), (2, two_fragments));
+
+        // Cut mid-box: the truncated mdat doesn't count either
+        let mut cut = file[..two_fragments as usize].to_vec();
+        cut.extend(mp4_box(b"moof", 32, false));
+        cut.extend(&mp4_box(b"mdat", 100, false)[..60]);
+        std::fs::write(&path, &cut).unwrap();
+        assert_eq!(complete_fragments(&path).unwrap(), (2, two_fragments));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn saved_screenshot_path_drops_the_temp_prefix() {
+        let dir = scratch_dir("saved_screenshot");
+        let temp = dir.join(format!("{}zureshot_screenshot_20240131_142501.png", TEMP_SCREENSHOT_PREFIX));
+        assert_eq!(saved_screenshot_path(&temp, false), dir.join("zureshot_screenshot_20240131_142501.png"));
+        assert_eq!(saved_screenshot_path(&temp, true), dir.join("screenshot_20240131_142501.png"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn sidecars_include_the_bookmark_stills() {
+        let dir = scratch_dir("sidecars");
+        let video = dir.join("clip.mp4");
+        for name in ["clip.mp4", "clip.bookmarks.json", ".clip.mousetrack.json", "clip.cursor.json", "other.icc"] {
+            std::fs::write(dir.join(name), b"x").unwrap();
+        }
+        std::fs::create_dir(dir.join("clip_bookmarks")).unwrap();
+
+        let mut found = recording_sidecars(&video.to_string_lossy());
+        found.sort();
+        let mut expected = vec![
+            dir.join("clip.bookmarks.json"),
+            dir.join(".clip.mousetrack.json"),
+            dir.join("clip_bookmarks"),
+        ];
+        expected.sort();
+        assert_eq!(found, expected);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn move_path_moves_folders_with_their_contents() {
+        let dir = scratch_dir("move_path");
+        let stills = dir.join("clip_bookmarks");
+        std::fs::create_dir(&stills).unwrap();
+        std::fs::write(stills.join("bookmark_01_00m05s.png"), b"png").unwrap();
+        std::fs::create_dir(dir.join("dest")).unwrap();
+
+        let moved = dir.join("dest").join("clip_bookmarks");
+        move_path(&stills, &moved).unwrap();
+        assert!(!stills.exists());
+        assert_eq!(std::fs::read(moved.join("bookmark_01_00m05s.png")).unwrap(), b"png");
+
+        // The cross-filesystem fallback, minus the rename
+        let copied = dir.join("copy");
+        copy_path(&moved, &copied).unwrap();
+        assert_eq!(std::fs::read(copied.join("bookmark_01_00m05s.png")).unwrap(), b"png");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn moved_bookmarks_index_points_at_the_moved_stills() {
+        let dir = scratch_dir("retarget_bookmarks");
+        let old = dir.join("clip.mp4").to_string_lossy().to_string();
+        let new = dir.join("dest").join("clip.mp4").to_string_lossy().to_string();
+        std::fs::create_dir(dir.join("dest")).unwrap();
+        let still = bookmarks_dir(&old).join("bookmark_01_00m05s.png");
+        let elsewhere = "/elsewhere/still.png".to_string();
+        let bookmarks = [
+            Bookmark { time_secs: 5.0, still_path: still.to_string_lossy().to_string() },
+            Bookmark { time_secs: 9.0, still_path: elsewhere.clone() },
+        ];
+        write_bookmarks_index(&new, &bookmarks);
+
+        retarget_bookmarks_index(&old, &new);
+        let json = std::fs::read_to_string(dir.join("dest").join("clip.bookmarks.json")).unwrap();
+        let index: BookmarksIndex = serde_json::from_str(&json).unwrap();
+        assert_eq!(index.recording, new);
+        assert_eq!(
+            index.bookmarks[0].still_path,
+            bookmarks_dir(&new).join("bookmark_01_00m05s.png").to_string_lossy()
+        );
+        // Stills that weren't in the folder are left alone
+        assert_eq!(index.bookmarks[1].still_path, elsewhere);
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -0,0 +1,196 @@
+//! Structured error for Tauri command results.
+//!
+//! Commands return `Result<_, ZureshotError>`, which reaches the frontend as
+//! `{ code, message }` so the UI can tell "permission denied" (show the
+//! permission helper) from "disk full" or "already recording" without
+//! matching on text. Errors whose cause is known where they happen (a
+//! privacy permission, no recording running, a feature missing on this
+//! platform, an io error) are built with their code there. Platform code
+//! still produces plain `String` errors for the rest; `From<String>` falls
+//! back to classifying those by their (stable) wording at the command
+//! boundary, so `?` keeps working everywhere.
+
+use serde::Serialize;
+use std::fmt;
+
+/// What went wrong, for the frontend to branch on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Screen Recording / Accessibility / Camera / Microphone not granted
+    PermissionDenied,
+    /// The OS refused a file or folder (EACCES/EPERM), not a privacy permission
+    AccessDenied,
+    /// Out of disk space while writing
+    DiskFull,
+    /// A recording (or replay, stream) is already running
+    AlreadyRecording,
+    /// The action needs a recording that isn't running
+    NotRecording,
+    /// A file, window, display or device doesn't exist
+    NotFound,
+    /// Not available on this platform or OS version
+    Unsupported,
+    /// Bad argument from the caller
+    InvalidInput,
+    /// ffmpeg missing or failed
+    Ffmpeg,
+    /// Anything else
+    Internal,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ZureshotError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ZureshotError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    /// The action needs a running recording and there is none.
+    pub fn not_recording() -> Self {
+        Self::new(ErrorCode::NotRecording, "No recording in progress")
+    }
+
+    /// Fallback for errors that reach a command as a plain message.
+    /// Checked in order, so the more specific phrases win ("Recording not
+    /// found" is `NotFound`, not `NotRecording`; the io "Permission denied
+    /// (os error 13)" is `AccessDenied`, not a privacy `PermissionDenied`).
+    fn classify(message: &str) -> ErrorCode {
+        let m = message.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| m.contains(n));
+        if has(&["os error 13", "os error 1)", "operation not permitted", "read-only file system"]) {
+            ErrorCode::AccessDenied
+        } else if has(&["permission", "not authorized", "not granted", "declined", "tcc"]) {
+            ErrorCode::PermissionDenied
+        } else if has(&["no space left", "disk full", "not enough space", "storage full", "enospc"]) {
+            ErrorCode::DiskFull
+        } else if has(&["already in progress", "already running", "already recording", "already paused"]) {
+            ErrorCode::AlreadyRecording
+        } else if has(&["not supported", "unsupported", "requires macos"]) {
+            ErrorCode::Unsupported
+        } else if has(&["ffmpeg"]) {
+            ErrorCode::Ffmpeg
+        } else if has(&["not found", "no longer on screen", "does not exist", "no such file"]) {
+            ErrorCode::NotFound
+        } else if has(&["no recording", "no active recording", "not recording", "is not paused"]) {
+            ErrorCode::NotRecording
+        } else if has(&["invalid", "must be", "expected", "at least", "out of range"]) {
+            ErrorCode::InvalidInput
+        } else {
+            ErrorCode::Internal
+        }
+    }
+}
+
+impl fmt::Display for ZureshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ZureshotError {}
+
+impl From<String> for ZureshotError {
+    fn from(message: String) -> Self {
+        Self { code: Self::classify(&message), message }
+    }
+}
+
+impl From<&str> for ZureshotError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<std::io::Error> for ZureshotError {
+    fn from(e: std::io::Error) -> Self {
+        // ENOSPC is 28 on both macOS and Linux
+        let code = match (e.kind(), e.raw_os_error()) {
+            (_, Some(28)) => ErrorCode::DiskFull,
+            (std::io::ErrorKind::NotFound, _) => ErrorCode::NotFound,
+            (std::io::ErrorKind::PermissionDenied, _) => ErrorCode::AccessDenied,
+            _ => Self::classify(&e.to_string()),
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+/// For `do_*` helpers (still `Result<_, String>`) that call commands.
+impl From<ZureshotError> for String {
+    fn from(e: ZureshotError) -> Self {
+        e.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(message: &str) -> ErrorCode {
+        ZureshotError::from(message).code
+    }
+
+    #[test]
+    fn classifies_privacy_permissions() {
+        assert_eq!(
+            code("Screen Recording permission denied. → System Settings > Privacy & Security"),
+            ErrorCode::PermissionDenied
+        );
+        assert_eq!(code("Microphone permission denied."), ErrorCode::PermissionDenied);
+        assert_eq!(code("The user declined TCCs for application"), ErrorCode::PermissionDenied);
+    }
+
+    #[test]
+    fn classifies_file_access_apart_from_privacy_permissions() {
+        assert_eq!(code("Failed to write /x.mp4: Permission denied (os error 13)"), ErrorCode::AccessDenied);
+        assert_eq!(code("Failed to move: Operation not permitted (os error 1)"), ErrorCode::AccessDenied);
+        let io = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert_eq!(ZureshotError::from(io).code, ErrorCode::AccessDenied);
+    }
+
+    #[test]
+    fn classifies_disk_full() {
+        assert_eq!(code("Writer failed: No space left on device"), ErrorCode::DiskFull);
+        assert_eq!(ZureshotError::from(std::io::Error::from_raw_os_error(28)).code, ErrorCode::DiskFull);
+    }
+
+    #[test]
+    fn classifies_recording_state() {
+        assert_eq!(code("Recording already in progress"), ErrorCode::AlreadyRecording);
+        assert_eq!(code("Instant replay is already running"), ErrorCode::AlreadyRecording);
+        assert_eq!(code("No recording in progress"), ErrorCode::NotRecording);
+        assert_eq!(code("Recording is not paused"), ErrorCode::NotRecording);
+    }
+
+    #[test]
+    fn specific_phrases_win() {
+        // "not found" beats "recording"
+        assert_eq!(code("Recording not found: /tmp/a.mp4"), ErrorCode::NotFound);
+        // "ffmpeg not found" is an ffmpeg problem, not a missing file
+        assert_eq!(code("ffmpeg not found or failed to run: No such file"), ErrorCode::Ffmpeg);
+        assert_eq!(code("OCR is not supported on this platform yet"), ErrorCode::Unsupported);
+        assert_eq!(
+            ZureshotError::from(std::io::Error::from(std::io::ErrorKind::NotFound)).code,
+            ErrorCode::NotFound
+        );
+    }
+
+    #[test]
+    fn classifies_invalid_input_and_falls_back_to_internal() {
+        assert_eq!(code("Invalid hotkey 'Foo': bad key"), ErrorCode::InvalidInput);
+        assert_eq!(code("Need at least two recordings to merge"), ErrorCode::InvalidInput);
+        assert_eq!(code("Something odd happened"), ErrorCode::Internal);
+    }
+
+    #[test]
+    fn explicit_codes_are_kept() {
+        assert_eq!(ZureshotError::not_recording().code, ErrorCode::NotRecording);
+        // Round-tripping through a String (do_* helpers) re-classifies
+        let message: String = ZureshotError::new(ErrorCode::DiskFull, "Disk full").into();
+        assert_eq!(code(&message), ErrorCode::DiskFull);
+    }
+}
@@ -0,0 +1,250 @@
+//! ffmpeg binary location and capability probe.
+//!
+//! GIF/WebP conversion, trim, watermark, merge and export all shell out to
+//! ffmpeg and depend on specific filters/encoders. `probe` runs
+//! `ffmpeg -filters` / `-encoders` once and caches what the installed build
+//! supports, so the frontend can disable features up front instead of
+//! failing at stop time. The cache is dropped when `ffmpeg_path` changes.
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+/// Configured ffmpeg binary (empty = `ffmpeg` from PATH).
+static FFMPEG_PATH: Mutex<String> = Mutex::new(String::new());
+/// Last probe result for the configured binary.
+static CAPABILITIES: Mutex<Option<FfmpegCapabilities>> = Mutex::new(None);
+
+/// What the installed ffmpeg can do.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FfmpegCapabilities {
+    /// ffmpeg ran at all
+    pub available: bool,
+    pub path: String,
+    /// First line of `ffmpeg -version`
+    pub version: String,
+    // Filters
+    pub palettegen: bool,
+    pub paletteuse: bool,
+    pub overlay: bool,
+    pub concat: bool,
+    pub drawtext: bool,
+    // Encoders
+    pub libwebp_anim: bool,
+    pub hevc_videotoolbox: bool,
+    pub libx264: bool,
+}
+
+/// Load the `ffmpeg_path` setting (call once at startup).
+pub fn init(app: &AppHandle) {
+    let path: String = crate::settings::get(app, "ffmpeg_path").unwrap_or_default();
+    if let Ok(mut p) = FFMPEG_PATH.lock() {
+        *p = path;
+    }
+}
+
+/// The ffmpeg binary to run.
+pub fn binary() -> String {
+    match FFMPEG_PATH.lock() {
+        Ok(p) if !p.is_empty() => p.clone(),
+        _ => "ffmpeg".to_string(),
+    }
+}
+
+/// A `Command` for the configured ffmpeg binary.
+pub fn command() -> Command {
+    Command::new(binary())
+}
+
+/// Use a different ffmpeg binary (empty = PATH). Persists the setting and
+/// drops the cached probe.
+pub fn set_path(app: &AppHandle, path: &str) {
+    let path = path.trim().to_string();
+    crate::settings::set(app, "ffmpeg_path", serde_json::json!(path));
+    if let Ok(mut p) = FFMPEG_PATH.lock() {
+        *p = path;
+    }
+    if let Ok(mut caps) = CAPABILITIES.lock() {
+        *caps = None;
+    }
+}
+
+/// Capabilities of the configured ffmpeg, probing it on first use (or when
+/// `refresh` is set). Blocks while ffmpeg runs — call off the main thread.
+pub fn probe(refresh: bool) -> FfmpegCapabilities {
+    if !refresh {
+        if let Some(caps) = CAPABILITIES.lock().ok().and_then(|c| c.clone()) {
+            return caps;
+        }
+    }
+
+    let path = binary();
+    let caps = run_probe(&path);
+    println!(
+        "[zureshot] ffmpeg probe ({}): {}",
+        path,
+        if caps.available { caps.version.as_str() } else { "not available" }
+    );
+    if let Ok(mut cached) = CAPABILITIES.lock() {
+        *cached = Some(caps.clone());
+    }
+    caps
+}
+
+fn run_probe(path: &str) -> FfmpegCapabilities {
+    let run = |arg: &str| -> Option<String> {
+        let output = Command::new(path).args(["-hide_banner", arg]).output().ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    let Some(version) = run("-version") else {
+        return FfmpegCapabilities { path: path.to_string(), ..Default::default() };
+    };
+    let filters = names(&run("-filters").unwrap_or_default());
+    let encoders = names(&run("-encoders").unwrap_or_default());
+    let has = |list: &[String], name: &str| list.iter().any(|n| n == name);
+
+    FfmpegCapabilities {
+        available: true,
+        path: path.to_string(),
+        version: version.lines().next().unwrap_or_default().trim().to_string(),
+        palettegen: has(&filters, "palettegen"),
+        paletteuse: has(&filters, "paletteuse"),
+        overlay: has(&filters, "overlay"),
+        concat: has(&filters, "concat"),
+        drawtext: has(&filters, "drawtext"),
+        libwebp_anim: has(&encoders, "libwebp_anim"),
+        hevc_videotoolbox: has(&encoders, "hevc_videotoolbox"),
+        libx264: has(&encoders, "libx264"),
+    }
+}
+
+/// Names from `-filters` / `-encoders` listings: rows look like
+/// ` T.. palettegen  V->V  Find the optimal palette…` and
+/// ` V....D libx264  libx264 H.264 …` — the name is the second column.
+/// Header rows (before the ` ------` separator for encoders) are skipped.
+fn names(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .filter(|l| l.starts_with(' '))
+        .filter_map(|l| {
+            let mut cols = l.split_whitespace();
+            let flags = cols.next()?;
+            let name = cols.next()?;
+            // Legend rows ("V..... = Video") have "=" as the second column
+            (name != "=" && !flags.starts_with('-')).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// The ffprobe next to the configured ffmpeg (`ffprobe` from PATH when
+/// ffmpeg comes from PATH).
+pub fn probe_binary() -> String {
+    let ffmpeg = binary();
+    let path = std::path::Path::new(&ffmpeg);
+    match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) if name.starts_with("ffmpeg") && path.parent().is_some_and(|p| !p.as_os_str().is_empty()) => {
+            path.with_file_name(name.replacen("ffmpeg", "ffprobe", 1)).to_string_lossy().to_string()
+        }
+        _ => "ffprobe".to_string(),
+    }
+}
+
+/// A file's video stream, as ffprobe reports it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VideoStream {
+    /// ffprobe `codec_name`: "hevc", "h264", "prores", …
+    pub codec: String,
+    pub width: u32,
+    pub height: u32,
+    /// Base frame rate (`r_frame_rate`: the nominal rate of a VFR
+    /// recording; 0 when unknown)
+    pub fps: f64,
+}
+
+/// One audio track, as ffprobe reports it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AudioStream {
+    pub codec: String,
+    pub channels: u32,
+    pub sample_rate: u32,
+}
+
+/// Streams and length of a media file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaInfo {
+    pub duration_secs: f64,
+    /// First video stream (None for audio-only files)
+    pub video: Option<VideoStream>,
+    /// Audio tracks in file order
+    pub audio: Vec<AudioStream>,
+}
+
+/// Probe `path` with ffprobe's JSON output.
+pub fn media_info(path: &str) -> Result<MediaInfo, String> {
+    let output = Command::new(probe_binary())
+        .args([
+            "-v", "error",
+            "-show_entries", "format=duration:stream=codec_type,codec_name,width,height,r_frame_rate,channels,sample_rate",
+            "-of", "json",
+            path,
+        ])
+        .output()
+        .map_err(|e| format!("ffprobe not found or failed to run: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "ffprobe couldn't read {}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    parse_media_info(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| format!("Can't read the streams of {}", path))
+}
+
+/// `MediaInfo` from `ffprobe -of json` output. ffprobe prints numbers such
+/// as the duration and sample rate as strings, and rates as fractions.
+fn parse_media_info(json: &str) -> Option<MediaInfo> {
+    let root: serde_json::Value = serde_json::from_str(json).ok()?;
+    let number = |v: &serde_json::Value| -> Option<f64> {
+        v.as_f64().or_else(|| v.as_str()?.parse().ok())
+    };
+    let rate = |v: &serde_json::Value| -> f64 {
+        let Some((n, d)) = v.as_str().and_then(|r| r.split_once('/')) else {
+            return 0.0;
+        };
+        match (n.parse::<f64>(), d.parse::<f64>()) {
+            (Ok(n), Ok(d)) if d > 0.0 => n / d,
+            _ => 0.0,
+        }
+    };
+
+    let mut info = MediaInfo {
+        duration_secs: number(&root["format"]["duration"]).unwrap_or(0.0),
+        ..Default::default()
+    };
+    for stream in root["streams"].as_array()? {
+        let codec = stream["codec_name"].as_str().unwrap_or_default().to_string();
+        match stream["codec_type"].as_str() {
+            Some("video") if info.video.is_none() => {
+                info.video = Some(VideoStream {
+                    codec,
+                    width: number(&stream["width"]).unwrap_or(0.0) as u32,
+                    height: number(&stream["height"]).unwrap_or(0.0) as u32,
+                    fps: rate(&stream["r_frame_rate"]),
+                });
+            }
+            Some("audio") => info.audio.push(AudioStream {
+                codec,
+                channels: number(&stream["channels"]).unwrap_or(0.0) as u32,
+                sample_rate: number(&stream["sample_rate"]).unwrap_or(0.0) as u32,
+            }),
+            _ => {}
+        }
+    }
+    Some(info)
+}
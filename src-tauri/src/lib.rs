@@ -3,7 +3,10 @@
 #![allow(non_snake_case)]
 
 mod commands;
+mod error;
+mod ffmpeg;
 mod platform;
+mod settings;
 mod tray;
 
 use commands::{RecordingState, ScrollCaptureStateWrapper};
@@ -18,12 +21,17 @@ pub static SHOULD_EXIT: AtomicBool = AtomicBool::new(false);
 use platform::macos::camera::NativeCameraState;
 #[cfg(target_os = "macos")]
 use platform::macos::mouse_tracker::MouseTrackerState;
+#[cfg(target_os = "macos")]
+use platform::macos::replay::InstantReplayState;
+#[cfg(target_os = "linux")]
+use platform::linux::LiveStreamState;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .setup(|app| {
             // Initialize recording state
             app.manage(Mutex::new(RecordingState::default()));
@@ -39,9 +47,58 @@ pub fn main() {
             #[cfg(target_os = "macos")]
             app.manage(Mutex::new(MouseTrackerState::default()));
 
+            // Initialize instant replay state (rolling in-memory buffer)
+            #[cfg(target_os = "macos")]
+            app.manage(Mutex::new(InstantReplayState::default()));
+
+            // Initialize live streaming state (GStreamer RTMP/SRT pipeline)
+            #[cfg(target_os = "linux")]
+            app.manage(Mutex::new(LiveStreamState::default()));
+
+            // Battery saver: follow AC ⇄ battery changes (main run loop)
+            #[cfg(target_os = "macos")]
+            {
+                let handle = app.handle().clone();
+                platform::macos::power::watch_power_source(move |on_battery| {
+                    commands::apply_power_state(&handle, on_battery)
+                });
+                commands::apply_power_state(app.handle(), platform::macos::power::on_battery());
+            }
+
+            // Finalize recordings whose capture the system stopped (window closed)
+            // or whose writer failed mid-recording
+            #[cfg(target_os = "macos")]
+            {
+                let handle = app.handle().clone();
+                platform::macos::capture::watch_stream_stops(move |output_path, reason| {
+                    commands::handle_capture_interrupted(&handle, &output_path, &reason)
+                });
+                let handle = app.handle().clone();
+                platform::macos::capture::watch_writer_failures(move |output_path, error| {
+                    commands::handle_writer_failed(&handle, &output_path, &error)
+                });
+            }
+
+            // UI sounds left muted by a recording that never stopped
+            commands::restore_ui_sounds(app.handle());
+
+            // ffmpeg binary override (ffmpeg_path setting)
+            ffmpeg::init(app.handle());
+
+            // Offer to trash zero-byte / unfinalized recordings from earlier failures
+            {
+                let handle = app.handle().clone();
+                std::thread::spawn(move || {
+                    commands::cleanup_broken_recordings_on_launch(&handle);
+                });
+            }
+
             // Setup tray icon
             tray::setup_tray(app.handle())?;
 
+            // System-wide start-region / screenshot hotkeys
+            commands::register_global_hotkeys(app.handle());
+
             // Hide the Dock icon — pure menu-bar app
             #[cfg(target_os = "macos")]
             {
@@ -59,17 +116,145 @@ pub fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::start_recording,
+            commands::append_recording,
             commands::stop_recording,
+            commands::cancel_recording,
             commands::get_recording_status,
+            commands::list_recordings,
+            commands::get_active_capture_config,
+            commands::cleanup_broken_recordings,
+            commands::get_cleanup_on_launch,
+            commands::set_cleanup_on_launch,
+            commands::list_recent_recordings,
+            commands::open_recording,
+            commands::get_delete_sidecars,
+            commands::set_delete_sidecars,
+            commands::delete_recording,
+            commands::move_recording,
+            commands::list_displays,
+            commands::list_windows,
+            commands::estimate_size,
             commands::reveal_in_finder,
             commands::get_recordings_dir,
+            commands::set_recordings_dir,
+            commands::get_filename_template,
+            commands::set_filename_template,
+            commands::get_hide_menu_bar,
+            commands::set_hide_menu_bar,
+            commands::get_scene_detection,
+            commands::set_scene_detection,
+            commands::get_update_schedule,
+            commands::set_update_schedule,
+            commands::set_empty_audio_behavior,
+            commands::set_mic_denied_behavior,
+            commands::list_microphones,
+            commands::get_microphone_device,
+            commands::set_microphone_device,
+            commands::set_pixel_format,
+            commands::get_compatibility_mode,
+            commands::set_compatibility_mode,
+            commands::get_match_display_color,
+            commands::set_match_display_color,
+            commands::get_color_space,
+            commands::set_color_space,
+            commands::get_cursor_capture,
+            commands::set_cursor_capture,
+            commands::get_show_cursor,
+            commands::set_show_cursor,
+            commands::get_power_mode,
+            commands::set_power_mode,
+            commands::get_write_timestamps,
+            commands::set_write_timestamps,
+            commands::get_force_cfr,
+            commands::set_force_cfr,
+            commands::get_keyframe_interval_secs,
+            commands::set_keyframe_interval_secs,
+            commands::get_frame_rate_bounds,
+            commands::set_frame_rate_bounds,
+            commands::get_recording_fps,
+            commands::set_recording_fps,
+            commands::get_bitrate_override_bps,
+            commands::set_bitrate_override_bps,
+            commands::get_quality_factor,
+            commands::set_quality_factor,
+            commands::get_rotation,
+            commands::set_rotation,
+            commands::get_countdown_secs,
+            commands::set_countdown_secs,
+            commands::cancel_countdown,
+            commands::get_region_retrigger,
+            commands::set_region_retrigger,
+            commands::get_video_codec,
+            commands::set_video_codec,
+            commands::get_default_format,
+            commands::set_default_format,
+            commands::get_keep_window_in_front,
+            commands::get_flush_on_stop,
+            commands::set_flush_on_stop,
+            commands::get_crash_resilient,
+            commands::set_crash_resilient,
+            commands::set_keep_window_in_front,
+            commands::get_max_duration_secs,
+            commands::get_mute_ui_sounds,
+            commands::set_mute_ui_sounds,
+            commands::get_global_hotkeys,
+            commands::set_global_hotkeys,
+            commands::set_max_duration_secs,
+            commands::get_trim_head_secs,
+            commands::set_trim_head_secs,
+            commands::get_trim_tail_secs,
+            commands::set_trim_tail_secs,
+            commands::get_gif_options,
+            commands::set_gif_options,
+            commands::get_checkpoint_interval_secs,
+            commands::set_checkpoint_interval_secs,
+            commands::get_intro_outro,
+            commands::set_intro_outro,
+            commands::probe_ffmpeg,
+            commands::get_ffmpeg_path,
+            commands::set_ffmpeg_path,
+            commands::get_recording_warmup,
+            commands::set_recording_warmup,
+            commands::get_keystroke_overlay,
+            commands::set_keystroke_overlay,
+            commands::get_click_highlight,
+            commands::set_click_highlight,
             commands::start_region_selection,
             commands::confirm_region_selection,
+            commands::snap_region_to_aspect,
+            commands::clamp_region_to_display,
+            commands::get_min_region_size,
+            commands::set_min_region_size,
+            commands::enforce_min_region_size,
             commands::cancel_region_selection,
+            commands::start_fullscreen_recording,
+            commands::start_all_displays_recording,
+            commands::start_audio_recording,
+            commands::record_focused_window,
+            commands::record_window,
+            commands::repeat_last_recording,
             commands::pause_recording,
             commands::resume_recording,
+            commands::request_keyframe,
+            commands::add_chapter_marker,
+            commands::add_bookmark,
+            commands::set_bar_always_on_top,
+            commands::get_bar_always_on_top,
+            commands::get_bar_spaces,
+            commands::set_bar_spaces,
+            commands::get_privacy_exclusions,
+            commands::set_privacy_exclusions,
+            commands::get_keep_ui_out_of_region,
+            commands::set_keep_ui_out_of_region,
+            commands::start_instant_replay,
+            commands::stop_instant_replay,
+            commands::save_instant_replay,
+            commands::start_streaming,
+            commands::stop_streaming,
             commands::start_screenshot_selection,
             commands::take_screenshot,
+            commands::screenshot_burst,
+            commands::cancel_screenshot_burst,
             commands::screenshot_to_clipboard,
             commands::copy_image_data_to_clipboard,
             commands::save_annotated_and_pin,
@@ -77,12 +262,22 @@ pub fn main() {
             commands::save_screenshot,
             commands::copy_screenshot,
             commands::dismiss_screenshot,
+            commands::copy_screenshot_as,
+            commands::get_upload_command,
+            commands::set_upload_command,
+            commands::cancel_screenshot_auto_dismiss,
+            commands::get_screenshot_preview_settings,
+            commands::set_screenshot_preview_settings,
             commands::open_camera_overlay,
             commands::open_camera_overlay_with_options,
             commands::close_camera_overlay,
             commands::toggle_camera_overlay,
+            commands::get_webcam,
+            commands::set_webcam,
             commands::move_camera_overlay,
             commands::list_native_camera_devices,
+            commands::list_cameras,
+            commands::set_camera,
             commands::start_native_camera,
             commands::stop_native_camera,
             // Pin + OCR commands
@@ -94,16 +289,26 @@ pub fn main() {
             commands::scroll_capture_tick,
             commands::finish_scroll_capture,
             commands::cancel_scroll_capture,
+            commands::scrolling_screenshot,
             // Video editor commands
             commands::open_video_editor,
             commands::get_video_metadata,
             commands::generate_timeline_thumbnails,
             commands::generate_waveform,
             commands::trim_video,
+            commands::extract_audio,
+            commands::export_frames,
+            commands::merge_recordings,
             commands::render_preview_frame,
             commands::start_export,
             commands::get_mouse_track,
             commands::suggest_zoom_keyframes,
+            commands::apply_zoom,
+            commands::export_vertical,
+            commands::get_vertical_export,
+            commands::set_vertical_export,
+            commands::get_letterbox_background,
+            commands::set_letterbox_background,
             commands::log_debug,
         ])
         // Tray-only app: use .build() + .run() to intercept ExitRequested.
@@ -8,13 +8,46 @@
 
 use image::GenericImageView;
 
-/// Take a screenshot of a specific screen region.
+use crate::platform::ImageFormat;
+
+/// Take a screenshot of a specific screen region. The capture tools all
+/// write PNG; JPEG is re-encoded from it with the image crate.
 pub fn take_screenshot_region(
     x: f64,
     y: f64,
     width: f64,
     height: f64,
     output_path: &str,
+    format: ImageFormat,
+) -> Result<(usize, usize, u64), String> {
+    let ImageFormat::Jpeg { quality } = format else {
+        return take_png(x, y, width, height, output_path);
+    };
+    let png_path = format!("{}.png", output_path);
+    let result = take_png(x, y, width, height, &png_path)
+        .and_then(|_| png_to_jpeg(&png_path, output_path, ImageFormat::jpeg_quality(quality)));
+    let _ = std::fs::remove_file(&png_path);
+    result
+}
+
+/// Re-encode the PNG at `src` as a JPEG at `dst` (`quality` 0.0 – 1.0).
+fn png_to_jpeg(src: &str, dst: &str, quality: f64) -> Result<(usize, usize, u64), String> {
+    let img = image::open(src).map_err(|e| format!("Failed to open screenshot: {e}"))?;
+    let file = std::fs::File::create(dst).map_err(|e| format!("Failed to create {dst}: {e}"))?;
+    let jpeg_quality = (quality * 100.0).round().clamp(1.0, 100.0) as u8;
+    image::codecs::jpeg::JpegEncoder::new_with_quality(std::io::BufWriter::new(file), jpeg_quality)
+        .encode_image(&img.to_rgb8())
+        .map_err(|e| format!("Failed to write JPEG file: {e}"))?;
+    let file_size = std::fs::metadata(dst).map(|m| m.len()).unwrap_or(0);
+    Ok((img.width() as usize, img.height() as usize, file_size))
+}
+
+fn take_png(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    output_path: &str,
 ) -> Result<(usize, usize, u64), String> {
     // 1. XDG Portal Screenshot (preferred — no external tools)
     if let Ok(result) = take_via_portal(x, y, width, height, output_path) {
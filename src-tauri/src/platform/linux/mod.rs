@@ -10,8 +10,11 @@
 
 pub mod capture;
 pub mod portal;
+pub mod stitch;
 pub mod writer;
 
+pub use stitch::ScrollCaptureSession;
+
 use std::os::fd::AsRawFd;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -19,7 +22,11 @@ use std::sync::{Arc, Mutex};
 
 use tauri::AppHandle;
 
-use super::{RecordingQuality, StartRecordingConfig};
+use super::{AppliedCaptureConfig, CaptureRegion, RecordingQuality, Rotation, StartRecordingConfig, StreamTarget};
+
+/// `window_id` for a window recording whose window is picked in the portal
+/// dialog. The portal doesn't expose window IDs, so this is the only one.
+pub const PICKED_WINDOW_ID: u32 = 0;
 
 // ── RecordingHandle ──────────────────────────────────────────────────
 
@@ -41,6 +48,10 @@ pub struct RecordingHandle {
     paused_flag: Arc<AtomicBool>,
     /// Final output file path.
     output_path: String,
+    /// Resolved settings reported by `applied_config`.
+    applied: AppliedCaptureConfig,
+    /// Counters read at EOS, before `stop_capture` shut the pipeline down.
+    final_stats: Mutex<Option<super::FrameStats>>,
 }
 
 // SAFETY: All interior state is behind Mutex or atomic types.
@@ -53,8 +64,10 @@ impl RecordingHandle {
         println!("[zureshot-linux] Stopping capture...");
         let mut pipeline_guard = self.pipeline.lock().unwrap();
         if let Some(ref pipeline) = *pipeline_guard {
-            if let Err(e) = pipeline.stop() {
-                println!("[zureshot-linux] Warning: stop error: {e}");
+            match pipeline.stop() {
+                // The counters go away with the pipeline
+                Ok(stats) => *self.final_stats.lock().unwrap() = Some(stats),
+                Err(e) => println!("[zureshot-linux] Warning: stop error: {e}"),
             }
         }
         *pipeline_guard = None;
@@ -62,7 +75,10 @@ impl RecordingHandle {
     }
 
     /// Finalize the output file: close portal session, release PipeWire.
-    pub fn finalize(&self) {
+    ///
+    /// Empty-audio-track detection is not implemented for the GStreamer
+    /// pipeline yet, so this never returns a warning.
+    pub fn finalize(&self) -> Option<String> {
         println!("[zureshot-linux] Finalizing recording: {}", self.output_path);
 
         // Close the portal session (releases PipeWire stream)
@@ -72,6 +88,7 @@ impl RecordingHandle {
         }
 
         println!("[zureshot-linux] Recording finalized: {}", self.output_path);
+        None
     }
 
     /// Pause recording: GStreamer pipeline PLAYING → PAUSED.
@@ -106,6 +123,39 @@ impl RecordingHandle {
         println!("[zureshot-linux] Recording resumed");
     }
 
+    /// Scene detection is not implemented on Linux yet.
+    pub fn scene_changes(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    /// Force the next encoded frame to be a keyframe.
+    pub fn request_keyframe(&self) -> Result<(), String> {
+        let pipeline_guard = self.pipeline.lock().unwrap();
+        match *pipeline_guard {
+            Some(ref pipeline) => pipeline.request_keyframe(),
+            None => Err("Recording pipeline is not running".into()),
+        }
+    }
+
+    /// The configuration this recording is actually running with.
+    pub fn applied_config(&self) -> AppliedCaptureConfig {
+        self.applied.clone()
+    }
+
+    /// First-frame latency isn't measured on Linux.
+    pub fn first_frame_latency_ms(&self) -> Option<f64> {
+        None
+    }
+
+    /// Frames written and dropped so far (the final counts once stopped).
+    pub fn frame_stats(&self) -> super::FrameStats {
+        let pipeline_guard = self.pipeline.lock().unwrap();
+        match pipeline_guard.as_ref() {
+            Some(p) => p.frame_stats(),
+            None => self.final_stats.lock().unwrap().unwrap_or_default(),
+        }
+    }
+
     /// Refresh window exclusion filter (no-op on Linux — Portal handles this).
     pub fn refresh_exclusion(&self, _app: &AppHandle) -> Result<(), String> {
         Ok(())
@@ -127,21 +177,75 @@ pub fn start_recording(
         config.capture_system_audio, config.capture_microphone
     );
 
+    if config.audio_only {
+        return start_audio_only_recording(config);
+    }
+    if config.all_displays {
+        return Err("Recording all displays at once isn't supported on Linux — pick a monitor in the portal dialog instead".into());
+    }
+
+    if config.hide_menu_bar && config.region.is_none() {
+        println!("[zureshot-linux] hide_menu_bar is not supported here; recording the full monitor");
+    }
+    // Any window ID means "a window": the portal dialog decides which
+    let target = match config.window_id {
+        Some(id) => {
+            if id != PICKED_WINDOW_ID {
+                println!("[zureshot-linux] Window {} can't be targeted by ID; the portal picker chooses the window", id);
+            }
+            portal::CaptureTarget::Window
+        }
+        None => portal::CaptureTarget::Monitor,
+    };
+    if config.display_id.is_some() {
+        println!("[zureshot-linux] display_id ignored; the portal picker chooses the monitor");
+    }
+    if config.pixel_format != super::PixelFormat::default() {
+        println!("[zureshot-linux] pixel_format {:?} ignored; PipeWire negotiates the format", config.pixel_format);
+    }
+    if config.show_cursor && config.cursor_capture != super::CursorCapture::System {
+        println!("[zureshot-linux] cursor_capture {:?} ignored; the portal draws the cursor", config.cursor_capture);
+    }
+    if config.warmup_secs > 0.0 {
+        println!("[zureshot-linux] Warm-up ignored; recording starts on the first frame");
+    }
+    if let Some(max_w) = config.max_width {
+        println!("[zureshot-linux] max_width {} ignored; capturing at native resolution", max_w);
+    }
+    if config.write_timestamps {
+        println!("[zureshot-linux] Frame timestamps sidecar is not supported yet");
+    }
+    if config.match_display_color {
+        println!("[zureshot-linux] match_display_color ignored; output is tagged BT.709");
+    }
+    if let Some(space) = config.color_space {
+        println!("[zureshot-linux] color_space {:?} ignored; output is tagged BT.709", space);
+    }
+    if config.scene_threshold.is_some() {
+        println!("[zureshot-linux] Scene detection is not supported yet; recording a single file");
+    }
+
     // ── Step 1: Request screen capture via XDG Portal (ashpd) ──
     // TODO: store and reuse restore_token across sessions
-    let session = portal::request_screencast(None)?;
+    let session = portal::request_screencast(None, config.show_cursor, target)?;
 
     // ── Step 2: Determine recording parameters ──
-    let fps = match config.quality {
-        RecordingQuality::Standard => 30,
-        RecordingQuality::High => 60,
-    };
+    // The portal doesn't report the monitor's refresh rate, so an explicit
+    // fps is only held to FRAME_RATE_LIMITS; videorate caps the stream to it.
+    let preset_fps = config.preset_fps();
+    let fps = config.frame_rate_bounds.max_for(preset_fps);
+    if fps != preset_fps {
+        println!(
+            "[zureshot-linux] Frame rate capped at {}fps ({:.1}ms interval, preset {}fps)",
+            fps, 1000.0 / fps as f64, preset_fps
+        );
+    }
 
     // Use portal-reported dimensions or defaults
     let src_width = session.width.unwrap_or(1920);
     let src_height = session.height.unwrap_or(1080);
 
-    // Region crop
+    // Region crop (relative to the window for window captures)
     let region = config.region.as_ref().map(|r| {
         (r.x as i32, r.y as i32, r.width as i32, r.height as i32)
     });
@@ -156,7 +260,24 @@ pub fn start_recording(
     // Detect best encoder for adaptive bitrate
     gstreamer::init().map_err(|e| format!("GStreamer init: {e}"))?;
     let encoder_info = writer::detect_best_encoder();
-    let bitrate_kbps = writer::compute_bitrate(out_w, out_h, &config.quality, &encoder_info);
+    let bitrate_kbps = match config.bitrate_override_bps {
+        Some(bps) => {
+            let auto = writer::compute_bitrate(out_w, out_h, &config.quality, &encoder_info) as i64 * 1000;
+            println!("[zureshot-linux] Bitrate override: {} bps (automatic would be {} bps)", bps, auto);
+            if let Some(warning) = super::low_bitrate_warning(bps, auto, out_w as usize, out_h as usize) {
+                eprintln!("[zureshot-linux] WARNING: {}", warning);
+            }
+            (bps / 1000) as i32
+        }
+        None => writer::compute_bitrate(out_w, out_h, &config.quality, &encoder_info),
+    };
+
+    // The portal doesn't report display orientation — rotate only on request
+    let rotation = config.rotation.unwrap_or_default();
+    let (out_w, out_h) = if rotation.is_quarter_turn() { (out_h, out_w) } else { (out_w, out_h) };
+    if rotation != Rotation::None {
+        println!("[zureshot-linux] Output rotation: {:?}", rotation);
+    }
 
     // ── Step 3: Start in-process GStreamer pipeline ──
     let pipeline_config = writer::PipelineConfig {
@@ -165,30 +286,257 @@ pub fn start_recording(
         output_path: config.output_path.clone(),
         fps,
         bitrate_kbps,
+        fixed_bitrate: config.bitrate_override_bps.is_some(),
+        quality_factor: config.quality_factor,
         source_width: Some(src_width),
         source_height: Some(src_height),
         region,
         capture_system_audio: config.capture_system_audio,
         capture_mic: config.capture_microphone,
+        compatibility_mode: config.compatibility_mode,
+        force_cfr: config.force_cfr,
+        stream: None,
+        keyframe_interval_secs: config.keyframe_interval_secs,
+        rotation,
+        codec: config.codec,
+        fragment_interval_secs: config.checkpoint_interval_secs,
+        mic_device: config.microphone_device_id.clone(),
     };
 
     let pipeline = writer::start_pipeline(&pipeline_config)?;
 
+    let used = pipeline.encoder_info();
     println!(
         "[zureshot-linux] Recording started: {}x{} @ {}fps, {}kbps, encoder={} ({}), node={}",
-        out_w, out_h, fps, bitrate_kbps,
-        encoder_info.name, encoder_info.description,
+        out_w, out_h, fps, pipeline.bitrate_kbps(),
+        used.name, used.description,
         session.node_id
     );
+    let mut applied = applied_config(
+        used,
+        (out_w, out_h),
+        fps,
+        pipeline.bitrate_kbps(),
+        config.capture_system_audio,
+        config.capture_microphone,
+        region.map(|(x, y, w, h)| CaptureRegion {
+            x: x as f64,
+            y: y as f64,
+            width: w as f64,
+            height: h as f64,
+        }),
+    );
+    applied.rotation = rotation;
 
     Ok(RecordingHandle {
         pipeline: Mutex::new(Some(pipeline)),
         session: Mutex::new(Some(session)),
         paused_flag: Arc::new(AtomicBool::new(false)),
         output_path: config.output_path,
+        applied,
+        final_stats: Mutex::new(None),
+    })
+}
+
+/// Audio-only recording to `.m4a` — PulseAudio sources only, so no portal
+/// prompt and no PipeWire stream.
+fn start_audio_only_recording(config: StartRecordingConfig) -> Result<RecordingHandle, String> {
+    let pipeline = writer::start_audio_pipeline(
+        &config.output_path,
+        config.capture_system_audio,
+        config.capture_microphone,
+        config.microphone_device_id.as_deref(),
+        config.checkpoint_interval_secs,
+    )?;
+    let mut audio_tracks = Vec::new();
+    if config.capture_system_audio {
+        audio_tracks.push("system-audio".to_string());
+    }
+    if config.capture_microphone {
+        audio_tracks.push("microphone".to_string());
+    }
+    let applied = AppliedCaptureConfig {
+        codec: "aac".to_string(),
+        encoder: pipeline.encoder_info().name.to_string(),
+        audio_tracks,
+        ..Default::default()
+    };
+
+    Ok(RecordingHandle {
+        pipeline: Mutex::new(Some(pipeline)),
+        session: Mutex::new(None),
+        paused_flag: Arc::new(AtomicBool::new(false)),
+        output_path: config.output_path,
+        applied,
+        final_stats: Mutex::new(None),
     })
 }
 
+/// Describe what the pipeline runs with: the encoder and bitrate that ended
+/// up in use (compatibility mode may swap HEVC → H.264).
+fn applied_config(
+    encoder: &writer::EncoderInfo,
+    (width, height): (u32, u32),
+    fps: i32,
+    bitrate_kbps: i32,
+    system_audio: bool,
+    mic: bool,
+    source_rect: Option<CaptureRegion>,
+) -> AppliedCaptureConfig {
+    let mut audio_tracks = Vec::new();
+    if system_audio {
+        audio_tracks.push("system-audio".to_string());
+    }
+    if mic {
+        audio_tracks.push("microphone".to_string());
+    }
+    AppliedCaptureConfig {
+        width: width as usize,
+        height: height as usize,
+        fps,
+        codec: if encoder.is_hevc { "hevc" } else { "h264" }.to_string(),
+        encoder: encoder.name.to_string(),
+        video_bitrate_bps: bitrate_kbps as u64 * 1000,
+        audio_tracks,
+        source_rect,
+        ..Default::default()
+    }
+}
+
+// ── Live streaming ───────────────────────────────────────────────────
+
+/// Managed state: at most one live stream runs at a time.
+#[derive(Default)]
+pub struct LiveStreamState {
+    pub session: Option<RecordingHandle>,
+    pub target: Option<StreamTarget>,
+}
+
+/// Stream the monitor picked in the portal dialog to `target`.
+///
+/// Same capture and encoder chain as `start_recording`; only the muxer and
+/// sink differ. The returned handle stops the stream via `stop_capture` +
+/// `finalize`, like a recording.
+pub fn start_streaming(
+    target: &StreamTarget,
+    quality: RecordingQuality,
+    capture_system_audio: bool,
+    capture_mic: bool,
+) -> Result<RecordingHandle, String> {
+    println!(
+        "[zureshot-linux] start_streaming: {} quality={:?}, audio={}, mic={}",
+        target.redacted(), quality, capture_system_audio, capture_mic
+    );
+
+    let session = portal::request_screencast(None, true, portal::CaptureTarget::Monitor)?;
+    let fps = quality.fps();
+    let src_width = session.width.unwrap_or(1920);
+    let src_height = session.height.unwrap_or(1080);
+
+    gstreamer::init().map_err(|e| format!("GStreamer init: {e}"))?;
+    let encoder_info = writer::detect_best_encoder();
+    let bitrate_kbps = writer::compute_bitrate(src_width, src_height, &quality, &encoder_info);
+
+    let pipeline_config = writer::PipelineConfig {
+        node_id: session.node_id,
+        fd: session.fd.as_raw_fd(),
+        output_path: String::new(),
+        fps,
+        bitrate_kbps,
+        fixed_bitrate: false,
+        // Ingest servers need the bitrate target
+        quality_factor: None,
+        source_width: Some(src_width),
+        source_height: Some(src_height),
+        region: None,
+        capture_system_audio,
+        capture_mic,
+        compatibility_mode: false,
+        // Ingest servers expect a steady frame rate
+        force_cfr: true,
+        stream: Some(target.clone()),
+        // Ingest servers generally require a keyframe at least every 2-4s
+        keyframe_interval_secs: crate::platform::DEFAULT_KEYFRAME_INTERVAL_SECS,
+        rotation: Rotation::None,
+        codec: super::VideoCodec::Hevc,
+        fragment_interval_secs: None,
+        mic_device: None,
+    };
+    let pipeline = writer::start_pipeline(&pipeline_config)?;
+
+    println!(
+        "[zureshot-linux] Streaming started: {}x{} @ {}fps, encoder={}",
+        src_width, src_height, fps, pipeline.encoder_info().name
+    );
+    let applied = applied_config(
+        pipeline.encoder_info(),
+        (src_width, src_height),
+        fps,
+        pipeline.bitrate_kbps(),
+        capture_system_audio,
+        // FLV carries one audio track; the pipeline drops the microphone
+        capture_mic && !(target.protocol == super::StreamProtocol::Rtmp && capture_system_audio),
+        None,
+    );
+
+    Ok(RecordingHandle {
+        pipeline: Mutex::new(Some(pipeline)),
+        session: Mutex::new(Some(session)),
+        paused_flag: Arc::new(AtomicBool::new(false)),
+        output_path: target.redacted(),
+        applied,
+        final_stats: Mutex::new(None),
+    })
+}
+
+/// Focused-window lookup is not available through the portal.
+pub fn focused_window_id() -> Option<u32> {
+    None
+}
+
+/// The portal can't look windows up by ID; only `PICKED_WINDOW_ID` (a
+/// window chosen in the portal dialog at start) counts as existing.
+pub fn window_exists(window_id: u32) -> bool {
+    window_id == PICKED_WINDOW_ID
+}
+
+/// The portal doesn't report where a window is.
+pub fn window_bounds(_window_id: u32) -> Result<super::CaptureRegion, String> {
+    Err("Window positions are not available on Linux yet".into())
+}
+
+/// Window enumeration is not available through the portal.
+pub fn list_windows() -> Result<Vec<super::WindowInfo>, String> {
+    Err("Listing windows is not supported on Linux yet".into())
+}
+
+/// Raising other apps' windows is not possible through the portal.
+pub fn raise_window(_window_id: u32) -> Result<(), String> {
+    Err("Raising windows is not supported on Linux yet".into())
+}
+
+/// Display enumeration is not available through the ScreenCast portal
+/// (the user picks the monitor in the portal dialog).
+pub fn list_displays() -> Result<Vec<super::DisplayInfo>, String> {
+    Err("Listing displays is not supported on Linux yet".into())
+}
+
+/// Estimate the output size of a recording. `size` is the output size in
+/// pixels; `None` assumes 1920×1080 (the portal only reports the real size
+/// once a stream is negotiated). The frame rate is capped by the bounds, as
+/// in `start_recording` (there's no refresh rate or battery saver here).
+pub fn estimate_size(
+    size: Option<(u32, u32)>,
+    settings: &super::EncodingSettings,
+    audio_tracks: usize,
+    duration_secs: f64,
+) -> Result<super::SizeEstimate, String> {
+    gstreamer::init().map_err(|e| format!("GStreamer init: {e}"))?;
+    let (w, h) = size.unwrap_or((1920, 1080));
+    let fps = settings.frame_rate_bounds.max_for(settings.preset_fps());
+    Ok(writer::estimate_size(w, h, settings, fps, audio_tracks, duration_secs))
+}
+
 /// Take a screenshot of a specific screen region.
 pub fn take_screenshot_region(
     x: f64,
@@ -196,8 +544,9 @@ pub fn take_screenshot_region(
     width: f64,
     height: f64,
     output_path: &str,
+    format: super::ImageFormat,
 ) -> Result<(usize, usize, u64), String> {
-    capture::take_screenshot_region(x, y, width, height, output_path)
+    capture::take_screenshot_region(x, y, width, height, output_path, format)
 }
 
 // ── System integration ───────────────────────────────────────────────
@@ -219,8 +568,9 @@ pub fn reveal_file(path: &str) -> Result<(), String> {
 /// Copy a PNG image to the clipboard.
 pub fn copy_image_to_clipboard(path: &str) -> Result<(), String> {
     // Try wl-copy first (Wayland), fall back to xclip (X11)
+    let mime = super::image_mime_type(path);
     let wl_result = std::process::Command::new("wl-copy")
-        .args(["--type", "image/png"])
+        .args(["--type", mime])
         .stdin(std::process::Stdio::piped())
         .spawn();
 
@@ -246,7 +596,7 @@ pub fn copy_image_to_clipboard(path: &str) -> Result<(), String> {
 
     // Fallback: xclip (X11)
     let output = std::process::Command::new("xclip")
-        .args(["-selection", "clipboard", "-target", "image/png", "-i", path])
+        .args(["-selection", "clipboard", "-target", mime, "-i", path])
         .output()
         .map_err(|e| format!("Neither wl-copy nor xclip available: {e}"))?;
 
@@ -259,6 +609,50 @@ pub fn copy_image_to_clipboard(path: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Put plain text on the clipboard.
+pub fn copy_text_to_clipboard(text: &str) -> Result<(), String> {
+    use std::io::Write;
+    // Try wl-copy first (Wayland), fall back to xclip (X11)
+    let candidates: [(&str, &[&str]); 2] = [
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+    ];
+    for (program, args) in candidates {
+        let Ok(mut child) = std::process::Command::new(program)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|e| format!("Failed to write to {program}: {e}"))?;
+        }
+        let status = child.wait().map_err(|e| format!("{program} failed: {e}"))?;
+        if status.success() {
+            return Ok(());
+        }
+    }
+    Err("Neither wl-copy nor xclip could copy text".into())
+}
+
+/// Move a file or folder to the desktop trash (`gio trash`, freedesktop.org spec).
+pub fn move_to_trash(path: &str) -> Result<(), String> {
+    let output = std::process::Command::new("gio")
+        .args(["trash", path])
+        .output()
+        .map_err(|e| format!("gio not available: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to move {path} to trash: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
 /// Show a confirmation dialog using zenity. Returns `true` if user clicked OK.
 pub fn show_confirm_dialog(title: &str, message: &str, accept: &str, cancel: &str) -> bool {
     let result = std::process::Command::new("zenity")
@@ -302,6 +696,37 @@ pub fn show_info_dialog(title: &str, message: &str) {
     }
 }
 
+/// Show a native folder picker (zenity, else kdialog) starting in
+/// `start_dir`. Returns `None` when the user cancels.
+pub fn pick_folder(prompt: &str, start_dir: &str) -> Option<String> {
+    let start = format!("{}/", start_dir.trim_end_matches('/'));
+    let output = std::process::Command::new("zenity")
+        .args(["--file-selection", "--directory", "--title", prompt, "--filename", &start])
+        .output()
+        .or_else(|_| {
+            // zenity not available — try kdialog
+            std::process::Command::new("kdialog")
+                .args(["--title", prompt, "--getexistingdirectory", start_dir])
+                .output()
+        })
+        .ok()?;
+    // Non-zero exit when the user cancels
+    if !output.status.success() {
+        return None;
+    }
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!path.is_empty()).then_some(path)
+}
+
+/// Open a file in its default application.
+pub fn open_file(path: &str) -> Result<(), String> {
+    std::process::Command::new("xdg-open")
+        .arg(path)
+        .spawn()
+        .map_err(|e| format!("Failed to open file: {e}"))?;
+    Ok(())
+}
+
 /// Open a folder in the default file manager.
 pub fn open_folder(path: &str) -> Result<(), String> {
     std::process::Command::new("xdg-open")
@@ -311,6 +736,98 @@ pub fn open_folder(path: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Microphone access isn't gated by a permission on Linux (PulseAudio /
+/// PipeWire sources are open to the session).
+pub fn microphone_access() -> bool {
+    true
+}
+
+/// The camera bubble asks through the webview itself on Linux.
+pub fn camera_access() -> bool {
+    true
+}
+
+// ── Microphones ──────────────────────────────────────────────────────
+
+/// PulseAudio / PipeWire input sources, without the sink monitors (those
+/// are system audio).
+pub fn list_microphones() -> Result<Vec<super::MicrophoneInfo>, String> {
+    // The field labels ("Name:", "Description:") are translated in other
+    // locales; the C locale keeps them parseable
+    let output = std::process::Command::new("pactl")
+        .env("LC_ALL", "C")
+        .args(["list", "sources"])
+        .output()
+        .map_err(|e| format!("pactl not found: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "pactl list sources failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let default = std::process::Command::new("pactl")
+        .arg("get-default-source")
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+
+    // Blocks of "Source #N" with indented "Name:" / "Description:" lines
+    let mut sources: Vec<(String, String)> = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("Name: ") {
+            sources.push((name.to_string(), name.to_string()));
+        } else if let Some(desc) = line.strip_prefix("Description: ") {
+            if let Some(last) = sources.last_mut() {
+                last.1 = desc.to_string();
+            }
+        }
+    }
+    Ok(sources
+        .into_iter()
+        .filter(|(name, _)| !name.ends_with(".monitor"))
+        .map(|(id, name)| super::MicrophoneInfo {
+            is_default: default.as_deref() == Some(id.as_str()),
+            id,
+            name,
+        })
+        .collect())
+}
+
+// ── UI sound effects ─────────────────────────────────────────────────
+
+/// GNOME event sounds (alerts, input feedback); honored by
+/// libcanberra-based desktops.
+const EVENT_SOUNDS_SCHEMA: &str = "org.gnome.desktop.sound";
+const EVENT_SOUNDS_KEY: &str = "event-sounds";
+
+/// Whether the desktop plays event sounds. `None` without gsettings/the schema.
+pub fn ui_sounds_enabled() -> Option<bool> {
+    let output = std::process::Command::new("gsettings")
+        .args(["get", EVENT_SOUNDS_SCHEMA, EVENT_SOUNDS_KEY])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim() == "true")
+}
+
+/// Turn desktop event sounds on or off.
+pub fn set_ui_sounds_enabled(enabled: bool) -> Result<(), String> {
+    let output = std::process::Command::new("gsettings")
+        .args(["set", EVENT_SOUNDS_SCHEMA, EVENT_SOUNDS_KEY, if enabled { "true" } else { "false" }])
+        .output()
+        .map_err(|e| format!("Failed to run gsettings: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to change event sounds: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
 // ── Autostart (Launch at Login) ──────────────────────────────────────
 
 /// Path to the autostart .desktop file.
@@ -17,6 +17,25 @@ use std::os::fd::OwnedFd;
 use ashpd::desktop::screencast::{CursorMode, Screencast, SourceType};
 use ashpd::desktop::PersistMode;
 
+/// What the portal dialog offers to share.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureTarget {
+    /// A whole monitor
+    Monitor,
+    /// A single window. The stream is the window's content at its own
+    /// size, so a region crop applies relative to the window.
+    Window,
+}
+
+impl CaptureTarget {
+    fn source_type(self) -> SourceType {
+        match self {
+            CaptureTarget::Monitor => SourceType::Monitor,
+            CaptureTarget::Window => SourceType::Window,
+        }
+    }
+}
+
 /// Result of a successful ScreenCast portal session.
 ///
 /// Owns the PipeWire fd, the ashpd D-Bus session (via a boxed trait object),
@@ -67,11 +86,24 @@ impl ScreencastSession {
 /// `restore_token`: Optional token from a previous session. If valid, the
 /// portal may skip the permission dialog and reuse the previous selection.
 ///
+/// `show_cursor`: embed the cursor in the stream, or ask the compositor to
+/// leave it out.
+///
+/// `target`: offer monitors or windows in the dialog. Fails up front if the
+/// desktop's portal can't share windows.
+///
 /// Returns a `ScreencastSession` that **must be kept alive** for the
 /// duration of recording (it owns the PipeWire fd and D-Bus session).
-pub fn request_screencast(restore_token: Option<&str>) -> Result<ScreencastSession, String> {
+pub fn request_screencast(
+    restore_token: Option<&str>,
+    show_cursor: bool,
+    target: CaptureTarget,
+) -> Result<ScreencastSession, String> {
     println!("[zureshot-linux] Requesting screen capture via XDG Portal (ashpd)...");
-    println!("[zureshot-linux] (A system dialog may appear — select a monitor and click Share)");
+    println!(
+        "[zureshot-linux] (A system dialog may appear — select a {} and click Share)",
+        if target == CaptureTarget::Window { "window" } else { "monitor" }
+    );
 
     // Create a dedicated single-threaded tokio runtime for D-Bus.
     // This runtime stays alive in the ScreencastSession to keep the
@@ -85,7 +117,7 @@ pub fn request_screencast(restore_token: Option<&str>) -> Result<ScreencastSessi
 
     // Run the async portal interaction on the runtime.
     // The async fn returns only plain data (no Session lifetime).
-    let result = runtime.block_on(request_screencast_async(restore.as_deref()));
+    let result = runtime.block_on(request_screencast_async(restore.as_deref(), show_cursor, target));
 
     match result {
         Ok((node_id, fd, session_handle, restore_token, width, height)) => {
@@ -111,7 +143,7 @@ pub fn request_screencast(restore_token: Option<&str>) -> Result<ScreencastSessi
 /// Async implementation of the portal interaction.
 ///
 /// Portal flow:
-///   CreateSession → SelectSources (user picks monitor) → Start → OpenPipeWireRemote
+///   CreateSession → SelectSources (user picks monitor/window) → Start → OpenPipeWireRemote
 ///
 /// The `Session` object stays alive within this async scope (keeping the
 /// D-Bus session open). We extract the PipeWire fd + node_id before
@@ -123,6 +155,8 @@ pub fn request_screencast(restore_token: Option<&str>) -> Result<ScreencastSessi
 /// session alive by holding the tokio runtime in ScreencastSession.
 async fn request_screencast_async(
     restore_token: Option<&str>,
+    show_cursor: bool,
+    target: CaptureTarget,
 ) -> Result<
     (
         u32,              // node_id
@@ -139,6 +173,17 @@ async fn request_screencast_async(
         .await
         .map_err(|e| format!("Portal connection failed: {e}. Is xdg-desktop-portal running?"))?;
 
+    // Some portal backends (e.g. older wlroots ones) share monitors only
+    if target == CaptureTarget::Window {
+        let available = proxy
+            .available_source_types()
+            .await
+            .map_err(|e| format!("Portal source types query failed: {e}"))?;
+        if !available.contains(SourceType::Window) {
+            return Err("This desktop's screen-cast portal can't share single windows — record the monitor instead".into());
+        }
+    }
+
     // Step 1: Create a session
     let session = proxy
         .create_session()
@@ -146,11 +191,16 @@ async fn request_screencast_async(
         .map_err(|e| format!("CreateSession failed: {e}"))?;
 
     // Step 2: Configure what to capture
+    let cursor_mode = if show_cursor {
+        CursorMode::Embedded // Cursor baked into stream
+    } else {
+        CursorMode::Hidden
+    };
     proxy
         .select_sources(
             &session,
-            CursorMode::Embedded,        // Cursor baked into stream
-            SourceType::Monitor.into(),  // Capture full monitor
+            cursor_mode,
+            target.source_type().into(), // Monitor or window, per target
             false,                       // Single source only
             restore_token,               // Reuse previous selection
             PersistMode::ExplicitlyRevoked, // Remember until user revokes
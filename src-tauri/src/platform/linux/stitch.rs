@@ -0,0 +1,136 @@
+//! Scrolling long screenshot on Linux — capture a screen region repeatedly
+//! as the user scrolls and stitch the frames into one tall image.
+//!
+//! Same flow as the macOS `scroll_capture` module:
+//!   1. `ScrollCaptureSession::new(x,y,w,h)` — capture first frame
+//!   2. `session.capture_frame()` — called on every frontend tick, returns true if new content
+//!   3. `session.finish(path)` — write stitched PNG, return dimensions + file size
+//!
+//! Each frame is a regular region screenshot (`capture::take_screenshot_region`:
+//! portal, grim, …) decoded to RGBA and matched against the stitched image
+//! with `platform::overlap`.
+
+use super::capture;
+use crate::platform::overlap::find_overlap;
+use crate::platform::ImageFormat;
+
+struct Frame {
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+fn capture_region_pixels(x: f64, y: f64, w: f64, h: f64) -> Result<Frame, String> {
+    let path = std::env::temp_dir().join(format!(".zureshot_scroll_{}.png", std::process::id()));
+    let path_str = path.to_string_lossy().to_string();
+    capture::take_screenshot_region(x, y, w, h, &path_str, ImageFormat::Png)?;
+    let img = image::open(&path).map_err(|e| format!("Failed to read scroll frame: {e}"));
+    let _ = std::fs::remove_file(&path);
+    let img = img?.to_rgba8();
+    Ok(Frame {
+        width: img.width() as usize,
+        height: img.height() as usize,
+        pixels: img.into_raw(),
+    })
+}
+
+// ── Public session API ───────────────────────────────────────────────
+
+pub struct ScrollCaptureSession {
+    region: (f64, f64, f64, f64),
+    accumulated: Vec<u8>,
+    acc_width: usize,
+    acc_height: usize,
+    frame_count: usize,
+}
+
+impl ScrollCaptureSession {
+    /// Start a new session by capturing the first frame of the given region.
+    pub fn new(x: f64, y: f64, w: f64, h: f64) -> Result<Self, String> {
+        let frame = capture_region_pixels(x, y, w, h)?;
+        println!("[zureshot-linux] Scroll capture first frame: {}x{}", frame.width, frame.height);
+        Ok(Self {
+            region: (x, y, w, h),
+            acc_width: frame.width,
+            acc_height: frame.height,
+            accumulated: frame.pixels,
+            frame_count: 1,
+        })
+    }
+
+    /// Capture the current screen region and stitch if new content is detected.
+    /// Returns `true` if new rows were appended.
+    pub fn capture_frame(&mut self) -> Result<bool, String> {
+        let (x, y, w, h) = self.region;
+        let frame = capture_region_pixels(x, y, w, h)?;
+        if frame.width != self.acc_width {
+            return Err(format!(
+                "Frame width changed: expected {}, got {}",
+                self.acc_width, frame.width
+            ));
+        }
+
+        let row_bytes = self.acc_width * 4;
+        let Some(overlap) = find_overlap(
+            &self.accumulated,
+            self.acc_height,
+            row_bytes,
+            &frame.pixels,
+            frame.height,
+            row_bytes,
+            row_bytes,
+        ) else {
+            // Scrolled too far (or the content changed): appending the
+            // whole frame would leave a gap or a duplicate, so wait for
+            // one that continues the image
+            println!("[zureshot-linux] Scroll frame skipped: no overlap with the stitched image");
+            return Ok(false);
+        };
+
+        // If overlap covers (almost) the entire new frame, nothing changed
+        if overlap >= frame.height.saturating_sub(2) {
+            return Ok(false);
+        }
+
+        let new_rows = frame.height - overlap;
+        self.accumulated.extend_from_slice(&frame.pixels[overlap * row_bytes..]);
+        self.acc_height += new_rows;
+        self.frame_count += 1;
+
+        println!(
+            "[zureshot-linux] Scroll frame #{}: overlap={}px, added={}px, total={}px",
+            self.frame_count, overlap, new_rows, self.acc_height
+        );
+        Ok(true)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    pub fn total_height(&self) -> usize {
+        self.acc_height
+    }
+
+    pub fn width(&self) -> usize {
+        self.acc_width
+    }
+
+    /// Finalize the session: write the stitched image as PNG.
+    pub fn finish(self, output_path: &str) -> Result<(usize, usize, u64), String> {
+        println!(
+            "[zureshot-linux] Saving scroll capture {}x{} ({} frames) → {}",
+            self.acc_width, self.acc_height, self.frame_count, output_path
+        );
+        image::save_buffer(
+            output_path,
+            &self.accumulated,
+            self.acc_width as u32,
+            self.acc_height as u32,
+            image::ColorType::Rgba8,
+        )
+        .map_err(|e| format!("Failed to write stitched PNG: {e}"))?;
+        let file_size = std::fs::metadata(output_path).map(|m| m.len()).unwrap_or(0);
+        Ok((self.acc_width, self.acc_height, file_size))
+    }
+}
@@ -10,16 +10,19 @@
 //!
 //! Pipeline topology:
 //!   pipewiresrc → videoconvert → [videocrop] → videorate → capsfilter
-//!     → encoder → parser → mp4mux → filesink
+//!     → encoder → parser → [capsfilter hvc1] → mp4mux → filesink
 //!   [pulsesrc → audioconvert → audioresample → capsfilter
 //!     → avenc_aac → aacparse → mp4mux]
+//!
+//! Streaming mode swaps `mp4mux → filesink` for `flvmux → rtmpsink` (RTMP)
+//! or `mpegtsmux → srtsink` (SRT); encoder and audio branches are shared.
 
 use std::path::{Path, PathBuf};
 
 use gstreamer as gst;
 use gst::prelude::*;
 
-use crate::platform::RecordingQuality;
+use crate::platform::{FrameStats, RecordingQuality, Rotation, StreamProtocol, StreamTarget, VideoCodec};
 
 /// Detected encoder information.
 #[derive(Debug, Clone)]
@@ -41,10 +44,12 @@ pub struct EncoderInfo {
 pub struct GstPipeline {
     /// The GStreamer pipeline instance.
     pipeline: gst::Pipeline,
-    /// Output file path.
+    /// Output file path (redacted URL when streaming).
     output_path: PathBuf,
     /// Encoder info (for logging).
     encoder_info: EncoderInfo,
+    /// Video bitrate the encoder was configured with.
+    bitrate_kbps: i32,
 }
 
 impl GstPipeline {
@@ -58,6 +63,11 @@ impl GstPipeline {
         &self.encoder_info
     }
 
+    /// Video bitrate in use (after any H.264 fallback).
+    pub fn bitrate_kbps(&self) -> i32 {
+        self.bitrate_kbps
+    }
+
     /// Pause the pipeline (instant, no segment files).
     ///
     /// GStreamer handles buffering internally. When resumed, recording
@@ -81,11 +91,55 @@ impl GstPipeline {
         Ok(())
     }
 
+    /// Frames out of `videorate` so far. Only valid until the pipeline
+    /// goes to Null: videorate resets its counters when it stops.
+    ///
+    /// `dropped` and `pts_skips` are always 0. videorate's own `drop` and
+    /// `dup` counters are rate conversion, not lost frames — it drops the
+    /// frames above the target rate and repeats the last one while the
+    /// compositor sends none (a still screen) — and it smooths over PTS
+    /// regressions.
+    pub fn frame_stats(&self) -> FrameStats {
+        let Some(rate) = self.pipeline.by_name("rate") else {
+            return FrameStats::default();
+        };
+        FrameStats {
+            frames: rate.property::<u64>("out"),
+            dropped: 0,
+            pts_skips: 0,
+        }
+    }
+
+    /// Ask the encoder to make the next frame a keyframe.
+    ///
+    /// Sends an upstream `GstForceKeyUnit` event from the parser (the
+    /// element right after the encoder), which every encoder we use honors.
+    pub fn request_keyframe(&self) -> Result<(), String> {
+        let parser = self
+            .pipeline
+            .by_name("vparse")
+            .ok_or("Video parser not found in pipeline")?;
+        let event = gst::event::CustomUpstream::builder(
+            gst::Structure::builder("GstForceKeyUnit")
+                .field("all-headers", true)
+                .field("count", 0u32)
+                .build(),
+        )
+        .build();
+        if !parser.send_event(event) {
+            return Err("Encoder rejected the force-keyframe request".into());
+        }
+        println!("[zureshot-linux] Keyframe requested");
+        Ok(())
+    }
+
     /// Stop the pipeline gracefully via EOS.
     ///
     /// Sends an EOS event through the pipeline, which flushes the muxer
     /// and writes a valid MP4 file. Then transitions to Null state.
-    pub fn stop(&self) -> Result<(), String> {
+    /// Returns the final frame counters, read once EOS has drained the
+    /// pipeline and before Null resets them.
+    pub fn stop(&self) -> Result<FrameStats, String> {
         println!("[zureshot-linux] Stopping GStreamer pipeline (sending EOS)...");
 
         // Send EOS event
@@ -120,6 +174,8 @@ impl GstPipeline {
             }
         }
 
+        let stats = self.frame_stats();
+
         // Transition to Null state
         self.pipeline
             .set_state(gst::State::Null)
@@ -129,7 +185,7 @@ impl GstPipeline {
             "[zureshot-linux] Pipeline stopped. Output: {}",
             self.output_path.display()
         );
-        Ok(())
+        Ok(stats)
     }
 }
 
@@ -152,6 +208,12 @@ pub struct PipelineConfig {
     pub fps: i32,
     /// Target bitrate in kbps.
     pub bitrate_kbps: i32,
+    /// `bitrate_kbps` is a user override: keep it when falling back to
+    /// H.264 instead of scaling it up.
+    pub fixed_bitrate: bool,
+    /// `StartRecordingConfig::quality_factor`, approximated per encoder
+    /// (see `build_encoder`). `None` = bitrate-targeted as usual.
+    pub quality_factor: Option<f64>,
     /// Source stream dimensions (from portal).
     pub source_width: Option<u32>,
     pub source_height: Option<u32>,
@@ -161,8 +223,29 @@ pub struct PipelineConfig {
     pub capture_system_audio: bool,
     /// Capture microphone input.
     pub capture_mic: bool,
+    /// Strict playback compatibility: if the HEVC stream negotiated by the
+    /// hardware encoder isn't Safari/QuickTime-playable, restart with H.264.
+    pub compatibility_mode: bool,
+    /// Duplicate frames so every frame has the same duration, even when
+    /// PipeWire only delivers frames on screen change.
+    pub force_cfr: bool,
+    /// Live stream instead of a file (`output_path` is ignored).
+    pub stream: Option<StreamTarget>,
+    /// Longest gap between keyframes (seconds).
+    pub keyframe_interval_secs: f64,
+    /// Rotate the pixels after cropping (`videoflip`).
+    pub rotation: Rotation,
+    /// `H264` skips the HEVC encoders even when one is available.
+    pub codec: VideoCodec,
+    /// `mp4mux` fragment duration (seconds). `None` = 1s.
+    pub fragment_interval_secs: Option<f64>,
+    /// PulseAudio source for the microphone. `None` = the default source.
+    pub mic_device: Option<String>,
 }
 
+/// HEVC needs ~65% of the H.264 bitrate for the same visual quality.
+const HEVC_BITRATE_FACTOR: f64 = 0.65;
+
 /// Detect the best available video encoder.
 ///
 /// Priority order:
@@ -222,13 +305,198 @@ pub fn detect_best_encoder() -> EncoderInfo {
     candidates.last().unwrap().clone()
 }
 
+/// Best available H.264 encoder (universally playable fallback).
+fn detect_best_h264_encoder() -> EncoderInfo {
+    let candidates: &[EncoderInfo] = &[
+        EncoderInfo {
+            name: "vaapih264enc",
+            is_hevc: false,
+            is_hardware: true,
+            description: "VA-API H.264 (Intel/AMD GPU)",
+        },
+        EncoderInfo {
+            name: "nvh264enc",
+            is_hevc: false,
+            is_hardware: true,
+            description: "NVENC H.264 (NVIDIA GPU)",
+        },
+        EncoderInfo {
+            name: "x264enc",
+            is_hevc: false,
+            is_hardware: false,
+            description: "x264 H.264 (CPU software)",
+        },
+    ];
+    candidates
+        .iter()
+        .find(|info| gst::ElementFactory::find(info.name).is_some())
+        .unwrap_or(candidates.last().unwrap())
+        .clone()
+}
+
 /// Build and start an in-process GStreamer recording pipeline.
 ///
+/// With an HEVC encoder, the negotiated stream is checked for QuickTime/
+/// Safari compatibility (hvc1, Main/Main10 profile, Main tier). A mismatch
+/// is logged; in `compatibility_mode` the pipeline is rebuilt with H.264.
+///
 /// Returns a `GstPipeline` handle for pause/resume/stop control.
 pub fn start_pipeline(config: &PipelineConfig) -> Result<GstPipeline, String> {
     // Initialize GStreamer (safe to call multiple times)
     gst::init().map_err(|e| format!("GStreamer init failed: {e}"))?;
 
+    let encoder_info = detect_best_encoder();
+    if rtmp_target(config) && encoder_info.is_hevc {
+        // FLV carries H.264 only
+        let h264 = detect_best_h264_encoder();
+        println!("[zureshot-linux] RTMP streaming: using {} instead of {}", h264.name, encoder_info.name);
+        return build_pipeline(config, h264, h264_bitrate_kbps(config));
+    }
+    if config.codec == VideoCodec::H264 && encoder_info.is_hevc {
+        let h264 = detect_best_h264_encoder();
+        println!("[zureshot-linux] H.264 requested: using {} instead of {}", h264.name, encoder_info.name);
+        return build_pipeline(config, h264, h264_bitrate_kbps(config));
+    }
+    let pipeline = build_pipeline(config, encoder_info.clone(), config.bitrate_kbps)?;
+    if !encoder_info.is_hevc || config.stream.is_some() {
+        return Ok(pipeline);
+    }
+
+    let Some(problem) = check_hevc_compatibility(&pipeline) else {
+        return Ok(pipeline);
+    };
+    println!(
+        "[zureshot-linux] Warning: {} produced an HEVC stream Safari/QuickTime may not play: {}",
+        encoder_info.name, problem
+    );
+    if !config.compatibility_mode {
+        return Ok(pipeline);
+    }
+
+    println!("[zureshot-linux] Compatibility mode: restarting with H.264");
+    drop(pipeline); // → Null state, releases the PipeWire stream
+    let _ = std::fs::remove_file(&config.output_path);
+    let h264 = detect_best_h264_encoder();
+    build_pipeline(config, h264, h264_bitrate_kbps(config))
+}
+
+/// Reported as the "encoder" of audio-only pipelines.
+const AAC_ONLY: EncoderInfo = EncoderInfo {
+    name: "avenc_aac",
+    is_hevc: false,
+    is_hardware: false,
+    description: "AAC (audio only)",
+};
+
+/// Start an audio-only pipeline: the audio branches into `mp4mux` →
+/// `.m4a`, with no PipeWire video source (and so no portal session).
+pub fn start_audio_pipeline(
+    output_path: &str,
+    capture_system_audio: bool,
+    capture_mic: bool,
+    mic_device: Option<&str>,
+    fragment_interval_secs: Option<f64>,
+) -> Result<GstPipeline, String> {
+    gst::init().map_err(|e| format!("GStreamer init failed: {e}"))?;
+    if !capture_system_audio && !capture_mic {
+        return Err("Audio-only recording needs system audio or the microphone".into());
+    }
+
+    let pipeline = gst::Pipeline::default();
+    let mux = gst::ElementFactory::make("mp4mux")
+        .name("mux")
+        .property(
+            "fragment-duration",
+            fragment_interval_secs.map_or(1000, |s| (s * 1000.0).round() as u32),
+        )
+        .build()
+        .map_err(|e| format!("mp4mux: {e}"))?;
+    let sink = gst::ElementFactory::make("filesink")
+        .property("location", output_path)
+        .build()
+        .map_err(|e| format!("filesink: {e}"))?;
+    pipeline
+        .add_many([&mux, &sink])
+        .map_err(|e| format!("Failed to add mux/sink: {e}"))?;
+    mux.link(&sink)
+        .map_err(|e| format!("Failed to link mux→sink: {e}"))?;
+
+    if capture_system_audio {
+        add_audio_branch(&pipeline, &mux, true, None, Some("audio_0"))?;
+    }
+    if capture_mic {
+        let pad_name = if capture_system_audio { "audio_1" } else { "audio_0" };
+        add_audio_branch(&pipeline, &mux, false, mic_device, Some(pad_name))?;
+    }
+
+    pipeline
+        .set_state(gst::State::Playing)
+        .map_err(|e| format!("Failed to start pipeline: {e:?}"))?;
+    println!("[zureshot-linux] Audio-only pipeline started: {}", output_path);
+
+    Ok(GstPipeline {
+        pipeline,
+        output_path: PathBuf::from(output_path),
+        encoder_info: AAC_ONLY,
+        bitrate_kbps: 0,
+    })
+}
+
+/// Bitrate for an H.264 fallback of an HEVC-sized `config.bitrate_kbps`.
+fn h264_bitrate_kbps(config: &PipelineConfig) -> i32 {
+    if config.fixed_bitrate {
+        config.bitrate_kbps
+    } else {
+        (config.bitrate_kbps as f64 / HEVC_BITRATE_FACTOR) as i32
+    }
+}
+
+/// Wait (up to 3s) for the HEVC output caps and check them against what
+/// Apple players accept. Returns a description of the problem, if any.
+fn check_hevc_compatibility(pipeline: &GstPipeline) -> Option<String> {
+    let pad = pipeline
+        .pipeline
+        .by_name("hevccaps")
+        .and_then(|e| e.static_pad("src"))?;
+
+    let mut caps = None;
+    for _ in 0..30 {
+        caps = pad.current_caps();
+        if caps.is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let Some(caps) = caps else {
+        println!("[zureshot-linux] HEVC caps not negotiated within 3s; compatibility not verified");
+        return None;
+    };
+    let s = caps.structure(0)?;
+    println!("[zureshot-linux] Negotiated HEVC caps: {s}");
+
+    let field = |name: &str| s.get::<String>(name).ok();
+    let mut problems = Vec::new();
+    if field("stream-format").as_deref() != Some("hvc1") {
+        problems.push(format!("stream-format={:?} (need hvc1)", field("stream-format")));
+    }
+    if let Some(profile) = field("profile") {
+        if profile != "main" && profile != "main-10" {
+            problems.push(format!("profile={profile} (need main or main-10)"));
+        }
+    }
+    if let Some(tier) = field("tier") {
+        if tier != "main" {
+            problems.push(format!("tier={tier} (need main)"));
+        }
+    }
+    (!problems.is_empty()).then(|| problems.join(", "))
+}
+
+fn build_pipeline(
+    config: &PipelineConfig,
+    encoder_info: EncoderInfo,
+    bitrate_kbps: i32,
+) -> Result<GstPipeline, String> {
     let pipeline = gst::Pipeline::default();
 
     // ── Video source: PipeWire ──
@@ -277,10 +545,32 @@ pub fn start_pipeline(config: &PipelineConfig) -> Result<GstPipeline, String> {
         None
     };
 
+    // ── Rotation (optional): MP4 rotation tags are ignored by too many
+    // players, so the frames themselves are turned ──
+    let flip = match config.rotation {
+        Rotation::None => None,
+        rotation => {
+            let direction = match rotation {
+                Rotation::Cw90 => "90r",
+                Rotation::Ccw90 => "90l",
+                _ => "180",
+            };
+            let elem = gst::ElementFactory::make("videoflip")
+                .property_from_str("video-direction", direction)
+                .build()
+                .map_err(|e| format!("videoflip: {e}"))?;
+            Some(elem)
+        }
+    };
+
     // ── Frame rate control ──
-    let rate = gst::ElementFactory::make("videorate")
-        .build()
-        .map_err(|e| format!("videorate: {e}"))?;
+    // Duplicates frames to fill gaps between screen updates unless told
+    // otherwise; force_cfr pins that explicitly.
+    let mut rate = gst::ElementFactory::make("videorate").name("rate");
+    if config.force_cfr {
+        rate = rate.property("drop-only", false);
+    }
+    let rate = rate.build().map_err(|e| format!("videorate: {e}"))?;
 
     let caps_filter = gst::ElementFactory::make("capsfilter")
         .property(
@@ -292,9 +582,8 @@ pub fn start_pipeline(config: &PipelineConfig) -> Result<GstPipeline, String> {
         .build()
         .map_err(|e| format!("capsfilter: {e}"))?;
 
-    // ── Video encoder (auto-detect best available) ──
-    let encoder_info = detect_best_encoder();
-    let encoder = build_encoder(&encoder_info, config)?;
+    // ── Video encoder ──
+    let encoder = build_encoder(&encoder_info, config, bitrate_kbps)?;
 
     // ── Parser (H.264 or H.265) ──
     let parser_name = if encoder_info.is_hevc {
@@ -303,21 +592,51 @@ pub fn start_pipeline(config: &PipelineConfig) -> Result<GstPipeline, String> {
         "h264parse"
     };
     let parser = gst::ElementFactory::make(parser_name)
+        .name("vparse")
         .build()
         .map_err(|e| format!("{parser_name}: {e}"))?;
 
-    // ── MP4 Muxer ──
-    let mux = gst::ElementFactory::make("mp4mux")
-        .name("mux")
-        .property("fragment-duration", 1000u32)
-        .build()
-        .map_err(|e| format!("mp4mux: {e}"))?;
+    // ── HEVC: force hvc1 (parameter sets in the sample entry). QuickTime
+    // and Safari refuse hev1, which h265parse may otherwise pick. MPEG-TS
+    // streams need byte-stream, so leave those to negotiation. ──
+    let hevc_caps = if encoder_info.is_hevc && config.stream.is_none() {
+        let elem = gst::ElementFactory::make("capsfilter")
+            .name("hevccaps")
+            .property(
+                "caps",
+                gst::Caps::builder("video/x-h265")
+                    .field("stream-format", "hvc1")
+                    .field("alignment", "au")
+                    .build(),
+            )
+            .build()
+            .map_err(|e| format!("capsfilter (hvc1): {e}"))?;
+        Some(elem)
+    } else {
+        None
+    };
+    // Last element of the video chain, linked to the muxer
+    let video_out = hevc_caps.as_ref().unwrap_or(&parser);
 
-    // ── File sink ──
-    let sink = gst::ElementFactory::make("filesink")
-        .property("location", &config.output_path)
-        .build()
-        .map_err(|e| format!("filesink: {e}"))?;
+    // ── Muxer + sink: MP4 file, or a live stream ──
+    let (mux, sink) = match config.stream {
+        Some(ref target) => build_stream_sink(target)?,
+        None => {
+            let mux = gst::ElementFactory::make("mp4mux")
+                .name("mux")
+                .property(
+                    "fragment-duration",
+                    config.fragment_interval_secs.map_or(1000, |s| (s * 1000.0).round() as u32),
+                )
+                .build()
+                .map_err(|e| format!("mp4mux: {e}"))?;
+            let sink = gst::ElementFactory::make("filesink")
+                .property("location", &config.output_path)
+                .build()
+                .map_err(|e| format!("filesink: {e}"))?;
+            (mux, sink)
+        }
+    };
 
     // ── Assemble video branch ──
     // Collect all video elements in order
@@ -325,7 +644,13 @@ pub fn start_pipeline(config: &PipelineConfig) -> Result<GstPipeline, String> {
     if let Some(ref c) = crop {
         video_elems.push(c);
     }
+    if let Some(ref f) = flip {
+        video_elems.push(f);
+    }
     video_elems.extend_from_slice(&[&rate, &caps_filter, &encoder, &parser]);
+    if let Some(ref c) = hevc_caps {
+        video_elems.push(c);
+    }
 
     // Add all video elements to pipeline
     for elem in &video_elems {
@@ -341,14 +666,15 @@ pub fn start_pipeline(config: &PipelineConfig) -> Result<GstPipeline, String> {
     gst::Element::link_many(video_elems.as_slice())
         .map_err(|e| format!("Failed to link video chain: {e}"))?;
 
-    // Link parser → mux (video pad)
+    // Link parser (or hvc1 capsfilter) → mux (video pad). mp4mux needs the
+    // pad named once audio pads exist; flvmux/mpegtsmux pick by caps.
     let has_audio = config.capture_system_audio || config.capture_mic;
-    if has_audio {
-        parser
+    if has_audio && config.stream.is_none() {
+        video_out
             .link_pads(Some("src"), &mux, Some("video_%u"))
             .map_err(|e| format!("Failed to link parser→mux: {e}"))?;
     } else {
-        parser
+        video_out
             .link(&mux)
             .map_err(|e| format!("Failed to link parser→mux: {e}"))?;
     }
@@ -358,16 +684,29 @@ pub fn start_pipeline(config: &PipelineConfig) -> Result<GstPipeline, String> {
         .map_err(|e| format!("Failed to link mux→sink: {e}"))?;
 
     // ── Audio branches (optional) ──
+    let streaming = config.stream.is_some();
+    let mut capture_mic = config.capture_mic;
+    if rtmp_target(config) && config.capture_system_audio && capture_mic {
+        // FLV has a single audio track
+        println!("[zureshot-linux] RTMP streams carry one audio track; dropping the microphone");
+        capture_mic = false;
+    }
     if config.capture_system_audio {
-        add_audio_branch(&pipeline, &mux, true, "audio_0")?;
+        add_audio_branch(&pipeline, &mux, true, None, (!streaming).then_some("audio_0"))?;
     }
-    if config.capture_mic {
+    if capture_mic {
         let pad_name = if config.capture_system_audio {
             "audio_1"
         } else {
             "audio_0"
         };
-        add_audio_branch(&pipeline, &mux, false, pad_name)?;
+        add_audio_branch(
+            &pipeline,
+            &mux,
+            false,
+            config.mic_device.as_deref(),
+            (!streaming).then_some(pad_name),
+        )?;
     }
 
     // ── Start playing ──
@@ -375,78 +714,165 @@ pub fn start_pipeline(config: &PipelineConfig) -> Result<GstPipeline, String> {
         .set_state(gst::State::Playing)
         .map_err(|e| format!("Failed to start pipeline: {e:?}"))?;
 
+    let destination = config
+        .stream
+        .as_ref()
+        .map(|t| t.redacted())
+        .unwrap_or_else(|| config.output_path.clone());
     println!(
         "[zureshot-linux] Pipeline started: {} @ {}fps {}kbps, encoder={}",
-        config.output_path, config.fps, config.bitrate_kbps, encoder_info.name
+        destination, config.fps, bitrate_kbps, encoder_info.name
     );
 
     Ok(GstPipeline {
         pipeline,
-        output_path: PathBuf::from(&config.output_path),
+        output_path: PathBuf::from(&destination),
         encoder_info,
+        bitrate_kbps,
     })
 }
 
+fn rtmp_target(config: &PipelineConfig) -> bool {
+    matches!(config.stream, Some(ref t) if t.protocol == StreamProtocol::Rtmp)
+}
+
+/// Build the muxer and network sink for a live stream.
+fn build_stream_sink(target: &StreamTarget) -> Result<(gst::Element, gst::Element), String> {
+    match target.protocol {
+        StreamProtocol::Rtmp => {
+            let mux = gst::ElementFactory::make("flvmux")
+                .name("mux")
+                .property("streamable", true)
+                .build()
+                .map_err(|e| format!("flvmux: {e}"))?;
+            // librtmp-based rtmpsink, or the newer rtmp2sink (also RTMPS)
+            let sink_name = ["rtmpsink", "rtmp2sink"]
+                .into_iter()
+                .find(|name| gst::ElementFactory::find(name).is_some())
+                .ok_or("rtmpsink not found. Install: gstreamer1.0-plugins-bad")?;
+            let sink = gst::ElementFactory::make(sink_name)
+                .property("location", &target.url)
+                .build()
+                .map_err(|e| format!("{sink_name}: {e}"))?;
+            Ok((mux, sink))
+        }
+        StreamProtocol::Srt => {
+            let mux = gst::ElementFactory::make("mpegtsmux")
+                .name("mux")
+                .build()
+                .map_err(|e| format!("mpegtsmux: {e}"))?;
+            let sink = gst::ElementFactory::make("srtsink")
+                .property("uri", &target.url)
+                .build()
+                .map_err(|e| format!("srtsink: {e}. Install: gstreamer1.0-plugins-bad"))?;
+            Ok((mux, sink))
+        }
+    }
+}
+
 /// Build the video encoder element with appropriate properties.
-fn build_encoder(info: &EncoderInfo, config: &PipelineConfig) -> Result<gst::Element, String> {
+fn build_encoder(
+    info: &EncoderInfo,
+    config: &PipelineConfig,
+    bitrate_kbps: i32,
+) -> Result<gst::Element, String> {
     let mut builder = gst::ElementFactory::make(info.name);
+    // Keyframe interval in frames
+    let gop = (config.fps as f64 * config.keyframe_interval_secs).round().max(1.0) as u32;
 
     match info.name {
         "x264enc" => {
             builder = builder
                 .property_from_str("speed-preset", "ultrafast")
                 .property_from_str("tune", "zerolatency")
-                .property("bitrate", config.bitrate_kbps as u32)
-                .property("key-int-max", (config.fps * 2) as u32);
+                .property("bitrate", bitrate_kbps as u32)
+                .property("key-int-max", gop);
+            // Constant quality instead of the bitrate target
+            if let Some(q) = config.quality_factor {
+                let crf = quality_factor_to_crf(q);
+                println!("[zureshot-linux] Quality factor {:.2} → x264 CRF {}", q, crf);
+                builder = builder.property_from_str("pass", "qual").property("quantizer", crf);
+            }
         }
         "vaapih264enc" | "vaapih265enc" => {
-            builder = builder.property("bitrate", config.bitrate_kbps as u32);
+            builder = builder
+                .property("bitrate", bitrate_kbps as u32)
+                .property("keyframe-period", gop);
+            // Encoder effort, not a quality target: the bitrate still applies
+            if let Some(q) = config.quality_factor {
+                let level = quality_factor_to_vaapi_level(q);
+                println!("[zureshot-linux] Quality factor {:.2} → VA-API quality-level {}", q, level);
+                builder = builder.property("quality-level", level);
+            }
         }
         "nvh264enc" | "nvh265enc" => {
-            builder = builder.property("bitrate", config.bitrate_kbps as u32);
+            builder = builder
+                .property("bitrate", bitrate_kbps as u32)
+                .property("gop-size", gop as i32);
         }
         "x265enc" => {
             builder = builder
                 .property_from_str("speed-preset", "ultrafast")
                 .property_from_str("tune", "zerolatency")
-                .property("bitrate", config.bitrate_kbps as u32);
+                .property("bitrate", bitrate_kbps as u32)
+                .property("key-int-max", gop as i32);
         }
         _ => {}
     }
+    if config.quality_factor.is_some() && !matches!(info.name, "x264enc" | "vaapih264enc" | "vaapih265enc") {
+        println!("[zureshot-linux] Quality factor ignored by {}; using the bitrate target", info.name);
+    }
 
     builder
         .build()
         .map_err(|e| format!("Failed to create encoder '{}': {e}", info.name))
 }
 
+/// x264 CRF (`quantizer` in `pass=qual`) for a quality factor: 0.0 → 40,
+/// 1.0 → 16, so the macOS presets (0.82 / 0.90) land near CRF 20 / 18.
+/// CRF and `AVVideoQualityKey` don't scale alike; this only approximates
+/// the same trade-off.
+fn quality_factor_to_crf(q: f64) -> u32 {
+    (40.0 - 24.0 * q.clamp(0.0, 1.0)).round() as u32
+}
+
+/// VA-API `quality-level` (1 = best … 7 = fastest) for a quality factor.
+/// It trades encoding effort for quality within the bitrate, so the
+/// effect is much coarser than on macOS.
+fn quality_factor_to_vaapi_level(q: f64) -> u32 {
+    (7.0 - 6.0 * q.clamp(0.0, 1.0)).round() as u32
+}
+
 /// Add an audio branch to the pipeline and link it to the muxer.
 ///
 /// For system audio: uses PulseAudio monitor source (captures desktop audio).
 /// For microphone: uses default PulseAudio input device.
+/// `mux_pad_name` of `None` lets the muxer pick a compatible request pad.
 fn add_audio_branch(
     pipeline: &gst::Pipeline,
     mux: &gst::Element,
     is_system_audio: bool,
-    mux_pad_name: &str,
+    mic_device: Option<&str>,
+    mux_pad_name: Option<&str>,
 ) -> Result<(), String> {
     let label = if is_system_audio {
         "system audio"
     } else {
         "microphone"
     };
-    println!("[zureshot-linux] Adding {label} branch → mux.{mux_pad_name}");
+    println!("[zureshot-linux] Adding {label} branch → mux.{}", mux_pad_name.unwrap_or("audio"));
 
     // Audio source
     // Note: monitor_source must be declared before src_builder so it outlives
     // the ElementBuilder borrow (variables drop in reverse declaration order).
-    let monitor_source = if is_system_audio {
+    let source = if is_system_audio {
         get_default_monitor_source()
     } else {
-        None
+        mic_device.map(str::to_string)
     };
     let mut src_builder = gst::ElementFactory::make("pulsesrc");
-    if let Some(ref monitor) = monitor_source {
-        src_builder = src_builder.property("device", monitor.as_str());
+    if let Some(ref device) = source {
+        src_builder = src_builder.property("device", device.as_str());
     }
     let audio_src = src_builder
         .build()
@@ -505,7 +931,7 @@ fn add_audio_branch(
 
     // Link to muxer audio pad
     aac_parse
-        .link_pads(Some("src"), mux, Some(mux_pad_name))
+        .link_pads(Some("src"), mux, mux_pad_name)
         .map_err(|e| format!("Failed to link {label}→mux: {e}"))?;
 
     Ok(())
@@ -535,6 +961,40 @@ fn get_default_monitor_source() -> Option<String> {
     Some(monitor)
 }
 
+/// Estimate the file size of a recording with the encoder that would be
+/// picked right now, using the bitrate `create_pipeline` would configure
+/// plus AAC audio per track. The automatic bitrate is tuned for the preset's
+/// frame rate, so a lower `fps` scales it down. A quality factor turns x264
+/// into constant quality with no bitrate to go by; the target is used.
+pub fn estimate_size(
+    width: u32,
+    height: u32,
+    settings: &crate::platform::EncodingSettings,
+    fps: i32,
+    audio_tracks: usize,
+    duration_secs: f64,
+) -> crate::platform::SizeEstimate {
+    let encoder = detect_best_encoder();
+    let h264_fallback = settings.codec == VideoCodec::H264 && encoder.is_hevc;
+    let video_bps = match settings.bitrate_override_bps {
+        Some(bps) => bps as u64,
+        None => {
+            let auto = compute_bitrate(width, height, &settings.quality, &encoder) as f64 * 1000.0;
+            let auto = if h264_fallback { auto / HEVC_BITRATE_FACTOR } else { auto };
+            (auto * (fps as f64 / settings.quality.fps() as f64).min(1.0)) as u64
+        }
+    };
+    let audio_bps = 128_000 * audio_tracks as u64;
+    let payload = (video_bps + audio_bps) as f64 / 8.0 * duration_secs.max(0.0);
+    crate::platform::SizeEstimate {
+        bytes: (payload * (1.0 + crate::platform::CONTAINER_OVERHEAD)) as u64,
+        video_bitrate_bps: video_bps,
+        audio_bitrate_bps: audio_bps,
+        codec: if encoder.is_hevc && !h264_fallback { "hevc" } else { "h264" }.to_string(),
+        fps,
+    }
+}
+
 /// Compute recording bitrate (kbps) based on resolution, quality, and encoder.
 ///
 /// Hardware/HEVC encoders are more efficient, so we can use lower bitrates
@@ -576,7 +1036,7 @@ pub fn compute_bitrate(
     // Adjust for encoder efficiency
     if encoder.is_hevc {
         // HEVC is ~40% more efficient than H.264
-        (base as f64 * 0.65) as i32
+        (base as f64 * HEVC_BITRATE_FACTOR) as i32
     } else if encoder.is_hardware {
         // Hardware H.264 is slightly less efficient than x264 ultrafast
         // but much faster, so use similar bitrate
@@ -6,7 +6,6 @@
 use objc2::rc::Retained;
 use objc2_av_foundation::AVCaptureDevice;
 use objc2_foundation::{NSArray, NSString};
-use std::process::Command;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -16,6 +15,8 @@ pub struct CameraDeviceInfo {
     pub device_id: String,
     pub label: String,
     pub model_id: String,
+    /// "front", "back" or "unspecified" (external / Continuity cameras)
+    pub position: String,
 }
 
 /// List all video capture devices visible to AVFoundation.
@@ -40,10 +41,17 @@ pub fn list_camera_devices() -> Vec<CameraDeviceInfo> {
             let uid = device.uniqueID().to_string();
             let name = device.localizedName().to_string();
             let model = device.modelID().to_string();
+            // AVCaptureDevicePosition: 0 = unspecified, 1 = back, 2 = front
+            let position = match device.position().0 {
+                1 => "back",
+                2 => "front",
+                _ => "unspecified",
+            };
             result.push(CameraDeviceInfo {
                 device_id: uid,
                 label: name,
                 model_id: model,
+                position: position.to_string(),
             });
         }
         result
@@ -104,7 +112,7 @@ pub fn start_native_camera_stream(
 
         // Use ffmpeg to capture JPEG frames from the camera
         // Output one JPEG per frame to stdout at 15fps, 320x320 resolution
-        let mut child = match Command::new("ffmpeg")
+        let mut child = match crate::ffmpeg::command()
             .args([
                 "-f", "avfoundation",
                 "-framerate", "15",
@@ -137,6 +145,7 @@ pub fn start_native_camera_stream(
         let mut byte = [0u8; 1];
         use std::io::Read;
 
+        let mut frame_index: u64 = 0;
         while running.load(Ordering::SeqCst) {
             match reader.read(&mut byte) {
                 Ok(0) => break, // EOF
@@ -146,6 +155,13 @@ pub fn start_native_camera_stream(
                     if buf.len() >= 2 && buf[buf.len() - 2] == 0xFF && buf[buf.len() - 1] == 0xD9 {
                         // Found complete JPEG frame
                         // Find the start marker (FF D8)
+                        // Battery saver: emit every other frame (~7.5fps preview)
+                        frame_index += 1;
+                        let skip = super::power::reduced_preview() && frame_index % 2 == 0;
+                        if skip {
+                            buf.clear();
+                            continue;
+                        }
                         if let Some(start) = buf.windows(2).position(|w| w[0] == 0xFF && w[1] == 0xD8) {
                             let jpeg_data = &buf[start..];
                             let b64 = base64::engine::general_purpose::STANDARD.encode(jpeg_data);
@@ -178,3 +194,24 @@ pub fn stop_native_camera_stream(state: &NativeCameraState) {
     state.running.store(false, Ordering::SeqCst);
 }
 
+/// Switch a running (or idle) stream to another camera without closing the overlay.
+///
+/// The old capture thread gets its own `running` flag, so it can wind down
+/// (kill ffmpeg) after the new stream has already started.
+pub fn switch_native_camera_stream(
+    app: &tauri::AppHandle,
+    device_id: &str,
+    state: &mut NativeCameraState,
+) -> Result<(), String> {
+    if device_index_for_uid(device_id).is_none() {
+        return Err(format!("Camera device not found: {}", device_id));
+    }
+    let was_running = state.running.load(Ordering::SeqCst);
+    stop_native_camera_stream(state);
+    state.running = Arc::new(AtomicBool::new(false));
+    if was_running {
+        start_native_camera_stream(app, device_id, state)?;
+    }
+    println!("[camera-native] Switched to camera {}", device_id);
+    Ok(())
+}
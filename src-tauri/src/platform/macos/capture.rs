@@ -7,23 +7,23 @@
 //! No CPU-side pixel copying in the entire path.
 
 use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
-use std::sync::mpsc;
+use std::sync::{mpsc, OnceLock};
 
 use block2::RcBlock;
 use dispatch2::DispatchQueue;
 use objc2::rc::Retained;
 use objc2::runtime::{NSObject, ProtocolObject};
 use objc2::runtime::NSObjectProtocol;
-use objc2::{define_class, msg_send, AllocAnyThread, DefinedClass};
+use objc2::{define_class, msg_send, sel, AllocAnyThread, DefinedClass};
 use objc2_av_foundation::{AVAssetWriter, AVAssetWriterInput};
 use objc2_core_media::CMSampleBuffer;
-use objc2_foundation::{NSArray, NSError, NSString};
+use objc2_foundation::{NSArray, NSDictionary, NSError, NSNumber, NSString};
 use objc2_core_foundation::{CGPoint, CGRect, CGSize};
 use objc2_screen_capture_kit::{
     SCCaptureResolutionType, SCContentFilter, SCDisplay, SCShareableContent, SCStream,
-    SCStreamConfiguration, SCStreamOutput, SCStreamOutputType, SCWindow,
+    SCStreamConfiguration, SCStreamDelegate, SCStreamOutput, SCStreamOutputType, SCWindow,
 };
-use objc2_core_graphics::kCGColorSpaceSRGB;
+use objc2_core_graphics::{kCGColorSpaceDisplayP3, kCGColorSpaceSRGB};
 use objc2_core_media::CMTime;
 
 // ── Screenshot support ────────────────────────────────────────────────
@@ -36,6 +36,7 @@ pub fn take_screenshot_region(
     width: f64,
     height: f64,
     output_path: &str,
+    format: ImageFormat,
 ) -> Result<(usize, usize, u64), String> {
     // CoreGraphics foreign functions
     extern "C" {
@@ -115,39 +116,62 @@ pub fn take_screenshot_region(
         CGWindowListCreateImage(rect, 1, 0, 0)
     };
 
-    if image.is_null() {
-        return Err("Failed to capture screenshot (CGWindowListCreateImage returned null)".into());
-    }
+    // CGWindowListCreateImage is deprecated and returns null on newer macOS
+    // releases / restricted setups — fall back to ScreenCaptureKit's
+    // screenshot API. Both paths stay in-process (no screencapture/osascript).
+    let image = if image.is_null() {
+        println!("[zureshot] CGWindowListCreateImage returned null, falling back to SCScreenshotManager");
+        screenshot_image_via_sck(rect)?
+    } else {
+        image
+    };
 
     let img_width = unsafe { CGImageGetWidth(image) };
     let img_height = unsafe { CGImageGetHeight(image) };
 
-    // Write to PNG file using ImageIO
+    // Write to PNG/JPEG file using ImageIO
     let url = cfurl_from_path(output_path);
-    let png_type = cfstring("public.png");
+    let (uti, label) = match format {
+        ImageFormat::Png => ("public.png", "PNG"),
+        ImageFormat::Jpeg { .. } => ("public.jpeg", "JPEG"),
+    };
+    let image_type = cfstring(uti);
 
     let dest = unsafe {
-        CGImageDestinationCreateWithURL(url, png_type, 1, std::ptr::null())
+        CGImageDestinationCreateWithURL(url, image_type, 1, std::ptr::null())
     };
 
     if dest.is_null() {
         unsafe {
             CGImageRelease(image);
             CFRelease(url);
-            CFRelease(png_type);
+            CFRelease(image_type);
         }
         return Err("Failed to create image destination".into());
     }
 
+    // kCGImageDestinationLossyCompressionQuality (NSDictionary is
+    // toll-free bridged to CFDictionary)
+    let properties: Option<Retained<NSDictionary<NSString, NSNumber>>> = match format {
+        ImageFormat::Png => None,
+        ImageFormat::Jpeg { quality } => Some(NSDictionary::from_slices(
+            &[&*NSString::from_str("kCGImageDestinationLossyCompressionQuality")],
+            &[&*NSNumber::new_f64(ImageFormat::jpeg_quality(quality))],
+        )),
+    };
+    let properties_ptr = properties
+        .as_ref()
+        .map_or(std::ptr::null(), |p| Retained::as_ptr(p) as *const std::ffi::c_void);
+
     unsafe {
-        CGImageDestinationAddImage(dest, image, std::ptr::null());
+        CGImageDestinationAddImage(dest, image, properties_ptr);
         let ok = CGImageDestinationFinalize(dest);
         CFRelease(dest);
         CGImageRelease(image);
         CFRelease(url);
-        CFRelease(png_type);
+        CFRelease(image_type);
         if !ok {
-            return Err("Failed to write PNG file".into());
+            return Err(format!("Failed to write {} file", label));
         }
     }
 
@@ -156,36 +180,84 @@ pub fn take_screenshot_region(
     Ok((img_width, img_height, file_size))
 }
 
-use crate::platform::RecordingQuality;
+use crate::platform::{FrameCounters, ImageFormat, PixelFormat, RecordingQuality};
+use super::audio_meter::{AudioMeter, AudioSource};
+use super::scene::SceneDetector;
+use super::writer::ColorTagging;
 
 // ────────────────────────────────────────────────────────────────
 //  StreamOutput — SCStreamOutput delegate (receives raw frames)
 // ────────────────────────────────────────────────────────────────
 
+/// Called with (output path, reason) when ScreenCaptureKit stops a stream.
+static STREAM_STOPPED_HANDLER: OnceLock<Box<dyn Fn(String, String) + Send + Sync>> = OnceLock::new();
+
+/// Call `handler(output_path, reason)` when ScreenCaptureKit stops a
+/// recording stream on its own — the captured window closed, the display
+/// was disconnected — so the writer can still be finalized.
+/// Only the first registration takes effect.
+pub fn watch_stream_stops(handler: impl Fn(String, String) + Send + Sync + 'static) {
+    let _ = STREAM_STOPPED_HANDLER.set(Box::new(handler));
+}
+
+/// Called with (output path, error) when a recording's AVAssetWriter fails.
+static WRITER_FAILED_HANDLER: OnceLock<Box<dyn Fn(String, String) + Send + Sync>> = OnceLock::new();
+
+/// Call `handler(output_path, error)` the first time a recording's writer
+/// enters the failed state (disk full, encoder error). Every later append
+/// would fail too, so the recording should be stopped.
+/// Only the first registration takes effect.
+pub fn watch_writer_failures(handler: impl Fn(String, String) + Send + Sync + 'static) {
+    let _ = WRITER_FAILED_HANDLER.set(Box::new(handler));
+}
+
 pub struct StreamOutputIvars {
     writer: Retained<AVAssetWriter>,
-    input: Retained<AVAssetWriterInput>,
+    /// Video writer input (`None` for audio-only recordings)
+    input: Option<Retained<AVAssetWriterInput>>,
     /// Optional system audio writer input
     audio_input: Option<Retained<AVAssetWriterInput>>,
     /// Optional microphone writer input
     mic_input: Option<Retained<AVAssetWriterInput>>,
     session_started: AtomicBool,
     error_logged: AtomicBool,
-    frame_count: AtomicU64,
-    dropped_count: AtomicU64,
+    /// Writer failure already handed to the WRITER_FAILED_HANDLER
+    failure_reported: AtomicBool,
+    /// Video frames appended / dropped / skipped (shared with the handle)
+    counters: std::sync::Arc<FrameCounters>,
     /// Audio frames successfully appended
     audio_frame_count: AtomicU64,
     /// Audio frames dropped (not ready, invalid, or append failed)
     audio_dropped_count: AtomicU64,
+    /// Samples appended to the system audio input (per-track, for empty-track detection)
+    system_audio_samples: AtomicU64,
+    /// Samples appended to the microphone input
+    mic_audio_samples: AtomicU64,
     /// Last appended PTS value (numerator) — for monotonicity enforcement.
     /// Stored as i64; -1 means "no frame yet".
     last_pts_value: AtomicI64,
     /// Last appended PTS timescale — for monotonicity enforcement.
     last_pts_timescale: AtomicI64,
-    /// Count of frames skipped due to non-monotonic PTS.
-    pts_skip_count: AtomicU64,
     /// Shared paused flag — when true, frames are dropped (not written to file).
     paused: std::sync::Arc<AtomicBool>,
+    /// Optional scene-change detector, sampled from appended frames.
+    scene: Option<std::sync::Arc<SceneDetector>>,
+    /// Optional audio level meter, fed every audio buffer.
+    meter: Option<std::sync::Arc<AudioMeter>>,
+    /// (PTS seconds, wall-clock unix seconds) per appended frame, when the
+    /// `.timestamps.json` sidecar is enabled. Flushed once at finalize.
+    timestamps: Option<std::sync::Mutex<Vec<(f64, f64)>>>,
+    /// Seconds of video discarded before the writer session starts, so the
+    /// encoder and capture pipeline are primed (0 = start on the first frame).
+    warmup_secs: f64,
+    /// PTS (seconds) of the first video frame, the warm-up reference point.
+    warmup_start: std::sync::OnceLock<f64>,
+    /// Video frames discarded during warm-up.
+    warmup_dropped: AtomicU64,
+    /// When the delegate was created (just before the stream starts).
+    created_at: std::time::Instant,
+    /// Time from stream start to the first video frame.
+    first_frame_latency_ms: std::sync::OnceLock<f64>,
 }
 
 define_class!(
@@ -200,6 +272,21 @@ define_class!(
     // NSObjectProtocol is required by SCStreamOutput
     unsafe impl NSObjectProtocol for StreamOutput {}
 
+    // SCStreamDelegate — the stream stopped without us asking
+    unsafe impl SCStreamDelegate for StreamOutput {
+        #[unsafe(method(stream:didStopWithError:))]
+        fn stream_didStopWithError(&self, _stream: &SCStream, error: &NSError) {
+            eprintln!("[zureshot] Capture stream stopped (code {}): {}", error.code(), error);
+            let path = unsafe {
+                let url: Option<Retained<NSObject>> = msg_send![&*self.ivars().writer, outputURL];
+                url.and_then(|u| -> Option<Retained<NSString>> { msg_send![&*u, path] })
+            };
+            if let (Some(path), Some(handler)) = (path, STREAM_STOPPED_HANDLER.get()) {
+                handler(path.to_string(), format!("{}", error));
+            }
+        }
+    }
+
     // SCStreamOutput protocol — receives captured frames
     unsafe impl SCStreamOutput for StreamOutput {
         #[unsafe(method(stream:didOutputSampleBuffer:ofType:))]
@@ -221,18 +308,21 @@ define_class!(
             if output_type.0 == 1 {
                 // System audio
                 if let Some(ref audio_input) = ivars.audio_input {
-                    self.append_audio(sample_buffer, audio_input, ivars, "system");
+                    self.append_audio(sample_buffer, audio_input, &ivars.system_audio_samples, ivars, AudioSource::System);
                 }
                 return;
             } else if output_type.0 == 2 {
                 // Microphone
                 if let Some(ref mic_input) = ivars.mic_input {
-                    self.append_audio(sample_buffer, mic_input, ivars, "mic");
+                    self.append_audio(sample_buffer, mic_input, &ivars.mic_audio_samples, ivars, AudioSource::Mic);
                 }
                 return;
             }
 
             // ── Screen frames (type 0) ──
+            let Some(ref input) = ivars.input else {
+                return;
+            };
 
             // ── 1. Validate CMSampleBuffer ──
             let is_valid: bool = unsafe { sample_buffer.is_valid() };
@@ -256,6 +346,11 @@ define_class!(
             if has_image.is_none() {
                 return;
             }
+            ivars.first_frame_latency_ms.get_or_init(|| {
+                let ms = ivars.created_at.elapsed().as_secs_f64() * 1000.0;
+                println!("[zureshot] First video frame after {:.0}ms", ms);
+                ms
+            });
 
             // ── 2. Get PTS and enforce monotonicity ──
             let pts = unsafe { sample_buffer.presentation_time_stamp() };
@@ -277,7 +372,7 @@ define_class!(
                 let rhs = (prev_val as i128) * (pts_timescale as i128);
                 if lhs <= rhs {
                     // Non-monotonic PTS — skip this frame
-                    let skip_n = ivars.pts_skip_count.fetch_add(1, Ordering::Relaxed);
+                    let skip_n = ivars.counters.pts_skips.fetch_add(1, Ordering::Relaxed);
                     if skip_n < 5 || skip_n % 100 == 0 {
                         println!(
                             "[zureshot] Skipping non-monotonic PTS: {}/{} <= {}/{} (skip #{})",
@@ -289,27 +384,57 @@ define_class!(
                 }
             }
 
-            // ── 3. Start session on first valid frame ──
+            // ── 2b. Warm-up: discard frames until the pipeline is primed ──
+            if ivars.warmup_secs > 0.0 && !ivars.session_started.load(Ordering::Relaxed) {
+                let t = pts_value as f64 / pts_timescale as f64;
+                let start = *ivars.warmup_start.get_or_init(|| t);
+                if t - start < ivars.warmup_secs {
+                    ivars.warmup_dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+            }
+
+            // ── 3. Start session on first valid (post-warm-up) frame ──
             if !ivars.session_started.swap(true, Ordering::Relaxed) {
                 unsafe {
                     let _: () = msg_send![&*ivars.writer, startSessionAtSourceTime: pts];
                 }
                 println!(
-                    "[zureshot] First frame captured, PTS={}/{}, encoding started",
-                    pts_value, pts_timescale
+                    "[zureshot] First frame captured, PTS={}/{}, encoding started ({} warm-up frames discarded)",
+                    pts_value, pts_timescale, ivars.warmup_dropped.load(Ordering::Relaxed)
                 );
             }
 
             // ── 4. Append frame to writer (zero-copy) ──
             unsafe {
-                let ready: bool = msg_send![&*ivars.input, isReadyForMoreMediaData];
+                let ready: bool = msg_send![&**input, isReadyForMoreMediaData];
                 if ready {
-                    let ok: bool = msg_send![&*ivars.input, appendSampleBuffer: sample_buffer];
+                    let ok: bool = msg_send![&**input, appendSampleBuffer: sample_buffer];
                     if ok {
                         // Update last PTS
                         ivars.last_pts_value.store(pts_value, Ordering::Relaxed);
                         ivars.last_pts_timescale.store(pts_timescale as i64, Ordering::Relaxed);
                         ivars.frames_inc();
+
+                        if let Some(ref timestamps) = ivars.timestamps {
+                            let wall = std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .map(|d| d.as_secs_f64())
+                                .unwrap_or(0.0);
+                            if let Ok(mut ts) = timestamps.lock() {
+                                ts.push((pts_value as f64 / pts_timescale as f64, wall));
+                            }
+                        }
+
+                        // ── 5. Scene detection (sampled ~1/s, luma-only) ──
+                        if let Some(ref scene) = ivars.scene {
+                            let t = pts_value as f64 / pts_timescale as f64;
+                            if scene.wants_sample(t) {
+                                if let Some(image) = sample_buffer.image_buffer() {
+                                    scene.process(&*image as *const _ as *const std::ffi::c_void, t);
+                                }
+                            }
+                        }
                     } else {
                         // Writer entered failed state — log full error ONCE
                         if !ivars.error_logged.swap(true, Ordering::Relaxed) {
@@ -327,7 +452,7 @@ define_class!(
                                 .map(|e| format!("{:?}", e.userInfo()))
                                 .unwrap_or_else(|| "none".into());
                             println!("[zureshot] !! Writer FAILED at frame {} !!",
-                                ivars.frame_count.load(Ordering::Relaxed));
+                                ivars.counters.frames.load(Ordering::Relaxed));
                             println!("[zureshot]    status={}", status);
                             println!("[zureshot]    error={}", err_desc);
                             println!("[zureshot]    domain={:?} code={}",
@@ -336,9 +461,13 @@ define_class!(
                             println!("[zureshot]    last PTS={}/{} current PTS={}/{}",
                                 prev_val, prev_ts, pts_value, pts_timescale);
                         }
+                        self.report_writer_failure(ivars);
                         ivars.dropped_inc();
                     }
                 } else {
+                    // A failed writer is never ready again: report it here
+                    // too, or the recording would just drop frames silently
+                    self.report_writer_failure(ivars);
                     ivars.dropped_inc();
                 }
             }
@@ -348,10 +477,10 @@ define_class!(
 
 impl StreamOutputIvars {
     fn frames_inc(&self) {
-        let n = self.frame_count.fetch_add(1, Ordering::Relaxed);
+        let n = self.counters.frames.fetch_add(1, Ordering::Relaxed);
         // Print progress every 60 frames (~1 second at 60fps)
         if (n + 1) % 60 == 0 {
-            let dropped = self.dropped_count.load(Ordering::Relaxed);
+            let dropped = self.counters.dropped.load(Ordering::Relaxed);
             let audio = self.audio_frame_count.load(Ordering::Relaxed);
             let audio_drop = self.audio_dropped_count.load(Ordering::Relaxed);
             println!(
@@ -362,7 +491,7 @@ impl StreamOutputIvars {
     }
 
     fn dropped_inc(&self) {
-        self.dropped_count.fetch_add(1, Ordering::Relaxed);
+        self.counters.dropped.fetch_add(1, Ordering::Relaxed);
     }
 
     fn audio_frames_inc(&self) {
@@ -375,6 +504,25 @@ impl StreamOutputIvars {
 }
 
 impl StreamOutput {
+    /// Hand a failed writer (status 3) to the WRITER_FAILED_HANDLER, once.
+    fn report_writer_failure(&self, ivars: &StreamOutputIvars) {
+        let (status, error, path) = unsafe {
+            let status: i64 = msg_send![&*ivars.writer, status];
+            let error: Option<Retained<NSError>> = msg_send![&*ivars.writer, error];
+            let url: Option<Retained<NSObject>> = msg_send![&*ivars.writer, outputURL];
+            let path: Option<Retained<NSString>> = url.and_then(|u| msg_send![&*u, path]);
+            (status, error, path)
+        };
+        // AVAssetWriterStatusFailed
+        if status != 3 || ivars.failure_reported.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let error = error.map(|e| format!("{}", e)).unwrap_or_else(|| "unknown".into());
+        if let (Some(path), Some(handler)) = (path, WRITER_FAILED_HANDLER.get()) {
+            handler(path.to_string(), error);
+        }
+    }
+
     /// Append an audio CMSampleBuffer to the given AVAssetWriterInput.
     ///
     /// Used for both system audio (type=1) and microphone (type=2).
@@ -385,9 +533,11 @@ impl StreamOutput {
         &self,
         sample_buffer: &CMSampleBuffer,
         audio_input: &AVAssetWriterInput,
+        track_samples: &AtomicU64,
         ivars: &StreamOutputIvars,
-        source_label: &str,
+        source: AudioSource,
     ) {
+        let source_label = source.as_str();
         let is_valid: bool = unsafe { sample_buffer.is_valid() };
         if !is_valid {
             ivars.audio_dropped_inc();
@@ -399,6 +549,11 @@ impl StreamOutput {
             return;
         }
 
+        // Meter before the warm-up/append checks so the level shows from the start
+        if let Some(ref meter) = ivars.meter {
+            meter.feed(source, sample_buffer);
+        }
+
         // ── Get PTS and basic validation ──
         let pts = unsafe { sample_buffer.presentation_time_stamp() };
         let pts_value = pts.value;
@@ -408,6 +563,11 @@ impl StreamOutput {
             return;
         }
 
+        // During warm-up the session starts on a video frame; earlier audio is dropped
+        if ivars.warmup_secs > 0.0 && !ivars.session_started.load(Ordering::Relaxed) {
+            return;
+        }
+
         // ── Start session on first valid frame (video or audio, whichever comes first) ──
         if !ivars.session_started.swap(true, Ordering::Relaxed) {
             unsafe {
@@ -434,6 +594,7 @@ impl StreamOutput {
                 let ok: bool = msg_send![audio_input, appendSampleBuffer: sample_buffer];
                 if ok {
                     ivars.audio_frames_inc();
+                    track_samples.fetch_add(1, Ordering::Relaxed);
                 } else {
                     // Audio append failed — log error details
                     let drop_n = ivars.audio_dropped_count.load(Ordering::Relaxed);
@@ -454,10 +615,13 @@ impl StreamOutput {
                             pts_value, pts_timescale
                         );
                     }
+                    self.report_writer_failure(ivars);
                     ivars.audio_dropped_inc();
                 }
             } else {
-                // Audio input not ready — encoder backlogged
+                // Audio input not ready — encoder backlogged, or the writer
+                // failed (reported once, as for video)
+                self.report_writer_failure(ivars);
                 let drop_n = ivars.audio_dropped_count.fetch_add(1, Ordering::Relaxed);
                 if drop_n < 3 {
                     println!(
@@ -471,10 +635,14 @@ impl StreamOutput {
 
     fn new_with(
         writer: Retained<AVAssetWriter>,
-        input: Retained<AVAssetWriterInput>,
+        input: Option<Retained<AVAssetWriterInput>>,
         audio_input: Option<Retained<AVAssetWriterInput>>,
         mic_input: Option<Retained<AVAssetWriterInput>>,
         paused: std::sync::Arc<AtomicBool>,
+        scene: Option<std::sync::Arc<SceneDetector>>,
+        meter: Option<std::sync::Arc<AudioMeter>>,
+        record_timestamps: bool,
+        warmup_secs: f64,
     ) -> Retained<Self> {
         let this = Self::alloc().set_ivars(StreamOutputIvars {
             writer,
@@ -483,26 +651,77 @@ impl StreamOutput {
             mic_input,
             session_started: AtomicBool::new(false),
             error_logged: AtomicBool::new(false),
-            frame_count: AtomicU64::new(0),
-            dropped_count: AtomicU64::new(0),
+            failure_reported: AtomicBool::new(false),
+            counters: std::sync::Arc::new(FrameCounters::default()),
             audio_frame_count: AtomicU64::new(0),
             audio_dropped_count: AtomicU64::new(0),
+            system_audio_samples: AtomicU64::new(0),
+            mic_audio_samples: AtomicU64::new(0),
             last_pts_value: AtomicI64::new(-1),
             last_pts_timescale: AtomicI64::new(0),
-            pts_skip_count: AtomicU64::new(0),
             paused,
+            scene,
+            meter,
+            // ~1 hour at 60 fps before the first reallocation
+            timestamps: record_timestamps
+                .then(|| std::sync::Mutex::new(Vec::with_capacity(216_000))),
+            warmup_secs,
+            warmup_start: std::sync::OnceLock::new(),
+            warmup_dropped: AtomicU64::new(0),
+            created_at: std::time::Instant::now(),
+            first_frame_latency_ms: std::sync::OnceLock::new(),
         });
         unsafe { msg_send![super(this), init] }
     }
 
-    #[allow(dead_code)]
-    pub fn frame_count(&self) -> u64 {
-        self.ivars().frame_count.load(Ordering::Relaxed)
+    /// Video frame counters, readable while the stream runs.
+    pub fn counters(&self) -> std::sync::Arc<FrameCounters> {
+        self.ivars().counters.clone()
+    }
+
+    /// Milliseconds from stream start to the first video frame, once seen.
+    pub fn first_frame_latency_ms(&self) -> Option<f64> {
+        self.ivars().first_frame_latency_ms.get().copied()
+    }
+
+    /// Samples successfully appended to the system audio track.
+    pub fn system_audio_samples(&self) -> u64 {
+        self.ivars().system_audio_samples.load(Ordering::Relaxed)
+    }
+
+    /// Samples successfully appended to the microphone track.
+    pub fn mic_audio_samples(&self) -> u64 {
+        self.ivars().mic_audio_samples.load(Ordering::Relaxed)
+    }
+
+    /// Per-frame (PTS seconds, wall-clock seconds), or `None` if not recorded.
+    /// Drains the buffer.
+    pub fn take_frame_timestamps(&self) -> Option<Vec<(f64, f64)>> {
+        let timestamps = self.ivars().timestamps.as_ref()?;
+        timestamps.lock().ok().map(|mut ts| std::mem::take(&mut *ts))
+    }
+
+    /// Start the writer session at `pts` unless a sample already did. For
+    /// video appended outside this delegate (whole-desktop recordings), so
+    /// the session starts exactly once whichever track is first.
+    pub fn start_session_once(&self, pts: CMTime, source_label: &str) {
+        let ivars = self.ivars();
+        if !ivars.session_started.swap(true, Ordering::Relaxed) {
+            unsafe {
+                let _: () = msg_send![&*ivars.writer, startSessionAtSourceTime: pts];
+            }
+            let (pts_value, pts_timescale) = (pts.value, pts.timescale);
+            println!(
+                "[zureshot] Session started from {} frame, PTS={}/{}",
+                source_label, pts_value, pts_timescale
+            );
+        }
     }
 
-    #[allow(dead_code)]
-    pub fn dropped_count(&self) -> u64 {
-        self.ivars().dropped_count.load(Ordering::Relaxed)
+    /// Report a failed append made outside this delegate, so a failed
+    /// writer still reaches the WRITER_FAILED_HANDLER (once).
+    pub fn report_external_append_failure(&self) {
+        self.report_writer_failure(self.ivars());
     }
 }
 
@@ -558,12 +777,29 @@ pub fn get_main_display() -> Result<Retained<SCDisplay>, String> {
 
 /// Get the main display and all windows (for exclusion filtering).
 pub fn get_display_and_windows() -> Result<(Retained<SCDisplay>, Vec<Retained<SCWindow>>), String> {
+    get_display_and_windows_for(None)
+}
+
+/// Like `get_display_and_windows`, but for a specific display ID
+/// (`None` = first display). Errors if the display is not connected.
+pub fn get_display_and_windows_for(
+    display_id: Option<u32>,
+) -> Result<(Retained<SCDisplay>, Vec<Retained<SCWindow>>), String> {
     let content = get_shareable_content()?;
     let displays = unsafe { content.displays() };
     if displays.is_empty() {
         return Err("No displays found".to_string());
     }
-    let display = displays.objectAtIndex(0);
+    let display = match display_id {
+        Some(id) => (0..displays.len())
+            .map(|i| displays.objectAtIndex(i))
+            .find(|d| {
+                let did: u32 = unsafe { msg_send![&**d, displayID] };
+                did == id
+            })
+            .ok_or_else(|| format!("Display {} not found", id))?,
+        None => displays.objectAtIndex(0),
+    };
     let sc_windows = unsafe { content.windows() };
     let mut windows = Vec::new();
     for i in 0..sc_windows.len() {
@@ -572,6 +808,75 @@ pub fn get_display_and_windows() -> Result<(Retained<SCDisplay>, Vec<Retained<SC
     Ok((display, windows))
 }
 
+/// All displays currently available to ScreenCaptureKit.
+pub fn all_displays() -> Result<Vec<Retained<SCDisplay>>, String> {
+    let content = get_shareable_content()?;
+    let displays = unsafe { content.displays() };
+    Ok((0..displays.len()).map(|i| displays.objectAtIndex(i)).collect())
+}
+
+/// Capture a region (logical points, top-left origin) of the main display
+/// as a CGImage via `+[SCScreenshotManager captureImageWithFilter:…]`
+/// (macOS 14+). Returns a +1 retained CGImageRef; caller must release it.
+fn screenshot_image_via_sck(rect: CGRect) -> Result<*const std::ffi::c_void, String> {
+    use objc2::runtime::AnyClass;
+
+    extern "C" {
+        fn CGImageRetain(image: *const std::ffi::c_void) -> *const std::ffi::c_void;
+    }
+
+    let manager_cls = AnyClass::get(c"SCScreenshotManager")
+        .ok_or("SCScreenshotManager unavailable (requires macOS 14+)")?;
+
+    let (display, _) = get_display_and_windows()?;
+    let (_, _, scale) = display_physical_size(&display);
+
+    let config = unsafe {
+        let c = SCStreamConfiguration::new();
+        c.setSourceRect(rect);
+        c.setWidth((rect.size.width * scale).round() as usize);
+        c.setHeight((rect.size.height * scale).round() as usize);
+        c.setShowsCursor(false);
+        c.setCaptureResolution(SCCaptureResolutionType::Best);
+        c.setColorSpaceName(kCGColorSpaceSRGB);
+        c
+    };
+    let filter = unsafe {
+        SCContentFilter::initWithDisplay_excludingWindows(
+            SCContentFilter::alloc(),
+            &display,
+            &NSArray::new(),
+        )
+    };
+
+    // The CGImage is only valid inside the handler — retain it before sending.
+    let (tx, rx) = mpsc::channel::<Result<usize, String>>();
+    let handler = RcBlock::new(move |image: *const std::ffi::c_void, error: *mut NSError| {
+        if !image.is_null() {
+            let retained = unsafe { CGImageRetain(image) };
+            let _ = tx.send(Ok(retained as usize));
+        } else if !error.is_null() {
+            let _ = tx.send(Err(unsafe { format!("{}", &*error) }));
+        } else {
+            let _ = tx.send(Err("no image returned".into()));
+        }
+    });
+    unsafe {
+        let _: () = msg_send![
+            manager_cls,
+            captureImageWithFilter: &*filter,
+            configuration: &*config,
+            completionHandler: &*handler
+        ];
+    }
+
+    match rx.recv_timeout(std::time::Duration::from_secs(5)) {
+        Ok(Ok(ptr)) => Ok(ptr as *const std::ffi::c_void),
+        Ok(Err(e)) => Err(format!("SCScreenshotManager capture failed: {}", e)),
+        Err(_) => Err("SCScreenshotManager capture timed out".into()),
+    }
+}
+
 /// Get the logical pixel dimensions of a display.
 pub fn display_size(display: &SCDisplay) -> (usize, usize) {
     unsafe {
@@ -619,50 +924,172 @@ pub fn display_physical_size(display: &SCDisplay) -> (usize, usize, f64) {
     }
 }
 
+/// Height of the menu bar on a display, in logical points.
+///
+/// Matches the display to its NSScreen via `NSScreenNumber` and measures the
+/// gap between the top of `frame` and the top of `visibleFrame` (the Dock
+/// only ever shrinks the sides/bottom). Accounts for the taller menu bar on
+/// notched MacBooks; returns 0 when the menu bar is auto-hidden.
+pub fn menu_bar_height(display: &SCDisplay) -> f64 {
+    use objc2::runtime::{AnyClass, AnyObject};
+
+    let display_id: u32 = unsafe { msg_send![display, displayID] };
+
+    let Some(screen_cls) = AnyClass::get(c"NSScreen") else {
+        return 0.0;
+    };
+
+    unsafe {
+        let screens: Option<Retained<NSArray<AnyObject>>> = msg_send![screen_cls, screens];
+        let Some(screens) = screens else {
+            return 0.0;
+        };
+        let key = NSString::from_str("NSScreenNumber");
+        for i in 0..screens.count() {
+            let screen = screens.objectAtIndex(i);
+            let desc: Option<Retained<AnyObject>> = msg_send![&*screen, deviceDescription];
+            let Some(desc) = desc else { continue };
+            let number: Option<Retained<AnyObject>> = msg_send![&*desc, objectForKey: &*key];
+            let Some(number) = number else { continue };
+            let screen_id: u32 = msg_send![&*number, unsignedIntValue];
+            if screen_id != display_id {
+                continue;
+            }
+            let frame: CGRect = msg_send![&*screen, frame];
+            let visible: CGRect = msg_send![&*screen, visibleFrame];
+            let top = frame.origin.y + frame.size.height;
+            let visible_top = visible.origin.y + visible.size.height;
+            return (top - visible_top).max(0.0);
+        }
+    }
+    0.0
+}
+
+/// Whether this macOS has the SCK audio selectors: (`capturesAudio`,
+/// macOS 13; `captureMicrophone`, macOS 15). Messaging a missing selector
+/// would raise, so check before configuring audio.
+pub fn audio_capture_support() -> (bool, bool) {
+    unsafe {
+        let config = SCStreamConfiguration::new();
+        let system: bool = msg_send![&*config, respondsToSelector: sel!(setCapturesAudio:)];
+        let mic: bool = msg_send![&*config, respondsToSelector: sel!(setCaptureMicrophone:)];
+        (system, mic)
+    }
+}
+
+/// What `create_and_start` captures and how. The defaults are the smallest
+/// stream SCK accepts (2×2 at 1fps, no video, no audio) — what audio-only
+/// recordings run to carry their audio outputs.
+pub struct CaptureOptions<'a> {
+    /// Output size in pixels (rounded to even dimensions).
+    pub width: usize,
+    pub height: usize,
+    /// Video input (`None` = don't add a screen output).
+    pub input: Option<Retained<AVAssetWriterInput>>,
+    pub audio_input: Option<Retained<AVAssetWriterInput>>,
+    pub mic_input: Option<Retained<AVAssetWriterInput>>,
+    /// Region of the display to capture, in logical points (macOS
+    /// bottom-left origin). `None` = the whole display.
+    pub source_rect: Option<CGRect>,
+    /// Windows filtered out of the capture (e.g. overlay/indicator).
+    pub exclude_windows: Vec<Retained<SCWindow>>,
+    pub quality: RecordingQuality,
+    pub paused_flag: std::sync::Arc<AtomicBool>,
+    pub capture_system_audio: bool,
+    pub capture_microphone: bool,
+    /// Microphone to capture (`None` = the default input device).
+    pub microphone_device_id: Option<&'a str>,
+    pub scene: Option<std::sync::Arc<SceneDetector>>,
+    pub meter: Option<std::sync::Arc<AudioMeter>>,
+    /// Single-window capture (the exclusion list is then ignored).
+    pub window: Option<Retained<SCWindow>>,
+    pub pixel_format: PixelFormat,
+    pub color: ColorTagging,
+    pub record_timestamps: bool,
+    pub fps: i32,
+    pub shows_cursor: bool,
+    pub warmup_secs: f64,
+}
+
+impl Default for CaptureOptions<'_> {
+    fn default() -> Self {
+        Self {
+            width: 2,
+            height: 2,
+            input: None,
+            audio_input: None,
+            mic_input: None,
+            source_rect: None,
+            exclude_windows: Vec::new(),
+            quality: RecordingQuality::default(),
+            paused_flag: Default::default(),
+            capture_system_audio: false,
+            capture_microphone: false,
+            microphone_device_id: None,
+            scene: None,
+            meter: None,
+            window: None,
+            pixel_format: PixelFormat::default(),
+            color: ColorTagging::default(),
+            record_timestamps: false,
+            fps: 1,
+            shows_cursor: false,
+            warmup_secs: 0.0,
+        }
+    }
+}
+
 /// Create an SCStream, wire up the delegate, and start capturing.
 ///
 /// The delegate receives CMSampleBuffers and directly appends them to the
 /// AVAssetWriterInput — zero-copy, hardware-encoded H.264.
-///
-/// If `source_rect` is provided, only that region of the display is captured
-/// (coordinates in logical points, macOS bottom-left origin).
-/// `exclude_windows` are filtered out of the capture (e.g. overlay/indicator).
 pub fn create_and_start(
     display: &SCDisplay,
-    width: usize,
-    height: usize,
     writer: Retained<AVAssetWriter>,
-    input: Retained<AVAssetWriterInput>,
-    audio_input: Option<Retained<AVAssetWriterInput>>,
-    mic_input: Option<Retained<AVAssetWriterInput>>,
-    source_rect: Option<CGRect>,
-    exclude_windows: Vec<Retained<SCWindow>>,
-    quality: RecordingQuality,
-    paused_flag: std::sync::Arc<AtomicBool>,
-    capture_system_audio: bool,
-    capture_microphone: bool,
-) -> Result<Retained<SCStream>, String> {
-    // ── Stream configuration ──
-    // H.264 requires even dimensions — round up if needed (must match writer settings)
-    let width = if width % 2 != 0 { width + 1 } else { width };
-    let height = if height % 2 != 0 { height + 1 } else { height };
+    options: CaptureOptions<'_>,
+) -> Result<(Retained<SCStream>, Retained<StreamOutput>, Retained<SCStreamConfiguration>), String> {
+    let CaptureOptions {
+        width,
+        height,
+        input,
+        audio_input,
+        mic_input,
+        source_rect,
+        exclude_windows,
+        quality,
+        paused_flag,
+        capture_system_audio,
+        capture_microphone,
+        microphone_device_id,
+        scene,
+        meter,
+        window,
+        pixel_format,
+        color,
+        record_timestamps,
+        fps,
+        shows_cursor,
+        warmup_secs,
+    } = options;
 
-    let fps: i32 = match quality {
-        RecordingQuality::Standard => 30,
-        RecordingQuality::High => 60,
-    };
+    // ── Stream configuration ──
+    // HEVC requires even dimensions — same rounding as the writer settings
+    let width = crate::platform::even_dimension(width);
+    let height = crate::platform::even_dimension(height);
 
     let config = unsafe {
         let c = SCStreamConfiguration::new();
         c.setWidth(width);
         c.setHeight(height);
-        // Frame interval based on quality
+        // Frame interval: preset within the configured bounds (capped on battery)
         c.setMinimumFrameInterval(CMTime::new(1, fps));
-        c.setShowsCursor(true);
+        // Off when the cursor is redrawn at standard size from the mouse track
+        c.setShowsCursor(shows_cursor);
         // NV12 (420v) pixel format — native format for HEVC/H.264 encoding
         // BGRA requires GPU color space conversion which can fail after a few seconds.
         // 420v is what the VideoToolbox HEVC encoder natively consumes → zero-copy.
-        c.setPixelFormat(u32::from_be_bytes(*b"420v"));
+        // Other formats are an advanced override for reproducing color bugs.
+        c.setPixelFormat(pixel_format.fourcc());
         // Queue depth: 3 frames (reduced from 5 for lower memory).
         // With zero-copy pipeline, frames move through quickly.
         // Lower queue = less IOSurface memory held = smaller RSS.
@@ -678,7 +1105,11 @@ pub fn create_and_start(
         // Explicitly set sRGB to prevent implicit color space conversions between
         // SCK capture → VideoToolbox encoder. Without this, the system may apply
         // Display P3 → BT.709 conversion that softens pixel edges.
-        c.setColorSpaceName(kCGColorSpaceSRGB);
+        // Wide-gamut tagging captures in Display P3 so the pixels match the tag.
+        c.setColorSpaceName(match color {
+            ColorTagging::Bt709 => kCGColorSpaceSRGB,
+            ColorTagging::DisplayP3 => kCGColorSpaceDisplayP3,
+        });
 
         // ── Opaque rendering ──
         // Tell SCK the content is fully opaque (no alpha channel needed).
@@ -695,12 +1126,15 @@ pub fn create_and_start(
             // Disable scaling-to-fit for region capture.
             // When sourceRect physical pixels == destinationRect, we want 1:1 pixel
             // mapping with no bilinear/bicubic interpolation that causes blur.
-            c.setScalesToFit(false);
+            // A deliberately smaller output (e.g. GIF) must scale to fit.
+            let (_, _, scale) = display_physical_size(display);
+            let scales_to_fit = ((rect.size.width * scale).round() as usize) > width + 1;
+            c.setScalesToFit(scales_to_fit);
             println!(
-                "[zureshot] Region capture: sourceRect=({},{} {}x{}), output={}x{}, scalesToFit=false",
+                "[zureshot] Region capture: sourceRect=({},{} {}x{}), output={}x{}, scalesToFit={}",
                 rect.origin.x, rect.origin.y,
                 rect.size.width, rect.size.height,
-                width, height
+                width, height, scales_to_fit
             );
         }
 
@@ -717,21 +1151,30 @@ pub fn create_and_start(
             println!("[zureshot] System audio capture enabled (48kHz stereo, excludesSelf=true)");
         }
         if capture_microphone {
-            // captureMicrophone requires macOS 14+
+            // captureMicrophone requires macOS 15+ (see audio_capture_support)
             // On Mac Mini there may be no built-in microphone — SCK will use
             // the default audio input device (e.g. headset mic, USB mic, etc.)
             // If no mic is available, SCK may silently skip mic samples.
             c.setCaptureMicrophone(true);
-            println!("[zureshot] Microphone capture enabled (uses default input device)");
+            match microphone_device_id {
+                Some(id) => {
+                    c.setMicrophoneCaptureDeviceID(Some(&NSString::from_str(id)));
+                    println!("[zureshot] Microphone capture enabled (device {})", id);
+                }
+                None => println!("[zureshot] Microphone capture enabled (uses default input device)"),
+            }
         }
 
         c
     };
 
     println!(
-        "[zureshot] Capture config: {}x{} @ {}fps, quality={:?}, resolution=Best, colorSpace=sRGB, opaque=true",
-        width, height, fps, quality
+        "[zureshot] Capture config: {}x{} @ {}fps, quality={:?}, pixelFormat={:?}, resolution=Best, color={:?}, opaque=true",
+        width, height, fps, quality, pixel_format, color
     );
+    if pixel_format == PixelFormat::Bgra {
+        println!("[zureshot] WARNING: BGRA capture needs GPU color conversion and may fail after a few seconds — use for short diagnostic captures only");
+    }
 
     // ── Content filter: capture display, excluding specified windows ──
     let exclude_array: Retained<NSArray<SCWindow>> = if exclude_windows.is_empty() {
@@ -740,16 +1183,25 @@ pub fn create_and_start(
         let refs: Vec<&SCWindow> = exclude_windows.iter().map(|w| &**w).collect();
         NSArray::from_slice(&refs)
     };
+    // Single-window capture follows the window wherever it moves and
+    // ignores everything else, so no exclusion list is needed.
     let filter = unsafe {
-        SCContentFilter::initWithDisplay_excludingWindows(
-            SCContentFilter::alloc(),
-            display,
-            &exclude_array,
-        )
+        if let Some(ref window) = window {
+            SCContentFilter::initWithDesktopIndependentWindow(SCContentFilter::alloc(), window)
+        } else {
+            SCContentFilter::initWithDisplay_excludingWindows(
+                SCContentFilter::alloc(),
+                display,
+                &exclude_array,
+            )
+        }
     };
 
     // ── Create delegate ──
-    let delegate = StreamOutput::new_with(writer, input, audio_input, mic_input, paused_flag);
+    let captures_video = input.is_some();
+    let delegate = StreamOutput::new_with(
+        writer, input, audio_input, mic_input, paused_flag, scene, meter, record_timestamps, warmup_secs,
+    );
 
     // ── Create stream ──
     let stream = unsafe {
@@ -757,20 +1209,24 @@ pub fn create_and_start(
             SCStream::alloc(),
             &filter,
             &config,
-            None, // No SCStreamDelegate (error handling), using SCStreamOutput only
+            // Reports the stream stopping on its own (e.g. the window closed)
+            Some(ProtocolObject::from_ref(&*delegate)),
         )
     };
 
     // ── Add output on a dedicated serial dispatch queue ──
     let queue = DispatchQueue::new("com.zureshot.capture", None);
     unsafe {
-        stream
-            .addStreamOutput_type_sampleHandlerQueue_error(
-                ProtocolObject::from_ref(&*delegate),
-                SCStreamOutputType(0), // Screen
-                Some(&queue),
-            )
-            .expect("Failed to add stream output");
+        // Audio-only recordings run the stream just for its audio outputs
+        if captures_video {
+            stream
+                .addStreamOutput_type_sampleHandlerQueue_error(
+                    ProtocolObject::from_ref(&*delegate),
+                    SCStreamOutputType(0), // Screen
+                    Some(&queue),
+                )
+                .expect("Failed to add stream output");
+        }
 
         // Add audio output if system audio capture is enabled
         if capture_system_audio {
@@ -797,29 +1253,160 @@ pub fn create_and_start(
         }
     }
 
-    // ── Start capture (blocking wait) ──
-    let (tx, rx) = mpsc::channel();
-    let start_handler = RcBlock::new(move |error: *mut NSError| {
+    // ── Start capture (blocking wait, retried on transient failures) ──
+    start_with_retry(&stream)?;
+
+    // The stream retains the delegate via addStreamOutput.
+    // We must NOT drop the Rust Retained<StreamOutput> early though,
+    // as that would decrement the refcount. Leak one reference — the stream
+    // owns it now — and hand another to the caller for reading counters.
+    std::mem::forget(delegate.clone());
+
+    Ok((stream, delegate, config))
+}
+
+/// Frame rate cap while recording on battery in `PowerMode::Auto`.
+pub const BATTERY_SAVER_FPS: i32 = 30;
+
+/// Change a running stream's frame rate (`updateConfiguration`). Returns
+/// immediately; the result is logged from the completion handler.
+pub fn update_frame_rate(stream: &SCStream, config: &SCStreamConfiguration, fps: i32) {
+    let handler = RcBlock::new(move |error: *mut NSError| {
         if !error.is_null() {
             let err = unsafe { format!("{}", &*error) };
-            let _ = tx.send(Err(err));
+            println!("[zureshot] Warning: frame rate update failed: {}", err);
         } else {
-            let _ = tx.send(Ok(()));
+            println!("[zureshot] Capture frame rate now {}fps", fps);
         }
     });
     unsafe {
-        stream.startCaptureWithCompletionHandler(Some(&start_handler));
+        config.setMinimumFrameInterval(CMTime::new(1, fps));
+        stream.updateConfiguration_completionHandler(config, Some(&handler));
     }
-    rx.recv()
-        .map_err(|_| "Capture start channel closed".to_string())?
-        .map_err(|e| format!("Failed to start capture: {}", e))?;
+}
 
-    // The stream retains the delegate via addStreamOutput.
-    // We must NOT drop the Rust Retained<StreamOutput> early though,
-    // as that would decrement the refcount. Leak it — the stream owns it now.
-    std::mem::forget(delegate);
+/// The captured display's color profile.
+pub struct DisplayColor {
+    /// Nearest standard tagging for the display's gamut.
+    pub tagging: ColorTagging,
+    /// Raw ICC profile data, if the display has one.
+    pub icc: Option<Vec<u8>>,
+}
+
+/// Query a display's color space (CGDisplayCopyColorSpace) and map it to
+/// the nearest tagging the encoder supports: wide-gamut → Display P3,
+/// everything else → BT.709.
+pub fn display_color(display_id: u32) -> DisplayColor {
+    extern "C" {
+        fn CGDisplayCopyColorSpace(display: u32) -> *const std::ffi::c_void;
+        fn CGColorSpaceIsWideGamutRGB(space: *const std::ffi::c_void) -> bool;
+        fn CGColorSpaceCopyICCData(space: *const std::ffi::c_void) -> *const std::ffi::c_void;
+        fn CFDataGetLength(data: *const std::ffi::c_void) -> isize;
+        fn CFDataGetBytePtr(data: *const std::ffi::c_void) -> *const u8;
+        fn CFRelease(cf: *const std::ffi::c_void);
+    }
 
-    Ok(stream)
+    unsafe {
+        let space = CGDisplayCopyColorSpace(display_id);
+        if space.is_null() {
+            return DisplayColor { tagging: ColorTagging::Bt709, icc: None };
+        }
+        let tagging = if CGColorSpaceIsWideGamutRGB(space) {
+            ColorTagging::DisplayP3
+        } else {
+            ColorTagging::Bt709
+        };
+        let data = CGColorSpaceCopyICCData(space);
+        let icc = if data.is_null() {
+            None
+        } else {
+            let len = CFDataGetLength(data).max(0) as usize;
+            let bytes = std::slice::from_raw_parts(CFDataGetBytePtr(data), len).to_vec();
+            CFRelease(data);
+            Some(bytes).filter(|b| !b.is_empty())
+        };
+        CFRelease(space);
+        DisplayColor { tagging, icc }
+    }
+}
+
+/// The display's refresh rate in Hz (CGDisplayModeGetRefreshRate), or
+/// `None` when it isn't reported — built-in panels often return 0.
+pub fn display_refresh_rate(display_id: u32) -> Option<i32> {
+    extern "C" {
+        fn CGDisplayCopyDisplayMode(display: u32) -> *const std::ffi::c_void;
+        fn CGDisplayModeGetRefreshRate(mode: *const std::ffi::c_void) -> f64;
+        fn CGDisplayModeRelease(mode: *const std::ffi::c_void);
+    }
+
+    unsafe {
+        let mode = CGDisplayCopyDisplayMode(display_id);
+        if mode.is_null() {
+            return None;
+        }
+        let hz = CGDisplayModeGetRefreshRate(mode);
+        CGDisplayModeRelease(mode);
+        Some(hz.round() as i32).filter(|&hz| hz > 0)
+    }
+}
+
+/// SCStreamErrorDomain codes that will not go away by retrying.
+const SCSTREAM_ERROR_USER_DECLINED: isize = -3801;
+const SCSTREAM_ERROR_MISSING_ENTITLEMENTS: isize = -3803;
+const SCSTREAM_ERROR_NO_CAPTURE_SOURCE: isize = -3815;
+
+/// Attempts (including the first) before a start failure is reported.
+const START_CAPTURE_ATTEMPTS: u32 = 3;
+/// Backoff before the first retry; doubled for each following one.
+const START_CAPTURE_BACKOFF_MS: u64 = 250;
+
+/// Start the stream, retrying transient failures (GPU busy, another app
+/// holding the capture service) with exponential backoff. Permission and
+/// missing-source errors fail immediately.
+pub(crate) fn start_with_retry(stream: &SCStream) -> Result<(), String> {
+    let mut attempt = 1;
+    loop {
+        let (tx, rx) = mpsc::channel();
+        let start_handler = RcBlock::new(move |error: *mut NSError| {
+            if !error.is_null() {
+                let err = unsafe { &*error };
+                let _ = tx.send(Err((err.code(), format!("{}", err))));
+            } else {
+                let _ = tx.send(Ok(()));
+            }
+        });
+        unsafe {
+            stream.startCaptureWithCompletionHandler(Some(&start_handler));
+        }
+        let (code, err) = match rx.recv() {
+            Ok(Ok(())) => {
+                if attempt > 1 {
+                    println!("[zureshot] Capture started on attempt {}", attempt);
+                }
+                return Ok(());
+            }
+            Ok(Err(e)) => e,
+            Err(_) => return Err("Capture start channel closed".to_string()),
+        };
+
+        let permanent = matches!(
+            code,
+            SCSTREAM_ERROR_USER_DECLINED
+                | SCSTREAM_ERROR_MISSING_ENTITLEMENTS
+                | SCSTREAM_ERROR_NO_CAPTURE_SOURCE
+        );
+        if permanent || attempt >= START_CAPTURE_ATTEMPTS {
+            return Err(format!("Failed to start capture: {}", err));
+        }
+
+        let backoff = START_CAPTURE_BACKOFF_MS << (attempt - 1);
+        println!(
+            "[zureshot] Capture start failed (code {}, attempt {}/{}), retrying in {}ms: {}",
+            code, attempt, START_CAPTURE_ATTEMPTS, backoff, err
+        );
+        std::thread::sleep(std::time::Duration::from_millis(backoff));
+        attempt += 1;
+    }
 }
 
 /// Stop the capture stream (blocking wait).
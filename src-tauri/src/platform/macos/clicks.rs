@@ -0,0 +1,44 @@
+//! Click monitor for the click highlight overlay.
+//!
+//! A listen-only `EventTap` for `kCGEventLeftMouseDown`; each click is
+//! handed to a callback with its global position (logical points, origin
+//! at the top-left of the main display, like `CaptureRegion`).
+
+use super::event_tap::EventTap;
+use objc2_core_foundation::CGPoint;
+use serde::Serialize;
+use std::ffi::c_void;
+
+/// One left click.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Click {
+    pub x: f64,
+    pub y: f64,
+}
+
+extern "C" {
+    fn CGEventGetLocation(event: *mut c_void) -> CGPoint;
+}
+
+/// kCGEventLeftMouseDown
+const LEFT_MOUSE_DOWN: u32 = 1;
+
+/// A running click tap. Stops (and releases the tap) when dropped.
+pub struct ClickMonitor {
+    _tap: EventTap,
+}
+
+impl ClickMonitor {
+    /// Install the tap and call `on_click` (from the tap thread) for every
+    /// left click until the monitor is dropped.
+    pub fn start(on_click: impl Fn(Click) + Send + 'static) -> Result<Self, String> {
+        let tap = EventTap::start("Click", 1 << LEFT_MOUSE_DOWN, move |event_type, event| {
+            if event_type != LEFT_MOUSE_DOWN {
+                return;
+            }
+            let point = unsafe { CGEventGetLocation(event) };
+            on_click(Click { x: point.x, y: point.y });
+        })?;
+        Ok(Self { _tap: tap })
+    }
+}
@@ -0,0 +1,659 @@
+//! Whole-desktop recording — every display composited into one video.
+//!
+//! Architecture:
+//!   SCStream per display → 420v CVPixelBuffer → kept as that display's
+//!                          newest frame (no copy, one lock per display)
+//!   compositor thread    → every frame interval, CoreImage composites the
+//!                          newest frames on the GPU into a buffer from the
+//!                          adaptor's pool at each display's place in the
+//!                          union rect
+//!                        → AVAssetWriterInputPixelBufferAdaptor → encoder
+//!
+//! ScreenCaptureKit only captures one display per stream, hence the canvas.
+//! Frames are stamped on the host clock (the clock SCK samples use), so the
+//! system audio / microphone from the audio carrier stream line up with
+//! them. Parts of the union rect no display covers stay black.
+//!
+//! All displays must use the same backing scale factor: mixing Retina and
+//! non-Retina displays would need per-display scaling, which isn't done.
+
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use dispatch2::DispatchQueue;
+use objc2::encode::{Encode, Encoding};
+use objc2::rc::{autoreleasepool, Retained};
+use objc2::runtime::{AnyClass, AnyObject, NSObject, NSObjectProtocol, ProtocolObject};
+use objc2::{class, define_class, msg_send, AllocAnyThread, DefinedClass};
+use objc2_av_foundation::AVAssetWriterInput;
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use objc2_core_graphics::{kCGColorSpaceSRGB, CGColorSpace};
+use objc2_core_media::{CMSampleBuffer, CMTime};
+use objc2_core_video::{CVPixelBuffer, CVPixelBufferPool};
+use objc2_foundation::{NSArray, NSNumber, NSString};
+use objc2_screen_capture_kit::{
+    SCCaptureResolutionType, SCContentFilter, SCDisplay, SCStream, SCStreamConfiguration,
+    SCStreamOutput, SCStreamOutputType, SCWindow,
+};
+
+use super::capture::{self, StreamOutput};
+use crate::platform::{FrameCounters, PixelFormat, VideoCodec};
+
+extern "C" {
+    fn CVPixelBufferRetain(pixel_buffer: *const c_void) -> *const c_void;
+    fn CVPixelBufferPoolCreatePixelBuffer(
+        allocator: *const c_void,
+        pool: *const CVPixelBufferPool,
+        pixel_buffer_out: *mut *mut CVPixelBuffer,
+    ) -> i32;
+    fn CGColorSpaceCreateWithName(name: *const c_void) -> *mut CGColorSpace;
+    fn CMClockGetHostTimeClock() -> *const c_void;
+    fn CMClockGetTime(clock: *const c_void) -> CMTime;
+    fn CFRelease(cf: *const c_void);
+}
+
+// CIImage / CIContext are looked up by name; make sure they're loaded
+#[link(name = "CoreImage", kind = "framework")]
+extern "C" {}
+
+/// CoreGraphics' affine transform, for `-[CIImage imageByApplyingTransform:]`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CGAffineTransform {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    tx: f64,
+    ty: f64,
+}
+
+unsafe impl Encode for CGAffineTransform {
+    const ENCODING: Encoding = Encoding::Struct("CGAffineTransform", &[f64::ENCODING; 6]);
+}
+
+impl CGAffineTransform {
+    fn translation(tx: f64, ty: f64) -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx, ty }
+    }
+}
+
+/// Largest frame side VideoToolbox encodes (HEVC / ProRes; H.264 stops at 4096).
+const MAX_HEVC_SIDE: usize = 8192;
+const MAX_H264_SIDE: usize = 4096;
+
+// ────────────────────────────────────────────────────────────────
+//  Layout
+// ────────────────────────────────────────────────────────────────
+
+/// One display's place on the canvas, in pixels.
+struct Placement {
+    display: Retained<SCDisplay>,
+    display_id: u32,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+/// All displays arranged as in System Settings, on one canvas.
+pub struct DesktopLayout {
+    /// Canvas size in pixels (the union rect at `scale`)
+    pub width: usize,
+    pub height: usize,
+    /// Backing scale factor shared by every display
+    pub scale: f64,
+    placements: Vec<Placement>,
+}
+
+impl DesktopLayout {
+    /// Lay out every display SCK lists. Fails if there are none or their
+    /// scale factors differ, or if the union is too large for `codec`.
+    pub fn all_displays(codec: VideoCodec) -> Result<Self, String> {
+        let displays = capture::all_displays()?;
+        if displays.is_empty() {
+            return Err("No displays available to record".into());
+        }
+
+        let scales: Vec<(u32, f64)> = displays
+            .iter()
+            .map(|d| {
+                let id: u32 = unsafe { msg_send![&**d, displayID] };
+                (id, capture::display_physical_size(d).2)
+            })
+            .collect();
+        let scale = scales[0].1;
+        if scales.iter().any(|&(_, s)| (s - scale).abs() > 0.01) {
+            let list: Vec<String> = scales.iter().map(|(id, s)| format!("display {} is {}x", id, s)).collect();
+            return Err(format!(
+                "Can't record all displays as one video: they use different scale factors ({}). \
+                 Record the displays separately, or set them to the same resolution scaling.",
+                list.join(", ")
+            ));
+        }
+
+        // Display frames are in global points, origin at the main display's top-left
+        let frames: Vec<CGRect> = displays.iter().map(|d| unsafe { d.frame() }).collect();
+        let min_x = frames.iter().map(|f| f.origin.x).fold(f64::INFINITY, f64::min);
+        let min_y = frames.iter().map(|f| f.origin.y).fold(f64::INFINITY, f64::min);
+        let max_x = frames.iter().map(|f| f.origin.x + f.size.width).fold(f64::NEG_INFINITY, f64::max);
+        let max_y = frames.iter().map(|f| f.origin.y + f.size.height).fold(f64::NEG_INFINITY, f64::max);
+        let width = crate::platform::even_pixels(max_x - min_x, scale);
+        let height = crate::platform::even_pixels(max_y - min_y, scale);
+
+        let max_side = match codec {
+            VideoCodec::H264 => MAX_H264_SIDE,
+            VideoCodec::Hevc | VideoCodec::ProRes422 => MAX_HEVC_SIDE,
+        };
+        if width > max_side || height > max_side {
+            return Err(format!(
+                "The displays together are {}x{} pixels, more than the encoder supports ({} per side{})",
+                width,
+                height,
+                max_side,
+                if codec == VideoCodec::H264 { " for H.264 — try HEVC" } else { "" }
+            ));
+        }
+
+        // Offsets stay even so the half-resolution chroma plane lines up
+        let placements = displays
+            .into_iter()
+            .zip(frames)
+            .zip(scales)
+            .map(|((display, frame), (display_id, _))| Placement {
+                display,
+                display_id,
+                x: (((frame.origin.x - min_x) * scale).round() as usize) & !1,
+                y: (((frame.origin.y - min_y) * scale).round() as usize) & !1,
+                width: crate::platform::even_pixels(frame.size.width, scale),
+                height: crate::platform::even_pixels(frame.size.height, scale),
+            })
+            .collect();
+
+        Ok(Self { width, height, scale, placements })
+    }
+
+    pub fn display_count(&self) -> usize {
+        self.placements.len()
+    }
+}
+
+// ────────────────────────────────────────────────────────────────
+//  Canvas
+// ────────────────────────────────────────────────────────────────
+
+/// A retained CVPixelBufferRef, released on drop.
+struct PixelBufferRef(*mut CVPixelBuffer);
+
+// SAFETY: CVPixelBuffers are reference counted and thread-safe to retain,
+// release and read.
+unsafe impl Send for PixelBufferRef {}
+
+impl PixelBufferRef {
+    unsafe fn retain(pixel_buffer: *const c_void) -> Self {
+        Self(CVPixelBufferRetain(pixel_buffer) as *mut CVPixelBuffer)
+    }
+}
+
+impl Clone for PixelBufferRef {
+    fn clone(&self) -> Self {
+        unsafe { Self::retain(self.0 as *const c_void) }
+    }
+}
+
+impl Drop for PixelBufferRef {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.0 as *const c_void) };
+    }
+}
+
+/// One display's newest frame. Each display has its own lock, held only to
+/// swap or take a reference to the frame, so a display's stream never
+/// waits for another display or for the compositor's GPU work.
+struct LayerFrame {
+    /// Bottom-left corner on the canvas (CoreImage's origin is bottom-left)
+    x: f64,
+    y: f64,
+    latest: Mutex<Option<PixelBufferRef>>,
+}
+
+/// The composited desktop: the displays' newest frames, drawn together on
+/// the GPU by CoreImage into a 420v buffer once per output frame.
+struct Canvas {
+    width: usize,
+    height: usize,
+    layers: Vec<LayerFrame>,
+    /// Set once any display has delivered a frame
+    drawn: AtomicBool,
+    context: Retained<AnyObject>,
+    /// Black, canvas-sized: what no display covers
+    background: Retained<AnyObject>,
+    /// Matches the displays' streams (`kCGColorSpaceSRGB`)
+    color_space: *mut CGColorSpace,
+}
+
+// SAFETY: CIContext and CIImage are thread-safe; the color space is
+// immutable and released on drop.
+unsafe impl Send for Canvas {}
+unsafe impl Sync for Canvas {}
+
+impl Canvas {
+    fn new(layout: &DesktopLayout) -> Result<Self, String> {
+        let layers = layout
+            .placements
+            .iter()
+            .map(|p| LayerFrame {
+                x: p.x as f64,
+                y: layout.height.saturating_sub(p.y + p.height) as f64,
+                latest: Mutex::new(None),
+            })
+            .collect();
+        let bounds = CGRect::new(
+            CGPoint::new(0.0, 0.0),
+            CGSize::new(layout.width as f64, layout.height as f64),
+        );
+        unsafe {
+            // A Metal-backed context: compositing runs on the GPU
+            let context: Option<Retained<AnyObject>> =
+                msg_send![class!(CIContext), contextWithOptions: Option::<&AnyObject>::None];
+            let context = context.ok_or("Failed to create the CoreImage context")?;
+            let black: Retained<AnyObject> = msg_send![class!(CIColor), blackColor];
+            let infinite: Retained<AnyObject> = msg_send![class!(CIImage), imageWithColor: &*black];
+            let background: Retained<AnyObject> = msg_send![&*infinite, imageByCroppingToRect: bounds];
+            let color_space = CGColorSpaceCreateWithName(kCGColorSpaceSRGB as *const _ as *const c_void);
+            if color_space.is_null() {
+                return Err("Failed to create the sRGB color space".into());
+            }
+            Ok(Self {
+                width: layout.width,
+                height: layout.height,
+                layers,
+                drawn: AtomicBool::new(false),
+                context,
+                background,
+                color_space,
+            })
+        }
+    }
+
+    /// Keep a 420v frame (CVPixelBufferRef) as display `layer`'s newest.
+    unsafe fn draw(&self, layer: usize, pixel_buffer: *const c_void) {
+        let Some(layer) = self.layers.get(layer) else { return };
+        let frame = PixelBufferRef::retain(pixel_buffer);
+        // The replaced frame is released after the lock is dropped
+        let _previous = match layer.latest.lock() {
+            Ok(mut latest) => latest.replace(frame),
+            Err(_) => return,
+        };
+        self.drawn.store(true, Ordering::Relaxed);
+    }
+
+    /// Composite the newest frames into a buffer from `pool` (+1 retained;
+    /// caller releases). A fresh buffer per frame: the encoder may still
+    /// hold the last one, and the pool recycles them once it lets go.
+    unsafe fn snapshot(&self, pool: *const CVPixelBufferPool) -> Option<*mut CVPixelBuffer> {
+        if pool.is_null() {
+            return None;
+        }
+        let mut buffer: *mut CVPixelBuffer = std::ptr::null_mut();
+        if CVPixelBufferPoolCreatePixelBuffer(std::ptr::null(), pool, &mut buffer) != 0 || buffer.is_null() {
+            return None;
+        }
+        autoreleasepool(|_| {
+            let mut image = self.background.clone();
+            for layer in &self.layers {
+                // The CIImage retains the frame; the lock is only held to clone it
+                let Some(frame) = layer.latest.lock().ok().and_then(|f| f.clone()) else {
+                    continue;
+                };
+                let frame_image: Option<Retained<AnyObject>> =
+                    msg_send![class!(CIImage), imageWithCVPixelBuffer: &*frame.0];
+                let Some(frame_image) = frame_image else { continue };
+                let placed: Retained<AnyObject> = msg_send![
+                    &*frame_image,
+                    imageByApplyingTransform: CGAffineTransform::translation(layer.x, layer.y)
+                ];
+                image = msg_send![&*placed, imageByCompositingOverImage: &*image];
+            }
+            let bounds = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(self.width as f64, self.height as f64));
+            let () = msg_send![
+                &*self.context,
+                render: &*image,
+                toCVPixelBuffer: &*buffer,
+                bounds: bounds,
+                colorSpace: &*self.color_space
+            ];
+        });
+        Some(buffer)
+    }
+}
+
+impl Drop for Canvas {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.color_space as *const c_void) };
+    }
+}
+
+// ────────────────────────────────────────────────────────────────
+//  LayerOutput — SCStreamOutput delegate drawing one display
+// ────────────────────────────────────────────────────────────────
+
+pub struct LayerOutputIvars {
+    canvas: Arc<Canvas>,
+    /// Index of this display in `Canvas::layers`
+    layer: usize,
+}
+
+define_class!(
+    // SAFETY: called on the layer's serial dispatch queue; the canvas is Sync.
+    #[unsafe(super(NSObject))]
+    #[thread_kind = AllocAnyThread]
+    #[name = "ZSDesktopLayerOutput"]
+    #[ivars = LayerOutputIvars]
+    pub struct LayerOutput;
+
+    unsafe impl NSObjectProtocol for LayerOutput {}
+
+    unsafe impl SCStreamOutput for LayerOutput {
+        #[unsafe(method(stream:didOutputSampleBuffer:ofType:))]
+        fn stream_didOutputSampleBuffer_ofType(
+            &self,
+            _stream: &SCStream,
+            sample_buffer: &CMSampleBuffer,
+            output_type: SCStreamOutputType,
+        ) {
+            if output_type.0 != 0 {
+                return;
+            }
+            unsafe {
+                if !sample_buffer.is_valid() || !sample_buffer.data_is_ready() {
+                    return;
+                }
+                // Status-only frames (Idle, Blank…) carry no image — the
+                // canvas keeps showing the display's last frame
+                let Some(image) = sample_buffer.image_buffer() else {
+                    return;
+                };
+                let ivars = self.ivars();
+                ivars.canvas.draw(ivars.layer, &*image as *const _ as *const c_void);
+            }
+        }
+    }
+);
+
+impl LayerOutput {
+    fn new_with(canvas: Arc<Canvas>, layer: usize) -> Retained<Self> {
+        let this = Self::alloc().set_ivars(LayerOutputIvars { canvas, layer });
+        unsafe { msg_send![super(this), init] }
+    }
+}
+
+// ────────────────────────────────────────────────────────────────
+//  Compositor
+// ────────────────────────────────────────────────────────────────
+
+/// Create the pixel buffer adaptor the compositor appends through. Its
+/// source attributes (420v, the canvas size, IOSurface-backed so CoreImage
+/// can render into them on the GPU) give it a `pixelBufferPool` the
+/// compositor draws each frame's buffer from. Must be called before the
+/// writer starts; the pool exists once it has.
+pub fn pixel_buffer_adaptor(
+    input: &AVAssetWriterInput,
+    width: usize,
+    height: usize,
+) -> Result<Retained<AnyObject>, String> {
+    let cls = AnyClass::get(c"AVAssetWriterInputPixelBufferAdaptor")
+        .ok_or("AVAssetWriterInputPixelBufferAdaptor unavailable")?;
+    let adaptor: Option<Retained<AnyObject>> = unsafe {
+        let attributes: Retained<AnyObject> = msg_send![class!(NSMutableDictionary), new];
+        // kCVPixelBufferPixelFormatTypeKey, kCVPixelBufferWidthKey,
+        // kCVPixelBufferHeightKey, kCVPixelBufferIOSurfacePropertiesKey
+        let format = NSNumber::new_u32(PixelFormat::Yuv420Video.fourcc());
+        let () = msg_send![&*attributes, setObject: &*format, forKey: &*NSString::from_str("PixelFormatType")];
+        let width = NSNumber::new_usize(width);
+        let () = msg_send![&*attributes, setObject: &*width, forKey: &*NSString::from_str("Width")];
+        let height = NSNumber::new_usize(height);
+        let () = msg_send![&*attributes, setObject: &*height, forKey: &*NSString::from_str("Height")];
+        let io_surface: Retained<AnyObject> = msg_send![class!(NSDictionary), dictionary];
+        let () = msg_send![&*attributes, setObject: &*io_surface, forKey: &*NSString::from_str("IOSurfaceProperties")];
+        msg_send![
+            cls,
+            assetWriterInputPixelBufferAdaptorWithAssetWriterInput: input,
+            sourcePixelBufferAttributes: &*attributes
+        ]
+    };
+    adaptor.ok_or_else(|| "Failed to create the pixel buffer adaptor".into())
+}
+
+/// Everything the compositor thread touches.
+struct Compositor {
+    canvas: Arc<Canvas>,
+    input: Retained<AVAssetWriterInput>,
+    adaptor: Retained<AnyObject>,
+    /// Audio carrier delegate — owns the writer session start
+    carrier: Retained<StreamOutput>,
+    running: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    fps: Arc<AtomicI32>,
+    counters: Arc<FrameCounters>,
+}
+
+// SAFETY: AVAssetWriterInput and the adaptor are used from this thread only
+// while it runs; the carrier's shared state is atomic.
+unsafe impl Send for Compositor {}
+
+impl Compositor {
+    fn run(self) {
+        let host_clock = unsafe { CMClockGetHostTimeClock() };
+        let mut next = Instant::now();
+        while self.running.load(Ordering::Relaxed) {
+            let interval = Duration::from_secs_f64(1.0 / self.fps.load(Ordering::Relaxed).max(1) as f64);
+            next += interval;
+            let now = Instant::now();
+            if next > now {
+                std::thread::sleep(next - now);
+            } else {
+                // Fell behind — carry on from now rather than bursting frames
+                next = now;
+            }
+
+            if self.paused.load(Ordering::Relaxed) || !self.canvas.drawn.load(Ordering::Relaxed) {
+                continue;
+            }
+            let pts = unsafe { CMClockGetTime(host_clock) };
+            self.carrier.start_session_once(pts, "desktop compositor");
+
+            let ready: bool = unsafe { msg_send![&*self.input, isReadyForMoreMediaData] };
+            if !ready {
+                self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            let pool: *const CVPixelBufferPool = unsafe { msg_send![&*self.adaptor, pixelBufferPool] };
+            let Some(buffer) = (unsafe { self.canvas.snapshot(pool) }) else {
+                self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                continue;
+            };
+            let ok = unsafe {
+                let ok: bool = msg_send![&*self.adaptor, appendPixelBuffer: &*buffer, withPresentationTime: pts];
+                CFRelease(buffer as *const c_void);
+                ok
+            };
+            if ok {
+                self.counters.frames.fetch_add(1, Ordering::Relaxed);
+            } else {
+                if self.counters.dropped.fetch_add(1, Ordering::Relaxed) < 5 {
+                    println!("[zureshot] Desktop frame append failed");
+                }
+                self.carrier.report_external_append_failure();
+            }
+        }
+        println!("[zureshot] Desktop compositor stopped");
+    }
+}
+
+// ────────────────────────────────────────────────────────────────
+//  DesktopCapture
+// ────────────────────────────────────────────────────────────────
+
+/// One display's running stream.
+struct Layer {
+    display: Retained<SCDisplay>,
+    stream: Retained<SCStream>,
+    config: Retained<SCStreamConfiguration>,
+    _output: Retained<LayerOutput>,
+}
+
+/// The per-display streams and the compositor of a whole-desktop recording.
+pub struct DesktopCapture {
+    layers: Vec<Layer>,
+    running: Arc<AtomicBool>,
+    fps: Arc<AtomicI32>,
+    compositor: Mutex<Option<std::thread::JoinHandle<()>>>,
+}
+
+// SAFETY: access is serialized via the Mutex<RecordingState> in commands.rs.
+unsafe impl Send for DesktopCapture {}
+unsafe impl Sync for DesktopCapture {}
+
+impl DesktopCapture {
+    /// Start a stream per display and the compositor appending to `input`
+    /// through `adaptor`. The writer must already be writing; `carrier`
+    /// starts its session (see `StreamOutput::start_session_once`).
+    pub fn start(
+        layout: &DesktopLayout,
+        input: Retained<AVAssetWriterInput>,
+        adaptor: Retained<AnyObject>,
+        carrier: Retained<StreamOutput>,
+        exclude_windows: &[Retained<SCWindow>],
+        fps: i32,
+        shows_cursor: bool,
+        paused: Arc<AtomicBool>,
+        counters: Arc<FrameCounters>,
+    ) -> Result<Self, String> {
+        let canvas = Arc::new(Canvas::new(layout)?);
+        let running = Arc::new(AtomicBool::new(true));
+        let fps = Arc::new(AtomicI32::new(fps));
+
+        let mut capture = Self {
+            layers: Vec::new(),
+            running: running.clone(),
+            fps: fps.clone(),
+            compositor: Mutex::new(None),
+        };
+        for (index, p) in layout.placements.iter().enumerate() {
+            match start_layer(p, index, canvas.clone(), exclude_windows, fps.load(Ordering::Relaxed), shows_cursor) {
+                Ok(layer) => capture.layers.push(layer),
+                Err(e) => {
+                    capture.stop();
+                    return Err(format!("Failed to capture display {}: {}", p.display_id, e));
+                }
+            }
+            println!(
+                "[zureshot] Desktop layer: display {} {}x{} at ({}, {})",
+                p.display_id, p.width, p.height, p.x, p.y
+            );
+        }
+
+        let compositor = Compositor { canvas, input, adaptor, carrier, running, paused, fps, counters };
+        let thread = std::thread::Builder::new()
+            .name("zureshot-desktop-compositor".into())
+            .spawn(move || compositor.run());
+        match thread {
+            Ok(thread) => {
+                if let Ok(mut slot) = capture.compositor.lock() {
+                    *slot = Some(thread);
+                }
+                Ok(capture)
+            }
+            Err(e) => {
+                capture.stop();
+                Err(format!("Failed to start the desktop compositor: {}", e))
+            }
+        }
+    }
+
+    /// Stop the compositor, then every display stream (blocks until done).
+    /// Nothing is appended to the writer once this returns.
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.compositor.lock().ok().and_then(|mut t| t.take()) {
+            if thread.join().is_err() {
+                eprintln!("[zureshot] Desktop compositor thread panicked");
+            }
+        }
+        for layer in &self.layers {
+            capture::stop(&layer.stream);
+        }
+    }
+
+    /// Change the output frame rate (and each display stream's with it).
+    pub fn set_frame_rate(&self, fps: i32) {
+        self.fps.store(fps, Ordering::Relaxed);
+        for layer in &self.layers {
+            capture::update_frame_rate(&layer.stream, &layer.config, fps);
+        }
+    }
+
+    /// Re-apply the window exclusion list to every display stream.
+    pub fn update_exclusion(&self, exclude_windows: &[Retained<SCWindow>]) -> Result<(), String> {
+        for layer in &self.layers {
+            capture::update_stream_filter(&layer.stream, &layer.display, exclude_windows.to_vec())?;
+        }
+        Ok(())
+    }
+}
+
+/// Start one display's 420v stream feeding canvas layer `index`.
+fn start_layer(
+    p: &Placement,
+    index: usize,
+    canvas: Arc<Canvas>,
+    exclude_windows: &[Retained<SCWindow>],
+    fps: i32,
+    shows_cursor: bool,
+) -> Result<Layer, String> {
+    let config = unsafe {
+        let c = SCStreamConfiguration::new();
+        c.setWidth(p.width);
+        c.setHeight(p.height);
+        c.setMinimumFrameInterval(CMTime::new(1, fps));
+        c.setShowsCursor(shows_cursor);
+        c.setPixelFormat(PixelFormat::Yuv420Video.fourcc());
+        // One more than usual: the canvas keeps each display's newest frame
+        c.setQueueDepth(4);
+        c.setCaptureResolution(SCCaptureResolutionType::Best);
+        c.setColorSpaceName(kCGColorSpaceSRGB);
+        c.setShouldBeOpaque(true);
+        c.setDestinationRect(CGRect::new(
+            CGPoint::new(0.0, 0.0),
+            CGSize::new(p.width as f64, p.height as f64),
+        ));
+        c
+    };
+
+    let refs: Vec<&SCWindow> = exclude_windows.iter().map(|w| &**w).collect();
+    let exclude_array = NSArray::from_slice(&refs);
+    let filter = unsafe {
+        SCContentFilter::initWithDisplay_excludingWindows(SCContentFilter::alloc(), &p.display, &exclude_array)
+    };
+
+    let output = LayerOutput::new_with(canvas, index);
+    let stream = unsafe {
+        SCStream::initWithFilter_configuration_delegate(SCStream::alloc(), &filter, &config, None)
+    };
+    let queue = DispatchQueue::new("com.zureshot.desktop-layer", None);
+    unsafe {
+        stream
+            .addStreamOutput_type_sampleHandlerQueue_error(
+                ProtocolObject::from_ref(&*output),
+                SCStreamOutputType(0),
+                Some(&queue),
+            )
+            .map_err(|e| format!("Failed to add desktop stream output: {}", e))?;
+    }
+    capture::start_with_retry(&stream)?;
+
+    Ok(Layer { display: p.display.clone(), stream, config, _output: output })
+}
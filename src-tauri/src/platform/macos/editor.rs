@@ -10,6 +10,8 @@
 use serde::{Deserialize, Serialize};
 use std::process::Command;
 
+use crate::platform::VideoCodec;
+
 /// Video metadata returned to the frontend.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct VideoMetadata {
@@ -45,6 +47,24 @@ pub struct WaveformData {
     pub total_duration: f64,
 }
 
+/// Result of extracting still frames from a video.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FrameExportResult {
+    pub output_dir: String,
+    pub frames: usize,
+    pub total_bytes: u64,
+}
+
+/// Result of concatenating several recordings into one file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MergeResult {
+    pub output_path: String,
+    pub duration_secs: f64,
+    pub file_size_bytes: u64,
+    /// True when the inputs matched and were joined without re-encoding.
+    pub stream_copy: bool,
+}
+
 /// Export progress info.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExportProgress {
@@ -83,6 +103,65 @@ pub struct ZoomKeyframe {
 
 fn default_hold() -> f64 { 0.5 }
 
+/// Tuning for post-hoc zoom (`apply_zoom`).
+/// Re-run with different values to re-tune zoom without re-recording.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ZoomConfig {
+    /// Upper bound for any zoom level (1.0 - 4.0)
+    #[serde(default = "default_max_zoom")]
+    pub max_zoom: f64,
+    /// Scales how far suggested zooms go above 1x (0.0 = none, 1.0 = as suggested)
+    #[serde(default = "default_zoom_strength")]
+    pub strength: f64,
+    /// Multiplier on each keyframe's hold duration
+    #[serde(default = "default_hold_scale")]
+    pub hold_scale: f64,
+    /// Explicit keyframes — skip cursor analysis and use these instead
+    #[serde(default)]
+    pub keyframes: Option<Vec<ZoomKeyframe>>,
+    /// Zoom in on every click (centered on the click point), not just on
+    /// places where the cursor dwells
+    #[serde(default = "default_zoom_on_click")]
+    pub zoom_on_click: bool,
+    /// How long a click zoom holds at peak before easing out (seconds,
+    /// before `hold_scale`)
+    #[serde(default = "default_click_zoom_hold_secs")]
+    pub click_zoom_hold_secs: f64,
+}
+
+fn default_max_zoom() -> f64 { 2.5 }
+fn default_zoom_strength() -> f64 { 1.0 }
+fn default_hold_scale() -> f64 { 1.0 }
+fn default_zoom_on_click() -> bool { true }
+fn default_click_zoom_hold_secs() -> f64 { 0.35 }
+
+impl ZoomConfig {
+    /// Click-zoom hold for `suggest_zoom_keyframes`, `None` when click zooms
+    /// are off.
+    pub fn click_zoom_hold(&self) -> Option<f64> {
+        self.zoom_on_click.then(|| {
+            if self.click_zoom_hold_secs.is_finite() {
+                self.click_zoom_hold_secs.clamp(0.1, 5.0)
+            } else {
+                default_click_zoom_hold_secs()
+            }
+        })
+    }
+}
+
+impl Default for ZoomConfig {
+    fn default() -> Self {
+        Self {
+            max_zoom: default_max_zoom(),
+            strength: default_zoom_strength(),
+            hold_scale: default_hold_scale(),
+            keyframes: None,
+            zoom_on_click: default_zoom_on_click(),
+            click_zoom_hold_secs: default_click_zoom_hold_secs(),
+        }
+    }
+}
+
 /// Cursor overlay settings for export.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CursorSettings {
@@ -476,7 +555,7 @@ pub fn generate_waveform(path: &str, num_samples: usize) -> Result<WaveformData,
     let num_samples = num_samples.max(50).min(2000);
 
     // Extract raw PCM audio using ffmpeg (optional — falls back to flat waveform)
-    let output = match Command::new("ffmpeg")
+    let output = match crate::ffmpeg::command()
         .args([
             "-i", path,
             "-ac", "1",
@@ -580,7 +659,7 @@ pub fn trim_video(
     // Remove existing output
     let _ = std::fs::remove_file(output_path);
 
-    let output = Command::new("ffmpeg")
+    let output = crate::ffmpeg::command()
         .args([
             "-ss", &format!("{:.3}", start_secs),
             "-i", input_path,
@@ -612,6 +691,195 @@ pub fn trim_video(
     Ok(output_path.to_string())
 }
 
+// ═══════════════════════════════════════════════════════════════════════
+//  Audio Extraction
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Whether `a` and `b` name the same file. `b` need not exist yet.
+fn same_file(a: &str, b: &str) -> bool {
+    let resolve = |p: &str| {
+        let path = std::path::Path::new(p);
+        let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(std::path::Path::new("."));
+        match (std::fs::canonicalize(dir), path.file_name()) {
+            (Ok(dir), Some(name)) => dir.join(name),
+            _ => path.to_path_buf(),
+        }
+    };
+    resolve(a) == resolve(b)
+}
+
+/// Write only the audio of a recording to `output_path`.
+/// `format` is "m4a" (default), "wav" or "mp3". `track` picks from
+/// two-track recordings (system audio first, microphone second, as the
+/// recorder writes them): "system", "mic" or "mixed" (default). Recordings
+/// with a single track always export that track.
+pub fn extract_audio(
+    input_path: &str,
+    output_path: &str,
+    format: &str,
+    track: &str,
+) -> Result<String, String> {
+    let codec: &[&str] = match format.to_ascii_lowercase().as_str() {
+        "" | "m4a" => &["-c:a", "aac", "-b:a", "192k"],
+        "wav" => &["-c:a", "pcm_s16le"],
+        "mp3" => &["-c:a", "libmp3lame", "-q:a", "2"],
+        other => return Err(format!("Unsupported audio format: {}", other)),
+    };
+    let track = match track.to_ascii_lowercase().as_str() {
+        "" | "mixed" => "mixed",
+        "system" => "system",
+        "mic" | "microphone" => "mic",
+        other => return Err(format!("Unknown audio track: {} (expected system, mic or mixed)", other)),
+    };
+
+    if same_file(input_path, output_path) {
+        return Err(format!("The audio can't be written over its own recording: {}", output_path));
+    }
+
+    let tracks = crate::ffmpeg::media_info(input_path)?.audio.len();
+    if tracks == 0 {
+        return Err("Recording has no audio track".into());
+    }
+    let select: &[&str] = match (tracks, track) {
+        (1, _) => &["-map", "0:a:0"],
+        (_, "system") => &["-map", "0:a:0"],
+        (_, "mic") => &["-map", "0:a:1"],
+        _ => &[
+            "-filter_complex", "[0:a:0][0:a:1]amix=inputs=2:duration=longest:normalize=0[a]",
+            "-map", "[a]",
+        ],
+    };
+    // A single AAC track goes into .m4a untouched
+    let codec: &[&str] = if select.len() == 2 && codec[1] == "aac" { &["-c:a", "copy"] } else { codec };
+
+    println!(
+        "[editor] Extracting audio {} → {} ({})",
+        input_path,
+        output_path,
+        if tracks == 1 { "single track".to_string() } else { format!("{} of {} tracks", track, tracks) }
+    );
+
+    // Written next to the output and renamed over it once ffmpeg succeeds,
+    // so a failed export never costs an existing file
+    let out = std::path::Path::new(output_path);
+    let ext = out.extension().and_then(|e| e.to_str()).unwrap_or("m4a");
+    let temp = out.with_extension(format!("extracting.{}", ext)).to_string_lossy().to_string();
+
+    let mut args: Vec<&str> = vec!["-i", input_path, "-vn"];
+    args.extend_from_slice(select);
+    args.extend_from_slice(codec);
+    args.extend_from_slice(&["-y", &temp]);
+    let output = crate::ffmpeg::command()
+        .args(&args)
+        .output()
+        .map_err(|e| format!("ffmpeg audio extraction failed: {}", e))?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temp);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Audio extraction error: {}", stderr));
+    }
+    std::fs::rename(&temp, output_path).map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        format!("Failed to write {}: {}", output_path, e)
+    })?;
+
+    let file_size = std::fs::metadata(output_path).map(|m| m.len()).unwrap_or(0);
+    println!(
+        "[editor] Audio extraction complete: {} ({:.1} MB)",
+        output_path,
+        file_size as f64 / 1_048_576.0
+    );
+
+    Ok(output_path.to_string())
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Frame Export (numbered stills)
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Extract frames as `frame_000001.<ext>` images into `output_dir`.
+/// `fps` = None keeps every source frame; e.g. 1.0 gives a storyboard.
+/// `format` is "png" (default) or "jpg".
+pub fn export_frames(
+    input_path: &str,
+    fps: Option<f64>,
+    output_dir: &str,
+    format: &str,
+) -> Result<FrameExportResult, String> {
+    let ext = match format.to_ascii_lowercase().as_str() {
+        "" | "png" => "png",
+        "jpg" | "jpeg" => "jpg",
+        other => return Err(format!("Unsupported frame format: {}", other)),
+    };
+    if let Some(f) = fps {
+        if !(f.is_finite() && f > 0.0) {
+            return Err(format!("Invalid frame rate: {}", f));
+        }
+    }
+
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Failed to create {}: {}", output_dir, e))?;
+
+    println!(
+        "[editor] Exporting frames {} → {} ({}, {})",
+        input_path,
+        output_dir,
+        fps.map(|f| format!("{} fps", f)).unwrap_or_else(|| "full rate".into()),
+        ext
+    );
+
+    let pattern = std::path::Path::new(output_dir)
+        .join(format!("frame_%06d.{}", ext))
+        .to_string_lossy()
+        .to_string();
+
+    let mut args: Vec<String> = vec!["-i".into(), input_path.into()];
+    if let Some(f) = fps {
+        args.extend(["-vf".into(), format!("fps={}", f)]);
+    } else {
+        // One image per decoded frame, no duplication/dropping
+        args.extend(["-fps_mode".into(), "passthrough".into()]);
+    }
+    if ext == "jpg" {
+        args.extend(["-q:v".into(), "2".into()]);
+    }
+    args.extend(["-an".into(), "-y".into(), pattern]);
+
+    let output = crate::ffmpeg::command()
+        .args(&args)
+        .output()
+        .map_err(|e| format!("ffmpeg frame export failed: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ffmpeg frame export error: {}", stderr));
+    }
+
+    let suffix = format!(".{}", ext);
+    let (frames, total_bytes) = std::fs::read_dir(output_dir)
+        .map_err(|e| format!("Failed to read {}: {}", output_dir, e))?
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.starts_with("frame_") && name.ends_with(&suffix)
+        })
+        .fold((0usize, 0u64), |(n, bytes), e| {
+            (n + 1, bytes + e.metadata().map(|m| m.len()).unwrap_or(0))
+        });
+
+    println!(
+        "[editor] Frame export complete: {} frames ({:.1} MB)",
+        frames,
+        total_bytes as f64 / 1_048_576.0
+    );
+
+    Ok(FrameExportResult {
+        output_dir: output_dir.to_string(),
+        frames,
+        total_bytes,
+    })
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 //  Render Single Preview Frame
 // ═══════════════════════════════════════════════════════════════════════
@@ -631,7 +899,7 @@ pub fn render_preview_frame(
 
     // Preview frames are rendered client-side (CSS) for real-time feedback.
     // This function extracts a raw frame for edge cases / server-side rendering.
-    let output = Command::new("ffmpeg")
+    let output = crate::ffmpeg::command()
         .args([
             "-hwaccel", "videotoolbox",
             "-ss", &format!("{:.3}", time_secs),
@@ -914,6 +1182,742 @@ fn prepend_zoom_to_filter(
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════
+//  Post-hoc Zoom (record wide, zoom in post)
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Render a zoomed copy of a wide recording from its cursor sidecar.
+///
+/// The source is never modified: the viewport path is computed offline
+/// (same keyframe model as the editor preview and export) and rendered
+/// with a per-frame zoompan crop/scale. `cursor_json` is the
+/// `.mousetrack.json` written next to the recording.
+pub fn apply_zoom(
+    input_path: &str,
+    cursor_json: &str,
+    config: &ZoomConfig,
+    output_path: &str,
+) -> Result<String, String> {
+    let keyframes = match &config.keyframes {
+        Some(kfs) => kfs.clone(),
+        None => {
+            let json = std::fs::read_to_string(cursor_json)
+                .map_err(|e| format!("Cursor track not found: {}", e))?;
+            let track: super::mouse_tracker::MouseTrack = serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse cursor track: {}", e))?;
+            super::mouse_tracker::suggest_zoom_keyframes(&track, config.click_zoom_hold())
+        }
+    };
+
+    let max_zoom = config.max_zoom.clamp(1.0, 4.0);
+    let strength = config.strength.clamp(0.0, 1.0);
+    let hold_scale = config.hold_scale.clamp(0.1, 5.0);
+    let keyframes: Vec<ZoomKeyframe> = keyframes
+        .into_iter()
+        .map(|mut kf| {
+            kf.zoom = (1.0 + (kf.zoom - 1.0) * strength).clamp(1.0, max_zoom);
+            kf.hold *= hold_scale;
+            kf
+        })
+        .filter(|kf| kf.zoom > 1.001)
+        .collect();
+
+    if keyframes.is_empty() {
+        return Err("No zoom regions found for this recording".into());
+    }
+
+    let meta = get_video_metadata(input_path)?;
+    let filter = prepend_zoom_to_filter("", &keyframes, meta.width, meta.height, 0.0, meta.fps);
+
+    println!(
+        "[editor] Post-hoc zoom: {} → {} ({} keyframes, max {:.1}x)",
+        input_path, output_path, keyframes.len(), max_zoom
+    );
+
+    let _ = std::fs::remove_file(output_path);
+
+    let run = |encoder: &[&str]| -> Result<std::process::Output, String> {
+        let mut args: Vec<&str> = vec![
+            "-i", input_path,
+            "-filter_complex", &filter,
+            "-map", "[out]",
+            "-map", "0:a?",
+        ];
+        args.extend_from_slice(encoder);
+        args.extend_from_slice(&[
+            "-movflags", "+faststart",
+            "-c:a", "copy",
+            "-y", output_path,
+        ]);
+        crate::ffmpeg::command()
+            .args(&args)
+            .output()
+            .map_err(|e| format!("ffmpeg zoom export failed: {}", e))
+    };
+
+    let output = run(&["-c:v", "hevc_videotoolbox", "-q:v", "60", "-tag:v", "hvc1"])?;
+    if !output.status.success() {
+        println!("[editor] Hardware encoding failed, falling back to software");
+        let output = run(&["-c:v", "libx264", "-preset", "fast", "-crf", "20", "-pix_fmt", "yuv420p"])?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Zoom export failed: {}", stderr));
+        }
+    }
+
+    let file_size = std::fs::metadata(output_path).map(|m| m.len()).unwrap_or(0);
+    println!(
+        "[editor] Post-hoc zoom complete: {} ({:.1} MB)",
+        output_path, file_size as f64 / 1_048_576.0
+    );
+
+    Ok(output_path.to_string())
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Letterbox Background (fill when padding to a target aspect)
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Fill for the parts of a padded frame the source doesn't cover.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LetterboxBackground {
+    /// `#RRGGBB` or `#RRGGBBAA`; alpha is blended over black.
+    Color { rgba: String },
+    /// Blurred, zoomed-to-fill copy of the source
+    BlurredSource,
+    /// Still image, zoomed to fill
+    Image { path: String },
+}
+
+impl Default for LetterboxBackground {
+    fn default() -> Self {
+        Self::Color { rgba: "#000000".into() }
+    }
+}
+
+impl LetterboxBackground {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Color { rgba } => {
+                let hex = rgba.trim_start_matches('#');
+                if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!("Invalid letterbox color: {} (expected #RRGGBB or #RRGGBBAA)", rgba));
+                }
+            }
+            Self::BlurredSource => {}
+            Self::Image { path } => {
+                if !std::path::Path::new(path).is_file() {
+                    return Err(format!("Letterbox image not found: {}", path));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Extra ffmpeg inputs, added after the video inputs; their index is
+    /// what `filter` takes as `extra_input`.
+    pub fn inputs(&self) -> Vec<String> {
+        match self {
+            Self::Image { path } => vec!["-loop".into(), "1".into(), "-i".into(), path.clone()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Filter graph that runs `[src]` through `fg` (which must fit within
+    /// `size`) and centers it over the background, labelled `[out]`.
+    pub fn filter(&self, src: &str, fg: &str, size: (u32, u32), fps: f64, extra_input: usize, out: &str) -> String {
+        let (w, h) = size;
+        let overlay = format!("overlay=(W-w)/2:(H-h)/2:shortest=1,setsar=1,format=yuv420p[{out}]");
+        match self {
+            Self::Color { rgba } => {
+                let (r, g, b) = hex_to_ffmpeg_color(rgba);
+                let hex = rgba.trim_start_matches('#');
+                let a = if hex.len() >= 8 { u32::from_str_radix(&hex[6..8], 16).unwrap_or(255) } else { 255 };
+                let blend = |c: u32| c * a / 255;
+                format!(
+                    "color=c=0x{:02x}{:02x}{:02x}:s={w}x{h}:r={fps}[{out}_bg];\
+[{src}]{fg}[{out}_fg];[{out}_bg][{out}_fg]{overlay}",
+                    blend(r), blend(g), blend(b)
+                )
+            }
+            Self::BlurredSource => format!(
+                "[{src}]split[{out}_bgsrc][{out}_fgsrc];\
+[{out}_bgsrc]scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h},boxblur=20:2[{out}_bg];\
+[{out}_fgsrc]{fg}[{out}_fg];[{out}_bg][{out}_fg]{overlay}"
+            ),
+            Self::Image { .. } => format!(
+                "[{extra_input}:v]scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h},\
+setsar=1,fps={fps}[{out}_bg];\
+[{src}]{fg}[{out}_fg];[{out}_bg][{out}_fg]{overlay}"
+            ),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Vertical Export (9:16 for Shorts / TikTok / Reels)
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Options for `export_vertical`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerticalExportConfig {
+    /// Pan a square window along the cursor path (from the
+    /// `.mousetrack.json` sidecar); otherwise show the whole frame.
+    #[serde(default = "default_follow_cursor")]
+    pub follow_cursor: bool,
+    #[serde(default = "default_vertical_background")]
+    pub background: LetterboxBackground,
+}
+
+fn default_follow_cursor() -> bool { true }
+
+fn default_vertical_background() -> LetterboxBackground { LetterboxBackground::BlurredSource }
+
+impl Default for VerticalExportConfig {
+    fn default() -> Self {
+        Self { follow_cursor: default_follow_cursor(), background: default_vertical_background() }
+    }
+}
+
+/// Tallest vertical output (1080×1920)
+const VERTICAL_MAX_HEIGHT: u32 = 1920;
+/// Cursor-follow pan updates per second
+const FOLLOW_RATE_HZ: f64 = 10.0;
+/// Moving-average window (seconds) that keeps the pan from twitching
+const FOLLOW_SMOOTHING_SECS: f64 = 0.8;
+/// Numbers the pan scripts of concurrent vertical exports
+static VERTICAL_EXPORT_SEQ: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Crop offsets (source pixels) along the cursor path as a `sendcmd`
+/// script for the `crop@follow` filter.
+fn follow_commands(
+    track: &super::mouse_tracker::MouseTrack,
+    src_w: u32,
+    crop_w: u32,
+) -> String {
+    let max_x = src_w.saturating_sub(crop_w) as f64;
+    let half = FOLLOW_SMOOTHING_SECS / 2.0;
+    let steps = (track.duration_secs * FOLLOW_RATE_HZ).ceil() as usize;
+
+    let mut script = String::new();
+    let mut last_x = -1.0;
+    for i in 0..=steps {
+        let t = i as f64 / FOLLOW_RATE_HZ;
+        let window: Vec<f64> = track
+            .samples
+            .iter()
+            .filter(|s| (s.time - t).abs() <= half)
+            .map(|s| track.normalize(s.x, s.y).0)
+            .collect();
+        if window.is_empty() {
+            continue;
+        }
+        let nx = window.iter().sum::<f64>() / window.len() as f64;
+        let x = (nx * src_w as f64 - crop_w as f64 / 2.0).clamp(0.0, max_x).round();
+        if x != last_x {
+            script.push_str(&format!("{:.2} crop@follow x {};\n", t, x));
+            last_x = x;
+        }
+    }
+    script
+}
+
+/// Render a 9:16 vertical copy of a recording.
+///
+/// With `follow_cursor`, a square window (full source height) pans along
+/// the smoothed cursor path and fills the frame's width; without it the
+/// whole frame is fitted to the width. The rest is filled with
+/// `config.background`. The source is never modified.
+pub fn export_vertical(
+    input_path: &str,
+    config: &VerticalExportConfig,
+    output_path: &str,
+) -> Result<String, String> {
+    config.background.validate()?;
+    let meta = get_video_metadata(input_path)?;
+    if meta.width == 0 || meta.height == 0 {
+        return Err("Could not read the recording's dimensions".into());
+    }
+
+    let out_h = crate::platform::even_dimension(meta.height.min(VERTICAL_MAX_HEIGHT) as usize) as u32;
+    let out_w = crate::platform::even_dimension((out_h as f64 * 9.0 / 16.0) as usize) as u32;
+
+    // Foreground: cursor-following square crop, or the whole frame
+    let track = if config.follow_cursor {
+        match super::mouse_tracker::load_mouse_track(input_path) {
+            Ok(track) if !track.samples.is_empty() => Some(track),
+            _ => {
+                println!("[editor] Vertical export: no cursor track, centering instead");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let crop_w = crate::platform::even_dimension(meta.width.min(meta.height) as usize) as u32;
+    let seq = VERTICAL_EXPORT_SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let cmd_path = std::env::temp_dir().join(format!("zureshot_vertical_{}_{}.cmd", std::process::id(), seq));
+    let foreground = match track {
+        Some(ref track) => {
+            std::fs::write(&cmd_path, follow_commands(track, meta.width, crop_w))
+                .map_err(|e| format!("Failed to write pan script: {}", e))?;
+            format!(
+                "sendcmd=f='{}',crop@follow={}:{}:{}:0,scale={}:-2",
+                cmd_path.to_string_lossy(),
+                crop_w, meta.height, (meta.width - crop_w) / 2, out_w
+            )
+        }
+        None => format!("scale={}:-2", out_w),
+    };
+
+    let filter = config.background.filter("0:v", &foreground, (out_w, out_h), meta.fps.max(1.0), 1, "out");
+    let bg_inputs = config.background.inputs();
+
+    println!(
+        "[editor] Vertical export: {} → {} ({}x{}, follow_cursor={})",
+        input_path, output_path, out_w, out_h, track.is_some()
+    );
+
+    let _ = std::fs::remove_file(output_path);
+
+    let run = |encoder: &[&str]| -> Result<std::process::Output, String> {
+        let mut args: Vec<&str> = vec!["-i", input_path];
+        args.extend(bg_inputs.iter().map(String::as_str));
+        args.extend_from_slice(&[
+            "-filter_complex", &filter,
+            "-map", "[out]",
+            "-map", "0:a?",
+        ]);
+        args.extend_from_slice(encoder);
+        args.extend_from_slice(&[
+            "-movflags", "+faststart",
+            "-c:a", "copy",
+            "-y", output_path,
+        ]);
+        crate::ffmpeg::command()
+            .args(&args)
+            .output()
+            .map_err(|e| format!("ffmpeg vertical export failed: {}", e))
+    };
+
+    let result = run(&["-c:v", "hevc_videotoolbox", "-q:v", "60", "-tag:v", "hvc1"]).and_then(|output| {
+        if output.status.success() {
+            return Ok(());
+        }
+        println!("[editor] Hardware encoding failed, falling back to software");
+        let output = run(&["-c:v", "libx264", "-preset", "fast", "-crf", "20", "-pix_fmt", "yuv420p"])?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Vertical export failed: {}", stderr));
+        }
+        Ok(())
+    });
+    let _ = std::fs::remove_file(&cmd_path);
+    result?;
+
+    let file_size = std::fs::metadata(output_path).map(|m| m.len()).unwrap_or(0);
+    println!(
+        "[editor] Vertical export complete: {} ({:.1} MB)",
+        output_path, file_size as f64 / 1_048_576.0
+    );
+
+    Ok(output_path.to_string())
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Merge Recordings (concat)
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Concatenate recordings in order into `output_path`.
+///
+/// Inputs with identical resolution, codec and audio layout are joined with
+/// the concat demuxer (stream copy, near-instant). Anything else is
+/// re-encoded through the concat filter: every clip is letterboxed to the
+/// first clip's size over `background` and clips without audio get
+/// silence, so the audio track stays in sync.
+pub fn merge_recordings(
+    paths: &[String],
+    output_path: &str,
+    background: &LetterboxBackground,
+) -> Result<MergeResult, String> {
+    if paths.len() < 2 {
+        return Err("Need at least two recordings to merge".into());
+    }
+    background.validate()?;
+
+    let metas = paths
+        .iter()
+        .map(|p| get_video_metadata(p))
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = &metas[0];
+
+    let mismatches: Vec<String> = metas
+        .iter()
+        .skip(1)
+        .filter(|m| {
+            m.width != first.width
+                || m.height != first.height
+                || m.codec != first.codec
+                || m.has_audio != first.has_audio
+        })
+        .map(|m| format!("{} ({}x{} {}, audio={})", m.path, m.width, m.height, m.codec, m.has_audio))
+        .collect();
+    let stream_copy = mismatches.is_empty();
+    if !stream_copy {
+        println!(
+            "[editor] WARNING: merge inputs differ from {} ({}x{} {}, audio={}) — re-encoding: {}",
+            first.path, first.width, first.height, first.codec, first.has_audio,
+            mismatches.join(", ")
+        );
+    }
+
+    println!(
+        "[editor] Merging {} recordings → {} ({})",
+        paths.len(), output_path, if stream_copy { "stream copy" } else { "re-encode" }
+    );
+
+    let _ = std::fs::remove_file(output_path);
+
+    if stream_copy {
+        // Concat demuxer list file: one `file '<path>'` per line
+        let list_path = std::env::temp_dir().join(format!(
+            "zureshot_merge_{}.txt",
+            chrono::Local::now().format("%Y%m%d_%H%M%S%3f")
+        ));
+        let list: String = paths
+            .iter()
+            .map(|p| format!("file '{}'\n", p.replace('\'', "'\\''")))
+            .collect();
+        std::fs::write(&list_path, list).map_err(|e| format!("Failed to write concat list: {}", e))?;
+        let list_str = list_path.to_string_lossy().to_string();
+
+        let output = crate::ffmpeg::command()
+            .args([
+                "-f", "concat",
+                "-safe", "0",
+                "-i", &list_str,
+                "-c", "copy",
+            ])
+            .args(VideoCodec::of_file(&paths[0]).stream_tag_args())
+            .args(["-movflags", "+faststart", "-y", output_path])
+            .output();
+        let _ = std::fs::remove_file(&list_path);
+        let output = output.map_err(|e| format!("ffmpeg merge failed: {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("ffmpeg merge error: {}", stderr));
+        }
+    } else {
+        let (w, h) = (first.width & !1, first.height & !1);
+        let fps = if first.fps > 0.0 { first.fps } else { 30.0 };
+        let any_audio = metas.iter().any(|m| m.has_audio);
+
+        // Each clip gets its own copy of any background input
+        let bg_inputs = background.inputs();
+        let mut filter = String::new();
+        let mut concat_inputs = String::new();
+        for (i, m) in metas.iter().enumerate() {
+            let fit = format!("scale={w}:{h}:force_original_aspect_ratio=decrease,setsar=1,fps={fps}");
+            filter.push_str(&background.filter(&format!("{i}:v"), &fit, (w, h), fps, paths.len() + i, &format!("v{i}")));
+            filter.push(';');
+            concat_inputs.push_str(&format!("[v{i}]"));
+            if any_audio {
+                if m.has_audio {
+                    filter.push_str(&format!(
+                        "[{i}:a]aresample=48000,aformat=channel_layouts=stereo[a{i}];"
+                    ));
+                } else {
+                    filter.push_str(&format!(
+                        "anullsrc=r=48000:cl=stereo,atrim=duration={:.3}[a{i}];",
+                        m.duration_secs
+                    ));
+                }
+                concat_inputs.push_str(&format!("[a{i}]"));
+            }
+        }
+        filter.push_str(&format!(
+            "{concat_inputs}concat=n={}:v=1:a={}[outv]{}",
+            metas.len(),
+            if any_audio { 1 } else { 0 },
+            if any_audio { "[outa]" } else { "" }
+        ));
+
+        let run = |encoder: &[&str]| -> Result<std::process::Output, String> {
+            let mut args: Vec<&str> = Vec::new();
+            for p in paths {
+                args.extend_from_slice(&["-i", p.as_str()]);
+            }
+            for _ in paths {
+                args.extend(bg_inputs.iter().map(String::as_str));
+            }
+            args.extend_from_slice(&["-filter_complex", &filter, "-map", "[outv]"]);
+            if any_audio {
+                args.extend_from_slice(&["-map", "[outa]", "-c:a", "aac", "-b:a", "128k"]);
+            }
+            args.extend_from_slice(encoder);
+            args.extend_from_slice(&["-movflags", "+faststart", "-y", output_path]);
+            crate::ffmpeg::command()
+                .args(&args)
+                .output()
+                .map_err(|e| format!("ffmpeg merge failed: {}", e))
+        };
+
+        let output = run(&["-c:v", "hevc_videotoolbox", "-q:v", "60", "-tag:v", "hvc1"])?;
+        if !output.status.success() {
+            println!("[editor] Hardware encoding failed, falling back to software");
+            let output = run(&["-c:v", "libx264", "-preset", "fast", "-crf", "20", "-pix_fmt", "yuv420p"])?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("Merge failed: {}", stderr));
+            }
+        }
+    }
+
+    let file_size = std::fs::metadata(output_path).map(|m| m.len()).unwrap_or(0);
+    let duration_secs = get_video_metadata(output_path)
+        .map(|m| m.duration_secs)
+        .unwrap_or_else(|_| metas.iter().map(|m| m.duration_secs).sum());
+
+    println!(
+        "[editor] Merge complete: {} ({:.1}s, {:.1} MB)",
+        output_path, duration_secs, file_size as f64 / 1_048_576.0
+    );
+
+    Ok(MergeResult {
+        output_path: output_path.to_string(),
+        duration_secs,
+        file_size_bytes: file_size,
+        stream_copy,
+    })
+}
+
+/// VideoToolbox encoder arguments that keep a recording in `codec` when it
+/// has to be re-encoded. `quality` is the `-q:v` value (0–100).
+fn hardware_encoder_args(codec: VideoCodec, quality: &str) -> Vec<&str> {
+    match codec {
+        VideoCodec::Hevc => vec!["-c:v", "hevc_videotoolbox", "-q:v", quality, "-tag:v", "hvc1"],
+        VideoCodec::H264 => vec!["-c:v", "h264_videotoolbox", "-q:v", quality, "-tag:v", "avc1"],
+        // ProRes has no quality knob; "standard" is ProRes 422
+        VideoCodec::ProRes422 => vec!["-c:v", "prores_videotoolbox", "-profile:v", "standard"],
+    }
+}
+
+/// Software fallback for `hardware_encoder_args`. HEVC falls back to H.264
+/// (libx265 is rarely built in); `crf` is the x264 CRF.
+fn software_encoder_args(codec: VideoCodec, crf: &str) -> Vec<&str> {
+    match codec {
+        VideoCodec::Hevc | VideoCodec::H264 => {
+            vec!["-c:v", "libx264", "-preset", "fast", "-crf", crf, "-pix_fmt", "yuv420p"]
+        }
+        VideoCodec::ProRes422 => vec!["-c:v", "prores_ks", "-profile:v", "2", "-pix_fmt", "yuv422p10le"],
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Constant Frame Rate
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Re-time the recording at `path` to a constant `fps`, in place.
+///
+/// ScreenCaptureKit only delivers a frame when the screen changes, so raw
+/// recordings are VFR. This duplicates frames to fill the gaps (audio is
+/// copied untouched), for editors and uploaders that mishandle VFR.
+pub fn convert_to_cfr(path: &str, fps: i32) -> Result<(), String> {
+    let source = std::path::Path::new(path);
+    let dir = source.parent().unwrap_or(std::path::Path::new("."));
+    let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("recording");
+    let ext = source.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+    let temp = dir.join(format!(".{}_cfr.{}", stem, ext)).to_string_lossy().to_string();
+    let fps_str = fps.to_string();
+    let codec = VideoCodec::of_file(path);
+
+    println!("[editor] Converting to constant {}fps: {}", fps, path);
+
+    let run = |encoder: &[&str]| -> Result<std::process::Output, String> {
+        let mut args: Vec<&str> = vec!["-i", path, "-map", "0", "-fps_mode", "cfr", "-r", &fps_str];
+        args.extend_from_slice(encoder);
+        args.extend_from_slice(&["-c:a", "copy", "-movflags", "+faststart", "-y", &temp]);
+        crate::ffmpeg::command()
+            .args(&args)
+            .output()
+            .map_err(|e| format!("ffmpeg CFR conversion failed: {}", e))
+    };
+
+    let mut output = run(&hardware_encoder_args(codec, "65"))?;
+    if !output.status.success() {
+        println!("[editor] Hardware encoding failed, falling back to software");
+        output = run(&software_encoder_args(codec, "18"))?;
+    }
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temp);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("CFR conversion error: {}", stderr));
+    }
+
+    std::fs::rename(&temp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        format!("Failed to replace recording: {}", e)
+    })
+}
+
+// ═══════════════════════════════════════════════════════════════════════
+//  Intro / Outro Cards
+// ═══════════════════════════════════════════════════════════════════════
+
+/// Pixel size of an image file (via `sips`, no decode dependency).
+fn image_size(path: &str) -> Result<(u32, u32), String> {
+    let output = Command::new("sips")
+        .args(["-g", "pixelWidth", "-g", "pixelHeight", path])
+        .output()
+        .map_err(|e| format!("sips failed: {}", e))?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let value = |key: &str| {
+        text.lines()
+            .find_map(|l| l.trim().strip_prefix(key))
+            .and_then(|v| v.trim_start_matches(':').trim().parse::<u32>().ok())
+    };
+    match (value("pixelWidth"), value("pixelHeight")) {
+        (Some(w), Some(h)) if w > 0 && h > 0 => Ok((w, h)),
+        _ => Err(format!("Not a readable image: {}", path)),
+    }
+}
+
+/// Render a still image into a clip matching `meta` (size, fps, codec and
+/// audio layout), so it can be stream-copied next to the recording.
+/// Mismatched aspect ratios are letterboxed over `background`.
+fn encode_card(
+    image: &str,
+    meta: &VideoMetadata,
+    duration: f64,
+    background: &LetterboxBackground,
+    output_path: &str,
+) -> Result<(), String> {
+    let (img_w, img_h) = image_size(image)?;
+    let (w, h) = (meta.width & !1, meta.height & !1);
+    if (img_w, img_h) != (w, h) {
+        println!("[editor] Card {} is {}x{} — scaling to {}x{}", image, img_w, img_h, w, h);
+    }
+
+    let fps = if meta.fps > 0.0 { meta.fps } else { 30.0 };
+    let fit = format!("scale={w}:{h}:force_original_aspect_ratio=decrease,setsar=1");
+    // Inputs: 0 = card, 1 = silence (if any), then the background's
+    let bg_index = if meta.has_audio { 2 } else { 1 };
+    let filter = background.filter("0:v", &fit, (w, h), fps, bg_index, "out");
+    let bg_inputs = background.inputs();
+    let fps_str = format!("{}", fps);
+    let dur_str = format!("{:.3}", duration);
+    let audio_src = format!(
+        "anullsrc=r={}:cl={}",
+        if meta.audio_sample_rate > 0 { meta.audio_sample_rate } else { 48000 },
+        if meta.audio_channels == 1 { "mono" } else { "stereo" }
+    );
+
+    let run = |encoder: &[&str]| -> Result<std::process::Output, String> {
+        let mut args: Vec<&str> = vec!["-loop", "1", "-framerate", &fps_str, "-t", &dur_str, "-i", image];
+        if meta.has_audio {
+            args.extend_from_slice(&["-f", "lavfi", "-t", &dur_str, "-i", &audio_src]);
+        }
+        args.extend(bg_inputs.iter().map(String::as_str));
+        args.extend_from_slice(&["-filter_complex", &filter, "-map", "[out]"]);
+        if meta.has_audio {
+            args.extend_from_slice(&["-map", "1:a"]);
+        }
+        args.extend_from_slice(&["-r", &fps_str]);
+        args.extend_from_slice(encoder);
+        if meta.has_audio {
+            args.extend_from_slice(&["-c:a", "aac", "-b:a", "128k", "-shortest"]);
+        }
+        args.extend_from_slice(&["-movflags", "+faststart", "-y", output_path]);
+        crate::ffmpeg::command()
+            .args(&args)
+            .output()
+            .map_err(|e| format!("ffmpeg card encode failed: {}", e))
+    };
+
+    let hw: &[&str] = if meta.codec == "h264" {
+        &["-c:v", "h264_videotoolbox", "-q:v", "60"]
+    } else {
+        &["-c:v", "hevc_videotoolbox", "-q:v", "60", "-tag:v", "hvc1"]
+    };
+    let output = run(hw)?;
+    if !output.status.success() {
+        // Codec mismatch just makes the merge re-encode
+        println!("[editor] Hardware card encoding failed, falling back to software");
+        let output = run(&["-c:v", "libx264", "-preset", "fast", "-crf", "20"])?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Card encode error: {}", stderr));
+        }
+    }
+    Ok(())
+}
+
+/// Prepend/append title and end cards to the recording at `path`, in place.
+///
+/// Cards are encoded to match the recording so the join is normally a
+/// stream copy; `merge_recordings` re-encodes if they still differ.
+/// Returns the seconds added before and after the original content (to
+/// shift chapter markers and other timelines, and extend the duration).
+/// Cards of a different aspect are letterboxed over `background`.
+pub fn add_intro_outro(
+    path: &str,
+    config: &crate::platform::IntroOutroConfig,
+    background: &LetterboxBackground,
+) -> Result<(f64, f64), String> {
+    if !config.is_enabled() {
+        return Ok((0.0, 0.0));
+    }
+    config.validate()?;
+
+    let meta = get_video_metadata(path)?;
+    let source = std::path::Path::new(path);
+    let dir = source.parent().unwrap_or(std::path::Path::new("."));
+    let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("recording");
+    let temp = |suffix: &str| dir.join(format!(".{}_{}.mp4", stem, suffix)).to_string_lossy().to_string();
+
+    let mut parts = Vec::new();
+    let mut temps = Vec::new();
+    let mut intro_secs = 0.0;
+    let mut outro_secs = 0.0;
+    let mut result = Ok(());
+
+    if let Some(ref image) = config.intro_image {
+        let card = temp("intro");
+        result = encode_card(image, &meta, config.duration_secs, background, &card);
+        temps.push(card.clone());
+        parts.push(card);
+        intro_secs = config.duration_secs;
+    }
+    parts.push(path.to_string());
+    if let (Ok(()), Some(image)) = (&result, config.outro_image.as_ref()) {
+        let card = temp("outro");
+        result = encode_card(image, &meta, config.duration_secs, background, &card);
+        temps.push(card.clone());
+        parts.push(card);
+        outro_secs = config.duration_secs;
+    }
+
+    let merged = temp("cards");
+    let result = result
+        .and_then(|_| merge_recordings(&parts, &merged, background))
+        .and_then(|_| {
+            std::fs::rename(&merged, path).map_err(|e| format!("Failed to replace recording: {}", e))
+        });
+    for t in temps.iter().chain(std::iter::once(&merged)) {
+        let _ = std::fs::remove_file(t);
+    }
+    result?;
+
+    println!(
+        "[editor] Added {}{} card(s) to {}",
+        if config.intro_image.is_some() { "intro " } else { "" },
+        if config.outro_image.is_some() { "outro " } else { "" },
+        path
+    );
+    Ok((intro_secs, outro_secs))
+}
+
 // ═══════════════════════════════════════════════════════════════════════
 //  Cursor Overlay Filter (ffmpeg drawbox for cursor dot + click highlight)
 // ═══════════════════════════════════════════════════════════════════════
@@ -1346,40 +2350,32 @@ fn export_multi_segment(
 ) -> Result<String, String> {
     let tmp_dir = std::env::temp_dir().join("zureshot_cuts");
     let _ = std::fs::create_dir_all(&tmp_dir);
+    let codec = VideoCodec::of_file(input_path);
 
     // Step 1: Export each segment as a separate file
     let mut part_paths: Vec<String> = Vec::new();
     for (i, seg) in segments.iter().enumerate() {
-        let part_path = tmp_dir.join(format!("part_{}.mp4", i)).to_string_lossy().to_string();
+        let part_path = tmp_dir
+            .join(format!("part_{}.{}", i, codec.file_extension()))
+            .to_string_lossy()
+            .to_string();
         let dur = seg.end - seg.start;
+        let start = format!("{:.3}", seg.start);
+        let length = format!("{:.3}", dur);
 
-        let output = Command::new("ffmpeg")
-            .args([
-                "-ss", &format!("{:.3}", seg.start),
-                "-i", input_path,
-                "-t", &format!("{:.3}", dur),
-                "-c:v", "hevc_videotoolbox",
-                "-q:v", "60",
-                "-tag:v", "hvc1",
-                "-c:a", "aac",
-                "-b:a", "128k",
-                "-y", &part_path,
-            ])
+        let output = crate::ffmpeg::command()
+            .args(["-ss", &start, "-i", input_path, "-t", &length])
+            .args(hardware_encoder_args(codec, "60"))
+            .args(["-c:a", "aac", "-b:a", "128k", "-y", &part_path])
             .output()
             .map_err(|e| format!("ffmpeg segment {} failed: {}", i, e))?;
 
         if !output.status.success() {
             // Fallback to software encoding
-            let output = Command::new("ffmpeg")
-                .args([
-                    "-ss", &format!("{:.3}", seg.start),
-                    "-i", input_path,
-                    "-t", &format!("{:.3}", dur),
-                    "-c:v", "libx264", "-preset", "fast", "-crf", "20",
-                    "-pix_fmt", "yuv420p",
-                    "-c:a", "aac", "-b:a", "128k",
-                    "-y", &part_path,
-                ])
+            let output = crate::ffmpeg::command()
+                .args(["-ss", &start, "-i", input_path, "-t", &length])
+                .args(software_encoder_args(codec, "20"))
+                .args(["-c:a", "aac", "-b:a", "128k", "-y", &part_path])
                 .output()
                 .map_err(|e| format!("ffmpeg segment {} SW failed: {}", i, e))?;
 
@@ -1404,7 +2400,7 @@ fn export_multi_segment(
     let _ = std::fs::remove_file(output_path);
 
     if as_gif {
-        let output = Command::new("ffmpeg")
+        let output = crate::ffmpeg::command()
             .args([
                 "-f", "concat", "-safe", "0", "-i", &list_path,
                 "-vf", "fps=15,scale='min(640,iw)':-1:flags=lanczos,split[s0][s1];[s0]palettegen[p];[s1][p]paletteuse",
@@ -1418,7 +2414,7 @@ fn export_multi_segment(
             return Err(format!("GIF concat failed: {}", stderr));
         }
     } else {
-        let output = Command::new("ffmpeg")
+        let output = crate::ffmpeg::command()
             .args([
                 "-f", "concat", "-safe", "0", "-i", &list_path,
                 "-c", "copy",
@@ -1457,15 +2453,12 @@ pub fn export_video(
     use tauri::Emitter;
 
     let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    let base = dirs::download_dir()
-        .or_else(dirs::home_dir)
-        .unwrap_or_else(|| std::path::PathBuf::from("."));
-    let zureshot_dir = base.join("Zureshot");
-    let _ = std::fs::create_dir_all(&zureshot_dir);
+    let zureshot_dir = crate::settings::recordings_dir(app);
 
+    // ProRes sources stay ProRes, which needs a QuickTime container
     let ext = match project.output_format.as_str() {
         "gif" => "gif",
-        _ => "mp4",
+        _ => VideoCodec::of_file(&project.source_path).file_extension(),
     };
     let output_path = zureshot_dir
         .join(format!("zureshot_edited_{}.{}", timestamp, ext))
@@ -1480,6 +2473,19 @@ pub fn export_video(
         error: None,
     });
 
+    // Cursor hidden at capture (standard-size mode) → always draw the overlay
+    let mut project = project.clone();
+    if project.cursor.is_none() {
+        let hidden = super::mouse_tracker::load_mouse_track(&project.source_path)
+            .map(|t| !t.cursor_in_video)
+            .unwrap_or(false);
+        if hidden {
+            println!("[editor] Cursor was not captured — drawing standard-size cursor overlay");
+            project.cursor = Some(CursorSettings::default());
+        }
+    }
+    let project = &project;
+
     let meta = get_video_metadata(&project.source_path)?;
     let has_segments = !project.segments.is_empty();
     let needs_trim = project.trim_start > 0.1 || (meta.duration_secs - project.trim_end).abs() > 0.1;
@@ -1688,7 +2694,7 @@ fn export_composited_video(
         output_path.to_string(),
     ]);
 
-    let output = Command::new("ffmpeg")
+    let output = crate::ffmpeg::command()
         .args(&args)
         .output()
         .map_err(|e| format!("ffmpeg composited export failed: {}", e))?;
@@ -1771,7 +2777,7 @@ fn export_composited_video_sw(
         output_path.to_string(),
     ]);
 
-    let output = Command::new("ffmpeg")
+    let output = crate::ffmpeg::command()
         .args(&args)
         .output()
         .map_err(|e| format!("ffmpeg SW composited export failed: {}", e))?;
@@ -1831,7 +2837,7 @@ fn export_composited_gif(
         );
     }
 
-    let output = Command::new("ffmpeg")
+    let output = crate::ffmpeg::command()
         .args([
             "-ss", &format!("{:.3}", start_secs),
             "-i", input_path,
@@ -1866,7 +2872,7 @@ fn export_as_gif(
     );
 
     let _ = std::fs::remove_file(output_path);
-    let output = Command::new("ffmpeg")
+    let output = crate::ffmpeg::command()
         .args([
             "-ss", &format!("{:.3}", start_secs),
             "-i", input_path,
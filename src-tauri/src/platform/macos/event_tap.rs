@@ -0,0 +1,161 @@
+//! Listen-only CGEventTap on its own thread and run loop, shared by the
+//! keystroke and click overlays.
+//!
+//! Creating a tap needs the Accessibility permission; without it
+//! `CGEventTapCreate` returns NULL and `EventTap::start` fails.
+
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::mpsc;
+
+type CGEventTapCallBack =
+    extern "C" fn(proxy: *mut c_void, event_type: u32, event: *mut c_void, user_info: *mut c_void) -> *mut c_void;
+
+extern "C" {
+    fn CGEventTapCreate(
+        tap: u32,
+        place: u32,
+        options: u32,
+        events_of_interest: u64,
+        callback: CGEventTapCallBack,
+        user_info: *mut c_void,
+    ) -> *mut c_void;
+    fn CGEventTapEnable(tap: *mut c_void, enable: bool);
+    fn CFMachPortCreateRunLoopSource(allocator: *const c_void, port: *mut c_void, order: isize) -> *mut c_void;
+    fn CFRunLoopGetCurrent() -> *mut c_void;
+    fn CFRunLoopAddSource(run_loop: *mut c_void, source: *mut c_void, mode: *const c_void);
+    fn CFRunLoopRun();
+    fn CFRunLoopStop(run_loop: *mut c_void);
+    fn CFRelease(cf: *const c_void);
+    static kCFRunLoopCommonModes: *const c_void;
+}
+
+/// kCGSessionEventTap
+const SESSION_EVENT_TAP: u32 = 1;
+/// kCGHeadInsertEventTap
+const HEAD_INSERT_EVENT_TAP: u32 = 0;
+/// kCGEventTapOptionListenOnly
+const LISTEN_ONLY: u32 = 1;
+/// kCGEventTapDisabledByTimeout / kCGEventTapDisabledByUserInput
+const TAP_DISABLED_BY_TIMEOUT: u32 = 0xFFFF_FFFE;
+const TAP_DISABLED_BY_USER_INPUT: u32 = 0xFFFF_FFFF;
+
+/// Handler for one event: (CGEventType, CGEventRef valid for the call).
+type EventHandler = Box<dyn Fn(u32, *mut c_void) + Send>;
+
+/// Passed to the tap callback as `user_info`; owned by the tap thread.
+struct TapContext {
+    on_event: EventHandler,
+    /// The tap itself, to re-enable it after a timeout
+    tap: AtomicPtr<c_void>,
+}
+
+extern "C" fn tap_callback(
+    _proxy: *mut c_void,
+    event_type: u32,
+    event: *mut c_void,
+    user_info: *mut c_void,
+) -> *mut c_void {
+    let ctx = unsafe { &*(user_info as *const TapContext) };
+    match event_type {
+        // macOS turns slow taps off; a listen-only tap just switches back on
+        TAP_DISABLED_BY_TIMEOUT | TAP_DISABLED_BY_USER_INPUT => {
+            let tap = ctx.tap.load(Ordering::Relaxed);
+            if !tap.is_null() {
+                unsafe { CGEventTapEnable(tap, true) };
+            }
+        }
+        _ => (ctx.on_event)(event_type, event),
+    }
+    // Listen-only taps ignore the return value
+    event
+}
+
+/// A running event tap. Stops (and releases the tap) when dropped.
+pub(super) struct EventTap {
+    name: &'static str,
+    run_loop: AtomicPtr<c_void>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl EventTap {
+    /// Install a tap for the event types in `events_mask` (`1 << type`)
+    /// and call `on_event` (from the tap thread) for each one until the
+    /// tap is dropped. `name` is used in logs.
+    pub(super) fn start(
+        name: &'static str,
+        events_mask: u64,
+        on_event: impl Fn(u32, *mut c_void) + Send + 'static,
+    ) -> Result<Self, String> {
+        let (tx, rx) = mpsc::channel::<Result<usize, String>>();
+        let thread = std::thread::spawn(move || unsafe {
+            let ctx = Box::into_raw(Box::new(TapContext {
+                on_event: Box::new(on_event),
+                tap: AtomicPtr::new(std::ptr::null_mut()),
+            }));
+            let tap = CGEventTapCreate(
+                SESSION_EVENT_TAP,
+                HEAD_INSERT_EVENT_TAP,
+                LISTEN_ONLY,
+                events_mask,
+                tap_callback,
+                ctx as *mut c_void,
+            );
+            if tap.is_null() {
+                drop(Box::from_raw(ctx));
+                let _ = tx.send(Err(format!(
+                    "Can't install the {} tap — grant Zureshot Accessibility access in System Settings > Privacy & Security",
+                    name.to_lowercase()
+                )));
+                return;
+            }
+            (*ctx).tap.store(tap, Ordering::Relaxed);
+
+            let source = CFMachPortCreateRunLoopSource(std::ptr::null(), tap, 0);
+            let run_loop = CFRunLoopGetCurrent();
+            CFRunLoopAddSource(run_loop, source, kCFRunLoopCommonModes);
+            CGEventTapEnable(tap, true);
+            let _ = tx.send(Ok(run_loop as usize));
+
+            // Until EventTap::drop stops the run loop
+            CFRunLoopRun();
+
+            CGEventTapEnable(tap, false);
+            CFRelease(source);
+            CFRelease(tap);
+            drop(Box::from_raw(ctx));
+            println!("[zureshot] {} tap removed", name);
+        });
+
+        match rx.recv() {
+            Ok(Ok(run_loop)) => {
+                println!("[zureshot] {} tap installed", name);
+                Ok(Self {
+                    name,
+                    run_loop: AtomicPtr::new(run_loop as *mut c_void),
+                    thread: Some(thread),
+                })
+            }
+            Ok(Err(e)) => {
+                let _ = thread.join();
+                Err(e)
+            }
+            Err(_) => Err(format!("{} tap thread exited unexpectedly", name)),
+        }
+    }
+}
+
+impl Drop for EventTap {
+    fn drop(&mut self) {
+        let run_loop = self.run_loop.swap(std::ptr::null_mut(), Ordering::Relaxed);
+        if !run_loop.is_null() {
+            // CFRunLoopStop may be called from any thread
+            unsafe { CFRunLoopStop(run_loop) };
+        }
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                eprintln!("[zureshot] {} tap thread panicked", self.name);
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Focused-window lookup via the Accessibility (AX) API.
+//!
+//! Resolves "the window I'm working in" — the focused window of the focused
+//! application — and matches it to an SCWindow by owning PID + frame, so it
+//! can be recorded without going through a picker.
+//!
+//! `raise_window` goes the other way: it brings a recorded SCWindow back to
+//! the front (activate its app, then `AXRaise` the matching AX window).
+
+use std::ffi::c_void;
+
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2::{class, msg_send};
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use objc2_screen_capture_kit::SCWindow;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+    fn AXUIElementCreateSystemWide() -> *const c_void;
+    fn AXUIElementCreateApplication(pid: i32) -> *const c_void;
+    fn AXUIElementPerformAction(element: *const c_void, action: *const c_void) -> i32;
+    fn AXUIElementCopyAttributeValue(
+        element: *const c_void,
+        attribute: *const c_void,
+        value: *mut *const c_void,
+    ) -> i32;
+    fn AXUIElementGetPid(element: *const c_void, pid: *mut i32) -> i32;
+    fn AXValueGetValue(value: *const c_void, value_type: u32, out: *mut c_void) -> bool;
+
+    fn CFStringCreateWithBytes(
+        alloc: *const c_void,
+        bytes: *const u8,
+        num_bytes: isize,
+        encoding: u32,
+        is_external: bool,
+    ) -> *const c_void;
+    fn CFRelease(cf: *const c_void);
+    fn CFArrayGetCount(array: *const c_void) -> isize;
+    fn CFArrayGetValueAtIndex(array: *const c_void, index: isize) -> *const c_void;
+}
+
+/// `NSApplicationActivateIgnoringOtherApps`
+const ACTIVATE_IGNORING_OTHER_APPS: usize = 1 << 1;
+
+/// kAXValueCGPointType / kAXValueCGSizeType
+const AX_VALUE_CGPOINT: u32 = 1;
+const AX_VALUE_CGSIZE: u32 = 2;
+
+/// Frames must match within this many points to count as the same window.
+const FRAME_TOLERANCE: f64 = 4.0;
+
+fn cfstring(s: &str) -> *const c_void {
+    unsafe {
+        CFStringCreateWithBytes(
+            std::ptr::null(),
+            s.as_ptr(),
+            s.len() as isize,
+            0x08000100, // kCFStringEncodingUTF8
+            false,
+        )
+    }
+}
+
+/// Copy an AX attribute; returns a +1 CF object or null.
+fn copy_attribute(element: *const c_void, name: &str) -> *const c_void {
+    let attr = cfstring(name);
+    let mut value: *const c_void = std::ptr::null();
+    let err = unsafe { AXUIElementCopyAttributeValue(element, attr, &mut value) };
+    unsafe { CFRelease(attr) };
+    if err != 0 {
+        return std::ptr::null();
+    }
+    value
+}
+
+/// PID and frame (logical points, top-left origin) of the focused window.
+///
+/// Returns `None` if Accessibility permission is not granted or nothing
+/// has keyboard focus.
+pub fn focused_window_frame() -> Option<(i32, CGRect)> {
+    if !unsafe { AXIsProcessTrusted() } {
+        println!("[zureshot] Accessibility permission not granted — cannot resolve focused window");
+        return None;
+    }
+
+    let system = unsafe { AXUIElementCreateSystemWide() };
+    if system.is_null() {
+        return None;
+    }
+    let app = copy_attribute(system, "AXFocusedApplication");
+    unsafe { CFRelease(system) };
+    if app.is_null() {
+        return None;
+    }
+
+    let mut pid: i32 = -1;
+    unsafe { AXUIElementGetPid(app, &mut pid) };
+    let window = copy_attribute(app, "AXFocusedWindow");
+    unsafe { CFRelease(app) };
+    if window.is_null() {
+        return None;
+    }
+
+    let frame = window_frame(window);
+    unsafe { CFRelease(window) };
+
+    if pid < 0 {
+        return None;
+    }
+    Some((pid, frame?))
+}
+
+/// Frame (logical points, top-left origin) of an AX window element.
+fn window_frame(window: *const c_void) -> Option<CGRect> {
+    let position = copy_attribute(window, "AXPosition");
+    let size = copy_attribute(window, "AXSize");
+
+    let mut origin = CGPoint::new(0.0, 0.0);
+    let mut extent = CGSize::new(0.0, 0.0);
+    let ok = !position.is_null()
+        && !size.is_null()
+        && unsafe { AXValueGetValue(position, AX_VALUE_CGPOINT, &mut origin as *mut _ as *mut c_void) }
+        && unsafe { AXValueGetValue(size, AX_VALUE_CGSIZE, &mut extent as *mut _ as *mut c_void) };
+    unsafe {
+        if !position.is_null() {
+            CFRelease(position);
+        }
+        if !size.is_null() {
+            CFRelease(size);
+        }
+    }
+
+    ok.then(|| CGRect::new(origin, extent))
+}
+
+fn frames_match(a: &CGRect, b: &CGRect) -> bool {
+    (a.origin.x - b.origin.x).abs() <= FRAME_TOLERANCE
+        && (a.origin.y - b.origin.y).abs() <= FRAME_TOLERANCE
+        && (a.size.width - b.size.width).abs() <= FRAME_TOLERANCE
+        && (a.size.height - b.size.height).abs() <= FRAME_TOLERANCE
+}
+
+/// Find the SCWindow matching the focused AX window (same PID, same frame).
+/// Falls back to the largest on-screen normal-layer window of that PID.
+pub fn resolve_focused_window(windows: &[Retained<SCWindow>]) -> Option<Retained<SCWindow>> {
+    let (pid, frame) = focused_window_frame()?;
+
+    let candidates: Vec<&Retained<SCWindow>> = windows
+        .iter()
+        .filter(|w| {
+            let owner = unsafe { w.owningApplication() }
+                .map(|a| unsafe { a.processID() })
+                .unwrap_or(-1);
+            owner == pid && unsafe { w.isOnScreen() } && unsafe { w.windowLayer() } == 0
+        })
+        .collect();
+
+    let exact = candidates.iter().find(|w| frames_match(&unsafe { w.frame() }, &frame));
+    if let Some(w) = exact {
+        return Some((*w).clone());
+    }
+
+    candidates
+        .into_iter()
+        .max_by(|a, b| {
+            let fa = unsafe { a.frame() };
+            let fb = unsafe { b.frame() };
+            (fa.size.width * fa.size.height).total_cmp(&(fb.size.width * fb.size.height))
+        })
+        .cloned()
+}
+
+/// Bring `window` to the front: activate its owning app, then raise the
+/// matching AX window (needs Accessibility; without it only the app's
+/// frontmost window comes forward).
+pub fn raise_window(window: &SCWindow) -> Result<(), String> {
+    let pid = unsafe { window.owningApplication() }
+        .map(|a| unsafe { a.processID() })
+        .ok_or("Window has no owning application")?;
+    let frame = unsafe { window.frame() };
+
+    unsafe {
+        let app: Option<Retained<AnyObject>> =
+            msg_send![class!(NSRunningApplication), runningApplicationWithProcessIdentifier: pid];
+        let app = app.ok_or_else(|| format!("No running application with PID {}", pid))?;
+        let _: bool = msg_send![&*app, activateWithOptions: ACTIVATE_IGNORING_OTHER_APPS];
+    }
+
+    if !unsafe { AXIsProcessTrusted() } {
+        return Ok(());
+    }
+    let ax_app = unsafe { AXUIElementCreateApplication(pid) };
+    if ax_app.is_null() {
+        return Ok(());
+    }
+    let windows = copy_attribute(ax_app, "AXWindows");
+    unsafe { CFRelease(ax_app) };
+    if windows.is_null() {
+        return Ok(());
+    }
+
+    let count = unsafe { CFArrayGetCount(windows) };
+    let target = (0..count)
+        .map(|i| unsafe { CFArrayGetValueAtIndex(windows, i) })
+        .find(|&w| window_frame(w).is_some_and(|f| frames_match(&f, &frame)));
+    if let Some(w) = target {
+        let action = cfstring("AXRaise");
+        unsafe {
+            AXUIElementPerformAction(w, action);
+            CFRelease(action);
+        }
+    }
+    unsafe { CFRelease(windows) };
+    Ok(())
+}
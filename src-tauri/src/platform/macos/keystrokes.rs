@@ -0,0 +1,237 @@
+//! Keystroke monitor for the on-screen keystroke overlay.
+//!
+//! A listen-only `EventTap` for `kCGEventKeyDown`. Each key press
+//! (auto-repeats skipped) is handed to a callback as a `Keystroke` with
+//! display symbols for the key and its modifiers. Character keys are
+//! labelled with `UCKeyTranslate` in the keyboard layout that was active
+//! when the monitor started (AZERTY, Dvorak, ...).
+
+use super::event_tap::EventTap;
+use dispatch2::DispatchQueue;
+use serde::Serialize;
+use std::ffi::c_void;
+use std::time::Duration;
+
+/// One key press, ready to display.
+#[derive(Clone, Debug, Serialize)]
+pub struct Keystroke {
+    /// Virtual key code (kVK_*)
+    pub keycode: u16,
+    /// Key cap label, e.g. "K", "⏎", "F5"
+    pub key: String,
+    /// Held modifiers in Apple's order (⌃ ⌥ ⇧ ⌘)
+    pub modifiers: Vec<&'static str>,
+}
+
+extern "C" {
+    fn CGEventGetIntegerValueField(event: *mut c_void, field: u32) -> i64;
+    fn CGEventGetFlags(event: *mut c_void) -> u64;
+    fn CFDataGetBytePtr(data: *const c_void) -> *const u8;
+    fn CFRelease(cf: *const c_void);
+    fn pthread_main_np() -> i32;
+}
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    fn TISCopyCurrentKeyboardLayoutInputSource() -> *const c_void;
+    /// Returns a CFDataRef owned by the input source (not retained).
+    fn TISGetInputSourceProperty(source: *const c_void, key: *const c_void) -> *const c_void;
+    static kTISPropertyUnicodeKeyLayoutData: *const c_void;
+    fn LMGetKbdType() -> u8;
+    fn UCKeyTranslate(
+        layout: *const u8,
+        virtual_key_code: u16,
+        key_action: u16,
+        modifier_key_state: u32,
+        keyboard_type: u32,
+        key_translate_options: u32,
+        dead_key_state: *mut u32,
+        max_string_length: usize,
+        actual_string_length: *mut usize,
+        unicode_string: *mut u16,
+    ) -> i32;
+}
+
+/// kUCKeyActionDisplay / kUCKeyTranslateNoDeadKeysMask
+const KEY_ACTION_DISPLAY: u16 = 3;
+const NO_DEAD_KEYS: u32 = 1;
+/// How long to wait for the main thread to look up the layout before
+/// falling back to ANSI labels
+const LAYOUT_LOOKUP_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// kCGEventKeyDown
+const KEY_DOWN: u32 = 10;
+/// kCGKeyboardEventAutorepeat / kCGKeyboardEventKeycode
+const FIELD_AUTOREPEAT: u32 = 8;
+const FIELD_KEYCODE: u32 = 9;
+
+/// kCGEventFlagMask* with their symbols, in display order
+const MODIFIERS: [(u64, &str); 4] = [
+    (0x0004_0000, "⌃"),
+    (0x0008_0000, "⌥"),
+    (0x0002_0000, "⇧"),
+    (0x0010_0000, "⌘"),
+];
+
+/// The current input source's `UCKeyboardLayout`.
+struct KeyboardLayout {
+    /// Retained TISInputSourceRef, which owns `data`
+    source: *const c_void,
+    data: *const u8,
+}
+
+// The layout data is immutable and the source is only released on drop
+unsafe impl Send for KeyboardLayout {}
+
+impl KeyboardLayout {
+    /// The layout in use now, or `None` for input sources without one
+    /// (some input methods). TIS must be called on the main thread.
+    fn current() -> Option<Self> {
+        if unsafe { pthread_main_np() } != 0 {
+            return Self::load();
+        }
+        let (tx, rx) = std::sync::mpsc::channel();
+        DispatchQueue::main().exec_async(move || {
+            let _ = tx.send(Self::load());
+        });
+        rx.recv_timeout(LAYOUT_LOOKUP_TIMEOUT).ok().flatten()
+    }
+
+    fn load() -> Option<Self> {
+        unsafe {
+            let source = TISCopyCurrentKeyboardLayoutInputSource();
+            if source.is_null() {
+                return None;
+            }
+            let data = TISGetInputSourceProperty(source, kTISPropertyUnicodeKeyLayoutData);
+            if data.is_null() {
+                CFRelease(source);
+                return None;
+            }
+            Some(Self { source, data: CFDataGetBytePtr(data) })
+        }
+    }
+
+    /// What the key types without modifiers, e.g. "q" on an AZERTY "A" key.
+    fn translate(&self, keycode: u16) -> Option<String> {
+        let mut dead_key_state = 0u32;
+        let mut chars = [0u16; 4];
+        let mut len = 0usize;
+        let status = unsafe {
+            UCKeyTranslate(
+                self.data,
+                keycode,
+                KEY_ACTION_DISPLAY,
+                0,
+                LMGetKbdType() as u32,
+                NO_DEAD_KEYS,
+                &mut dead_key_state,
+                chars.len(),
+                &mut len,
+                chars.as_mut_ptr(),
+            )
+        };
+        if status != 0 || len == 0 {
+            return None;
+        }
+        let text = String::from_utf16(&chars[..len.min(chars.len())]).ok()?;
+        (!text.chars().any(char::is_control)).then(|| text.to_uppercase())
+    }
+}
+
+impl Drop for KeyboardLayout {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.source) };
+    }
+}
+
+/// Key cap label for a virtual key code: named keys get their symbol,
+/// character keys what `layout` types (ANSI US without a layout).
+fn key_label(keycode: u16, layout: Option<&KeyboardLayout>) -> String {
+    if let Some(label) = named_key_label(keycode) {
+        return label.to_string();
+    }
+    layout
+        .and_then(|l| l.translate(keycode))
+        .or_else(|| ansi_key_label(keycode).map(str::to_string))
+        .unwrap_or_else(|| format!("#{}", keycode))
+}
+
+/// Labels of keys that don't type a character (same on every layout).
+fn named_key_label(keycode: u16) -> Option<&'static str> {
+    let label = match keycode {
+        0x24 | 0x4C => "⏎",
+        0x30 => "⇥",
+        0x31 => "Space",
+        0x33 => "⌫",
+        0x35 => "⎋",
+        0x75 => "⌦",
+        0x73 => "Home",
+        0x77 => "End",
+        0x74 => "PgUp",
+        0x79 => "PgDn",
+        0x7B => "←",
+        0x7C => "→",
+        0x7D => "↓",
+        0x7E => "↑",
+        0x7A => "F1", 0x78 => "F2", 0x63 => "F3", 0x76 => "F4",
+        0x60 => "F5", 0x61 => "F6", 0x62 => "F7", 0x64 => "F8",
+        0x65 => "F9", 0x6D => "F10", 0x67 => "F11", 0x6F => "F12",
+        _ => return None,
+    };
+    Some(label)
+}
+
+/// Character keys on the ANSI US layout.
+fn ansi_key_label(keycode: u16) -> Option<&'static str> {
+    let label = match keycode {
+        0x00 => "A", 0x01 => "S", 0x02 => "D", 0x03 => "F", 0x04 => "H",
+        0x05 => "G", 0x06 => "Z", 0x07 => "X", 0x08 => "C", 0x09 => "V",
+        0x0B => "B", 0x0C => "Q", 0x0D => "W", 0x0E => "E", 0x0F => "R",
+        0x10 => "Y", 0x11 => "T", 0x12 => "1", 0x13 => "2", 0x14 => "3",
+        0x15 => "4", 0x16 => "6", 0x17 => "5", 0x18 => "=", 0x19 => "9",
+        0x1A => "7", 0x1B => "-", 0x1C => "8", 0x1D => "0", 0x1E => "]",
+        0x1F => "O", 0x20 => "U", 0x21 => "[", 0x22 => "I", 0x23 => "P",
+        0x25 => "L", 0x26 => "J", 0x27 => "'", 0x28 => "K", 0x29 => ";",
+        0x2A => "\\", 0x2B => ",", 0x2C => "/", 0x2D => "N", 0x2E => "M",
+        0x2F => ".", 0x32 => "`",
+        _ => return None,
+    };
+    Some(label)
+}
+
+/// A running keystroke tap. Stops (and releases the tap) when dropped.
+pub struct KeystrokeMonitor {
+    _tap: EventTap,
+}
+
+impl KeystrokeMonitor {
+    /// Install the tap and call `on_key` (from the tap thread) for every
+    /// key press until the monitor is dropped.
+    pub fn start(on_key: impl Fn(Keystroke) + Send + 'static) -> Result<Self, String> {
+        let layout = KeyboardLayout::current();
+        if layout.is_none() {
+            println!("[zureshot] No keyboard layout data — labelling keys as ANSI US");
+        }
+        let tap = EventTap::start("Keystroke", 1 << KEY_DOWN, move |event_type, event| {
+            if event_type != KEY_DOWN {
+                return;
+            }
+            let (repeat, keycode, flags) = unsafe {
+                (
+                    CGEventGetIntegerValueField(event, FIELD_AUTOREPEAT),
+                    CGEventGetIntegerValueField(event, FIELD_KEYCODE) as u16,
+                    CGEventGetFlags(event),
+                )
+            };
+            if repeat == 0 {
+                on_key(Keystroke {
+                    keycode,
+                    key: key_label(keycode, layout.as_ref()),
+                    modifiers: MODIFIERS.iter().filter(|(mask, _)| flags & mask != 0).map(|(_, s)| *s).collect(),
+                });
+            }
+        })?;
+        Ok(Self { _tap: tap })
+    }
+}
@@ -1,23 +1,38 @@
 //! macOS platform implementation — ScreenCaptureKit + AVAssetWriter.
 
+pub mod audio_meter;
 pub mod camera;
 pub mod capture;
+pub mod clicks;
+pub mod desktop;
 pub mod editor;
+mod event_tap;
+pub mod focus;
+pub mod keystrokes;
 pub mod mouse_tracker;
 pub mod ocr;
+pub mod power;
+pub mod replay;
+pub mod scene;
 pub mod scroll_capture;
 pub mod writer;
 
+pub use scroll_capture::ScrollCaptureSession;
+
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use objc2::rc::Retained;
 use objc2_av_foundation::{AVAssetWriter, AVAssetWriterInput};
 use objc2_core_foundation::{CGPoint, CGRect, CGSize};
-use objc2_screen_capture_kit::{SCStream, SCWindow};
+use objc2_screen_capture_kit::{SCStream, SCStreamConfiguration, SCWindow};
 use tauri::{AppHandle, Manager};
 
-use super::StartRecordingConfig;
+use super::{
+    AppliedCaptureConfig, BarSpacesConfig, CaptureRegion, CursorCapture, DisplayInfo, EmptyAudioBehavior,
+    ColorSpace, EncodingSettings, FrameCounters, FrameRateBounds, FrameStats, ImageFormat, MicrophoneInfo, PixelFormat, PowerMode, RecordingQuality, Rotation, SizeEstimate,
+    StartRecordingConfig, VideoCodec, WindowInfo,
+};
 
 // ── RecordingHandle ──────────────────────────────────────────────────
 
@@ -27,10 +42,34 @@ use super::StartRecordingConfig;
 pub struct RecordingHandle {
     pub(crate) stream: Retained<SCStream>,
     pub(crate) writer: Retained<AVAssetWriter>,
-    pub(crate) input: Retained<AVAssetWriterInput>,
+    /// Video input (`None` for audio-only recordings).
+    pub(crate) input: Option<Retained<AVAssetWriterInput>>,
     pub(crate) audio_input: Option<Retained<AVAssetWriterInput>>,
     pub(crate) mic_input: Option<Retained<AVAssetWriterInput>>,
     pub(crate) paused_flag: Arc<AtomicBool>,
+    pub(crate) scene: Option<Arc<scene::SceneDetector>>,
+    /// Capture delegate — read for per-track sample counters at finalize.
+    pub(crate) delegate: Retained<capture::StreamOutput>,
+    /// Video frame counters, shared with the capture delegate.
+    pub(crate) frame_counters: Arc<FrameCounters>,
+    pub(crate) output_path: String,
+    pub(crate) empty_audio: EmptyAudioBehavior,
+    /// Set for single-window captures (content filter must not be replaced).
+    pub(crate) window_id: Option<u32>,
+    /// Display being recorded (`None` = main display).
+    pub(crate) display_id: Option<u32>,
+    /// Kept for `updateConfiguration` (battery saver frame-rate changes).
+    pub(crate) stream_config: Retained<SCStreamConfiguration>,
+    /// Frame rate for the chosen quality and `max_fps` (without the battery cap).
+    pub(crate) full_fps: i32,
+    /// Frame-rate bounds the battery cap is applied within.
+    pub(crate) frame_rate_bounds: FrameRateBounds,
+    pub(crate) battery_saver: AtomicBool,
+    /// Resolved settings reported by `applied_config` (fps/battery are live).
+    pub(crate) applied: AppliedCaptureConfig,
+    /// Per-display streams and compositor of a whole-desktop recording;
+    /// `stream` is then only the audio carrier.
+    pub(crate) desktop: Option<desktop::DesktopCapture>,
 }
 
 // SAFETY: The ObjC objects inside are thread-safe. Access is serialized
@@ -42,19 +81,70 @@ impl RecordingHandle {
     /// Stop the SCStream capture (blocks until confirmed).
     pub fn stop_capture(&self) {
         println!("[zureshot] Stopping capture stream...");
+        if let Some(ref desktop) = self.desktop {
+            desktop.stop();
+        }
         capture::stop(&self.stream);
         println!("[zureshot] Capture stream stopped");
     }
 
     /// Finalize the MP4 file (writes moov atom).
-    pub fn finalize(&self) {
+    ///
+    /// Audio tracks that never received a sample are dropped (re-muxed out)
+    /// or kept with a warning, per `EmptyAudioBehavior`. Returns a
+    /// user-facing warning if the output still has a problem worth mentioning.
+    pub fn finalize(&self) -> Option<String> {
         println!("[zureshot] Finalizing MP4...");
         writer::finalize(
             &self.writer,
-            &self.input,
+            self.input.as_deref(),
             self.audio_input.as_deref(),
             self.mic_input.as_deref(),
         );
+
+        if let Some(timestamps) = self.delegate.take_frame_timestamps() {
+            write_timestamps_sidecar(&self.output_path, &timestamps);
+        }
+
+        // Track order matches the order inputs were added to the writer
+        let mut tracks: Vec<(&str, u64)> = Vec::new();
+        if self.audio_input.is_some() {
+            tracks.push(("system audio", self.delegate.system_audio_samples()));
+        }
+        if self.mic_input.is_some() {
+            tracks.push(("microphone", self.delegate.mic_audio_samples()));
+        }
+        let empty: Vec<&str> = tracks.iter().filter(|(_, n)| *n == 0).map(|(l, _)| *l).collect();
+        if empty.is_empty() {
+            return None;
+        }
+
+        let names = empty.join(" and ");
+        println!("[zureshot] WARNING: no samples received from {}", names);
+        // The remux keeps the video track — audio-only files just warn
+        let behavior = if self.input.is_some() { self.empty_audio } else { EmptyAudioBehavior::Warn };
+        match behavior {
+            EmptyAudioBehavior::Warn => Some(format!(
+                "No sound was captured from {}. The recording contains an empty audio track.",
+                names
+            )),
+            EmptyAudioBehavior::Drop => {
+                let keep: Vec<bool> = tracks.iter().map(|(_, n)| *n > 0).collect();
+                match writer::drop_audio_tracks(&self.output_path, &keep) {
+                    Ok(()) => {
+                        println!("[zureshot] Removed empty audio track(s): {}", names);
+                        None
+                    }
+                    Err(e) => {
+                        eprintln!("[zureshot] {}", e);
+                        Some(format!(
+                            "No sound was captured from {}, and the empty audio track could not be removed.",
+                            names
+                        ))
+                    }
+                }
+            }
+        }
     }
 
     /// Set the paused flag — capture delegate will drop frames.
@@ -67,14 +157,101 @@ impl RecordingHandle {
         self.paused_flag.store(false, Ordering::Relaxed);
     }
 
+    /// Cap (battery) or restore (AC) the capture frame rate. Encoder power
+    /// efficiency is fixed at start and not changed here.
+    pub fn set_battery_saver(&self, enabled: bool) {
+        if self.battery_saver.swap(enabled, Ordering::Relaxed) == enabled {
+            return;
+        }
+        let fps = self.capture_fps(enabled);
+        if fps == self.full_fps && enabled {
+            return; // Already at or below the cap
+        }
+        println!(
+            "[zureshot] Battery saver {} → {}fps",
+            if enabled { "on" } else { "off" },
+            fps
+        );
+        match self.desktop {
+            Some(ref desktop) => desktop.set_frame_rate(fps),
+            None => capture::update_frame_rate(&self.stream, &self.stream_config, fps),
+        }
+    }
+
+    /// Capture rate with or without the battery cap, within the bounds.
+    fn capture_fps(&self, battery_saver: bool) -> i32 {
+        if battery_saver {
+            self.frame_rate_bounds.clamp(self.full_fps, self.full_fps.min(capture::BATTERY_SAVER_FPS))
+        } else {
+            self.full_fps
+        }
+    }
+
+    /// Scene-change timestamps detected so far (empty if detection is off).
+    pub fn scene_changes(&self) -> Vec<f64> {
+        self.scene.as_ref().map(|s| s.scenes()).unwrap_or_default()
+    }
+
+    /// Forcing a keyframe needs the encoder's VTCompressionSession, which
+    /// AVAssetWriter keeps private — only instant replay (own session) can.
+    pub fn request_keyframe(&self) -> Result<(), String> {
+        Err("Forcing a keyframe is not supported for macOS recordings (AVAssetWriter owns the encoder)".into())
+    }
+
+    /// The configuration this recording is actually running with.
+    pub fn applied_config(&self) -> AppliedCaptureConfig {
+        let battery_saver = self.battery_saver.load(Ordering::Relaxed);
+        AppliedCaptureConfig {
+            fps: self.capture_fps(battery_saver),
+            battery_saver,
+            ..self.applied.clone()
+        }
+    }
+
+    /// Stream start → first frame latency, once the first frame arrived.
+    pub fn first_frame_latency_ms(&self) -> Option<f64> {
+        self.delegate.first_frame_latency_ms()
+    }
+
+    /// Frames written, dropped and skipped so far.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_counters.snapshot()
+    }
+
     /// Update the SCStream content filter to exclude all windows belonging
     /// to our PID. Called after creating new Tauri windows (recording bar,
     /// dim overlay) so they don't appear in the captured video.
     pub fn refresh_exclusion(&self, app: &AppHandle) -> Result<(), String> {
-        let (display, all_windows) = capture::get_display_and_windows()
+        if self.window_id.is_some() {
+            // Window filter already captures only the target window
+            return Ok(());
+        }
+        let (display, all_windows) = capture::get_display_and_windows_for(self.display_id)
             .map_err(|e| format!("Failed to get windows for exclusion refresh: {}", e))?;
         let exclude_windows = collect_app_windows_to_exclude(app, &all_windows);
-        capture::update_stream_filter(&self.stream, &display, exclude_windows)
+
+        // A window created just before the refresh may not be listed by SCK
+        // yet (more often over a fullscreen Space) — report it so the caller
+        // retries instead of leaving it in the video
+        let excluded_ids: Vec<u32> = exclude_windows.iter().map(|w| unsafe { w.windowID() }).collect();
+        let missing: Vec<&str> = ["recording-bar", "recording-overlay"]
+            .into_iter()
+            .filter(|label| {
+                app.get_webview_window(label)
+                    .filter(|win| win.is_visible().unwrap_or(false))
+                    .and_then(|win| window_number(&win))
+                    .is_some_and(|number| !excluded_ids.contains(&number))
+            })
+            .collect();
+
+        match self.desktop {
+            Some(ref desktop) => desktop.update_exclusion(&exclude_windows)?,
+            None => capture::update_stream_filter(&self.stream, &display, exclude_windows)?,
+        }
+        if !missing.is_empty() {
+            return Err(format!("Not yet listed by ScreenCaptureKit: {}", missing.join(", ")));
+        }
+        Ok(())
     }
 }
 
@@ -97,7 +274,7 @@ fn catch_objc_cmd(context: &str, f: impl FnOnce()) {
 /// → audio inputs → capture start → window exclusion.
 pub fn start_recording(
     app: &AppHandle,
-    config: StartRecordingConfig,
+    mut config: StartRecordingConfig,
 ) -> Result<RecordingHandle, String> {
     let path = &config.output_path;
 
@@ -106,136 +283,872 @@ pub fn start_recording(
 
     println!("[zureshot] Starting recording to: {}", path);
 
+    // The SCK audio API is newer than our minimum macOS: without it the
+    // writer would get an audio track that never receives samples
+    let (system_audio_supported, mic_supported) = capture::audio_capture_support();
+    let mut unavailable = Vec::new();
+    if config.capture_system_audio && !system_audio_supported {
+        config.capture_system_audio = false;
+        unavailable.push("system audio");
+    }
+    if config.capture_microphone && !mic_supported {
+        config.capture_microphone = false;
+        unavailable.push("the microphone");
+    }
+    if !unavailable.is_empty() {
+        use tauri::Emitter;
+        let msg = format!(
+            "This version of macOS can't record {} (system audio needs macOS 13, microphone needs macOS 15) — recording without it.",
+            unavailable.join(" or ")
+        );
+        eprintln!("[zureshot] WARNING: {}", msg);
+        let _ = app.emit("recording-warning", &msg);
+    }
+
+    if config.audio_only {
+        return start_audio_only_recording(app, &config);
+    }
+    if config.all_displays {
+        return start_desktop_recording(app, &config);
+    }
+
     // Get display and windows for potential exclusion
-    let (display, all_windows) = capture::get_display_and_windows().map_err(|e| {
+    let (display, all_windows) = capture::get_display_and_windows_for(config.display_id).map_err(|e| {
         eprintln!("[zureshot] {}", e);
         e
     })?;
     let (phys_width, phys_height, retina_scale) = capture::display_physical_size(&display);
+    let display_id: u32 = unsafe { objc2::msg_send![&*display, displayID] };
     println!(
-        "[zureshot] Display: {}x{} physical, scale={}",
-        phys_width, phys_height, retina_scale
+        "[zureshot] Display {}: {}x{} physical, scale={}",
+        display_id, phys_width, phys_height, retina_scale
     );
 
+    // Resolve the target window for single-window capture
+    let window = config.window_id.and_then(|id| {
+        let found = all_windows.iter().find(|w| unsafe { w.windowID() } == id).cloned();
+        if found.is_none() {
+            println!("[zureshot] Window {} not found, recording full screen instead", id);
+        }
+        found
+    });
+
     // Determine output dimensions and source rect
-    let (width, height, source_rect) = if let Some(ref rgn) = config.region {
-        let pixel_w = (rgn.width * retina_scale) as usize;
-        let pixel_h = (rgn.height * retina_scale) as usize;
-        // Ensure even dimensions for HEVC
-        let pixel_w = if pixel_w % 2 != 0 { pixel_w + 1 } else { pixel_w };
-        let pixel_h = if pixel_h % 2 != 0 { pixel_h + 1 } else { pixel_h };
+    let (width, height, source_rect) = if let Some(ref win) = window {
+        let frame = unsafe { win.frame() };
+        let pixel_w = super::even_pixels(frame.size.width, retina_scale);
+        let pixel_h = super::even_pixels(frame.size.height, retina_scale);
+        println!(
+            "[zureshot] Window {}: {}x{}pt → pixels({}x{}) scale={} quality={:?}",
+            unsafe { win.windowID() }, frame.size.width, frame.size.height,
+            pixel_w, pixel_h, retina_scale, config.quality
+        );
+        (pixel_w, pixel_h, None)
+    } else if let Some(ref rgn) = config.region {
+        // Even dimensions for HEVC; the source rect is sized back from the
+        // pixel count so it maps 1:1 onto the output with no uncovered edge
+        let pixel_w = super::even_pixels(rgn.width, retina_scale);
+        let pixel_h = super::even_pixels(rgn.height, retina_scale);
 
         let rect = CGRect::new(
             CGPoint::new(rgn.x, rgn.y),
-            CGSize::new(rgn.width, rgn.height),
+            CGSize::new(pixel_w as f64 / retina_scale, pixel_h as f64 / retina_scale),
         );
         println!(
             "[zureshot] Region: css({},{} {}x{}) → pixels({}x{}) scale={} quality={:?}",
             rgn.x, rgn.y, rgn.width, rgn.height, pixel_w, pixel_h, retina_scale, config.quality
         );
         (pixel_w, pixel_h, Some(rect))
+    } else if config.hide_menu_bar {
+        // Capture only the content area below the menu bar
+        let (logical_w, logical_h) = capture::display_size(&display);
+        let menu_bar = capture::menu_bar_height(&display);
+        let (pixel_w, pixel_h, src) = super::menu_bar_crop(
+            logical_w as f64, logical_h as f64, phys_width, menu_bar, retina_scale,
+        );
+
+        let rect = CGRect::new(CGPoint::new(src.x, src.y), CGSize::new(src.width, src.height));
+        println!(
+            "[zureshot] Full screen without menu bar ({}pt): {}x{} (physical, {}x Retina) quality={:?}",
+            menu_bar, pixel_w, pixel_h, retina_scale, config.quality
+        );
+        (pixel_w, pixel_h, Some(rect))
     } else {
         println!(
             "[zureshot] Full screen: {}x{} (physical, {}x Retina) quality={:?}",
             phys_width, phys_height, retina_scale, config.quality
         );
-        (phys_width, phys_height, None)
+        (super::even_dimension(phys_width), super::even_dimension(phys_height), None)
+    };
+
+    // Downscale in the capture itself so the whole pipeline moves less data
+    let (width, height) = match config.max_width {
+        Some(max_w) if max_w > 0 && width > max_w => {
+            let (w, h) = (
+                super::even_dimension(max_w),
+                super::even_pixels(height as f64, max_w as f64 / width as f64),
+            );
+            println!("[zureshot] Capture downscaled {}x{} → {}x{} (max width {})", width, height, w, h, max_w);
+            (w, h)
+        }
+        _ => (width, height),
     };
 
     // Collect windows to exclude (our own app windows)
     let exclude_windows = collect_app_windows_to_exclude(app, &all_windows);
+    let excluded_windows = exclude_windows.len();
+
+    // Color tagging: forced, generic BT.709, or the nearest match for the display's profile
+    let color = if let Some(space) = config.color_space {
+        let tagging = match space {
+            ColorSpace::Rec709 => writer::ColorTagging::Bt709,
+            ColorSpace::DisplayP3 => writer::ColorTagging::DisplayP3,
+        };
+        if tagging == writer::ColorTagging::DisplayP3 {
+            if capture::display_color(display_id).tagging != writer::ColorTagging::DisplayP3 {
+                use tauri::Emitter;
+                let msg = "Display P3 is forced, but this display isn't wide-gamut — colors may look washed out on it.";
+                eprintln!("[zureshot] WARNING: {}", msg);
+                let _ = app.emit("recording-warning", msg);
+            }
+            // Wide-gamut content needs more than 8 bits to avoid banding;
+            // H.264 has no 10-bit encoder, so it stays at 420v
+            if config.pixel_format == PixelFormat::Yuv420Video && config.codec != VideoCodec::H264 {
+                config.pixel_format = PixelFormat::Yuv420Video10;
+            }
+        }
+        println!("[zureshot] Color space: {:?} (pixel format {:?})", space, config.pixel_format);
+        tagging
+    } else if config.match_display_color {
+        let dc = capture::display_color(display_id);
+        if let Some(ref icc) = dc.icc {
+            // MP4 can't embed the ICC profile — keep it next to the recording
+            let icc_path = std::path::Path::new(path).with_extension("icc");
+            match std::fs::write(&icc_path, icc) {
+                Ok(()) => println!("[zureshot] Display ICC profile saved to {}", icc_path.display()),
+                Err(e) => eprintln!("[zureshot] Failed to save ICC profile: {}", e),
+            }
+        }
+        println!("[zureshot] Matching display color: {:?}", dc.tagging);
+        dc.tagging
+    } else {
+        writer::ColorTagging::Bt709
+    };
+
+    let battery_saver = config.power_mode == PowerMode::Auto && power::on_battery();
+    if battery_saver {
+        println!("[zureshot] On battery: capping at {}fps, power-efficient encoding", capture::BATTERY_SAVER_FPS);
+    }
+
+    let preset_fps = config.preset_fps();
+    let bounds = config.frame_rate_bounds;
+    let (full_fps, capture_fps) = frame_rates(preset_fps, bounds, display_id, battery_saver);
+    println!(
+        "[zureshot] Frame interval {:.1}ms (at most {}fps; preset {}fps, bounds {:?})",
+        1000.0 / capture_fps as f64, capture_fps, preset_fps, bounds
+    );
+    if config.force_cfr {
+        println!("[zureshot] Constant frame rate requested — re-timing to {}fps when recording stops", full_fps);
+    }
+
+    let rotation = config.rotation.unwrap_or_else(|| detect_rotation(display_id, &display));
+    if rotation != Rotation::None {
+        println!("[zureshot] Output rotation: {:?}", rotation);
+    }
 
     // Create HEVC writer
-    let (w, input) = writer::create_writer(path, width, height, config.quality).map_err(|e| {
+    let ten_bit = config.pixel_format.is_ten_bit();
+    if ten_bit && config.codec == VideoCodec::H264 {
+        println!("[zureshot] WARNING: 10-bit encoding needs HEVC — encoding 8-bit H.264");
+    }
+    if config.codec == VideoCodec::ProRes422 {
+        println!("[zureshot] WARNING: ProRes 422 recordings are very large (~1 GB/min at 1080p30)");
+        if config.bitrate_override_bps.is_some() {
+            println!("[zureshot] ProRes has a fixed data rate — ignoring the bitrate override");
+        }
+    }
+    let fragment_interval_secs = config.fragment_interval_secs();
+    if let Some(secs) = fragment_interval_secs {
+        println!("[zureshot] Fragmented MP4: a fragment every {}s", secs);
+    }
+    let encoder_options = writer::EncoderOptions {
+        codec: config.codec,
+        ten_bit: ten_bit && config.codec == VideoCodec::Hevc,
+        color,
+        power_efficient: battery_saver,
+        keyframe_interval_secs: config.keyframe_interval_secs,
+        bitrate_override_bps: config.bitrate_override_bps,
+        quality_factor: config.quality_factor,
+        rotation,
+        fragment_interval_secs,
+        fps: full_fps,
+    };
+    let video_bitrate_bps = writer::video_bitrate(width, height, config.quality, &encoder_options);
+    if config.bitrate_override_bps.is_some() && config.codec != VideoCodec::ProRes422 {
+        let auto = writer::compute_bitrate_for(width, height, config.quality, config.codec, full_fps);
+        println!("[zureshot] Bitrate override: {} bps (automatic would be {} bps)", video_bitrate_bps, auto);
+        if let Some(warning) = crate::platform::low_bitrate_warning(video_bitrate_bps, auto, width, height) {
+            eprintln!("[zureshot] WARNING: {}", warning);
+        }
+    }
+    if let Some(q) = config.quality_factor {
+        if config.codec == VideoCodec::ProRes422 {
+            println!("[zureshot] ProRes has a fixed quality — ignoring the quality factor");
+        } else {
+            println!("[zureshot] Quality factor {:.2} instead of the {:?} preset's", q, config.quality);
+        }
+    }
+    let (w, input) = writer::create_writer(path, width, height, config.quality, encoder_options).map_err(|e| {
         eprintln!("[zureshot] {}", e);
         e
     })?;
 
     // Create audio writer inputs if requested
-    let audio_input = if config.capture_system_audio {
-        let ai = writer::create_audio_input("system-audio").map_err(|e| {
-            eprintln!("[zureshot] {}", e);
-            e
-        })?;
-        let can_add: bool = unsafe { objc2::msg_send![&*w, canAddInput: &*ai] };
-        if can_add {
-            catch_objc_cmd("addInput(audio)", || unsafe { w.addInput(&ai) });
-            println!("[zureshot] System audio track added to writer");
-            Some(ai)
-        } else {
-            eprintln!("[zureshot] WARNING: Writer cannot add system audio input");
-            None
+    let (audio_input, mic_input) = add_audio_inputs(&w, &config)?;
+
+    // Start writing AFTER all inputs are added
+    writer::start_writing(&w).map_err(|e| {
+        eprintln!("[zureshot] {}", e);
+        e
+    })?;
+
+    // Shared paused flag
+    let paused_flag = Arc::new(AtomicBool::new(false));
+
+    // Optional scene-change detection → `scene-detected` events
+    let scene = config.scene_threshold.map(|threshold| {
+        use tauri::Emitter;
+        let app = app.clone();
+        println!("[zureshot] Scene detection enabled (threshold={:.2})", threshold);
+        Arc::new(scene::SceneDetector::new(threshold, move |t| {
+            let _ = app.emit("scene-detected", serde_json::json!({ "time_secs": t }));
+        }))
+    });
+
+    let meter = audio_level_meter(app, &config);
+
+    // Start capture
+    let (stream, delegate, stream_config) = capture::create_and_start(
+        &display,
+        w.clone(),
+        capture::CaptureOptions {
+            width,
+            height,
+            input: Some(input.clone()),
+            audio_input: audio_input.clone(),
+            mic_input: mic_input.clone(),
+            source_rect,
+            exclude_windows,
+            quality: config.quality,
+            paused_flag: paused_flag.clone(),
+            capture_system_audio: config.capture_system_audio,
+            capture_microphone: config.capture_microphone,
+            microphone_device_id: config.microphone_device_id.as_deref(),
+            scene: scene.clone(),
+            meter,
+            window: window.clone(),
+            pixel_format: config.pixel_format,
+            color,
+            record_timestamps: config.write_timestamps,
+            fps: capture_fps,
+            shows_cursor: config.show_cursor && config.cursor_capture == CursorCapture::System,
+            warmup_secs: config.warmup_secs,
+        },
+    )
+    .map_err(|e| {
+        eprintln!("[zureshot] {}", e);
+        e
+    })?;
+
+    println!(
+        "[zureshot] Recording started! systemAudio={}, mic={}, audioInput={}, micInput={}",
+        config.capture_system_audio,
+        config.capture_microphone,
+        audio_input.is_some(),
+        mic_input.is_some()
+    );
+
+    let applied = AppliedCaptureConfig {
+        width,
+        height,
+        fps: capture_fps,
+        codec: match config.codec {
+            VideoCodec::Hevc if encoder_options.ten_bit => "hevc-main10",
+            VideoCodec::Hevc => "hevc",
+            VideoCodec::H264 => "h264",
+            VideoCodec::ProRes422 => "prores422",
         }
-    } else {
-        None
+        .to_string(),
+        encoder: "VideoToolbox".to_string(),
+        video_bitrate_bps: video_bitrate_bps as u64,
+        audio_tracks: audio_track_names(&audio_input, &mic_input),
+        source_rect: source_rect.map(|r| CaptureRegion {
+            x: r.origin.x,
+            y: r.origin.y,
+            width: r.size.width,
+            height: r.size.height,
+        }),
+        display_id: Some(display_id),
+        window_id: window.as_ref().map(|w| unsafe { w.windowID() }),
+        excluded_windows,
+        pixel_format: config.pixel_format,
+        battery_saver,
+        rotation,
     };
 
-    let mic_input = if config.capture_microphone {
-        let mi = writer::create_audio_input("microphone").map_err(|e| {
+    Ok(RecordingHandle {
+        stream,
+        writer: w,
+        input: Some(input),
+        audio_input,
+        mic_input,
+        paused_flag,
+        scene,
+        frame_counters: delegate.counters(),
+        delegate,
+        output_path: config.output_path.clone(),
+        empty_audio: config.empty_audio,
+        window_id: window.map(|w| unsafe { w.windowID() }),
+        display_id: config.display_id,
+        stream_config,
+        full_fps,
+        frame_rate_bounds: bounds,
+        battery_saver: AtomicBool::new(battery_saver),
+        applied,
+        desktop: None,
+    })
+}
+
+/// Create the requested system audio / microphone AAC inputs and add them
+/// to the writer. An input the writer refuses is left out with a warning.
+fn add_audio_inputs(
+    w: &AVAssetWriter,
+    config: &StartRecordingConfig,
+) -> Result<(Option<Retained<AVAssetWriterInput>>, Option<Retained<AVAssetWriterInput>>), String> {
+    let add = |label: &str, name: &str| -> Result<Option<Retained<AVAssetWriterInput>>, String> {
+        let input = writer::create_audio_input(label).map_err(|e| {
             eprintln!("[zureshot] {}", e);
             e
         })?;
-        let can_add: bool = unsafe { objc2::msg_send![&*w, canAddInput: &*mi] };
+        let can_add: bool = unsafe { objc2::msg_send![w, canAddInput: &*input] };
         if can_add {
-            catch_objc_cmd("addInput(mic)", || unsafe { w.addInput(&mi) });
-            println!("[zureshot] Microphone track added to writer");
-            Some(mi)
+            catch_objc_cmd(&format!("addInput({})", label), || unsafe { w.addInput(&input) });
+            println!("[zureshot] {} track added to writer", name);
+            Ok(Some(input))
         } else {
-            eprintln!("[zureshot] WARNING: Writer cannot add microphone input");
-            None
+            eprintln!("[zureshot] WARNING: Writer cannot add {} input", name.to_lowercase());
+            Ok(None)
         }
-    } else {
-        None
     };
+    let audio_input = if config.capture_system_audio { add("system-audio", "System audio")? } else { None };
+    let mic_input = if config.capture_microphone { add("microphone", "Microphone")? } else { None };
+    Ok((audio_input, mic_input))
+}
 
-    // Start writing AFTER all inputs are added
+/// Live levels for the recording bar's VU meters → `audio-level` events
+fn audio_level_meter(app: &AppHandle, config: &StartRecordingConfig) -> Option<Arc<audio_meter::AudioMeter>> {
+    (config.capture_system_audio || config.capture_microphone).then(|| {
+        use tauri::Emitter;
+        let app = app.clone();
+        Arc::new(audio_meter::AudioMeter::new(move |source, peak, rms| {
+            let _ = app.emit(
+                "audio-level",
+                serde_json::json!({ "source": source.as_str(), "peak": peak, "rms": rms }),
+            );
+        }))
+    })
+}
+
+/// Track names for `AppliedCaptureConfig::audio_tracks`, in writer order.
+fn audio_track_names(
+    audio_input: &Option<Retained<AVAssetWriterInput>>,
+    mic_input: &Option<Retained<AVAssetWriterInput>>,
+) -> Vec<String> {
+    let mut audio_tracks = Vec::new();
+    if audio_input.is_some() {
+        audio_tracks.push("system-audio".to_string());
+    }
+    if mic_input.is_some() {
+        audio_tracks.push("microphone".to_string());
+    }
+    audio_tracks
+}
+
+/// Audio-only recording to `.m4a`: the writer gets just the AAC inputs and
+/// the stream no screen output. SCK only delivers system audio and the
+/// microphone through a running stream, so one still runs on the display,
+/// configured at 2×2 and 1fps.
+fn start_audio_only_recording(app: &AppHandle, config: &StartRecordingConfig) -> Result<RecordingHandle, String> {
+    if !config.capture_system_audio && !config.capture_microphone {
+        return Err("Audio-only recording needs system audio or the microphone".into());
+    }
+    let (display, all_windows) = capture::get_display_and_windows_for(config.display_id).map_err(|e| {
+        eprintln!("[zureshot] {}", e);
+        e
+    })?;
+    let display_id: u32 = unsafe { objc2::msg_send![&*display, displayID] };
+    let exclude_windows = collect_app_windows_to_exclude(app, &all_windows);
+
+    let fragment_interval_secs = config.fragment_interval_secs();
+    let w = writer::create_audio_writer(&config.output_path, fragment_interval_secs).map_err(|e| {
+        eprintln!("[zureshot] {}", e);
+        e
+    })?;
+    let (audio_input, mic_input) = add_audio_inputs(&w, config)?;
+    if audio_input.is_none() && mic_input.is_none() {
+        return Err("Could not add any audio track to the recording".into());
+    }
     writer::start_writing(&w).map_err(|e| {
         eprintln!("[zureshot] {}", e);
         e
     })?;
 
-    // Shared paused flag
     let paused_flag = Arc::new(AtomicBool::new(false));
+    // Smallest stream SCK accepts: it only carries the audio outputs
+    let (stream, delegate, stream_config) = capture::create_and_start(
+        &display,
+        w.clone(),
+        capture::CaptureOptions {
+            audio_input: audio_input.clone(),
+            mic_input: mic_input.clone(),
+            exclude_windows: exclude_windows,
+            quality: config.quality,
+            paused_flag: paused_flag.clone(),
+            capture_system_audio: config.capture_system_audio,
+            capture_microphone: config.capture_microphone,
+            microphone_device_id: config.microphone_device_id.as_deref(),
+            meter: audio_level_meter(app, config),
+            ..Default::default()
+        },
+    )
+    .map_err(|e| {
+        eprintln!("[zureshot] {}", e);
+        e
+    })?;
 
-    // Start capture
-    let stream = capture::create_and_start(
+    println!(
+        "[zureshot] Audio-only recording started! systemAudio={}, mic={}",
+        audio_input.is_some(),
+        mic_input.is_some()
+    );
+
+    let applied = AppliedCaptureConfig {
+        codec: "aac".to_string(),
+        encoder: "AVAssetWriter".to_string(),
+        audio_tracks: audio_track_names(&audio_input, &mic_input),
+        display_id: Some(display_id),
+        ..Default::default()
+    };
+
+    Ok(RecordingHandle {
+        stream,
+        writer: w,
+        input: None,
+        audio_input,
+        mic_input,
+        paused_flag,
+        scene: None,
+        frame_counters: delegate.counters(),
+        delegate,
+        output_path: config.output_path.clone(),
+        empty_audio: config.empty_audio,
+        window_id: None,
+        display_id: config.display_id,
+        stream_config,
+        full_fps: 1,
+        frame_rate_bounds: FrameRateBounds::default(),
+        battery_saver: AtomicBool::new(false),
+        applied,
+        desktop: None,
+    })
+}
+
+/// Whole-desktop recording: every display composited into one video the
+/// size of their union rect (see `desktop`). Audio comes from a 2×2 carrier
+/// stream on the main display, as in audio-only recordings. Region, window,
+/// rotation, scene detection, timestamps and the standard-size cursor don't
+/// apply here.
+fn start_desktop_recording(app: &AppHandle, config: &StartRecordingConfig) -> Result<RecordingHandle, String> {
+    let layout = desktop::DesktopLayout::all_displays(config.codec).map_err(|e| {
+        eprintln!("[zureshot] {}", e);
+        e
+    })?;
+    let (width, height) = (layout.width, layout.height);
+    println!(
+        "[zureshot] All displays: {} display(s) → {}x{} (scale={}) quality={:?}",
+        layout.display_count(), width, height, layout.scale, config.quality
+    );
+    if config.scene_threshold.is_some() || config.write_timestamps || config.rotation.is_some() {
+        println!("[zureshot] Scene detection, timestamps and rotation are ignored for all-displays recordings");
+    }
+
+    let (display, all_windows) = capture::get_display_and_windows_for(None).map_err(|e| {
+        eprintln!("[zureshot] {}", e);
+        e
+    })?;
+    let exclude_windows = collect_app_windows_to_exclude(app, &all_windows);
+
+    let battery_saver = config.power_mode == PowerMode::Auto && power::on_battery();
+    let bounds = config.frame_rate_bounds;
+    let full_fps = bounds.max_for(config.preset_fps());
+    let capture_fps = if battery_saver {
+        bounds.clamp(full_fps, full_fps.min(capture::BATTERY_SAVER_FPS))
+    } else {
+        full_fps
+    };
+
+    let fragment_interval_secs = config.fragment_interval_secs();
+    let encoder_options = writer::EncoderOptions {
+        codec: config.codec,
+        ten_bit: false,
+        color: writer::ColorTagging::Bt709,
+        power_efficient: battery_saver,
+        keyframe_interval_secs: config.keyframe_interval_secs,
+        bitrate_override_bps: config.bitrate_override_bps,
+        quality_factor: config.quality_factor,
+        rotation: Rotation::None,
+        fragment_interval_secs,
+        fps: full_fps,
+    };
+    let video_bitrate_bps = writer::video_bitrate(width, height, config.quality, &encoder_options);
+    let (w, input) = writer::create_writer(&config.output_path, width, height, config.quality, encoder_options)
+        .map_err(|e| {
+            eprintln!("[zureshot] {}", e);
+            e
+        })?;
+    let (audio_input, mic_input) = add_audio_inputs(&w, config)?;
+    // The adaptor has to exist before the writer starts
+    let adaptor = desktop::pixel_buffer_adaptor(&input, layout.width, layout.height)?;
+    writer::start_writing(&w).map_err(|e| {
+        eprintln!("[zureshot] {}", e);
+        e
+    })?;
+
+    let paused_flag = Arc::new(AtomicBool::new(false));
+    // Smallest stream SCK accepts: it only carries the audio outputs
+    let (stream, delegate, stream_config) = capture::create_and_start(
         &display,
-        width,
-        height,
         w.clone(),
+        capture::CaptureOptions {
+            audio_input: audio_input.clone(),
+            mic_input: mic_input.clone(),
+            exclude_windows: exclude_windows.clone(),
+            quality: config.quality,
+            paused_flag: paused_flag.clone(),
+            capture_system_audio: config.capture_system_audio,
+            capture_microphone: config.capture_microphone,
+            microphone_device_id: config.microphone_device_id.as_deref(),
+            meter: audio_level_meter(app, config),
+            ..Default::default()
+        },
+    )
+    .map_err(|e| {
+        eprintln!("[zureshot] {}", e);
+        e
+    })?;
+
+    let frame_counters = Arc::new(FrameCounters::default());
+    let desktop = desktop::DesktopCapture::start(
+        &layout,
         input.clone(),
-        audio_input.clone(),
-        mic_input.clone(),
-        source_rect,
-        exclude_windows,
-        config.quality,
+        adaptor,
+        delegate.clone(),
+        &exclude_windows,
+        capture_fps,
+        config.show_cursor,
         paused_flag.clone(),
-        config.capture_system_audio,
-        config.capture_microphone,
+        frame_counters.clone(),
     )
     .map_err(|e| {
         eprintln!("[zureshot] {}", e);
+        capture::stop(&stream);
         e
     })?;
 
     println!(
-        "[zureshot] Recording started! systemAudio={}, mic={}, audioInput={}, micInput={}",
-        config.capture_system_audio,
-        config.capture_microphone,
+        "[zureshot] All-displays recording started! audioInput={}, micInput={}",
         audio_input.is_some(),
         mic_input.is_some()
     );
 
+    let applied = AppliedCaptureConfig {
+        width,
+        height,
+        fps: capture_fps,
+        codec: match config.codec {
+            VideoCodec::Hevc => "hevc",
+            VideoCodec::H264 => "h264",
+            VideoCodec::ProRes422 => "prores422",
+        }
+        .to_string(),
+        encoder: "VideoToolbox".to_string(),
+        video_bitrate_bps: video_bitrate_bps as u64,
+        audio_tracks: audio_track_names(&audio_input, &mic_input),
+        excluded_windows: exclude_windows.len(),
+        battery_saver,
+        ..Default::default()
+    };
+
     Ok(RecordingHandle {
         stream,
         writer: w,
-        input,
+        input: Some(input),
         audio_input,
         mic_input,
         paused_flag,
+        scene: None,
+        frame_counters,
+        delegate,
+        output_path: config.output_path.clone(),
+        empty_audio: config.empty_audio,
+        window_id: None,
+        display_id: None,
+        stream_config,
+        full_fps,
+        frame_rate_bounds: bounds,
+        battery_saver: AtomicBool::new(battery_saver),
+        applied,
+        desktop: Some(desktop),
     })
 }
 
+/// Write `<stem>.timestamps.json`: one entry per video frame with its
+/// position in the file, the raw capture PTS (host clock) and wall-clock
+/// time, for aligning the video with external logs or sensor data.
+fn write_timestamps_sidecar(output_path: &str, timestamps: &[(f64, f64)]) {
+    let start = timestamps.first().map(|(pts, _)| *pts).unwrap_or(0.0);
+    let frames: Vec<serde_json::Value> = timestamps
+        .iter()
+        .map(|(pts, wall)| {
+            serde_json::json!({
+                "video_time_secs": pts - start,
+                "pts_secs": pts,
+                "wall_clock_unix_secs": wall,
+            })
+        })
+        .collect();
+    let sidecar = serde_json::json!({
+        "recording": output_path,
+        "frame_count": frames.len(),
+        "frames": frames,
+    });
+    let path = std::path::Path::new(output_path).with_extension("timestamps.json");
+    match std::fs::write(&path, serde_json::to_string(&sidecar).unwrap_or_default()) {
+        Ok(()) => println!("[zureshot] Frame timestamps written: {} ({} frames)", path.display(), timestamps.len()),
+        Err(e) => eprintln!("[zureshot] Failed to write frame timestamps: {}", e),
+    }
+}
+
+/// Start an instant-replay buffer on a display (full screen, app windows
+/// excluded). Nothing is written until `ReplayClip::write`.
+pub fn start_instant_replay(
+    app: &AppHandle,
+    display_id: Option<u32>,
+    quality: RecordingQuality,
+    capacity_secs: f64,
+) -> Result<replay::ReplaySession, String> {
+    let (display, all_windows) = capture::get_display_and_windows_for(display_id)?;
+    let (phys_width, phys_height, _) = capture::display_physical_size(&display);
+    let exclude_windows = collect_app_windows_to_exclude(app, &all_windows);
+    replay::start(&display, phys_width, phys_height, quality, capacity_secs, exclude_windows)
+}
+
+/// Connected displays (ScreenCaptureKit order; the first is the main display).
+pub fn list_displays() -> Result<Vec<DisplayInfo>, String> {
+    extern "C" {
+        fn CGMainDisplayID() -> u32;
+    }
+    let main_id = unsafe { CGMainDisplayID() };
+    Ok(capture::all_displays()?
+        .iter()
+        .map(|d| {
+            let (width, height) = capture::display_size(d);
+            let (_, _, scale) = capture::display_physical_size(d);
+            let id: u32 = unsafe { objc2::msg_send![&**d, displayID] };
+            let frame: CGRect = unsafe { objc2::msg_send![&**d, frame] };
+            DisplayInfo { id, x: frame.origin.x, y: frame.origin.y, width, height, scale, is_main: id == main_id }
+        })
+        .collect())
+}
+
+/// Rotation for recordings of a display when none was chosen.
+/// ScreenCaptureKit normally delivers a rotated display's frames upright;
+/// they only need a transform when CoreGraphics reports a quarter turn but
+/// SCK still hands out frames in the panel's native orientation.
+fn detect_rotation(display_id: u32, display: &objc2_screen_capture_kit::SCDisplay) -> Rotation {
+    extern "C" {
+        fn CGDisplayRotation(display: u32) -> f64;
+        fn CGDisplayPixelsWide(display: u32) -> usize;
+        fn CGDisplayPixelsHigh(display: u32) -> usize;
+    }
+    let (degrees, cg_portrait) = unsafe {
+        (CGDisplayRotation(display_id), CGDisplayPixelsHigh(display_id) > CGDisplayPixelsWide(display_id))
+    };
+    let rotation = Rotation::from_degrees(degrees);
+    let (width, height) = capture::display_size(display);
+    if rotation.is_quarter_turn() && (height > width) != cg_portrait {
+        println!(
+            "[zureshot] Display {} is rotated {}° but captures {}x{} — tagging the output",
+            display_id, degrees, width, height
+        );
+        rotation
+    } else {
+        Rotation::None
+    }
+}
+
+/// Window ID of the window that currently has keyboard focus, resolved via
+/// Accessibility → SCWindow. `None` if permission is denied or no match.
+pub fn focused_window_id() -> Option<u32> {
+    let (_, all_windows) = capture::get_display_and_windows().ok()?;
+    let window = focus::resolve_focused_window(&all_windows)?;
+    let id = unsafe { window.windowID() };
+    let title = unsafe { window.title() }.map(|t| t.to_string()).unwrap_or_default();
+    println!("[zureshot] Focused window resolved: id={} title={:?}", id, title);
+    Some(id)
+}
+
+/// Whether a window (SCWindow ID) is still on screen.
+pub fn window_exists(window_id: u32) -> bool {
+    capture::get_display_and_windows()
+        .map(|(_, windows)| windows.iter().any(|w| unsafe { w.windowID() } == window_id))
+        .unwrap_or(false)
+}
+
+/// Frame of a window (SCWindow ID) in global logical points, the space
+/// `list_displays` reports display origins in.
+pub fn window_bounds(window_id: u32) -> Result<CaptureRegion, String> {
+    let (_, windows) = capture::get_display_and_windows()?;
+    let window = windows
+        .iter()
+        .find(|w| unsafe { w.windowID() } == window_id)
+        .ok_or_else(|| format!("Window {} is no longer on screen", window_id))?;
+    let frame = unsafe { window.frame() };
+    Ok(CaptureRegion {
+        x: frame.origin.x,
+        y: frame.origin.y,
+        width: frame.size.width,
+        height: frame.size.height,
+    })
+}
+
+/// Titled, normal-layer windows of other apps that are on screen, sorted by
+/// app name then title (pass an `id` as `window_id`).
+pub fn list_windows() -> Result<Vec<WindowInfo>, String> {
+    let our_pid = std::process::id() as i32;
+    let (_, all_windows) = capture::get_display_and_windows()?;
+    let mut windows: Vec<WindowInfo> = all_windows
+        .iter()
+        .filter_map(|w| unsafe {
+            let owner = w.owningApplication()?;
+            if owner.processID() == our_pid || !w.isOnScreen() || w.windowLayer() != 0 {
+                return None;
+            }
+            let title = w.title().map(|t| t.to_string()).filter(|t| !t.is_empty())?;
+            Some(WindowInfo {
+                id: w.windowID(),
+                title,
+                app_name: owner.applicationName().to_string(),
+                bundle_id: Some(owner.bundleIdentifier().to_string()),
+            })
+        })
+        .collect();
+    windows.sort_by(|a, b| {
+        a.app_name
+            .to_lowercase()
+            .cmp(&b.app_name.to_lowercase())
+            .then_with(|| a.title.cmp(&b.title))
+    });
+    Ok(windows)
+}
+
+/// Bring a window (SCWindow ID) and its app to the front.
+pub fn raise_window(window_id: u32) -> Result<(), String> {
+    let (_, windows) = capture::get_display_and_windows()?;
+    let window = windows
+        .iter()
+        .find(|w| unsafe { w.windowID() } == window_id)
+        .ok_or_else(|| format!("Window {} is no longer on screen", window_id))?;
+    focus::raise_window(window)
+}
+
+/// Scrolling screenshot of a window (SCWindow ID): raises it, scrolls it
+/// to the end and writes the stitched PNG. Returns (width, height,
+/// file_size, viewports).
+pub fn scrolling_window_screenshot(
+    window_id: u32,
+    output_path: &str,
+) -> Result<(usize, usize, u64, usize), String> {
+    let (_, windows) = capture::get_display_and_windows()?;
+    let window = windows
+        .iter()
+        .find(|w| unsafe { w.windowID() } == window_id)
+        .ok_or_else(|| format!("Window {} is no longer on screen", window_id))?;
+    let frame = unsafe { window.frame() };
+    focus::raise_window(window)?;
+    // Let activation finish before the first capture and scroll event
+    std::thread::sleep(std::time::Duration::from_millis(300));
+
+    // Scroll events go to whatever is under this point — the middle of the
+    // window is inside the main scroll view in nearly every app
+    let center = CGPoint::new(
+        frame.origin.x + frame.size.width / 2.0,
+        frame.origin.y + frame.size.height / 2.0,
+    );
+    scroll_capture::capture_scrolling_window(window_id, center, frame.size.height, output_path)
+}
+
+/// Estimate the output size of a recording. `size` is the output size in
+/// physical pixels; `None` = the main display at native resolution. The
+/// frame rate is capped as a recording on the main display would be.
+pub fn estimate_size(
+    size: Option<(u32, u32)>,
+    settings: &EncodingSettings,
+    audio_tracks: usize,
+    duration_secs: f64,
+) -> Result<SizeEstimate, String> {
+    let (display, _) = capture::get_display_and_windows()?;
+    let display_id: u32 = unsafe { objc2::msg_send![&*display, displayID] };
+    let (w, h) = match size {
+        Some((w, h)) => (w as usize, h as usize),
+        None => {
+            let (w, h, _) = capture::display_physical_size(&display);
+            (w, h)
+        }
+    };
+    let battery_saver = settings.power_mode == PowerMode::Auto && power::on_battery();
+    let (full_fps, capture_fps) =
+        frame_rates(settings.preset_fps(), settings.frame_rate_bounds, display_id, battery_saver);
+    let options = writer::EncoderOptions {
+        codec: settings.codec,
+        bitrate_override_bps: settings.bitrate_override_bps,
+        quality_factor: settings.quality_factor,
+        fps: full_fps,
+        ..Default::default()
+    };
+    Ok(writer::estimate_size(w, h, settings.quality, &options, capture_fps, audio_tracks, duration_secs))
+}
+
+/// Frame rates of a recording on `display_id`: the encoder's (`preset_fps`
+/// capped at the display's refresh rate, then by `bounds`) and the
+/// capture's (lowered further by battery saver).
+fn frame_rates(preset_fps: i32, bounds: FrameRateBounds, display_id: u32, battery_saver: bool) -> (i32, i32) {
+    let mut preset_fps = preset_fps;
+    if let Some(refresh) = capture::display_refresh_rate(display_id) {
+        if preset_fps > refresh {
+            println!("[zureshot] {}fps requested but the display refreshes at {}Hz — capping", preset_fps, refresh);
+            preset_fps = refresh;
+        }
+    }
+    let full_fps = bounds.max_for(preset_fps);
+    let capture_fps = if battery_saver {
+        bounds.clamp(full_fps, full_fps.min(capture::BATTERY_SAVER_FPS))
+    } else {
+        full_fps
+    };
+    (full_fps, capture_fps)
+}
+
 /// Take a screenshot of a specific screen region. Returns (width, height, file_size).
 pub fn take_screenshot_region(
     x: f64,
@@ -243,8 +1156,9 @@ pub fn take_screenshot_region(
     width: f64,
     height: f64,
     output_path: &str,
+    format: ImageFormat,
 ) -> Result<(usize, usize, u64), String> {
-    capture::take_screenshot_region(x, y, width, height, output_path)
+    capture::take_screenshot_region(x, y, width, height, output_path, format)
 }
 
 // ── System integration ───────────────────────────────────────────────
@@ -258,8 +1172,8 @@ pub fn reveal_file(path: &str) -> Result<(), String> {
     Ok(())
 }
 
-/// Copy a PNG image to the clipboard using NSPasteboard directly (via JXA).
-/// Sets PNG, TIFF, and optionally the file path as plain text so terminal
+/// Copy a PNG/JPEG image to the clipboard using NSPasteboard directly (via JXA).
+/// Sets PNG (for PNG files), TIFF, and optionally the file path as plain text so terminal
 /// emulators (e.g. Ghostty) can paste the path with Cmd+V.
 pub fn copy_image_to_clipboard(path: &str) -> Result<(), String> {
     copy_image_to_clipboard_with_path(path, None)
@@ -271,6 +1185,7 @@ pub fn copy_image_to_clipboard_with_path(path: &str, file_path: Option<&str>) ->
         Some(p) => format!("'{}'", p.replace('\\', "\\\\").replace('\'', "\\'")),
         None => "null".to_string(),
     };
+    let is_png = crate::platform::image_mime_type(path) == "image/png";
     let script = format!(
         r#"ObjC.import('AppKit');
 ObjC.import('Foundation');
@@ -281,7 +1196,9 @@ if (!nsImg || nsImg.isNil()) {{ throw new Error('Failed to create NSImage'); }}
 var tiffData = nsImg.TIFFRepresentation;
 var pb = $.NSPasteboard.generalPasteboard;
 pb.clearContents;
-pb.setDataForType(data, $.NSPasteboardTypePNG);
+if ({is_png}) {{
+    pb.setDataForType(data, $.NSPasteboardTypePNG);
+}}
 pb.setDataForType(tiffData, $.NSPasteboardTypeTIFF);
 var filePath = {path_js};
 if (filePath) {{
@@ -302,6 +1219,46 @@ if (filePath) {{
     Ok(())
 }
 
+/// Put plain text on the clipboard (via `pbcopy`).
+pub fn copy_text_to_clipboard(text: &str) -> Result<(), String> {
+    use std::io::Write;
+    let mut child = std::process::Command::new("pbcopy")
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run pbcopy: {}", e))?;
+    if let Some(ref mut stdin) = child.stdin {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to write to pbcopy: {}", e))?;
+    }
+    let status = child.wait().map_err(|e| format!("pbcopy failed: {}", e))?;
+    if !status.success() {
+        return Err("Failed to copy text to clipboard".into());
+    }
+    Ok(())
+}
+
+/// Move a file or folder to the Trash (NSFileManager via JXA, so Finder's "Put Back" works).
+pub fn move_to_trash(path: &str) -> Result<(), String> {
+    let safe_path = path.replace('\\', "\\\\").replace('\'', "\\'");
+    let script = format!(
+        r#"ObjC.import('Foundation');
+var url = $.NSURL.fileURLWithPath('{safe_path}');
+var ok = $.NSFileManager.defaultManager.trashItemAtURLResultingItemURLError(url, null, null);
+if (!ok) {{ throw new Error('trashItemAtURL failed'); }}
+'ok'"#
+    );
+    let output = std::process::Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", &script])
+        .output()
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to move {} to Trash: {}", path, stderr.trim()));
+    }
+    Ok(())
+}
+
 /// Show a native confirmation dialog. Returns `true` if user clicked `accept`.
 pub fn show_confirm_dialog(title: &str, message: &str, accept: &str, cancel: &str) -> bool {
     let script = format!(
@@ -326,6 +1283,36 @@ pub fn show_info_dialog(title: &str, message: &str) {
         .output();
 }
 
+/// Show a native folder picker starting in `start_dir`. Returns `None` when
+/// the user cancels.
+pub fn pick_folder(prompt: &str, start_dir: &str) -> Option<String> {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "POSIX path of (choose folder with prompt \"{}\" default location (POSIX file \"{}\"))",
+        escape(prompt),
+        escape(start_dir)
+    );
+    let output = std::process::Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .ok()?;
+    // Non-zero exit when the user clicks Cancel
+    if !output.status.success() {
+        return None;
+    }
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!path.is_empty()).then_some(path)
+}
+
+/// Open a file in its default app (QuickTime for recordings).
+pub fn open_file(path: &str) -> Result<(), String> {
+    std::process::Command::new("open")
+        .arg(path)
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 /// Open a folder in Finder.
 pub fn open_folder(path: &str) -> Result<(), String> {
     std::process::Command::new("open")
@@ -335,6 +1322,42 @@ pub fn open_folder(path: &str) -> Result<(), String> {
     Ok(())
 }
 
+// ── UI sound effects ─────────────────────────────────────────────────
+
+/// Defaults domain/key behind System Settings → Sound → "Play user
+/// interface sound effects" (Trash, screenshot shutter, volume feedback…)
+const UI_SOUNDS_DOMAIN: &str = "com.apple.systemsound";
+const UI_SOUNDS_KEY: &str = "com.apple.sound.uiaudio.enabled";
+
+/// Whether macOS plays UI sound effects. `None` if the preference can't be read.
+pub fn ui_sounds_enabled() -> Option<bool> {
+    let output = std::process::Command::new("defaults")
+        .args(["read", UI_SOUNDS_DOMAIN, UI_SOUNDS_KEY])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        // Never toggled → the default, enabled
+        return Some(true);
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim() != "0")
+}
+
+/// Turn macOS UI sound effects on or off (takes effect for sounds played
+/// from now on; nothing to restart).
+pub fn set_ui_sounds_enabled(enabled: bool) -> Result<(), String> {
+    let output = std::process::Command::new("defaults")
+        .args(["write", UI_SOUNDS_DOMAIN, UI_SOUNDS_KEY, "-int", if enabled { "1" } else { "0" }])
+        .output()
+        .map_err(|e| format!("Failed to run defaults: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to change UI sound effects: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
 // ── Autostart (Launch at Login) ──────────────────────────────────────
 
 /// Check whether Zureshot is configured to launch at login.
@@ -408,6 +1431,90 @@ pub fn set_autostart_enabled(enabled: bool) {
     }
 }
 
+// ── Microphone / camera permission ───────────────────────────────────
+
+/// AVAuthorizationStatus values
+const AV_AUTHORIZATION_NOT_DETERMINED: isize = 0;
+const AV_AUTHORIZATION_AUTHORIZED: isize = 3;
+/// How long to wait for the user to answer a permission prompt
+const MIC_PROMPT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// Whether Zureshot may record the microphone. If the user was never asked,
+/// shows the system prompt and blocks until it is answered. Without access
+/// SCStream's mic output silently delivers no samples.
+pub fn microphone_access() -> bool {
+    media_access("soun", "microphone") // AVMediaTypeAudio
+}
+
+/// Whether Zureshot may use the camera (same prompt behavior as
+/// `microphone_access`).
+pub fn camera_access() -> bool {
+    media_access("vide", "camera") // AVMediaTypeVideo
+}
+
+fn media_access(media_type: &str, label: &str) -> bool {
+    use objc2::ClassType;
+    use objc2_av_foundation::AVCaptureDevice;
+
+    let media = objc2_foundation::NSString::from_str(media_type);
+    let status: isize = unsafe {
+        objc2::msg_send![AVCaptureDevice::class(), authorizationStatusForMediaType: &*media]
+    };
+    if status != AV_AUTHORIZATION_NOT_DETERMINED {
+        return status == AV_AUTHORIZATION_AUTHORIZED;
+    }
+
+    println!("[zureshot] Asking for {} permission", label);
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handler = block2::RcBlock::new(move |granted: objc2::runtime::Bool| {
+        let _ = tx.send(granted.as_bool());
+    });
+    unsafe {
+        let _: () = objc2::msg_send![
+            AVCaptureDevice::class(),
+            requestAccessForMediaType: &*media,
+            completionHandler: &*handler
+        ];
+    }
+    let granted = rx.recv_timeout(MIC_PROMPT_TIMEOUT).unwrap_or(false);
+    println!("[zureshot] {} permission {}", label, if granted { "granted" } else { "not granted" });
+    granted
+}
+
+// ── Microphones ──────────────────────────────────────────────────────
+
+/// Audio input devices visible to AVFoundation (built-in, USB, Bluetooth…).
+pub fn list_microphones() -> Result<Vec<MicrophoneInfo>, String> {
+    use objc2::ClassType;
+    use objc2_av_foundation::AVCaptureDevice;
+    use objc2_foundation::NSArray;
+
+    let audio_type = objc2_foundation::NSString::from_str("soun"); // AVMediaTypeAudio
+    unsafe {
+        let default: Option<Retained<AVCaptureDevice>> =
+            objc2::msg_send![AVCaptureDevice::class(), defaultDeviceWithMediaType: &*audio_type];
+        let default_id = default.map(|d| d.uniqueID().to_string());
+
+        // +[AVCaptureDevice devicesWithMediaType:]
+        let devices: Option<Retained<NSArray<AVCaptureDevice>>> =
+            objc2::msg_send![AVCaptureDevice::class(), devicesWithMediaType: &*audio_type];
+        let Some(devices) = devices else {
+            return Err("Could not list audio input devices".to_string());
+        };
+        Ok((0..devices.count())
+            .map(|i| {
+                let device = devices.objectAtIndex(i);
+                let id = device.uniqueID().to_string();
+                MicrophoneInfo {
+                    is_default: default_id.as_deref() == Some(id.as_str()),
+                    name: device.localizedName().to_string(),
+                    id,
+                }
+            })
+            .collect())
+    }
+}
+
 // ── First-run (no-op on macOS — handled by system permission dialog) ─
 
 /// On macOS, the system handles the screen recording permission prompt
@@ -418,12 +1525,64 @@ pub fn show_first_run_guide() {
 
 // ── Helpers ──────────────────────────────────────────────────────────
 
-/// Collect SCWindow objects that belong to our app (for exclusion from capture).
+/// `NSWindowCollectionBehavior` bits
+const COLLECTION_CAN_JOIN_ALL_SPACES: usize = 1 << 0;
+const COLLECTION_STATIONARY: usize = 1 << 4;
+const COLLECTION_FULL_SCREEN_AUXILIARY: usize = 1 << 8;
+
+/// Set how one of our windows behaves across Spaces. Without
+/// `FullScreenAuxiliary`, macOS won't show it over a fullscreen app.
+pub fn apply_spaces_behavior(window: &tauri::WebviewWindow, config: &BarSpacesConfig) -> Result<(), String> {
+    let ns_window = window.ns_window().map_err(|e| format!("No NSWindow: {}", e))?;
+    let mut behavior = 0;
+    if config.join_all_spaces {
+        behavior |= COLLECTION_CAN_JOIN_ALL_SPACES | COLLECTION_FULL_SCREEN_AUXILIARY;
+    }
+    if config.stationary {
+        behavior |= COLLECTION_STATIONARY;
+    }
+    let ns_window: *mut objc2::runtime::AnyObject = ns_window.cast();
+    unsafe {
+        let _: () = objc2::msg_send![ns_window, setCollectionBehavior: behavior];
+    }
+    Ok(())
+}
+
+/// WindowServer number of one of our windows (same as its `SCWindow.windowID`).
+fn window_number(window: &tauri::WebviewWindow) -> Option<u32> {
+    let ns_window: *mut objc2::runtime::AnyObject = window.ns_window().ok()?.cast();
+    let number: isize = unsafe { objc2::msg_send![ns_window, windowNumber] };
+    (number > 0).then_some(number as u32)
+}
+
+/// Why `w` is kept out of display recordings: it's one of ours (except
+/// the camera, keystroke and click overlays, which belong in recordings) or
+/// matches the user's `privacy_exclusions`. `None` to capture it.
+fn exclusion_reason(w: &SCWindow, our_pid: i32, privacy: &super::PrivacyExclusions) -> Option<&'static str> {
+    let owner = unsafe { w.owningApplication() };
+    let pid = owner.as_ref().map(|app_ref| unsafe { app_ref.processID() }).unwrap_or(-1);
+    let title = unsafe { w.title() }.map(|t| t.to_string()).unwrap_or_default();
+    if pid == our_pid {
+        (title != "Camera" && title != "Keystrokes" && title != "Clicks").then_some("own window")
+    } else if !privacy.is_empty() {
+        let bundle_id = owner
+            .map(|app_ref| unsafe { app_ref.bundleIdentifier() }.to_string())
+            .unwrap_or_default();
+        privacy.matches(&bundle_id, &title).then_some("privacy")
+    } else {
+        None
+    }
+}
+
+/// Collect SCWindow objects to exclude from capture: our app's windows, plus
+/// other apps' windows matching the user's `privacy_exclusions`.
 fn collect_app_windows_to_exclude(
     app: &AppHandle,
     all_windows: &[Retained<SCWindow>],
 ) -> Vec<Retained<SCWindow>> {
     let our_pid = std::process::id() as i32;
+    let privacy: super::PrivacyExclusions =
+        crate::settings::get(app, "privacy_exclusions").unwrap_or_default();
 
     let our_labels: Vec<String> = app.webview_windows().keys().cloned().collect();
     println!(
@@ -433,21 +1592,29 @@ fn collect_app_windows_to_exclude(
 
     let mut excluded = Vec::new();
     for w in all_windows {
-        let pid = unsafe { w.owningApplication() }
-            .map(|app_ref| unsafe { app_ref.processID() })
-            .unwrap_or(-1);
-        if pid == our_pid {
-            let title = unsafe { w.title() }
-                .map(|t| t.to_string())
-                .unwrap_or_default();
-            // Don't exclude the camera overlay — it should appear in recordings
-            if title == "Camera" {
-                println!("[zureshot] NOT excluding camera window: PID={} title={:?}", pid, title);
-                continue;
-            }
-            println!("[zureshot] Excluding window: PID={} title={:?}", pid, title);
+        if let Some(reason) = exclusion_reason(w, our_pid, &privacy) {
+            let title = unsafe { w.title() }.map(|t| t.to_string()).unwrap_or_default();
+            println!("[zureshot] Excluding window ({}): title={:?}", reason, title);
             excluded.push(w.clone());
         }
     }
     excluded
 }
+
+/// Sorted IDs of the windows a display recording should exclude right now
+/// (see `collect_app_windows_to_exclude`). The filter is a snapshot of
+/// windows, so a change here (a private app opened a window, one of ours
+/// appeared) means the filter needs rebuilding.
+pub fn excluded_window_ids(app: &AppHandle, display_id: Option<u32>) -> Result<Vec<u32>, String> {
+    let our_pid = std::process::id() as i32;
+    let privacy: super::PrivacyExclusions =
+        crate::settings::get(app, "privacy_exclusions").unwrap_or_default();
+    let (_, all_windows) = capture::get_display_and_windows_for(display_id)?;
+    let mut ids: Vec<u32> = all_windows
+        .iter()
+        .filter(|w| exclusion_reason(w, our_pid, &privacy).is_some())
+        .map(|w| unsafe { w.windowID() })
+        .collect();
+    ids.sort_unstable();
+    Ok(ids)
+}
@@ -30,6 +30,33 @@ pub struct MouseTrack {
     pub samples: Vec<MouseSample>,
     pub duration_secs: f64,
     pub sample_rate_hz: f64,
+    /// False when the cursor was hidden from the capture (standard-size
+    /// cursor mode) — the editor must draw it from these samples.
+    #[serde(default = "default_cursor_in_video")]
+    pub cursor_in_video: bool,
+    /// The area the video shows, in the samples' coordinates (logical,
+    /// main display top-left). `None` for window recordings and older
+    /// tracks, which are taken as the main display.
+    #[serde(default)]
+    pub capture_rect: Option<crate::platform::CaptureRegion>,
+}
+
+fn default_cursor_in_video() -> bool { true }
+
+impl MouseTrack {
+    /// Where (`x`, `y`) falls in the recorded area, 0-1 on each axis
+    /// (clamped: the cursor may leave the area).
+    pub fn normalize(&self, x: f64, y: f64) -> (f64, f64) {
+        let (left, top, width, height) = match self.capture_rect {
+            Some(ref r) => (r.x, r.y, r.width, r.height),
+            None => {
+                let (w, h) = get_main_display_logical_size();
+                (0.0, 0.0, w, h)
+            }
+        };
+        let axis = |v: f64, start: f64, len: f64| if len > 0.0 { ((v - start) / len).clamp(0.0, 1.0) } else { 0.5 };
+        (axis(x, left, width), axis(y, top, height))
+    }
 }
 
 /// State for the mouse tracker thread.
@@ -37,6 +64,8 @@ pub struct MouseTrackerState {
     pub running: Arc<AtomicBool>,
     pub samples: Arc<Mutex<Vec<MouseSample>>>,
     pub start_time: Arc<Mutex<Option<std::time::Instant>>>,
+    /// Set while the recording is paused; no samples are taken
+    pub pause_start: Arc<Mutex<Option<std::time::Instant>>>,
 }
 
 impl Default for MouseTrackerState {
@@ -45,6 +74,7 @@ impl Default for MouseTrackerState {
             running: Arc::new(AtomicBool::new(false)),
             samples: Arc::new(Mutex::new(Vec::new())),
             start_time: Arc::new(Mutex::new(None)),
+            pause_start: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -65,10 +95,14 @@ pub fn start_mouse_tracking(state: &MouseTrackerState) {
     if let Ok(mut start) = state.start_time.lock() {
         *start = Some(std::time::Instant::now());
     }
+    if let Ok(mut paused) = state.pause_start.lock() {
+        *paused = None;
+    }
 
     let running = state.running.clone();
     let samples = state.samples.clone();
     let start_time = state.start_time.clone();
+    let pause_start = state.pause_start.clone();
 
     std::thread::spawn(move || {
         println!("[mouse-tracker] Started mouse position tracking at ~30 Hz");
@@ -80,11 +114,20 @@ pub fn start_mouse_tracking(state: &MouseTrackerState) {
             // Get current mouse position using CoreGraphics
             let (x, y, clicked) = get_mouse_position();
 
-            let time = start_time
-                .lock()
-                .ok()
-                .and_then(|s| s.map(|t| t.elapsed().as_secs_f64()))
-                .unwrap_or(0.0);
+            // None while paused. Same lock order as resume_mouse_tracking,
+            // so a sample never mixes the old start time with a resume
+            let time = start_time.lock().ok().and_then(|s| {
+                let paused = pause_start.lock().map(|p| p.is_some()).unwrap_or(false);
+                if paused {
+                    None
+                } else {
+                    Some(s.map(|t| t.elapsed().as_secs_f64()).unwrap_or(0.0))
+                }
+            });
+            let Some(time) = time else {
+                std::thread::sleep(interval);
+                continue;
+            };
 
             if let Ok(mut s) = samples.lock() {
                 s.push(MouseSample {
@@ -116,17 +159,39 @@ pub fn stop_mouse_tracking(state: &MouseTrackerState) -> MouseTrack {
         .map(|s| s.clone())
         .unwrap_or_default();
 
+    // Stopped while paused: the track ends where the pause began
+    let paused_at = state.pause_start.lock().ok().and_then(|mut p| p.take());
     let duration = state
         .start_time
         .lock()
         .ok()
-        .and_then(|s| s.map(|t| t.elapsed().as_secs_f64()))
+        .and_then(|s| s.map(|t| paused_at.unwrap_or_else(std::time::Instant::now).duration_since(t).as_secs_f64()))
         .unwrap_or(0.0);
 
     MouseTrack {
         samples,
         duration_secs: duration,
         sample_rate_hz: 30.0,
+        cursor_in_video: true,
+        capture_rect: None,
+    }
+}
+
+/// Stop sampling while the recording is paused.
+pub fn pause_mouse_tracking(state: &MouseTrackerState) {
+    if let Ok(mut paused) = state.pause_start.lock() {
+        paused.get_or_insert_with(std::time::Instant::now);
+    }
+}
+
+/// Resume sampling after a pause. The start time moves forward by the
+/// pause, so sample times stay on the video's timeline (paused frames are
+/// dropped from the recording).
+pub fn resume_mouse_tracking(state: &MouseTrackerState) {
+    let Ok(mut start) = state.start_time.lock() else { return };
+    let Some(paused_at) = state.pause_start.lock().ok().and_then(|mut p| p.take()) else { return };
+    if let Some(t) = start.as_mut() {
+        *t += paused_at.elapsed();
     }
 }
 
@@ -175,28 +240,24 @@ pub fn load_mouse_track(video_path: &str) -> Result<MouseTrack, String> {
 /// Analyze mouse track to suggest zoom keyframes (FocuSee-quality).
 ///
 /// Two-pass algorithm:
-/// 1. **Click detection**: Clicks are high-priority zoom targets (brief 2x pulse)
+/// 1. **Click detection**: Clicks are high-priority zoom targets (brief 2x
+///    pulse held for `click_hold` seconds; `None` skips this pass)
 /// 2. **Dwell detection**: Mouse staying still = reading/focusing (sustained zoom)
 ///
 /// Uses normalized coordinates (0-1) for resolution independence.
 /// Each keyframe includes a `hold` duration so the frontend knows how long
 /// to sustain the zoom before transitioning out.
-pub fn suggest_zoom_keyframes(track: &MouseTrack) -> Vec<super::editor::ZoomKeyframe> {
+pub fn suggest_zoom_keyframes(track: &MouseTrack, click_hold: Option<f64>) -> Vec<super::editor::ZoomKeyframe> {
     if track.samples.len() < 30 {
         return Vec::new();
     }
 
-    // ── Normalize to 0-1 using full screen bounds ──
-    // CGEvent::location() returns logical screen coordinates.
-    // We need the main display's logical size to normalize properly.
-    let (screen_w, screen_h) = get_main_display_logical_size();
-
+    // ── Normalize to 0-1 within the recorded area ──
+    // CGEvent::location() returns global logical coordinates.
     struct Sample { time: f64, nx: f64, ny: f64, clicked: bool }
-    let norm: Vec<Sample> = track.samples.iter().map(|s| Sample {
-        time: s.time,
-        nx: if screen_w > 0.0 { (s.x / screen_w).clamp(0.0, 1.0) } else { 0.5 },
-        ny: if screen_h > 0.0 { (s.y / screen_h).clamp(0.0, 1.0) } else { 0.5 },
-        clicked: s.clicked,
+    let norm: Vec<Sample> = track.samples.iter().map(|s| {
+        let (nx, ny) = track.normalize(s.x, s.y);
+        Sample { time: s.time, nx, ny, clicked: s.clicked }
     }).collect();
 
     // ── Pass 1: Click detection ──
@@ -205,20 +266,23 @@ pub fn suggest_zoom_keyframes(track: &MouseTrack) -> Vec<super::editor::ZoomKeyf
     struct ZoomCandidate { time: f64, cx: f64, cy: f64, zoom: f64, hold: f64, priority: f64 }
     let mut candidates: Vec<ZoomCandidate> = Vec::new();
 
-    let mut last_click_time = -1.0_f64;
-    for s in &norm {
-        if s.clicked && (s.time - last_click_time) > 0.4 {
-            candidates.push(ZoomCandidate {
-                time: s.time,
-                cx: s.nx,
-                cy: s.ny,
-                zoom: 2.0,      // clicks get a crisp 2x zoom
-                hold: 0.35,     // brief hold — snap in and out
-                priority: 10.0, // clicks are highest priority
-            });
-            last_click_time = s.time;
+    if let Some(hold) = click_hold {
+        let mut last_click_time = -1.0_f64;
+        for s in &norm {
+            if s.clicked && (s.time - last_click_time) > 0.4 {
+                candidates.push(ZoomCandidate {
+                    time: s.time,
+                    cx: s.nx,
+                    cy: s.ny,
+                    zoom: 2.0,      // clicks get a crisp 2x zoom
+                    hold,           // brief by default — snap in and out
+                    priority: 10.0, // clicks are highest priority
+                });
+                last_click_time = s.time;
+            }
         }
     }
+    let clicks = candidates.len();
 
     // ── Pass 2: Dwell detection (velocity-based) ──
     // Compute per-sample velocity, then find runs of low velocity.
@@ -359,10 +423,8 @@ pub fn suggest_zoom_keyframes(track: &MouseTrack) -> Vec<super::editor::ZoomKeyf
     }
     let keyframes = filtered;
 
-    println!("[mouse-tracker] Suggested {} zoom keyframes ({} clicks, {} dwells)",
-        keyframes.len(),
-        keyframes.iter().filter(|k| k.hold < 0.4).count(),
-        keyframes.iter().filter(|k| k.hold >= 0.4).count());
+    println!("[mouse-tracker] Suggested {} zoom keyframes (from {} clicks)",
+        keyframes.len(), clicks);
     keyframes
 }
 
@@ -371,7 +433,7 @@ pub fn suggest_zoom_keyframes(track: &MouseTrack) -> Vec<super::editor::ZoomKeyf
 /// Get the current mouse position and click state via CoreGraphics.
 /// Get the main display's logical size (points, not pixels).
 /// Used to normalize mouse coordinates (CGEvent returns logical coords).
-fn get_main_display_logical_size() -> (f64, f64) {
+pub fn get_main_display_logical_size() -> (f64, f64) {
     use objc2_core_graphics::CGMainDisplayID;
     let display_id = CGMainDisplayID();
     let w = objc2_core_graphics::CGDisplayPixelsWide(display_id) as f64;
@@ -0,0 +1,97 @@
+//! Power source detection (IOKit IOPowerSources) for battery-saver recording.
+//!
+//! `watch_power_source` registers an IOPS run-loop source on the main run
+//! loop; the handler fires only when the providing source actually changes
+//! (AC ⇄ battery), not on every percentage update.
+
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use objc2_foundation::NSString;
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPSCopyPowerSourcesInfo() -> *const c_void;
+    /// Returns a CFStringRef owned by the snapshot (not retained).
+    fn IOPSGetProvidingPowerSourceType(snapshot: *const c_void) -> *const c_void;
+    fn IOPSNotificationCreateRunLoopSource(
+        callback: extern "C" fn(context: *mut c_void),
+        context: *mut c_void,
+    ) -> *const c_void;
+}
+
+extern "C" {
+    fn CFRunLoopGetMain() -> *const c_void;
+    fn CFRunLoopAddSource(run_loop: *const c_void, source: *const c_void, mode: *const c_void);
+    fn CFRelease(cf: *const c_void);
+    static kCFRunLoopDefaultMode: *const c_void;
+}
+
+/// `kIOPMBatteryPowerKey`
+const BATTERY_POWER: &str = "Battery Power";
+
+/// Last observed state, so the handler only runs on transitions.
+static ON_BATTERY: AtomicBool = AtomicBool::new(false);
+/// Camera preview runs at half rate while set.
+static REDUCED_PREVIEW: AtomicBool = AtomicBool::new(false);
+static HANDLER: OnceLock<Box<dyn Fn(bool) + Send + Sync>> = OnceLock::new();
+
+/// True when the Mac is currently running on battery.
+pub fn on_battery() -> bool {
+    unsafe {
+        let snapshot = IOPSCopyPowerSourcesInfo();
+        if snapshot.is_null() {
+            return false; // Desktop Macs without power source info
+        }
+        let source = IOPSGetProvidingPowerSourceType(snapshot);
+        let battery = !source.is_null()
+            && (*(source as *const NSString)).to_string() == BATTERY_POWER;
+        CFRelease(snapshot);
+        battery
+    }
+}
+
+/// Call `handler(on_battery)` whenever the power source changes.
+/// Must be called on the main thread (the source joins the main run loop).
+/// Only the first registration takes effect.
+pub fn watch_power_source(handler: impl Fn(bool) + Send + Sync + 'static) {
+    if HANDLER.set(Box::new(handler)).is_err() {
+        return;
+    }
+    ON_BATTERY.store(on_battery(), Ordering::Relaxed);
+
+    unsafe {
+        let source = IOPSNotificationCreateRunLoopSource(power_source_changed, std::ptr::null_mut());
+        if source.is_null() {
+            eprintln!("[zureshot] Failed to register for power source notifications");
+            return;
+        }
+        CFRunLoopAddSource(CFRunLoopGetMain(), source, kCFRunLoopDefaultMode);
+        // The run loop retains the source
+        CFRelease(source);
+    }
+    println!(
+        "[zureshot] Watching power source (currently {})",
+        if ON_BATTERY.load(Ordering::Relaxed) { "battery" } else { "AC" }
+    );
+}
+
+extern "C" fn power_source_changed(_context: *mut c_void) {
+    let now = on_battery();
+    if ON_BATTERY.swap(now, Ordering::Relaxed) == now {
+        return;
+    }
+    println!("[zureshot] Power source changed: {}", if now { "battery" } else { "AC" });
+    if let Some(handler) = HANDLER.get() {
+        handler(now);
+    }
+}
+
+pub fn set_reduced_preview(reduced: bool) {
+    REDUCED_PREVIEW.store(reduced, Ordering::Relaxed);
+}
+
+pub fn reduced_preview() -> bool {
+    REDUCED_PREVIEW.load(Ordering::Relaxed)
+}
@@ -0,0 +1,595 @@
+//! Instant replay — keep the last N seconds in memory, save on demand.
+//!
+//! Architecture:
+//!   SCStream → CVPixelBuffer → VTCompressionSession (HEVC, keyframe every 1s)
+//!            → ring of encoded CMSampleBuffers (whole GOPs, oldest dropped)
+//!   save     → AVAssetWriter passthrough input → MP4 (no re-encode)
+//!
+//! Only compressed frames are held (a few MB per second at most), and nothing
+//! touches the disk until the user saves. Video only — audio is not buffered.
+
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use dispatch2::DispatchQueue;
+use objc2::rc::Retained;
+use objc2::runtime::{NSObject, NSObjectProtocol, ProtocolObject};
+use objc2::{define_class, msg_send, AllocAnyThread, DefinedClass};
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use objc2_core_graphics::kCGColorSpaceSRGB;
+use objc2_core_media::{CMFormatDescription, CMSampleBuffer, CMTime};
+use objc2_foundation::{NSArray, NSNumber};
+use objc2_screen_capture_kit::{
+    SCCaptureResolutionType, SCContentFilter, SCDisplay, SCStream, SCStreamConfiguration,
+    SCStreamOutput, SCStreamOutputType, SCWindow,
+};
+
+use crate::platform::{PixelFormat, RecordingQuality};
+
+/// Default buffer length when the caller doesn't specify one.
+pub const DEFAULT_REPLAY_SECS: f64 = 30.0;
+/// Upper bound on the buffer (memory grows linearly with it).
+pub const MAX_REPLAY_SECS: f64 = 300.0;
+/// Keyframe spacing — a saved clip starts at most this much before the request.
+const REPLAY_KEYFRAME_INTERVAL_SECS: f64 = 1.0;
+
+/// 'hvc1'
+const CODEC_TYPE_HEVC: u32 = 0x6876_6331;
+
+type VTOutputCallback = extern "C" fn(
+    refcon: *mut c_void,
+    source_frame_refcon: *mut c_void,
+    status: i32,
+    info_flags: u32,
+    sample_buffer: *const c_void,
+);
+
+#[link(name = "VideoToolbox", kind = "framework")]
+extern "C" {
+    fn VTCompressionSessionCreate(
+        allocator: *const c_void,
+        width: i32,
+        height: i32,
+        codec_type: u32,
+        encoder_specification: *const c_void,
+        source_image_buffer_attributes: *const c_void,
+        compressed_data_allocator: *const c_void,
+        output_callback: Option<VTOutputCallback>,
+        output_callback_refcon: *mut c_void,
+        session_out: *mut *mut c_void,
+    ) -> i32;
+    fn VTSessionSetProperty(session: *mut c_void, key: *const c_void, value: *const c_void) -> i32;
+    fn VTCompressionSessionPrepareToEncodeFrames(session: *mut c_void) -> i32;
+    fn VTCompressionSessionEncodeFrame(
+        session: *mut c_void,
+        image_buffer: *const c_void,
+        pts: CMTime,
+        duration: CMTime,
+        frame_properties: *const c_void,
+        source_frame_refcon: *mut c_void,
+        info_flags_out: *mut u32,
+    ) -> i32;
+    fn VTCompressionSessionCompleteFrames(session: *mut c_void, complete_until: CMTime) -> i32;
+    fn VTCompressionSessionInvalidate(session: *mut c_void);
+
+    static kVTCompressionPropertyKey_RealTime: *const c_void;
+    static kVTCompressionPropertyKey_AllowFrameReordering: *const c_void;
+    static kVTCompressionPropertyKey_MaxKeyFrameIntervalDuration: *const c_void;
+    static kVTCompressionPropertyKey_AverageBitRate: *const c_void;
+    static kVTCompressionPropertyKey_ProfileLevel: *const c_void;
+    static kVTProfileLevel_HEVC_Main_AutoLevel: *const c_void;
+    static kVTEncodeFrameOptionKey_ForceKeyFrame: *const c_void;
+}
+
+extern "C" {
+    fn CMSampleBufferGetSampleAttachmentsArray(sbuf: *const c_void, create: bool) -> *const c_void;
+    fn CMSampleBufferGetPresentationTimeStamp(sbuf: *const c_void) -> CMTime;
+    fn CMSampleBufferGetFormatDescription(sbuf: *const c_void) -> *const c_void;
+    fn CFArrayGetCount(array: *const c_void) -> isize;
+    fn CFArrayGetValueAtIndex(array: *const c_void, index: isize) -> *const c_void;
+    fn CFDictionaryGetValue(dict: *const c_void, key: *const c_void) -> *const c_void;
+    fn CFBooleanGetValue(boolean: *const c_void) -> bool;
+    fn CFRetain(cf: *const c_void) -> *const c_void;
+    fn CFRelease(cf: *const c_void);
+    fn CFDictionaryCreate(
+        allocator: *const c_void,
+        keys: *const *const c_void,
+        values: *const *const c_void,
+        num_values: isize,
+        key_callbacks: *const c_void,
+        value_callbacks: *const c_void,
+    ) -> *const c_void;
+    static kCFTypeDictionaryKeyCallBacks: c_void;
+    static kCFTypeDictionaryValueCallBacks: c_void;
+
+    static kCMSampleAttachmentKey_NotSync: *const c_void;
+    static kCMTimeInvalid: CMTime;
+    static kCFBooleanTrue: *const c_void;
+    static kCFBooleanFalse: *const c_void;
+}
+
+// ────────────────────────────────────────────────────────────────
+//  Ring of encoded frames
+// ────────────────────────────────────────────────────────────────
+
+/// One encoded frame (retained CMSampleBufferRef).
+struct EncodedFrame {
+    sample: *const c_void,
+    pts_secs: f64,
+    keyframe: bool,
+}
+
+impl Clone for EncodedFrame {
+    fn clone(&self) -> Self {
+        unsafe { CFRetain(self.sample) };
+        Self { sample: self.sample, pts_secs: self.pts_secs, keyframe: self.keyframe }
+    }
+}
+
+impl Drop for EncodedFrame {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.sample) };
+    }
+}
+
+// SAFETY: CMSampleBuffers are immutable once encoded; retain/release are thread-safe.
+unsafe impl Send for EncodedFrame {}
+
+/// Encodes captured frames and keeps the most recent `capacity_secs`.
+pub struct ReplayBuffer {
+    session: *mut c_void,
+    frames: Mutex<VecDeque<EncodedFrame>>,
+    capacity_secs: f64,
+    /// Encode the next frame as a keyframe (`request_keyframe`)
+    force_keyframe: AtomicBool,
+}
+
+// SAFETY: VTCompressionSession is thread-safe for encode/complete calls;
+// the ring is guarded by its Mutex.
+unsafe impl Send for ReplayBuffer {}
+unsafe impl Sync for ReplayBuffer {}
+
+impl ReplayBuffer {
+    /// Create the HEVC compression session. The returned Arc's pointer is
+    /// the VideoToolbox callback refcon, so it must outlive the session
+    /// (ensured by invalidating the session in `Drop`).
+    fn new(
+        width: usize,
+        height: usize,
+        quality: RecordingQuality,
+        capacity_secs: f64,
+    ) -> Result<Arc<Self>, String> {
+        let mut buffer = Arc::new(Self {
+            session: std::ptr::null_mut(),
+            frames: Mutex::new(VecDeque::new()),
+            capacity_secs,
+            force_keyframe: AtomicBool::new(false),
+        });
+
+        let refcon = Arc::as_ptr(&buffer) as *mut c_void;
+        let mut session: *mut c_void = std::ptr::null_mut();
+        let status = unsafe {
+            VTCompressionSessionCreate(
+                std::ptr::null(),
+                width as i32,
+                height as i32,
+                CODEC_TYPE_HEVC,
+                std::ptr::null(),
+                std::ptr::null(),
+                std::ptr::null(),
+                Some(encoded_frame_callback),
+                refcon,
+                &mut session,
+            )
+        };
+        if status != 0 || session.is_null() {
+            return Err(format!("VTCompressionSessionCreate failed: OSStatus {}", status));
+        }
+
+        let bitrate = super::writer::compute_bitrate(width, height, quality);
+        unsafe {
+            let bitrate_num = NSNumber::new_i64(bitrate);
+            let keyframe_num = NSNumber::new_f64(REPLAY_KEYFRAME_INTERVAL_SECS);
+            VTSessionSetProperty(session, kVTCompressionPropertyKey_RealTime, kCFBooleanTrue);
+            VTSessionSetProperty(session, kVTCompressionPropertyKey_AllowFrameReordering, kCFBooleanFalse);
+            VTSessionSetProperty(
+                session,
+                kVTCompressionPropertyKey_ProfileLevel,
+                kVTProfileLevel_HEVC_Main_AutoLevel,
+            );
+            VTSessionSetProperty(
+                session,
+                kVTCompressionPropertyKey_AverageBitRate,
+                &*bitrate_num as *const NSNumber as *const c_void,
+            );
+            VTSessionSetProperty(
+                session,
+                kVTCompressionPropertyKey_MaxKeyFrameIntervalDuration,
+                &*keyframe_num as *const NSNumber as *const c_void,
+            );
+            VTCompressionSessionPrepareToEncodeFrames(session);
+        }
+
+        // Only reference is ours — the callback can't fire before the first encode
+        Arc::get_mut(&mut buffer).expect("replay buffer not shared yet").session = session;
+        Ok(buffer)
+    }
+
+    /// Submit a captured frame (CVImageBufferRef) for encoding.
+    fn encode(&self, image_buffer: *const c_void, pts: CMTime) {
+        unsafe {
+            // { kVTEncodeFrameOptionKey_ForceKeyFrame: true } when requested
+            let frame_properties = if self.force_keyframe.swap(false, Ordering::Relaxed) {
+                let keys = [kVTEncodeFrameOptionKey_ForceKeyFrame];
+                let values = [kCFBooleanTrue];
+                CFDictionaryCreate(
+                    std::ptr::null(),
+                    keys.as_ptr(),
+                    values.as_ptr(),
+                    1,
+                    &kCFTypeDictionaryKeyCallBacks as *const c_void,
+                    &kCFTypeDictionaryValueCallBacks as *const c_void,
+                )
+            } else {
+                std::ptr::null()
+            };
+            VTCompressionSessionEncodeFrame(
+                self.session,
+                image_buffer,
+                pts,
+                kCMTimeInvalid,
+                frame_properties,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            );
+            if !frame_properties.is_null() {
+                CFRelease(frame_properties);
+            }
+        }
+    }
+
+    /// Called from the VideoToolbox output callback.
+    fn push(&self, sample: *const c_void) {
+        let (pts_secs, keyframe) = unsafe {
+            let pts = CMSampleBufferGetPresentationTimeStamp(sample);
+            let (value, timescale) = (pts.value, pts.timescale);
+            if timescale <= 0 {
+                return;
+            }
+            (value as f64 / timescale as f64, is_keyframe(sample))
+        };
+        unsafe { CFRetain(sample) };
+        let frame = EncodedFrame { sample, pts_secs, keyframe };
+
+        let mut frames = match self.frames.lock() {
+            Ok(f) => f,
+            Err(_) => return,
+        };
+        // Nothing before the first keyframe is decodable
+        if frames.is_empty() && !frame.keyframe {
+            return;
+        }
+        frames.push_back(frame);
+
+        // Drop whole GOPs while the next keyframe still covers the window
+        loop {
+            let newest = frames.back().map(|f| f.pts_secs).unwrap_or(0.0);
+            let next_key = frames.iter().skip(1).position(|f| f.keyframe).map(|i| i + 1);
+            match next_key {
+                Some(k) if newest - frames[k].pts_secs >= self.capacity_secs => {
+                    frames.drain(..k);
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Seconds currently buffered.
+    pub fn buffered_secs(&self) -> f64 {
+        let frames = match self.frames.lock() {
+            Ok(f) => f,
+            Err(_) => return 0.0,
+        };
+        match (frames.front(), frames.back()) {
+            (Some(a), Some(b)) => b.pts_secs - a.pts_secs,
+            _ => 0.0,
+        }
+    }
+
+    /// Copy out the frames covering the last `secs`, starting at the
+    /// nearest keyframe at or before the requested start.
+    fn snapshot(&self, secs: f64) -> Vec<EncodedFrame> {
+        unsafe { VTCompressionSessionCompleteFrames(self.session, kCMTimeInvalid) };
+
+        let frames = match self.frames.lock() {
+            Ok(f) => f,
+            Err(_) => return Vec::new(),
+        };
+        let Some(newest) = frames.back().map(|f| f.pts_secs) else {
+            return Vec::new();
+        };
+        let target = newest - secs;
+        let start = frames
+            .iter()
+            .rposition(|f| f.keyframe && f.pts_secs <= target)
+            .unwrap_or(0);
+        frames.iter().skip(start).cloned().collect()
+    }
+}
+
+impl Drop for ReplayBuffer {
+    fn drop(&mut self) {
+        if !self.session.is_null() {
+            unsafe {
+                VTCompressionSessionCompleteFrames(self.session, kCMTimeInvalid);
+                VTCompressionSessionInvalidate(self.session);
+                CFRelease(self.session);
+            }
+        }
+    }
+}
+
+/// True unless the sample carries `kCMSampleAttachmentKey_NotSync = true`.
+unsafe fn is_keyframe(sample: *const c_void) -> bool {
+    let attachments = CMSampleBufferGetSampleAttachmentsArray(sample, false);
+    if attachments.is_null() || CFArrayGetCount(attachments) == 0 {
+        return true;
+    }
+    let dict = CFArrayGetValueAtIndex(attachments, 0);
+    let not_sync = CFDictionaryGetValue(dict, kCMSampleAttachmentKey_NotSync);
+    not_sync.is_null() || !CFBooleanGetValue(not_sync)
+}
+
+extern "C" fn encoded_frame_callback(
+    refcon: *mut c_void,
+    _source_frame_refcon: *mut c_void,
+    status: i32,
+    _info_flags: u32,
+    sample_buffer: *const c_void,
+) {
+    if status != 0 || sample_buffer.is_null() || refcon.is_null() {
+        return;
+    }
+    // SAFETY: refcon is the ReplayBuffer that owns (and outlives) the session
+    let buffer = unsafe { &*(refcon as *const ReplayBuffer) };
+    buffer.push(sample_buffer);
+}
+
+// ────────────────────────────────────────────────────────────────
+//  ReplayOutput — SCStreamOutput delegate feeding the encoder
+// ────────────────────────────────────────────────────────────────
+
+pub struct ReplayOutputIvars {
+    buffer: Arc<ReplayBuffer>,
+}
+
+define_class!(
+    // SAFETY: called on a serial dispatch queue; the buffer is Sync.
+    #[unsafe(super(NSObject))]
+    #[thread_kind = AllocAnyThread]
+    #[name = "ZSReplayOutput"]
+    #[ivars = ReplayOutputIvars]
+    pub struct ReplayOutput;
+
+    unsafe impl NSObjectProtocol for ReplayOutput {}
+
+    unsafe impl SCStreamOutput for ReplayOutput {
+        #[unsafe(method(stream:didOutputSampleBuffer:ofType:))]
+        fn stream_didOutputSampleBuffer_ofType(
+            &self,
+            _stream: &SCStream,
+            sample_buffer: &CMSampleBuffer,
+            output_type: SCStreamOutputType,
+        ) {
+            if output_type.0 != 0 {
+                return;
+            }
+            unsafe {
+                if !sample_buffer.is_valid() || !sample_buffer.data_is_ready() {
+                    return;
+                }
+                // Status-only frames (Idle, Blank…) carry no image
+                let Some(image) = sample_buffer.image_buffer() else {
+                    return;
+                };
+                let pts = sample_buffer.presentation_time_stamp();
+                let (value, timescale) = (pts.value, pts.timescale);
+                if value <= 0 || timescale <= 0 {
+                    return;
+                }
+                self.ivars()
+                    .buffer
+                    .encode(&*image as *const _ as *const c_void, pts);
+            }
+        }
+    }
+);
+
+impl ReplayOutput {
+    fn new_with(buffer: Arc<ReplayBuffer>) -> Retained<Self> {
+        let this = Self::alloc().set_ivars(ReplayOutputIvars { buffer });
+        unsafe { msg_send![super(this), init] }
+    }
+}
+
+// ────────────────────────────────────────────────────────────────
+//  Session
+// ────────────────────────────────────────────────────────────────
+
+/// A running replay capture (stream + buffer).
+pub struct ReplaySession {
+    stream: Retained<SCStream>,
+    _output: Retained<ReplayOutput>,
+    buffer: Arc<ReplayBuffer>,
+}
+
+// SAFETY: access is serialized via the Mutex<InstantReplayState> in commands.rs.
+unsafe impl Send for ReplaySession {}
+unsafe impl Sync for ReplaySession {}
+
+/// Managed state: at most one replay buffer runs at a time.
+#[derive(Default)]
+pub struct InstantReplayState {
+    pub session: Option<ReplaySession>,
+}
+
+/// Start capturing `display` into a rolling buffer of `capacity_secs`.
+pub fn start(
+    display: &SCDisplay,
+    width: usize,
+    height: usize,
+    quality: RecordingQuality,
+    capacity_secs: f64,
+    exclude_windows: Vec<Retained<SCWindow>>,
+) -> Result<ReplaySession, String> {
+    let width = crate::platform::even_dimension(width);
+    let height = crate::platform::even_dimension(height);
+    let fps: i32 = match quality {
+        RecordingQuality::Standard => 30,
+        RecordingQuality::High => 60,
+    };
+
+    let buffer = ReplayBuffer::new(width, height, quality, capacity_secs)?;
+
+    let config = unsafe {
+        let c = SCStreamConfiguration::new();
+        c.setWidth(width);
+        c.setHeight(height);
+        c.setMinimumFrameInterval(CMTime::new(1, fps));
+        c.setShowsCursor(true);
+        c.setPixelFormat(PixelFormat::default().fourcc());
+        c.setQueueDepth(3);
+        c.setCaptureResolution(SCCaptureResolutionType::Best);
+        c.setColorSpaceName(kCGColorSpaceSRGB);
+        c.setShouldBeOpaque(true);
+        c.setDestinationRect(CGRect::new(
+            CGPoint::new(0.0, 0.0),
+            CGSize::new(width as f64, height as f64),
+        ));
+        c
+    };
+
+    let exclude_array: Retained<NSArray<SCWindow>> = if exclude_windows.is_empty() {
+        NSArray::new()
+    } else {
+        let refs: Vec<&SCWindow> = exclude_windows.iter().map(|w| &**w).collect();
+        NSArray::from_slice(&refs)
+    };
+    let filter = unsafe {
+        SCContentFilter::initWithDisplay_excludingWindows(
+            SCContentFilter::alloc(),
+            display,
+            &exclude_array,
+        )
+    };
+
+    let output = ReplayOutput::new_with(buffer.clone());
+    let stream = unsafe {
+        SCStream::initWithFilter_configuration_delegate(SCStream::alloc(), &filter, &config, None)
+    };
+    let queue = DispatchQueue::new("com.zureshot.replay", None);
+    unsafe {
+        stream
+            .addStreamOutput_type_sampleHandlerQueue_error(
+                ProtocolObject::from_ref(&*output),
+                SCStreamOutputType(0),
+                Some(&queue),
+            )
+            .map_err(|e| format!("Failed to add replay stream output: {}", e))?;
+    }
+    super::capture::start_with_retry(&stream)?;
+
+    println!(
+        "[zureshot] Instant replay started: {}x{} @ {}fps, buffer={:.0}s",
+        width, height, fps, capacity_secs
+    );
+    Ok(ReplaySession { stream, _output: output, buffer })
+}
+
+impl ReplaySession {
+    pub fn capacity_secs(&self) -> f64 {
+        self.buffer.capacity_secs
+    }
+
+    pub fn buffered_secs(&self) -> f64 {
+        self.buffer.buffered_secs()
+    }
+
+    /// Stop the capture stream (blocks until confirmed). Buffered frames
+    /// are released when the session is dropped.
+    pub fn stop(&self) {
+        super::capture::stop(&self.stream);
+        println!("[zureshot] Instant replay stopped");
+    }
+
+    /// Make the next encoded frame a keyframe (an exact cut point).
+    pub fn request_keyframe(&self) {
+        self.buffer.force_keyframe.store(true, Ordering::Relaxed);
+    }
+
+    /// Frames covering the last `secs` — cheap (retains only), so it can be
+    /// taken under the state lock and written out after releasing it.
+    pub fn snapshot(&self, secs: f64) -> ReplayClip {
+        ReplayClip { frames: self.buffer.snapshot(secs) }
+    }
+}
+
+/// Encoded frames detached from the ring, ready to be written.
+pub struct ReplayClip {
+    frames: Vec<EncodedFrame>,
+}
+
+// SAFETY: see EncodedFrame.
+unsafe impl Send for ReplayClip {}
+
+impl ReplayClip {
+    /// Mux the clip into an MP4 without re-encoding. Returns seconds written.
+    ///
+    /// Blocks on the writer's completion handler — call off the main thread.
+    pub fn write(&self, output_path: &str) -> Result<f64, String> {
+        let (Some(first), Some(last)) = (self.frames.first(), self.frames.last()) else {
+            return Err("Instant replay buffer is empty".into());
+        };
+        let duration = last.pts_secs - first.pts_secs;
+
+        let format = unsafe { CMSampleBufferGetFormatDescription(first.sample) };
+        if format.is_null() {
+            return Err("Encoded replay frames have no format description".into());
+        }
+        let format = unsafe { &*(format as *const CMFormatDescription) };
+
+        let _ = std::fs::remove_file(output_path);
+        let (writer, input) = super::writer::create_passthrough_writer(output_path, format)?;
+        super::writer::start_writing(&writer)?;
+
+        unsafe {
+            let start_pts = CMSampleBufferGetPresentationTimeStamp(first.sample);
+            let _: () = msg_send![&*writer, startSessionAtSourceTime: start_pts];
+        }
+
+        for frame in &self.frames {
+            let sample = unsafe { &*(frame.sample as *const CMSampleBuffer) };
+            // Offline write: wait for the input instead of dropping frames
+            let mut waited = 0;
+            while !unsafe { msg_send![&*input, isReadyForMoreMediaData] } && waited < 2000 {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                waited += 1;
+            }
+            let ok: bool = unsafe { msg_send![&*input, appendSampleBuffer: sample] };
+            if !ok {
+                let err = unsafe { writer.error() }
+                    .map(|e| format!("{}", e))
+                    .unwrap_or_else(|| "unknown".into());
+                return Err(format!("Failed to write replay frame: {}", err));
+            }
+        }
+
+        super::writer::finalize(&writer, Some(&input), None, None);
+        if !std::path::Path::new(output_path).exists() {
+            return Err("Instant replay file was not written".into());
+        }
+        Ok(duration)
+    }
+}
@@ -0,0 +1,162 @@
+//! Cheap scene-change detection on the live capture stream.
+//!
+//! Roughly once per second the capture delegate hands us the frame's
+//! CVPixelBuffer. We sample a coarse grid of the luma plane (420v plane 0,
+//! no conversion, no copy of the full frame) into a small signature and
+//! compare it with the previous one. A large mean difference = new scene.
+//!
+//! Detected timestamps (seconds since the first frame) are collected so the
+//! recording can be split into chapter-like segments after it stops.
+
+use std::ffi::c_void;
+use std::sync::Mutex;
+
+/// Signature grid size (GRID × GRID luma samples).
+const GRID: usize = 16;
+
+/// Minimum distance between two scene changes, so a burst of motion
+/// (e.g. window animation) doesn't produce a marker every second.
+const MIN_SCENE_GAP_SECS: f64 = 2.0;
+
+extern "C" {
+    fn CVPixelBufferLockBaseAddress(pixel_buffer: *const c_void, lock_flags: u64) -> i32;
+    fn CVPixelBufferUnlockBaseAddress(pixel_buffer: *const c_void, lock_flags: u64) -> i32;
+    fn CVPixelBufferGetBaseAddressOfPlane(pixel_buffer: *const c_void, plane: usize) -> *const u8;
+    fn CVPixelBufferGetBytesPerRowOfPlane(pixel_buffer: *const c_void, plane: usize) -> usize;
+    fn CVPixelBufferGetWidthOfPlane(pixel_buffer: *const c_void, plane: usize) -> usize;
+    fn CVPixelBufferGetHeightOfPlane(pixel_buffer: *const c_void, plane: usize) -> usize;
+}
+
+/// kCVPixelBufferLock_ReadOnly
+const LOCK_READ_ONLY: u64 = 1;
+
+struct SceneState {
+    /// Stream time of the first sampled frame (timeline origin)
+    first_time: Option<f64>,
+    last_sample_time: f64,
+    last_scene_time: f64,
+    signature: Option<Vec<u8>>,
+    scenes: Vec<f64>,
+}
+
+/// Shared between the capture delegate (writes) and the RecordingHandle (reads).
+pub struct SceneDetector {
+    threshold: f64,
+    sample_interval_secs: f64,
+    on_scene: Box<dyn Fn(f64) + Send + Sync>,
+    state: Mutex<SceneState>,
+}
+
+impl SceneDetector {
+    /// `threshold` is clamped to 0.05 – 1.0. `on_scene` is called (on the
+    /// capture queue) with the timestamp of every detected scene change.
+    pub fn new(threshold: f64, on_scene: impl Fn(f64) + Send + Sync + 'static) -> Self {
+        Self {
+            threshold: threshold.clamp(0.05, 1.0),
+            sample_interval_secs: 1.0,
+            on_scene: Box::new(on_scene),
+            state: Mutex::new(SceneState {
+                first_time: None,
+                last_sample_time: f64::NEG_INFINITY,
+                last_scene_time: 0.0,
+                signature: None,
+                scenes: Vec::new(),
+            }),
+        }
+    }
+
+    /// Whether a frame at `stream_time` is due for sampling.
+    /// Cheap check so the delegate can skip the pixel buffer lock entirely.
+    pub fn wants_sample(&self, stream_time: f64) -> bool {
+        self.state
+            .lock()
+            .map(|s| stream_time - s.last_sample_time >= self.sample_interval_secs)
+            .unwrap_or(false)
+    }
+
+    /// Sample a 420v/NV12 pixel buffer at `stream_time` (seconds, stream clock).
+    pub fn process(&self, pixel_buffer: *const c_void, stream_time: f64) {
+        let Some(signature) = luma_signature(pixel_buffer) else {
+            return;
+        };
+
+        let detected = {
+            let Ok(mut s) = self.state.lock() else { return };
+            let origin = *s.first_time.get_or_insert(stream_time);
+            let t = stream_time - origin;
+            s.last_sample_time = stream_time;
+
+            let changed = s
+                .signature
+                .as_ref()
+                .map(|prev| signature_distance(prev, &signature) >= self.threshold)
+                .unwrap_or(false);
+            s.signature = Some(signature);
+
+            if changed && t - s.last_scene_time >= MIN_SCENE_GAP_SECS {
+                s.last_scene_time = t;
+                s.scenes.push(t);
+                Some(t)
+            } else {
+                None
+            }
+        };
+
+        if let Some(t) = detected {
+            println!("[zureshot] Scene change detected at {:.1}s", t);
+            (self.on_scene)(t);
+        }
+    }
+
+    /// All scene-change timestamps detected so far (seconds since first frame).
+    pub fn scenes(&self) -> Vec<f64> {
+        self.state.lock().map(|s| s.scenes.clone()).unwrap_or_default()
+    }
+}
+
+/// Sample a GRID × GRID luma signature from plane 0 of a bi-planar buffer.
+fn luma_signature(pixel_buffer: *const c_void) -> Option<Vec<u8>> {
+    if pixel_buffer.is_null() {
+        return None;
+    }
+    unsafe {
+        if CVPixelBufferLockBaseAddress(pixel_buffer, LOCK_READ_ONLY) != 0 {
+            return None;
+        }
+        let base = CVPixelBufferGetBaseAddressOfPlane(pixel_buffer, 0);
+        let bpr = CVPixelBufferGetBytesPerRowOfPlane(pixel_buffer, 0);
+        let w = CVPixelBufferGetWidthOfPlane(pixel_buffer, 0);
+        let h = CVPixelBufferGetHeightOfPlane(pixel_buffer, 0);
+
+        let result = if base.is_null() || w < GRID || h < GRID {
+            None
+        } else {
+            let mut sig = Vec::with_capacity(GRID * GRID);
+            for gy in 0..GRID {
+                let y = (gy * 2 + 1) * h / (GRID * 2);
+                let row = base.add(y * bpr);
+                for gx in 0..GRID {
+                    let x = (gx * 2 + 1) * w / (GRID * 2);
+                    sig.push(*row.add(x));
+                }
+            }
+            Some(sig)
+        };
+
+        CVPixelBufferUnlockBaseAddress(pixel_buffer, LOCK_READ_ONLY);
+        result
+    }
+}
+
+/// Mean absolute difference between two signatures, normalized to 0.0 – 1.0.
+fn signature_distance(a: &[u8], b: &[u8]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let total: u64 = a
+        .iter()
+        .zip(b)
+        .map(|(x, y)| (*x as i32 - *y as i32).unsigned_abs() as u64)
+        .sum();
+    total as f64 / (a.len() as f64 * 255.0)
+}
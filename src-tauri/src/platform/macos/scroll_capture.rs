@@ -6,9 +6,13 @@
 //!   1. `ScrollCaptureSession::new(x,y,w,h)` — capture first frame
 //!   2. `session.capture_frame()` — called periodically (~300ms), returns true if new content
 //!   3. `session.finish(path)` — write stitched PNG, return dimensions + file size
+//!
+//! `capture_scrolling_window` automates the same thing for one window: it
+//! posts scroll-wheel events itself and stops when the content stops moving.
 
 use std::ffi::c_void;
 use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use crate::platform::overlap::find_overlap;
 
 // ── Raw frame from CGWindowListCreateImage ───────────────────────────
 
@@ -94,6 +98,17 @@ extern "C" {
         path_style: isize,
         is_directory: bool,
     ) -> *const c_void;
+
+    fn CGEventCreate(source: *const c_void) -> *const c_void;
+    fn CGEventGetLocation(event: *const c_void) -> CGPoint;
+    fn CGEventCreateScrollWheelEvent(
+        source: *const c_void,
+        units: u32,
+        wheel_count: u32,
+        wheel1: i32, ...
+    ) -> *const c_void;
+    fn CGEventPost(tap: u32, event: *const c_void);
+    fn CGWarpMouseCursorPosition(point: CGPoint) -> i32;
 }
 
 fn cfstring(s: &str) -> *const c_void {
@@ -155,73 +170,32 @@ fn capture_region_pixels(x: f64, y: f64, w: f64, h: f64) -> Result<RawFrame, Str
     Ok(RawFrame { pixels, width, height, bytes_per_row, bitmap_info })
 }
 
-// ── Overlap detection via SAD (Sum of Absolute Differences) ──────────
-//
-// Takes the bottom `strip_height` rows of the accumulated image and slides
-// them across the top portion of the new frame. The position with the
-// minimum SAD is the overlap offset.
-
-fn find_overlap(
-    acc: &[u8],
-    acc_height: usize,
-    acc_bpr: usize,
-    new_pixels: &[u8],
-    new_height: usize,
-    new_bpr: usize,
-    compare_width_bytes: usize,
-) -> usize {
-    let strip_height = 40.min(acc_height / 2).min(new_height / 2);
-    if strip_height < 4 {
-        return 0;
-    }
-
-    let strip_start = (acc_height - strip_height) * acc_bpr;
-    let strip = &acc[strip_start..];
-
-    let max_search = new_height.saturating_sub(strip_height);
-
-    let mut best_pos = 0usize;
-    let mut best_sad = u64::MAX;
-
-    // Step by 4 pixels (16 bytes) for speed; compare every row
-    let step = 16.min(compare_width_bytes);
-
-    for pos in 0..=max_search {
-        let mut sad: u64 = 0;
-        let mut bailed = false;
-
-        for row in 0..strip_height {
-            let s_off = row * acc_bpr;
-            let n_off = (pos + row) * new_bpr;
-
-            let mut col = 0;
-            while col < compare_width_bytes {
-                let a = strip[s_off + col] as i64;
-                let b = new_pixels[n_off + col] as i64;
-                sad += (a - b).unsigned_abs();
-                col += step;
-            }
-
-            if sad > best_sad {
-                bailed = true;
-                break;
-            }
-        }
-
-        if !bailed && sad < best_sad {
-            best_sad = sad;
-            best_pos = pos;
-        }
+/// Capture one window by ID (without its shadow), even where other
+/// windows cover it.
+fn capture_window_pixels(window_id: u32) -> Result<RawFrame, String> {
+    // CGRectNull = the window's own bounds
+    let null_rect = CGRect::new(CGPoint::new(f64::INFINITY, f64::INFINITY), CGSize::new(0.0, 0.0));
+    // kCGWindowListOptionIncludingWindow = 8, kCGWindowImageBoundsIgnoreFraming = 1
+    let image = unsafe { CGWindowListCreateImage(null_rect, 8, window_id, 1) };
+    if image.is_null() {
+        return Err(format!("Window {} could not be captured", window_id));
     }
+    let width = unsafe { CGImageGetWidth(image) };
+    let height = unsafe { CGImageGetHeight(image) };
+    let bytes_per_row = unsafe { CGImageGetBytesPerRow(image) };
+    let bitmap_info = unsafe { CGImageGetBitmapInfo(image) };
 
-    // If no good match, return 0 (append entire frame)
-    let samples = strip_height * (compare_width_bytes / step + 1);
-    let threshold = samples as u64 * 12;
-    if best_sad > threshold {
-        0
-    } else {
-        best_pos + strip_height
+    let data = unsafe { CGDataProviderCopyData(CGImageGetDataProvider(image)) };
+    unsafe { CGImageRelease(image) };
+    if data.is_null() {
+        return Err("CGDataProviderCopyData returned null".into());
     }
+    let pixels = unsafe {
+        let bytes = std::slice::from_raw_parts(CFDataGetBytePtr(data), CFDataGetLength(data) as usize).to_vec();
+        CFRelease(data);
+        bytes
+    };
+    Ok(RawFrame { pixels, width, height, bytes_per_row, bitmap_info })
 }
 
 // ── Save raw pixel buffer as PNG via ImageIO ─────────────────────────
@@ -292,6 +266,139 @@ fn save_pixels_as_png(
     Ok((width, height, file_size))
 }
 
+// ── Automatic window scrolling ───────────────────────────────────────
+
+/// Scroll steps before giving up on reaching the end (very long pages).
+const MAX_SCROLL_STEPS: usize = 60;
+/// Time for the app to redraw after a scroll (smooth scrolling included).
+const SCROLL_SETTLE: std::time::Duration = std::time::Duration::from_millis(350);
+/// Fraction of the viewport scrolled per step; the rest is overlap to match on.
+const SCROLL_STEP_FRACTION: f64 = 0.7;
+
+/// Scroll the content under `point` by `points` (positive = down the page)
+/// with a pixel-unit scroll-wheel event. Needs Accessibility permission.
+fn post_scroll(point: CGPoint, points: i32) {
+    unsafe {
+        CGWarpMouseCursorPosition(point);
+        // kCGScrollEventUnitPixel = 0; negative wheel1 scrolls content up
+        let event = CGEventCreateScrollWheelEvent(std::ptr::null(), 0, 1, -points);
+        if !event.is_null() {
+            CGEventPost(0, event); // kCGHIDEventTap
+            CFRelease(event);
+        }
+    }
+}
+
+fn cursor_location() -> Option<CGPoint> {
+    unsafe {
+        let event = CGEventCreate(std::ptr::null());
+        if event.is_null() {
+            return None;
+        }
+        let point = CGEventGetLocation(event);
+        CFRelease(event);
+        Some(point)
+    }
+}
+
+/// Rows at the top (or bottom, `from_bottom`) that are identical in two
+/// frames of the same size — sticky headers, toolbars, footers. Capped at a
+/// third of the height so a mostly blank page isn't mistaken for chrome.
+fn fixed_rows(a: &RawFrame, b: &RawFrame, from_bottom: bool) -> usize {
+    let row_bytes = a.width * 4;
+    let max = a.height / 3;
+    (0..max)
+        .take_while(|&i| {
+            let row = if from_bottom { a.height - 1 - i } else { i };
+            let off_a = row * a.bytes_per_row;
+            let off_b = row * b.bytes_per_row;
+            a.pixels[off_a..off_a + row_bytes] == b.pixels[off_b..off_b + row_bytes]
+        })
+        .count()
+}
+
+/// Scrolling screenshot of one window: starting from its current scroll
+/// position, scroll down one viewport at a time until the content stops
+/// moving, then stitch the viewports into a tall PNG.
+///
+/// `center` is a point inside the scrollable area (global logical
+/// coordinates) where the scroll events are delivered; `height_points` is
+/// the window's height, for converting pixels to scroll distance. Rows that
+/// never move (sticky headers and footers) appear once. Scroll amounts that
+/// differ from the requested step are handled by overlap detection.
+pub fn capture_scrolling_window(
+    window_id: u32,
+    center: CGPoint,
+    height_points: f64,
+    output_path: &str,
+) -> Result<(usize, usize, u64, usize), String> {
+    let first = capture_window_pixels(window_id)?;
+    let scale = (first.height as f64 / height_points.max(1.0)).max(1.0);
+    let restore_cursor = cursor_location();
+
+    let mut frames = vec![first];
+    while frames.len() < MAX_SCROLL_STEPS {
+        let last = frames.last().unwrap();
+        let step = (last.height as f64 / scale * SCROLL_STEP_FRACTION).max(1.0) as i32;
+        post_scroll(center, step);
+        std::thread::sleep(SCROLL_SETTLE);
+
+        let frame = capture_window_pixels(window_id)?;
+        if frame.width != last.width || frame.height != last.height {
+            return Err("The window was resized during the scrolling screenshot".into());
+        }
+        if frame.pixels == last.pixels {
+            break; // End of the content (or the app ignored the scroll)
+        }
+        frames.push(frame);
+    }
+    if let Some(point) = restore_cursor {
+        unsafe { CGWarpMouseCursorPosition(point) };
+    }
+    if frames.len() == 1 {
+        return Err(
+            "The window didn't scroll — make sure Zureshot has Accessibility permission \
+             and the window has scrollable content"
+                .into(),
+        );
+    }
+
+    // Sticky chrome, judged on the first scroll (later frames can end in a
+    // partial step that shifts less)
+    let header = fixed_rows(&frames[0], &frames[1], false);
+    let footer = fixed_rows(&frames[0], &frames[1], true);
+    let (width, height, bpr) = (frames[0].width, frames[0].height, frames[0].bytes_per_row);
+    let band_end = height - footer;
+    println!(
+        "[scroll-capture] Window {}: {} viewports, fixed header={}px footer={}px",
+        window_id, frames.len(), header, footer
+    );
+
+    // Everything above the footer of the first viewport, then only the
+    // moving band of each following one
+    let mut accumulated = frames[0].pixels[..band_end * bpr].to_vec();
+    let mut acc_height = band_end;
+    for frame in &frames[1..] {
+        let band = &frame.pixels[header * bpr..band_end * bpr];
+        let band_height = band_end - header;
+        let Some(overlap) = find_overlap(&accumulated, acc_height, bpr, band, band_height, bpr, width * 4) else {
+            println!("[scroll-capture] Viewport skipped: no overlap with the stitched image");
+            continue;
+        };
+        if overlap >= band_height.saturating_sub(2) {
+            continue;
+        }
+        accumulated.extend_from_slice(&band[overlap * bpr..]);
+        acc_height += band_height - overlap;
+    }
+    let last = frames.last().unwrap();
+    accumulated.extend_from_slice(&last.pixels[band_end * bpr..height * bpr]);
+    acc_height += footer;
+
+    let (w, h, size) = save_pixels_as_png(&accumulated, width, acc_height, bpr, frames[0].bitmap_info, output_path)?;
+    Ok((w, h, size, frames.len()))
+}
+
 // ── Public session API ───────────────────────────────────────────────
 
 pub struct ScrollCaptureSession {
@@ -339,7 +446,7 @@ impl ScrollCaptureSession {
 
         let compare_bytes = self.acc_width * 4; // 4 bytes per pixel
 
-        let overlap = find_overlap(
+        let Some(overlap) = find_overlap(
             &self.accumulated,
             self.acc_height,
             self.bytes_per_row,
@@ -347,7 +454,13 @@ impl ScrollCaptureSession {
             frame.height,
             frame.bytes_per_row,
             compare_bytes,
-        );
+        ) else {
+            // Scrolled too far (or the content changed): appending the
+            // whole frame would leave a gap or a duplicate, so wait for
+            // one that continues the image
+            println!("[scroll-capture] Frame skipped: no overlap with the stitched image");
+            return Ok(false);
+        };
 
         // If overlap covers (almost) the entire new frame, nothing changed
         if overlap >= frame.height.saturating_sub(2) {
@@ -24,16 +24,19 @@ use objc2_av_foundation::{
     AVVideoCompressionPropertiesKey, AVVideoAverageBitRateKey,
     AVVideoMaxKeyFrameIntervalDurationKey,
     AVVideoExpectedSourceFrameRateKey,
-    AVVideoCodecTypeHEVC, AVVideoAllowFrameReorderingKey,
+    AVVideoCodecTypeHEVC, AVVideoCodecTypeH264, AVVideoCodecTypeAppleProRes422,
+    AVVideoAllowFrameReorderingKey,
     AVVideoQualityKey, AVVideoColorPropertiesKey,
-    AVVideoColorPrimariesKey, AVVideoColorPrimaries_ITU_R_709_2,
+    AVVideoColorPrimariesKey, AVVideoColorPrimaries_ITU_R_709_2, AVVideoColorPrimaries_P3_D65,
     AVVideoTransferFunctionKey, AVVideoTransferFunction_ITU_R_709_2,
     AVVideoYCbCrMatrixKey, AVVideoYCbCrMatrix_ITU_R_709_2,
     AVVideoProfileLevelKey,
 };
+use objc2_core_foundation::CGAffineTransform;
+use objc2_core_media::{CMFormatDescription, CMTime};
 use objc2_foundation::{NSError, NSString, NSNumber};
 
-use crate::platform::RecordingQuality;
+use crate::platform::{RecordingQuality, Rotation, VideoCodec};
 
 /// Audio encoding settings for AAC in MP4.
 const AUDIO_SAMPLE_RATE: f64 = 48000.0;
@@ -51,6 +54,44 @@ fn catch_objc<R>(context: &str, f: impl FnOnce() -> R) -> Result<R, String> {
     })
 }
 
+/// Color space the stream is tagged with (and captured in).
+///
+/// MP4 has no way to carry an arbitrary ICC profile through AVAssetWriter,
+/// so a calibrated display is mapped to the nearest standard tagging.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorTagging {
+    /// sRGB capture, BT.709 primaries/transfer/matrix.
+    #[default]
+    Bt709,
+    /// Display P3 capture, P3-D65 primaries with the sRGB (BT.709) transfer.
+    DisplayP3,
+}
+
+/// Per-recording encoder options on top of the quality preset.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EncoderOptions {
+    pub codec: VideoCodec,
+    /// Encode 10-bit (HEVC Main10) — used with the `l10r` capture format.
+    pub ten_bit: bool,
+    pub color: ColorTagging,
+    /// Ask VideoToolbox to favor power over speed (battery saver). Fixed for
+    /// the life of the writer.
+    pub power_efficient: bool,
+    /// `AVVideoMaxKeyFrameIntervalDurationKey` (seconds)
+    pub keyframe_interval_secs: f64,
+    /// Average bitrate to use instead of `compute_bitrate_for`.
+    pub bitrate_override_bps: Option<i64>,
+    /// `AVVideoQualityKey` to use instead of the quality preset's.
+    pub quality_factor: Option<f64>,
+    /// Stored as the video track's transform; frames are encoded as captured.
+    pub rotation: Rotation,
+    /// `movieFragmentInterval` (seconds). `None` = a single `moov` written
+    /// at finalize, so nothing is playable until the recording stops.
+    pub fragment_interval_secs: Option<f64>,
+    /// `AVVideoExpectedSourceFrameRateKey`. 0 = the quality preset's rate.
+    pub fps: i32,
+}
+
 /// Create an AVAssetWriter + AVAssetWriterInput configured for HEVC recording.
 ///
 /// The writer is NOT started — call `start_writing()` after adding all inputs.
@@ -60,7 +101,104 @@ pub fn create_writer(
     width: usize,
     height: usize,
     quality: RecordingQuality,
+    options: EncoderOptions,
 ) -> Result<(Retained<AVAssetWriter>, Retained<AVAssetWriterInput>), String> {
+    let (writer, output_str) = new_asset_writer(output_path)?;
+
+    // Fragmented output: each fragment is complete on disk once written
+    if let Some(secs) = options.fragment_interval_secs {
+        let interval = CMTime::new((secs * 600.0).round() as i64, 600);
+        catch_objc("setMovieFragmentInterval", || unsafe {
+            writer.setMovieFragmentInterval(interval);
+        })?;
+    }
+
+    // Video encoding settings (HEVC Main or H.264 High, VBR)
+    let settings = create_video_settings(width, height, quality, options);
+
+    // AVMediaType: "vide" (video)
+    let media_type = NSString::from_str("vide");
+
+    // Create AVAssetWriterInput
+    let input: Retained<AVAssetWriterInput> =
+        catch_objc("AVAssetWriterInput creation", || unsafe {
+            msg_send![
+                class!(AVAssetWriterInput),
+                assetWriterInputWithMediaType: &*media_type,
+                outputSettings: &*settings
+            ]
+        })?;
+
+    // Critical for screen recording: real-time mode keeps memory low
+    // by not accumulating too many frames in the encoding pipeline
+    unsafe {
+        input.setExpectsMediaDataInRealTime(true);
+    }
+
+    // Players apply the track transform on playback (must be set before writing starts)
+    if options.rotation != Rotation::None {
+        let transform = rotation_transform(options.rotation, width as f64, height as f64);
+        catch_objc("setTransform", || unsafe {
+            let _: () = msg_send![&*input, setTransform: transform];
+        })?;
+    }
+
+    // Add video input (caller adds audio inputs, then calls start_writing)
+    catch_objc("addInput(video)", || unsafe {
+        writer.addInput(&input);
+    })?;
+
+    println!(
+        "[zureshot] Writer ready: {:?} {}x{} {:?} rotation={:?} → {}",
+        options.codec, width, height, options.color, options.rotation, output_str
+    );
+    Ok((writer, input))
+}
+
+/// Track transform turning a `width`×`height` frame by `rotation`
+/// (clockwise, top-left origin), translated back into positive coordinates
+/// the way QuickTime writes rotated movies.
+fn rotation_transform(rotation: Rotation, width: f64, height: f64) -> CGAffineTransform {
+    let (a, b, c, d, tx, ty) = match rotation {
+        Rotation::None => (1.0, 0.0, 0.0, 1.0, 0.0, 0.0),
+        Rotation::Cw90 => (0.0, 1.0, -1.0, 0.0, height, 0.0),
+        Rotation::Ccw90 => (0.0, -1.0, 1.0, 0.0, 0.0, width),
+        Rotation::Rot180 => (-1.0, 0.0, 0.0, -1.0, width, height),
+    };
+    CGAffineTransform { a, b, c, d, tx, ty }
+}
+
+/// Create a writer whose video input muxes already-encoded samples as-is
+/// (no `outputSettings` → passthrough). `format_hint` is the encoded
+/// stream's CMFormatDescription. Used by instant replay.
+pub fn create_passthrough_writer(
+    output_path: &str,
+    format_hint: &CMFormatDescription,
+) -> Result<(Retained<AVAssetWriter>, Retained<AVAssetWriterInput>), String> {
+    let (writer, output_str) = new_asset_writer(output_path)?;
+    let media_type = NSString::from_str("vide");
+    let no_settings: Option<&AnyObject> = None;
+
+    let input: Retained<AVAssetWriterInput> =
+        catch_objc("AVAssetWriterInput(passthrough) creation", || unsafe {
+            msg_send![
+                class!(AVAssetWriterInput),
+                assetWriterInputWithMediaType: &*media_type,
+                outputSettings: no_settings,
+                sourceFormatHint: format_hint
+            ]
+        })?;
+    catch_objc("addInput(passthrough)", || unsafe {
+        writer.addInput(&input);
+    })?;
+
+    println!("[zureshot] Passthrough writer ready → {}", output_str);
+    Ok((writer, input))
+}
+
+/// AVAssetWriter for an MP4 at `output_path` (made absolute). Returns the
+/// writer and the resolved path.
+fn new_asset_writer(output_path: &str) -> Result<(Retained<AVAssetWriter>, String), String> {
     // Resolve to absolute path (AVAssetWriter requires it)
     let abs_path = std::path::Path::new(output_path);
     let abs_path = if abs_path.is_absolute() {
@@ -75,8 +213,21 @@ pub fn create_writer(
     let url: Retained<AnyObject> =
         unsafe { msg_send![class!(NSURL), fileURLWithPath: &*path_str] };
 
-    // AVFileType: "public.mpeg-4" (MP4 container)
-    let file_type = NSString::from_str("public.mpeg-4");
+    // AVFileType: QuickTime for `.mov` (ProRes), Apple M4A for audio-only
+    // recordings, otherwise MP4
+    let is_mov = abs_path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("mov"));
+    let is_m4a = abs_path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("m4a"));
+    let file_type = NSString::from_str(if is_mov {
+        "com.apple.quicktime-movie"
+    } else if is_m4a {
+        "com.apple.m4a-audio"
+    } else {
+        "public.mpeg-4"
+    });
 
     // Create AVAssetWriter
     let writer: Retained<AVAssetWriter> = catch_objc("AVAssetWriter creation", || {
@@ -102,38 +253,24 @@ pub fn create_writer(
         }
     })??;
 
-    // Video encoding settings (H.264 High Profile, VBR)
-    let settings = create_video_settings(width, height, quality);
-
-    // AVMediaType: "vide" (video)
-    let media_type = NSString::from_str("vide");
+    Ok((writer, output_str.to_string()))
+}
 
-    // Create AVAssetWriterInput
-    let input: Retained<AVAssetWriterInput> =
-        catch_objc("AVAssetWriterInput creation", || unsafe {
-            msg_send![
-                class!(AVAssetWriterInput),
-                assetWriterInputWithMediaType: &*media_type,
-                outputSettings: &*settings
-            ]
+/// Create an AVAssetWriter for an audio-only `.m4a` recording. Add the
+/// `create_audio_input` tracks, then call `start_writing()`.
+pub fn create_audio_writer(
+    output_path: &str,
+    fragment_interval_secs: Option<f64>,
+) -> Result<Retained<AVAssetWriter>, String> {
+    let (writer, output_str) = new_asset_writer(output_path)?;
+    if let Some(secs) = fragment_interval_secs {
+        let interval = CMTime::new((secs * 600.0).round() as i64, 600);
+        catch_objc("setMovieFragmentInterval", || unsafe {
+            writer.setMovieFragmentInterval(interval);
         })?;
-
-    // Critical for screen recording: real-time mode keeps memory low
-    // by not accumulating too many frames in the encoding pipeline
-    unsafe {
-        input.setExpectsMediaDataInRealTime(true);
     }
-
-    // Add video input (caller adds audio inputs, then calls start_writing)
-    catch_objc("addInput(video)", || unsafe {
-        writer.addInput(&input);
-    })?;
-
-    println!(
-        "[zureshot] Writer ready: HEVC {}x{} BT.709 → {}",
-        width, height, output_str
-    );
-    Ok((writer, input))
+    println!("[zureshot] Audio-only writer created: {}", output_str);
+    Ok(writer)
 }
 
 /// Create an AVAssetWriterInput for AAC audio encoding.
@@ -216,7 +353,7 @@ pub fn start_writing(writer: &AVAssetWriter) -> Result<(), String> {
 /// blocking it causes a deadlock where the moov atom is never written.
 pub fn finalize(
     writer: &AVAssetWriter,
-    input: &AVAssetWriterInput,
+    input: Option<&AVAssetWriterInput>,
     audio_input: Option<&AVAssetWriterInput>,
     mic_input: Option<&AVAssetWriterInput>,
 ) {
@@ -240,7 +377,9 @@ pub fn finalize(
 
     println!("[zureshot] Finalize: marking inputs as finished...");
     let mark_result = catch_objc("markAsFinished", || unsafe {
-        input.markAsFinished();
+        if let Some(vi) = input {
+            vi.markAsFinished();
+        }
         if let Some(ai) = audio_input {
             ai.markAsFinished();
             println!("[zureshot] Finalize: audio input marked finished");
@@ -314,34 +453,83 @@ pub fn finalize(
     }
 }
 
+/// Re-mux a recording (.mp4 or .mov) keeping only the audio tracks flagged
+/// in `keep_audio` (in track order). Video and kept audio are stream-copied.
+pub fn drop_audio_tracks(path: &str, keep_audio: &[bool]) -> Result<(), String> {
+    let tmp_path = crate::platform::remux_temp_path(std::path::Path::new(path))
+        .to_string_lossy()
+        .to_string();
+    let mut args: Vec<String> = vec![
+        "-i".into(), path.into(),
+        "-map".into(), "0:v:0".into(),
+    ];
+    for (i, keep) in keep_audio.iter().enumerate() {
+        if *keep {
+            args.push("-map".into());
+            args.push(format!("0:a:{}", i));
+        }
+    }
+    args.extend(["-c".into(), "copy".into()]);
+    // Keep the QuickTime-compatible sample entry (hvc1/avc1)
+    args.extend(VideoCodec::of_file(path).stream_tag_args().into_iter().map(String::from));
+    args.extend([
+        "-movflags".into(), "+faststart".into(),
+        "-y".into(), tmp_path.clone(),
+    ]);
+
+    let output = crate::ffmpeg::command()
+        .args(&args)
+        .output()
+        .map_err(|e| format!("ffmpeg not found or failed to run: {}", e))?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(format!(
+            "ffmpeg remux failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    std::fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace recording: {}", e))
+}
+
 // ────────────────────────────────────────────────────────────────
 //  Video encoding settings
 // ────────────────────────────────────────────────────────────────
 
 /// Build the NSDictionary for AVAssetWriterInput video output settings.
 ///
-/// Uses HEVC (H.265) for best quality-to-size ratio:
+/// Uses HEVC (H.265) by default for best quality-to-size ratio:
 /// - Hardware-accelerated on all Apple Silicon
 /// - ~40-50% smaller files than H.264 at equal quality
 /// - Combined bitrate + quality targeting for optimal output
-fn create_video_settings(width: usize, height: usize, quality: RecordingQuality) -> Retained<AnyObject> {
-    let fps: isize = match quality {
-        RecordingQuality::Standard => 30,
-        RecordingQuality::High => 60,
-    };
+///
+/// `VideoCodec::H264` trades file size for players without HEVC.
+/// `VideoCodec::ProRes422` is intra-frame with a fixed data rate, so it
+/// gets no compression properties at all.
+fn create_video_settings(
+    width: usize,
+    height: usize,
+    quality: RecordingQuality,
+    options: EncoderOptions,
+) -> Retained<AnyObject> {
+    let fps: isize = if options.fps > 0 { options.fps as isize } else { quality.fps() as isize };
 
     unsafe {
         let dict: Retained<AnyObject> = msg_send![class!(NSMutableDictionary), new];
 
-        // ── AVVideoCodecKey: HEVC (H.265) ──
+        // ── AVVideoCodecKey: HEVC (H.265), H.264 or ProRes 422 ──
         let codec_key = AVVideoCodecKey.expect("AVVideoCodecKey not available");
-        let codec_val = AVVideoCodecTypeHEVC.expect("AVVideoCodecTypeHEVC not available");
+        let codec_val = match options.codec {
+            VideoCodec::Hevc => AVVideoCodecTypeHEVC.expect("AVVideoCodecTypeHEVC not available"),
+            VideoCodec::H264 => AVVideoCodecTypeH264.expect("AVVideoCodecTypeH264 not available"),
+            VideoCodec::ProRes422 => AVVideoCodecTypeAppleProRes422
+                .expect("AVVideoCodecTypeAppleProRes422 not available"),
+        };
         dict_set_nsstring(&dict, codec_key, codec_val);
 
         // ── AVVideoWidthKey / AVVideoHeightKey ──
-        // HEVC also requires even dimensions
-        let w = if width % 2 != 0 { width + 1 } else { width };
-        let h = if height % 2 != 0 { height + 1 } else { height };
+        // Both codecs require even dimensions (same rounding as the capture config)
+        let w = crate::platform::even_dimension(width);
+        let h = crate::platform::even_dimension(height);
         let width_key = AVVideoWidthKey.expect("AVVideoWidthKey not available");
         let height_key = AVVideoHeightKey.expect("AVVideoHeightKey not available");
         let width_num = NSNumber::new_isize(w as isize);
@@ -350,68 +538,90 @@ fn create_video_settings(width: usize, height: usize, quality: RecordingQuality)
         dict_set_nsstring(&dict, height_key, &height_num);
 
         // ── AVVideoCompressionPropertiesKey: encoding parameters ──
-        let comp: Retained<AnyObject> = msg_send![class!(NSMutableDictionary), new];
-
-        // Adaptive bitrate for HEVC (lower than H.264 at same visual quality)
-        let bitrate = compute_bitrate(width, height, quality);
-        let bitrate_key = AVVideoAverageBitRateKey.expect("AVVideoAverageBitRateKey not available");
-        let bitrate_num: Retained<AnyObject> =
-            msg_send![class!(NSNumber), numberWithLongLong: bitrate];
-        dict_set_nsstring(&comp, bitrate_key, &bitrate_num);
-
-        // AVVideoQualityKey: 0.0–1.0, hint to encoder for quality-targeted VBR.
-        // Combined with bitrate, the encoder uses bitrate as ceiling and quality
-        // as the target — sharp screen text with minimal file size bloat.
-        // Screen content (text, UI, code editors) is mostly static with sharp edges.
-        // HEVC handles this very efficiently — even moderate quality values preserve
-        // pixel-perfect text because inter-frame prediction handles static regions
-        // nearly losslessly. Lower values than camera video are perfectly fine here.
-        let quality_val: f64 = match quality {
-            RecordingQuality::Standard => 0.82,
-            RecordingQuality::High => 0.90,
-        };
-        let quality_key = AVVideoQualityKey.expect("AVVideoQualityKey not available");
-        let quality_num = NSNumber::new_f64(quality_val);
-        dict_set_nsstring(&comp, quality_key, &quality_num);
-
-        // Max keyframe interval: 2 seconds (duration-based, works for any fps).
-        // Longer interval than before = better compression. 2s is still fine
-        // for seeking precision.
-        let keyframe_key = AVVideoMaxKeyFrameIntervalDurationKey
-            .expect("AVVideoMaxKeyFrameIntervalDurationKey not available");
-        let keyframe_num = NSNumber::new_f64(2.0);
-        dict_set_nsstring(&comp, keyframe_key, &keyframe_num);
-
-        // Expected source frame rate — helps encoder allocate resources
-        let fps_key = AVVideoExpectedSourceFrameRateKey.expect("AVVideoExpectedSourceFrameRateKey not available");
-        let fps_num = NSNumber::new_isize(fps);
-        dict_set_nsstring(&comp, fps_key, &fps_num);
-
-        // Disable frame reordering for real-time screen recording (lower latency)
-        let reorder_key = AVVideoAllowFrameReorderingKey.expect("AVVideoAllowFrameReorderingKey not available");
-        let no = NSNumber::new_bool(false);
-        dict_set_nsstring(&comp, reorder_key, &no);
-
-        // ── HEVC Profile: Main Auto Level ──
-        // Explicitly request Main profile to ensure the hardware encoder uses
-        // the optimal encoding tools for screen content on Apple Silicon.
-        // "HEVC_Main_AutoLevel" is the VideoToolbox profile string for HEVC Main.
-        let profile_key = AVVideoProfileLevelKey.expect("AVVideoProfileLevelKey not available");
-        let profile_val = NSString::from_str("HEVC_Main_AutoLevel");
-        dict_set_nsstring(&comp, profile_key, &profile_val);
-
-        let comp_key = AVVideoCompressionPropertiesKey.expect("AVVideoCompressionPropertiesKey not available");
-        dict_set_nsstring(&dict, comp_key, &comp);
+        // ProRes rejects bitrate/quality/keyframe keys (every frame is a keyframe)
+        if options.codec != VideoCodec::ProRes422 {
+            let comp: Retained<AnyObject> = msg_send![class!(NSMutableDictionary), new];
+
+            // Adaptive bitrate (HEVC needs less than H.264 at same visual quality)
+            let bitrate = video_bitrate(width, height, quality, &options);
+            let bitrate_key = AVVideoAverageBitRateKey.expect("AVVideoAverageBitRateKey not available");
+            let bitrate_num: Retained<AnyObject> =
+                msg_send![class!(NSNumber), numberWithLongLong: bitrate];
+            dict_set_nsstring(&comp, bitrate_key, &bitrate_num);
+
+            // AVVideoQualityKey: 0.0–1.0, hint to encoder for quality-targeted VBR.
+            // Combined with bitrate, the encoder uses bitrate as ceiling and quality
+            // as the target — sharp screen text with minimal file size bloat.
+            // Screen content (text, UI, code editors) is mostly static with sharp edges.
+            // HEVC handles this very efficiently — even moderate quality values preserve
+            // pixel-perfect text because inter-frame prediction handles static regions
+            // nearly losslessly. Lower values than camera video are perfectly fine here.
+            let quality_val: f64 = options.quality_factor.unwrap_or_else(|| preset_quality_factor(quality));
+            let quality_key = AVVideoQualityKey.expect("AVVideoQualityKey not available");
+            let quality_num = NSNumber::new_f64(quality_val);
+            dict_set_nsstring(&comp, quality_key, &quality_num);
+
+            // Max keyframe interval: 2 seconds (duration-based, works for any fps).
+            // Longer interval than before = better compression. 2s is still fine
+            // for seeking precision.
+            let keyframe_key = AVVideoMaxKeyFrameIntervalDurationKey
+                .expect("AVVideoMaxKeyFrameIntervalDurationKey not available");
+            let keyframe_num = NSNumber::new_f64(options.keyframe_interval_secs);
+            dict_set_nsstring(&comp, keyframe_key, &keyframe_num);
+
+            // Expected source frame rate — helps encoder allocate resources
+            let fps_key = AVVideoExpectedSourceFrameRateKey.expect("AVVideoExpectedSourceFrameRateKey not available");
+            let fps_num = NSNumber::new_isize(fps);
+            dict_set_nsstring(&comp, fps_key, &fps_num);
+
+            // Disable frame reordering for real-time screen recording (lower latency)
+            let reorder_key = AVVideoAllowFrameReorderingKey.expect("AVVideoAllowFrameReorderingKey not available");
+            let no = NSNumber::new_bool(false);
+            dict_set_nsstring(&comp, reorder_key, &no);
+
+            // ── Profile: HEVC Main / H.264 High, Auto Level ──
+            // Explicitly request the profile to ensure the hardware encoder uses
+            // the optimal encoding tools for screen content on Apple Silicon.
+            // "HEVC_Main_AutoLevel" is the VideoToolbox profile string for HEVC Main.
+            // 10-bit sources need Main10, otherwise they're truncated to 8-bit
+            // (H.264 is always 8-bit here).
+            let profile_key = AVVideoProfileLevelKey.expect("AVVideoProfileLevelKey not available");
+            let profile_val = NSString::from_str(match options.codec {
+                VideoCodec::Hevc if options.ten_bit => "HEVC_Main10_AutoLevel",
+                VideoCodec::Hevc => "HEVC_Main_AutoLevel",
+                VideoCodec::H264 => "H264_High_AutoLevel",
+                // Skipped above: ProRes has no compression properties
+                VideoCodec::ProRes422 => unreachable!("ProRes takes no profile level"),
+            });
+            dict_set_nsstring(&comp, profile_key, &profile_val);
+
+            // kVTCompressionPropertyKey_MaximizePowerEfficiency — passed through
+            // to VideoToolbox by AVAssetWriter.
+            if options.power_efficient {
+                let power_key = NSString::from_str("MaximizePowerEfficiency");
+                let yes = NSNumber::new_bool(true);
+                dict_set_nsstring(&comp, &power_key, &yes);
+            }
+
+            let comp_key = AVVideoCompressionPropertiesKey.expect("AVVideoCompressionPropertiesKey not available");
+            dict_set_nsstring(&dict, comp_key, &comp);
+        }
 
         // ── BT.709 color properties ──
         // Explicitly tag the video stream with BT.709 color space metadata.
         // This prevents implicit color space conversions between capture (sRGB)
         // and encoding that can cause softening of text edges.
         // sRGB ≈ BT.709 transfer + BT.709 primaries — a lossless metadata match.
+        // Display P3 keeps the same transfer/matrix and swaps the primaries.
         let color_props: Retained<AnyObject> = msg_send![class!(NSMutableDictionary), new];
 
         let primaries_key = AVVideoColorPrimariesKey.expect("AVVideoColorPrimariesKey not available");
-        let primaries_val = AVVideoColorPrimaries_ITU_R_709_2.expect("AVVideoColorPrimaries_ITU_R_709_2 not available");
+        let primaries_val = match options.color {
+            ColorTagging::Bt709 => AVVideoColorPrimaries_ITU_R_709_2
+                .expect("AVVideoColorPrimaries_ITU_R_709_2 not available"),
+            ColorTagging::DisplayP3 => AVVideoColorPrimaries_P3_D65
+                .expect("AVVideoColorPrimaries_P3_D65 not available"),
+        };
         dict_set_nsstring(&color_props, primaries_key, primaries_val);
 
         let transfer_key = AVVideoTransferFunctionKey.expect("AVVideoTransferFunctionKey not available");
@@ -434,6 +644,95 @@ unsafe fn dict_set_nsstring(dict: &AnyObject, key: &NSString, value: &AnyObject)
     let () = msg_send![dict, setObject: value, forKey: key];
 }
 
+/// Estimate the file size of a recording, using the same bitrate the
+/// encoder is configured with (`options`) plus AAC audio per track.
+///
+/// The automatic bitrate is a VBR ceiling tuned for the preset's frame rate
+/// and quality; a lower `capture_fps` or quality factor fills less of it, so
+/// the estimate is scaled down in proportion (never above the ceiling). A
+/// bitrate override is taken as is; ProRes is a fixed rate per frame.
+pub fn estimate_size(
+    width: usize,
+    height: usize,
+    quality: RecordingQuality,
+    options: &EncoderOptions,
+    capture_fps: i32,
+    audio_tracks: usize,
+    duration_secs: f64,
+) -> crate::platform::SizeEstimate {
+    let video_bps = match options.codec {
+        VideoCodec::ProRes422 => compute_bitrate_for(width, height, quality, options.codec, capture_fps),
+        _ if options.bitrate_override_bps.is_some() => video_bitrate(width, height, quality, options),
+        _ => {
+            let fps_share = (capture_fps as f64 / quality.fps() as f64).min(1.0);
+            let quality_share = options
+                .quality_factor
+                .map_or(1.0, |q| (q / preset_quality_factor(quality)).min(1.0));
+            (video_bitrate(width, height, quality, options) as f64 * fps_share * quality_share) as i64
+        }
+    } as u64;
+    let audio_bps = AUDIO_BITRATE as u64 * audio_tracks as u64;
+    let payload = (video_bps + audio_bps) as f64 / 8.0 * duration_secs.max(0.0);
+    crate::platform::SizeEstimate {
+        bytes: (payload * (1.0 + crate::platform::CONTAINER_OVERHEAD)) as u64,
+        video_bitrate_bps: video_bps,
+        audio_bitrate_bps: audio_bps,
+        codec: match options.codec {
+            VideoCodec::Hevc => "hevc",
+            VideoCodec::H264 => "h264",
+            VideoCodec::ProRes422 => "prores",
+        }
+        .to_string(),
+        fps: capture_fps,
+    }
+}
+
+/// Average bitrate the encoder is configured with: the override, else
+/// `compute_bitrate_for` at the options' frame rate. ProRes has a fixed
+/// data rate and ignores the override.
+pub(crate) fn video_bitrate(width: usize, height: usize, quality: RecordingQuality, options: &EncoderOptions) -> i64 {
+    match options.bitrate_override_bps {
+        Some(bps) if options.codec != VideoCodec::ProRes422 => bps,
+        _ => compute_bitrate_for(width, height, quality, options.codec, options.fps),
+    }
+}
+
+/// `AVVideoQualityKey` for a quality preset (without a quality factor).
+fn preset_quality_factor(quality: RecordingQuality) -> f64 {
+    match quality {
+        RecordingQuality::Standard => 0.82,
+        RecordingQuality::High => 0.90,
+    }
+}
+
+/// `compute_bitrate` for `codec`: H.264 needs about 1.5× the HEVC bitrate
+/// for the same visual quality. ProRes ignores the bitrate; this is its
+/// nominal data rate (~147 Mbps at 1080p30) at `fps` (0 = the preset's),
+/// used for size estimates.
+pub(crate) fn compute_bitrate_for(
+    width: usize,
+    height: usize,
+    quality: RecordingQuality,
+    codec: VideoCodec,
+    fps: i32,
+) -> i64 {
+    let hevc = compute_bitrate(width, height, quality);
+    match codec {
+        VideoCodec::Hevc => hevc,
+        VideoCodec::H264 => (hevc as f64 / HEVC_BITRATE_FACTOR) as i64,
+        VideoCodec::ProRes422 => {
+            let fps = if fps > 0 { fps } else { quality.fps() };
+            (width as f64 * height as f64 * fps as f64 * PRORES_422_BITS_PER_PIXEL) as i64
+        }
+    }
+}
+
+/// HEVC needs ~65% of the H.264 bitrate for the same visual quality.
+const HEVC_BITRATE_FACTOR: f64 = 0.65;
+
+/// Apple's published ProRes 422 data rate, per pixel per frame.
+const PRORES_422_BITS_PER_PIXEL: f64 = 2.36;
+
 /// Compute VBR target bitrate based on resolution and quality.
 ///
 /// HEVC achieves equivalent visual quality at ~60% of H.264 bitrate.
@@ -451,7 +750,7 @@ unsafe fn dict_set_nsstring(dict: &AnyObject, key: &NSString, value: &AnyObject)
 ///   Standard (30fps): ~30-40 MB/min (1440p Retina)
 ///   High (60fps):     ~50-70 MB/min (1440p Retina)
 ///   CleanShot X:      ~75-110 MB/min (1440p Retina, H.264)
-fn compute_bitrate(width: usize, height: usize, quality: RecordingQuality) -> i64 {
+pub(crate) fn compute_bitrate(width: usize, height: usize, quality: RecordingQuality) -> i64 {
     let pixels = width * height;
     // Bitrates tuned to match CleanShot X visual quality using HEVC efficiency.
     // HEVC at 8 Mbps ≈ H.264 at 13 Mbps for screen content.
@@ -482,3 +781,121 @@ fn compute_bitrate(width: usize, height: usize, quality: RecordingQuality) -> i6
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::c_void;
+    use objc2_core_video::{CVPixelBuffer, CVPixelBufferPool};
+
+    extern "C" {
+        fn CVPixelBufferPoolCreatePixelBuffer(
+            allocator: *const c_void,
+            pool: *const CVPixelBufferPool,
+            pixel_buffer_out: *mut *mut CVPixelBuffer,
+        ) -> i32;
+        fn CFRelease(cf: *const c_void);
+    }
+
+    /// Set in the child process: the file it records into until killed
+    const KILL_TEST_OUTPUT_ENV: &str = "ZURESHOT_KILL_TEST_OUTPUT";
+    const FRAGMENT_SECS: f64 = 1.0;
+
+    /// Child side: record blank 30 fps frames into a fragmented file,
+    /// forever (the parent kills this process).
+    fn record_until_killed(path: &str) -> ! {
+        let options = EncoderOptions {
+            codec: VideoCodec::H264,
+            keyframe_interval_secs: FRAGMENT_SECS,
+            fragment_interval_secs: Some(FRAGMENT_SECS),
+            fps: 30,
+            ..Default::default()
+        };
+        let (writer, input) = create_writer(path, 320, 240, RecordingQuality::Standard, options).unwrap();
+        let adaptor = crate::platform::macos::desktop::pixel_buffer_adaptor(&input, 320, 240).unwrap();
+        start_writing(&writer).unwrap();
+        let _: () = unsafe { msg_send![&*writer, startSessionAtSourceTime: CMTime::new(0, 30)] };
+
+        for frame in 0i64.. {
+            std::thread::sleep(std::time::Duration::from_millis(33));
+            let ready: bool = unsafe { msg_send![&*input, isReadyForMoreMediaData] };
+            if !ready {
+                continue;
+            }
+            unsafe {
+                let pool: *const CVPixelBufferPool = msg_send![&*adaptor, pixelBufferPool];
+                let mut buffer: *mut CVPixelBuffer = std::ptr::null_mut();
+                if CVPixelBufferPoolCreatePixelBuffer(std::ptr::null(), pool, &mut buffer) != 0 || buffer.is_null() {
+                    continue;
+                }
+                let _: bool =
+                    msg_send![&*adaptor, appendPixelBuffer: &*buffer, withPresentationTime: CMTime::new(frame, 30)];
+                CFRelease(buffer as *const c_void);
+            }
+        }
+        unreachable!()
+    }
+
+    /// A fragmented recording whose process is killed mid-stream (no
+    /// finalize, no `moov` rewrite) still opens, up to its last complete
+    /// fragment. Needs the hardware encoder and ffprobe.
+    #[test]
+    #[ignore = "records with VideoToolbox and probes with ffprobe: cargo test -- --ignored"]
+    fn fragmented_recording_survives_kill() {
+        if let Ok(path) = std::env::var(KILL_TEST_OUTPUT_ENV) {
+            record_until_killed(&path);
+        }
+
+        let path = std::env::temp_dir().join(format!("zureshot_kill_test_{}.mp4", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut child = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--ignored", "--exact", "platform::macos::writer::tests::fragmented_recording_survives_kill"])
+            .env(KILL_TEST_OUTPUT_ENV, &path)
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_secs_f64(FRAGMENT_SECS * 4.5));
+        child.kill().unwrap(); // SIGKILL: the writer gets no chance to finalize
+        let _ = child.wait();
+
+        let path_str = path.to_string_lossy().to_string();
+        let info = crate::ffmpeg::media_info(&path_str);
+        let _ = std::fs::remove_file(&path);
+        let info = info.expect("killed recording should still open");
+        assert!(info.video.is_some(), "killed recording lost its video track");
+        // At least the fragments completed before the kill (~4 of them)
+        assert!(
+            info.duration_secs >= FRAGMENT_SECS * 2.0,
+            "only {:.2}s survived the kill",
+            info.duration_secs
+        );
+    }
+
+    #[test]
+    fn estimate_uses_the_encoder_settings() {
+        let minute = |options: EncoderOptions, fps| estimate_size(1920, 1080, RecordingQuality::Standard, &options, fps, 0, 60.0);
+        let hevc = EncoderOptions { fps: 30, ..Default::default() };
+        let auto = minute(hevc, 30);
+        assert_eq!(auto.video_bitrate_bps, 6_000_000);
+
+        // Half the frames, or a lower quality target, fill less of the ceiling
+        assert_eq!(minute(hevc, 15).video_bitrate_bps, 3_000_000);
+        let low_quality = EncoderOptions { quality_factor: Some(0.41), ..hevc };
+        assert_eq!(minute(low_quality, 30).video_bitrate_bps, 3_000_000);
+        // ...but a higher one never goes past it
+        let high_quality = EncoderOptions { quality_factor: Some(1.0), ..hevc };
+        assert_eq!(minute(high_quality, 30).video_bitrate_bps, 6_000_000);
+
+        // An override is the bitrate the encoder gets, whatever the frame rate
+        let fixed = EncoderOptions { bitrate_override_bps: Some(2_000_000), ..hevc };
+        assert_eq!(minute(fixed, 15).video_bitrate_bps, 2_000_000);
+
+        let h264 = minute(EncoderOptions { codec: VideoCodec::H264, ..hevc }, 30);
+        assert_eq!((h264.codec.as_str(), h264.video_bitrate_bps), ("h264", 9_230_769));
+
+        // ProRes: a fixed rate per frame, overrides ignored
+        let prores = EncoderOptions { codec: VideoCodec::ProRes422, bitrate_override_bps: Some(2_000_000), ..hevc };
+        assert_eq!(minute(prores, 24).video_bitrate_bps, (1920.0 * 1080.0 * 24.0 * PRORES_422_BITS_PER_PIXEL) as u64);
+        assert!(minute(prores, 30).bytes > auto.bytes * 20);
+    }
+}
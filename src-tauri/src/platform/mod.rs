@@ -3,10 +3,11 @@
 //! Each platform provides:
 //!   - `RecordingHandle` — owns all recording state (stream, encoder, etc.)
 //!   - `start_recording()` — set up capture pipeline and begin recording
-//!   - `take_screenshot_region()` — capture a screen region to PNG
+//!   - `take_screenshot_region()` — capture a screen region to PNG or JPEG
 //!   - System integration helpers (file reveal, clipboard, dialogs)
 
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 // ── Common types shared across all platforms ─────────────────────────
 
@@ -20,6 +21,455 @@ pub enum RecordingQuality {
     High,
 }
 
+impl RecordingQuality {
+    /// Capture frame rate for this preset.
+    pub fn fps(self) -> i32 {
+        match self {
+            RecordingQuality::Standard => 30,
+            RecordingQuality::High => 60,
+        }
+    }
+}
+
+/// Frame-rate bounds applied on top of the quality preset (persisted as
+/// `frame_rate_bounds`). ScreenCaptureKit only delivers frames when the
+/// screen changes, so a low `max_fps` makes mostly static recordings
+/// (slides, terminals) far smaller; pair it with `force_cfr` for editors
+/// that need a steady rate.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct FrameRateBounds {
+    /// Capture at most this many frames per second (`None` = the preset's rate)
+    #[serde(default)]
+    pub max_fps: Option<i32>,
+    /// Automatic reductions (battery saver) never go below this
+    #[serde(default)]
+    pub min_fps: Option<i32>,
+}
+
+/// Frame rates `FrameRateBounds` accepts
+pub const FRAME_RATE_LIMITS: (i32, i32) = (1, 120);
+
+impl FrameRateBounds {
+    pub fn validate(&self) -> Result<(), String> {
+        let (lo, hi) = FRAME_RATE_LIMITS;
+        for (name, fps) in [("max_fps", self.max_fps), ("min_fps", self.min_fps)] {
+            if let Some(fps) = fps {
+                if !(lo..=hi).contains(&fps) {
+                    return Err(format!("{} must be between {} and {} (got {})", name, lo, hi, fps));
+                }
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_fps, self.max_fps) {
+            if min > max {
+                return Err(format!("min_fps ({}) must not exceed max_fps ({})", min, max));
+            }
+        }
+        Ok(())
+    }
+
+    /// Highest capture rate for a preset running at `preset_fps`.
+    pub fn max_for(&self, preset_fps: i32) -> i32 {
+        self.max_fps.map_or(preset_fps, |max| preset_fps.min(max))
+    }
+
+    /// Bound `fps` (the preset rate, possibly reduced automatically) for a
+    /// preset running at `preset_fps`.
+    pub fn clamp(&self, preset_fps: i32, fps: i32) -> i32 {
+        let max = self.max_for(preset_fps);
+        let min = self.min_fps.unwrap_or(FRAME_RATE_LIMITS.0).min(max);
+        fps.clamp(min, max)
+    }
+}
+
+/// Lowest accepted `bitrate_override_bps`.
+pub const MIN_BITRATE_OVERRIDE_BPS: i64 = 500_000;
+
+/// Warning for a bitrate override under a quarter of what would be picked
+/// automatically for this frame size — motion will smear into blocks.
+pub fn low_bitrate_warning(override_bps: i64, auto_bps: i64, width: usize, height: usize) -> Option<String> {
+    (override_bps * 4 < auto_bps).then(|| {
+        format!(
+            "Bitrate override {:.1} Mbps is very low for {}x{} (automatic: {:.1} Mbps); expect heavy compression artifacts",
+            override_bps as f64 / 1e6, width, height, auto_bps as f64 / 1e6
+        )
+    })
+}
+
+/// Longest gap between keyframes unless configured (seconds).
+pub const DEFAULT_KEYFRAME_INTERVAL_SECS: f64 = 2.0;
+/// Accepted keyframe intervals (seconds): shorter bloats the file, longer
+/// makes seeking in editors imprecise.
+pub const KEYFRAME_INTERVAL_RANGE: (f64, f64) = (0.5, 10.0);
+
+/// Clamp a keyframe interval into `KEYFRAME_INTERVAL_RANGE`; non-finite
+/// values fall back to the default.
+pub fn clamp_keyframe_interval(secs: f64) -> f64 {
+    if !secs.is_finite() {
+        return DEFAULT_KEYFRAME_INTERVAL_SECS;
+    }
+    secs.clamp(KEYFRAME_INTERVAL_RANGE.0, KEYFRAME_INTERVAL_RANGE.1)
+}
+
+/// Accepted checkpoint intervals (seconds). Each checkpoint writes one
+/// small `moof` header and syncs it, so even 1s costs only a few KB/min;
+/// beyond 5 minutes a crash simply loses too much.
+pub const CHECKPOINT_INTERVAL_RANGE: (f64, f64) = (1.0, 300.0);
+
+/// Clamp a checkpoint interval into `CHECKPOINT_INTERVAL_RANGE`; non-finite
+/// values turn checkpoints off.
+pub fn clamp_checkpoint_interval(secs: f64) -> Option<f64> {
+    secs.is_finite()
+        .then(|| secs.clamp(CHECKPOINT_INTERVAL_RANGE.0, CHECKPOINT_INTERVAL_RANGE.1))
+}
+
+/// Capture pixel format (advanced, for compatibility testing).
+///
+/// `420v` is the production default: it's what VideoToolbox consumes
+/// natively, so frames go to the encoder zero-copy.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub enum PixelFormat {
+    /// 8-bit 4:2:0 bi-planar, video range (default)
+    #[default]
+    #[serde(rename = "420v")]
+    Yuv420Video,
+    /// 8-bit 4:2:0 bi-planar, full range
+    #[serde(rename = "420f")]
+    Yuv420Full,
+    /// 8-bit BGRA — needs a GPU color conversion before encoding and is known
+    /// to fail after a few seconds; meant for short diagnostic captures only.
+    #[serde(rename = "BGRA")]
+    Bgra,
+    /// 10-bit packed RGB (ARGB2101010 LE), encoded as HEVC Main10
+    #[serde(rename = "l10r")]
+    Rgb10,
+    /// 10-bit 4:2:0 bi-planar, video range, encoded as HEVC Main10 (used
+    /// for Display P3 capture)
+    #[serde(rename = "x420")]
+    Yuv420Video10,
+}
+
+impl PixelFormat {
+    /// CoreVideo FourCC for this format.
+    pub fn fourcc(self) -> u32 {
+        let code = match self {
+            PixelFormat::Yuv420Video => b"420v",
+            PixelFormat::Yuv420Full => b"420f",
+            PixelFormat::Bgra => b"BGRA",
+            PixelFormat::Rgb10 => b"l10r",
+            PixelFormat::Yuv420Video10 => b"x420",
+        };
+        u32::from_be_bytes(*code)
+    }
+
+    /// Whether frames carry 10 bits per component.
+    pub fn is_ten_bit(self) -> bool {
+        matches!(self, PixelFormat::Rgb10 | PixelFormat::Yuv420Video10)
+    }
+}
+
+/// Color space a recording is captured in and tagged with, persisted as
+/// `color_space`. Unset, recordings use BT.709 (or the display's gamut
+/// with `match_display_color`).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    /// sRGB capture tagged BT.709
+    Rec709,
+    /// Display P3 capture tagged P3-D65, 10-bit with HEVC
+    DisplayP3,
+}
+
+/// Screenshot file format. PNG is lossless (best for text and UI); JPEG
+/// is far smaller for photos and video frames.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    /// `quality` is the compression factor, 0.0 (smallest) – 1.0 (best)
+    Jpeg { quality: f64 },
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg { .. } => "jpg",
+        }
+    }
+
+    /// JPEG quality clamped to 0.0 – 1.0 (non-finite = 0.9).
+    pub fn jpeg_quality(quality: f64) -> f64 {
+        if quality.is_finite() { quality.clamp(0.0, 1.0) } else { 0.9 }
+    }
+}
+
+/// MIME type of a screenshot file, from its extension.
+pub fn image_mime_type(path: &str) -> &'static str {
+    let ext = std::path::Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        _ => "image/png",
+    }
+}
+
+/// Video codec for recordings (persisted as `video_codec`).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoCodec {
+    /// HEVC (H.265): roughly a third smaller at the same quality
+    #[default]
+    Hevc,
+    /// H.264 High profile, for players without HEVC support (older Windows)
+    H264,
+    /// Apple ProRes 422 in a QuickTime `.mov`, for editing workflows
+    /// (macOS only). Intra-frame and roughly 10× the size of HEVC.
+    #[serde(rename = "prores422")]
+    ProRes422,
+}
+
+impl VideoCodec {
+    /// Container extension for recordings in this codec.
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            VideoCodec::ProRes422 => "mov",
+            VideoCodec::Hevc | VideoCodec::H264 => "mp4",
+        }
+    }
+
+    /// Codec from an ffprobe `codec_name` ("hevc", "h264", "prores") or the
+    /// names Spotlight and AVFoundation use ("H.265", "avc1", "Apple ProRes 422").
+    pub fn from_codec_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        match name.as_str() {
+            "hevc" | "h265" | "h.265" | "hvc1" | "hev1" => Some(VideoCodec::Hevc),
+            "h264" | "h.264" | "avc" | "avc1" => Some(VideoCodec::H264),
+            _ if name.contains("prores") || name.starts_with("apc") => Some(VideoCodec::ProRes422),
+            _ => None,
+        }
+    }
+
+    /// Codec of the video stream in `path` (probed with ffprobe). Files that
+    /// can't be probed are assumed to be the default codec.
+    pub fn of_file(path: &str) -> Self {
+        crate::ffmpeg::media_info(path)
+            .ok()
+            .and_then(|info| info.video)
+            .and_then(|v| Self::from_codec_name(&v.codec))
+            .unwrap_or_default()
+    }
+
+    /// `-tag:v` value for an ffmpeg stream copy. QuickTime only plays HEVC
+    /// tagged `hvc1` and H.264 tagged `avc1`; ProRes keeps its own fourcc.
+    pub fn stream_tag(self) -> Option<&'static str> {
+        match self {
+            VideoCodec::Hevc => Some("hvc1"),
+            VideoCodec::H264 => Some("avc1"),
+            VideoCodec::ProRes422 => None,
+        }
+    }
+
+    /// ffmpeg `-tag:v` arguments for a stream copy (empty for ProRes).
+    pub fn stream_tag_args(self) -> Vec<&'static str> {
+        self.stream_tag().map(|tag| vec!["-tag:v", tag]).unwrap_or_default()
+    }
+}
+
+/// Clockwise rotation for recordings of a sideways-mounted display. On
+/// macOS it is stored as the video track's transform (players rotate on
+/// playback); on Linux the pixels are rotated with `videoflip`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Rotation {
+    #[default]
+    None,
+    Cw90,
+    Ccw90,
+    Rot180,
+}
+
+impl Rotation {
+    /// Nearest rotation for a clockwise angle in degrees.
+    pub fn from_degrees(degrees: f64) -> Self {
+        match (degrees.round() as i64).rem_euclid(360) {
+            90 => Rotation::Cw90,
+            180 => Rotation::Rot180,
+            270 => Rotation::Ccw90,
+            _ => Rotation::None,
+        }
+    }
+
+    /// Width and height swap on playback.
+    pub fn is_quarter_turn(self) -> bool {
+        matches!(self, Rotation::Cw90 | Rotation::Ccw90)
+    }
+}
+
+/// How the cursor ends up in the recording.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorCapture {
+    /// The system draws the cursor into the capture, at whatever size the
+    /// user's accessibility settings make it.
+    #[default]
+    System,
+    /// Hide the cursor from the capture; it is drawn at a standard size from
+    /// the tracked mouse position by the editor's cursor overlay.
+    Standard,
+}
+
+/// How the recording bar and dim overlay behave across macOS Spaces
+/// (persisted as `bar_spaces`). Ignored on Linux.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BarSpacesConfig {
+    /// Show on every Space, including fullscreen apps' own Spaces
+    /// (`CanJoinAllSpaces` + `FullScreenAuxiliary`). Off keeps the bar on
+    /// the Space the recording started on.
+    #[serde(default = "default_true")]
+    pub join_all_spaces: bool,
+    /// Stay put during Mission Control and Space switch animations
+    /// (`Stationary`) instead of sliding with the desktop.
+    #[serde(default = "default_true")]
+    pub stationary: bool,
+}
+
+fn default_true() -> bool { true }
+
+impl Default for BarSpacesConfig {
+    fn default() -> Self {
+        Self { join_all_spaces: true, stationary: true }
+    }
+}
+
+/// Title/end cards added around a finished recording (persisted as
+/// `intro_outro`). Each card is a still image shown for `duration_secs`,
+/// scaled and letterboxed to the recording's size.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct IntroOutroConfig {
+    #[serde(default)]
+    pub intro_image: Option<String>,
+    #[serde(default)]
+    pub outro_image: Option<String>,
+    #[serde(default = "default_card_secs")]
+    pub duration_secs: f64,
+}
+
+fn default_card_secs() -> f64 { 3.0 }
+
+impl IntroOutroConfig {
+    /// Longest allowed card duration
+    pub const MAX_CARD_SECS: f64 = 30.0;
+
+    /// True when at least one card is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.intro_image.is_some() || self.outro_image.is_some()
+    }
+
+    /// Check the card images exist and the duration is sane.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.duration_secs.is_finite()
+            || self.duration_secs <= 0.0
+            || self.duration_secs > Self::MAX_CARD_SECS
+        {
+            return Err(format!(
+                "Card duration must be between 0 and {} seconds (got {})",
+                Self::MAX_CARD_SECS, self.duration_secs
+            ));
+        }
+        for image in self.intro_image.iter().chain(self.outro_image.iter()) {
+            if !std::path::Path::new(image).is_file() {
+                return Err(format!("Card image not found: {}", image));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Live-stream transport.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamProtocol {
+    /// FLV over RTMP(S) — Twitch, YouTube and most ingest servers. H.264 only.
+    #[default]
+    Rtmp,
+    /// MPEG-TS over SRT — low-latency custom ingest.
+    Srt,
+}
+
+/// Where a live stream goes, e.g. `rtmp://live.twitch.tv/app/<key>`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StreamTarget {
+    pub url: String,
+    #[serde(default)]
+    pub protocol: StreamProtocol,
+}
+
+impl StreamTarget {
+    /// Check the URL scheme matches the protocol.
+    pub fn validate(&self) -> Result<(), String> {
+        let schemes: &[&str] = match self.protocol {
+            StreamProtocol::Rtmp => &["rtmp://", "rtmps://"],
+            StreamProtocol::Srt => &["srt://"],
+        };
+        if !schemes.iter().any(|s| self.url.starts_with(s)) {
+            return Err(format!(
+                "{:?} stream URL must start with {}",
+                self.protocol,
+                schemes.join(" or ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// `scheme://host` only, for logs — the path usually holds the stream key.
+    pub fn redacted(&self) -> String {
+        let (scheme, rest) = self.url.split_once("://").unwrap_or(("", self.url.as_str()));
+        let host = rest.split(['/', '?']).next().unwrap_or_default();
+        format!("{}://{}/…", scheme, host)
+    }
+}
+
+/// How recordings behave on battery power.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerMode {
+    /// On battery: cap at 30 fps, power-efficient encoding, half-rate camera
+    /// preview. Full quality is restored when plugged in. Opt-in.
+    Auto,
+    /// Always record at full quality, even on battery (as before power
+    /// modes existed).
+    #[default]
+    MaxQuality,
+}
+
+/// What to do at finalize when an enabled audio source delivered no samples
+/// (muted system output, unplugged mic). Empty AAC tracks make some players
+/// refuse the whole file.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmptyAudioBehavior {
+    /// Re-mux the file without the empty audio tracks.
+    #[default]
+    Drop,
+    /// Keep the file as-is and warn the user.
+    Warn,
+}
+
+/// What to do when the microphone is requested but the OS denied access
+/// (the mic track would otherwise record silence without a warning).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MicDeniedBehavior {
+    /// Record without the microphone and emit `mic-permission-denied`.
+    #[default]
+    Continue,
+    /// Don't start the recording.
+    Abort,
+}
+
 /// Configuration passed to `start_recording()`.
 pub struct StartRecordingConfig {
     pub output_path: String,
@@ -27,6 +477,273 @@ pub struct StartRecordingConfig {
     pub quality: RecordingQuality,
     pub capture_system_audio: bool,
     pub capture_microphone: bool,
+    /// Full-screen only: crop the menu bar (clock, status icons, recording
+    /// indicator) out of the capture so only the content below it is recorded.
+    pub hide_menu_bar: bool,
+    /// Scene-change sensitivity (0.0 – 1.0). `None` disables detection.
+    pub scene_threshold: Option<f64>,
+    pub empty_audio: EmptyAudioBehavior,
+    /// Record a single window (platform window ID) instead of a display/region.
+    pub window_id: Option<u32>,
+    /// Display to record (platform display ID). `None` = main display.
+    /// `region` is relative to this display.
+    pub display_id: Option<u32>,
+    pub pixel_format: PixelFormat,
+    /// Prefer universally playable output over file size. Linux: fall back
+    /// to H.264 when the HEVC stream isn't QuickTime/Safari compatible.
+    /// (macOS always writes QuickTime-compatible hvc1.)
+    pub compatibility_mode: bool,
+    /// Tag the output with the captured display's color space (nearest
+    /// standard match, e.g. Display P3) instead of generic BT.709.
+    pub match_display_color: bool,
+    /// Forced color space; overrides `match_display_color` (macOS only).
+    pub color_space: Option<ColorSpace>,
+    /// Write a `.timestamps.json` sidecar with each frame's PTS and
+    /// wall-clock time.
+    pub write_timestamps: bool,
+    pub power_mode: PowerMode,
+    pub cursor_capture: CursorCapture,
+    /// Off hides the cursor from the recording entirely: the system doesn't
+    /// draw it and the editor doesn't add a standard-size one either.
+    pub show_cursor: bool,
+    /// Capture at no more than this many pixels wide (aspect kept), for
+    /// outputs that are downscaled anyway (GIF). `None` = native resolution.
+    pub max_width: Option<usize>,
+    /// Run the stream this long before the writer session starts, discarding
+    /// frames, so the recording doesn't begin with ramp-up artifacts.
+    pub warmup_secs: f64,
+    /// Constant frame rate output (Linux: videorate duplicates frames;
+    /// macOS re-times the finished file instead).
+    pub force_cfr: bool,
+    /// Longest gap between keyframes (seconds, see `clamp_keyframe_interval`).
+    /// Shorter intervals make cuts and seeking in editors more precise.
+    pub keyframe_interval_secs: f64,
+    /// Explicit capture rate (e.g. 24, 50, 120) instead of the quality
+    /// preset's 30/60. Clamped to the display's refresh rate where known.
+    pub fps: Option<i32>,
+    pub frame_rate_bounds: FrameRateBounds,
+    /// Fixed video bitrate (bits/s, at least `MIN_BITRATE_OVERRIDE_BPS`)
+    /// instead of the resolution-tier bitrate. `None` = automatic.
+    pub bitrate_override_bps: Option<i64>,
+    /// Encoder quality (0.0 – 1.0) instead of the quality preset's. macOS
+    /// uses it as `AVVideoQualityKey`; Linux maps it onto the encoder's
+    /// nearest constant-quality knob. `None` = the preset's value.
+    pub quality_factor: Option<f64>,
+    pub codec: VideoCodec,
+    /// Output rotation. `None` = follow the display (macOS detects
+    /// displays whose frames arrive sideways; Linux never rotates).
+    pub rotation: Option<Rotation>,
+    /// Stop the recording automatically after this many seconds of
+    /// recorded (unpaused) time. Enforced by the command layer, not the
+    /// platform pipelines. `None` = unlimited.
+    pub max_duration_secs: Option<f64>,
+    /// Write the file as fragmented MP4, completing a fragment every this
+    /// many seconds so everything before the last fragment survives a
+    /// crash. `None` = the platform default (macOS: one `moov` at the end;
+    /// Linux: 1s fragments).
+    pub checkpoint_interval_secs: Option<f64>,
+    /// Microphone to record (`MicrophoneInfo::id`). `None` = the system
+    /// default input.
+    pub microphone_device_id: Option<String>,
+    /// Fragment the file every `CRASH_RESILIENT_FRAGMENT_SECS` even without
+    /// checkpoints, so it stays playable if finalize never runs. Linux
+    /// always fragments.
+    pub crash_resilient: bool,
+    /// Record only the audio tracks to an `.m4a`, with no video track.
+    /// Needs system audio or the microphone.
+    pub audio_only: bool,
+    /// Record every display at once into one video the size of their
+    /// combined bounds (macOS only; the displays need matching scale
+    /// factors). `region`, `window_id` and `display_id` are ignored.
+    pub all_displays: bool,
+}
+
+impl StartRecordingConfig {
+    /// Frame rate the recording aims for before bounds and battery saver:
+    /// the explicit `fps`, else the quality preset's.
+    pub fn preset_fps(&self) -> i32 {
+        self.fps.unwrap_or_else(|| self.quality.fps())
+    }
+
+    /// Seconds between the fragments the writer completes: the checkpoint
+    /// interval, else `CRASH_RESILIENT_FRAGMENT_SECS` in crash-resilient
+    /// mode, else `None` (not fragmented on macOS).
+    pub fn fragment_interval_secs(&self) -> Option<f64> {
+        self.checkpoint_interval_secs
+            .or(self.crash_resilient.then_some(CRASH_RESILIENT_FRAGMENT_SECS))
+    }
+}
+
+/// Check an explicit recording frame rate against `FRAME_RATE_LIMITS`.
+pub fn validate_fps(fps: i32) -> Result<(), String> {
+    let (lo, hi) = FRAME_RATE_LIMITS;
+    if !(lo..=hi).contains(&fps) {
+        return Err(format!("fps must be between {} and {} (got {})", lo, hi, fps));
+    }
+    Ok(())
+}
+
+/// Fragment interval for `crash_resilient` recordings (seconds). At most
+/// the last 2 seconds are lost; the extra `moof` headers cost a few KB/min.
+pub const CRASH_RESILIENT_FRAGMENT_SECS: f64 = 2.0;
+
+/// What a running recording actually got, after even-rounding, clamping
+/// and fallbacks (see `get_active_capture_config`).
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct AppliedCaptureConfig {
+    /// Encoded frame size in pixels
+    pub width: usize,
+    pub height: usize,
+    /// Current capture frame rate (battery saver may cap it)
+    pub fps: i32,
+    /// "hevc", "hevc-main10" or "h264" ("aac" for audio-only recordings)
+    pub codec: String,
+    /// Encoder implementation ("VideoToolbox", or the GStreamer element)
+    pub encoder: String,
+    pub video_bitrate_bps: u64,
+    /// Audio tracks in file order ("system-audio", "microphone")
+    pub audio_tracks: Vec<String>,
+    /// Captured area in logical points (`None` = whole display or window)
+    pub source_rect: Option<CaptureRegion>,
+    pub display_id: Option<u32>,
+    pub window_id: Option<u32>,
+    /// Own windows hidden from the capture when it started
+    pub excluded_windows: usize,
+    pub pixel_format: PixelFormat,
+    pub battery_saver: bool,
+    /// Rotation applied to the output
+    pub rotation: Rotation,
+}
+
+/// Estimated output size for a recording configuration (`estimate_size()`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SizeEstimate {
+    pub bytes: u64,
+    pub video_bitrate_bps: u64,
+    pub audio_bitrate_bps: u64,
+    /// Codec the recording would use ("hevc", "h264" or "prores")
+    pub codec: String,
+    /// Capture frame rate the estimate assumes, after the display,
+    /// frame-rate bounds and battery saver caps
+    pub fps: i32,
+}
+
+/// The `StartRecordingConfig` settings that size a recording, for
+/// `estimate_size()` to apply the way `start_recording()` does.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EncodingSettings {
+    pub quality: RecordingQuality,
+    pub codec: VideoCodec,
+    pub fps: Option<i32>,
+    pub frame_rate_bounds: FrameRateBounds,
+    pub bitrate_override_bps: Option<i64>,
+    pub quality_factor: Option<f64>,
+    pub power_mode: PowerMode,
+}
+
+impl EncodingSettings {
+    /// Same as `StartRecordingConfig::preset_fps`.
+    pub fn preset_fps(&self) -> i32 {
+        self.fps.unwrap_or_else(|| self.quality.fps())
+    }
+}
+
+/// MP4 container overhead (moov atom, sample tables) as a fraction of payload.
+pub(crate) const CONTAINER_OVERHEAD: f64 = 0.01;
+
+/// Video frame counters, shared by the capture callback that increments
+/// them and whoever reads them while the recording runs.
+#[derive(Debug, Default)]
+pub struct FrameCounters {
+    /// Frames appended to the writer
+    pub frames: AtomicU64,
+    /// Frames dropped (writer not ready, invalid buffer, append failed)
+    pub dropped: AtomicU64,
+    /// Frames skipped because their timestamp went backwards
+    pub pts_skips: AtomicU64,
+}
+
+impl FrameCounters {
+    pub fn snapshot(&self) -> FrameStats {
+        FrameStats {
+            frames: self.frames.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            pts_skips: self.pts_skips.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Point-in-time copy of `FrameCounters`.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct FrameStats {
+    pub frames: u64,
+    pub dropped: u64,
+    pub pts_skips: u64,
+}
+
+/// A connected display, as reported by `list_displays()`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DisplayInfo {
+    pub id: u32,
+    /// Top-left corner in the global logical coordinate space (the main
+    /// display is at 0,0), matching Tauri's monitor positions / scale
+    pub x: f64,
+    pub y: f64,
+    /// Logical size (points)
+    pub width: usize,
+    pub height: usize,
+    pub scale: f64,
+    pub is_main: bool,
+}
+
+/// An audio input device, as reported by `list_microphones()`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MicrophoneInfo {
+    /// macOS: AVCaptureDevice unique ID; Linux: PulseAudio source name
+    pub id: String,
+    pub name: String,
+    /// The system default input
+    pub is_default: bool,
+}
+
+/// An application window that can be recorded, as reported by `list_windows()`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowInfo {
+    pub id: u32,
+    pub title: String,
+    /// Name of the owning application
+    pub app_name: String,
+    /// Bundle identifier of the owning application (macOS)
+    pub bundle_id: Option<String>,
+}
+
+/// Other apps' windows to keep out of display recordings for privacy
+/// (password managers, chat), persisted as `privacy_exclusions`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PrivacyExclusions {
+    /// Exact bundle identifiers, e.g. `com.tinyspeck.slackmacgap`
+    #[serde(default)]
+    pub bundle_ids: Vec<String>,
+    /// Window-title substrings (case-insensitive)
+    #[serde(default)]
+    pub window_titles: Vec<String>,
+}
+
+impl PrivacyExclusions {
+    pub fn is_empty(&self) -> bool {
+        self.bundle_ids.is_empty() && self.window_titles.is_empty()
+    }
+
+    /// Whether a window of `bundle_id` titled `title` should be hidden.
+    pub fn matches(&self, bundle_id: &str, title: &str) -> bool {
+        if self.bundle_ids.iter().any(|b| b.eq_ignore_ascii_case(bundle_id)) {
+            return true;
+        }
+        let title = title.to_lowercase();
+        self.window_titles
+            .iter()
+            .any(|t| !t.is_empty() && title.contains(&t.to_lowercase()))
+    }
 }
 
 /// Region definition for region-based capture (web coordinates: top-left origin, CSS pixels).
@@ -38,6 +755,157 @@ pub struct CaptureRegion {
     pub height: f64,
 }
 
+/// Smallest region edge (points) the capture path accepts.
+const MIN_REGION_EDGE: f64 = 2.0;
+
+/// Shrink `region` to the aspect `ratio` (width / height), keeping its
+/// top-left corner and staying inside the drawn rectangle. Sizes are
+/// rounded to whole points so the frontend and the capture path agree
+/// on the exact rectangle.
+pub fn snap_region_to_aspect(region: &CaptureRegion, ratio: f64) -> CaptureRegion {
+    if !(ratio.is_finite() && ratio > 0.0) || region.width <= 0.0 || region.height <= 0.0 {
+        return region.clone();
+    }
+    let (mut w, mut h) = (region.width.round(), region.height.round());
+    if w / h > ratio {
+        w = (h * ratio).round();
+    } else {
+        h = (w / ratio).round();
+    }
+    CaptureRegion {
+        x: region.x.round(),
+        y: region.y.round(),
+        width: w.max(MIN_REGION_EDGE),
+        height: h.max(MIN_REGION_EDGE),
+    }
+}
+
+/// Check a capture region against the target display before it becomes a
+/// `sourceRect`. Zero-size, non-finite and fully off-screen regions are
+/// rejected; a partially off-screen region is cut down to its on-screen part.
+pub fn validate_region(region: &CaptureRegion, display_bounds: &CaptureRegion) -> Result<CaptureRegion, String> {
+    let describe = |r: &CaptureRegion| format!("{}x{} at ({}, {})", r.width, r.height, r.x, r.y);
+
+    let finite = [region.x, region.y, region.width, region.height].iter().all(|v| v.is_finite());
+    if !finite || region.width < MIN_REGION_EDGE || region.height < MIN_REGION_EDGE {
+        return Err(format!(
+            "Invalid capture region {}: width and height must be at least {}pt",
+            describe(region), MIN_REGION_EDGE
+        ));
+    }
+
+    let left = region.x.max(display_bounds.x);
+    let top = region.y.max(display_bounds.y);
+    let right = (region.x + region.width).min(display_bounds.x + display_bounds.width);
+    let bottom = (region.y + region.height).min(display_bounds.y + display_bounds.height);
+    if right - left < MIN_REGION_EDGE || bottom - top < MIN_REGION_EDGE {
+        return Err(format!(
+            "Capture region {} is outside the display bounds {}",
+            describe(region), describe(display_bounds)
+        ));
+    }
+
+    let visible = CaptureRegion { x: left, y: top, width: right - left, height: bottom - top };
+    Ok(clamp_region_to_display(&visible, display_bounds))
+}
+
+/// Fit `region` inside `display_bounds` (same coordinate space): the size
+/// is capped at the display size, then the origin is moved so the whole
+/// region is on-screen. Result is whole points.
+pub fn clamp_region_to_display(region: &CaptureRegion, display_bounds: &CaptureRegion) -> CaptureRegion {
+    let bx = display_bounds.x.round();
+    let by = display_bounds.y.round();
+    let bw = display_bounds.width.floor().max(MIN_REGION_EDGE);
+    let bh = display_bounds.height.floor().max(MIN_REGION_EDGE);
+
+    let width = region.width.round().clamp(MIN_REGION_EDGE, bw);
+    let height = region.height.round().clamp(MIN_REGION_EDGE, bh);
+    CaptureRegion {
+        x: region.x.round().clamp(bx, bx + bw - width),
+        y: region.y.round().clamp(by, by + bh - height),
+        width,
+        height,
+    }
+}
+
+/// Default smallest recording region (logical points). Accidental tiny
+/// drags make useless recordings and trip SCK's minimum-size handling.
+pub const DEFAULT_MIN_REGION_SIZE: f64 = 64.0;
+
+/// Grow `region` around its center to at least `min`×`min`, then fit it
+/// back inside `display_bounds`. Larger regions are only clamped.
+pub fn expand_region_to_min(region: &CaptureRegion, min: f64, display_bounds: &CaptureRegion) -> CaptureRegion {
+    let width = region.width.max(min);
+    let height = region.height.max(min);
+    let grown = CaptureRegion {
+        x: region.x - (width - region.width) / 2.0,
+        y: region.y - (height - region.height) / 2.0,
+        width,
+        height,
+    };
+    clamp_region_to_display(&grown, display_bounds)
+}
+
+/// Marks the temp file a recording is remuxed into before it replaces it
+const REMUX_TEMP_MARKER: &str = "remux";
+
+/// Temp path for remuxing `path`: `<stem>.remux.<ext>`, keeping the
+/// output's extension so the muxer picks the same container (.mp4/.mov).
+pub fn remux_temp_path(path: &std::path::Path) -> std::path::PathBuf {
+    let ext = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_else(|| "mp4".into());
+    path.with_extension(format!("{}.{}", REMUX_TEMP_MARKER, ext))
+}
+
+/// Whether `path` is a `remux_temp_path` (possibly left over from a crash).
+pub fn is_remux_temp(path: &std::path::Path) -> bool {
+    path.file_stem()
+        .and_then(|s| std::path::Path::new(s).extension())
+        .is_some_and(|e| e == REMUX_TEMP_MARKER)
+}
+
+/// Encoder-safe frame dimension: rounded *down* to even (HEVC/H.264 need
+/// even sizes), at least 2. Rounding down means the output never has a
+/// row or column the capture source doesn't cover (the green/black edge).
+pub fn even_dimension(pixels: usize) -> usize {
+    (pixels & !1).max(2)
+}
+
+/// Frame size in pixels for a length in points at `scale`: the nearest
+/// whole pixel, made even. Capture output, writer settings and the
+/// destination rect must all use this one value.
+pub fn even_pixels(points: f64, scale: f64) -> usize {
+    even_dimension((points * scale).round().max(0.0) as usize)
+}
+
+/// Full-screen capture with the top `menu_bar` points of a `display_w` ×
+/// `display_h` display cut off: output width and height in pixels, and the
+/// display-local source rect in points. The menu bar height is clamped to
+/// what the display can spare; 0 (auto-hidden) captures the whole display.
+pub fn menu_bar_crop(
+    display_w: f64,
+    display_h: f64,
+    phys_width: usize,
+    menu_bar: f64,
+    scale: f64,
+) -> (usize, usize, CaptureRegion) {
+    let menu_bar = if menu_bar.is_finite() {
+        menu_bar.clamp(0.0, (display_h - MIN_REGION_EDGE).max(0.0))
+    } else {
+        0.0
+    };
+    // Floor, not round: the rect must end on the display at fractional scales
+    let pixel_h = even_dimension(((display_h - menu_bar) * scale).floor().max(0.0) as usize);
+    let source = CaptureRegion {
+        x: 0.0,
+        y: menu_bar,
+        width: display_w,
+        height: pixel_h as f64 / scale,
+    };
+    (even_dimension(phys_width), pixel_h, source)
+}
+
+pub mod overlap;
+
 // ── Platform-specific modules ────────────────────────────────────────
 
 #[cfg(target_os = "macos")]
@@ -53,3 +921,205 @@ pub use macos as imp;
 
 #[cfg(target_os = "linux")]
 pub use linux as imp;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(x: f64, y: f64, width: f64, height: f64) -> CaptureRegion {
+        CaptureRegion { x, y, width, height }
+    }
+
+    fn parts(r: &CaptureRegion) -> (f64, f64, f64, f64) {
+        (r.x, r.y, r.width, r.height)
+    }
+
+    fn snap(r: CaptureRegion, ratio: f64) -> (f64, f64, f64, f64) {
+        parts(&snap_region_to_aspect(&r, ratio))
+    }
+
+    #[test]
+    fn snap_to_aspect_shrinks_the_long_side() {
+        // Too wide: the width gives
+        assert_eq!(snap(region(0.0, 0.0, 2000.0, 900.0), 16.0 / 9.0), (0.0, 0.0, 1600.0, 900.0));
+        // Too tall: the height gives
+        assert_eq!(snap(region(0.0, 0.0, 800.0, 800.0), 4.0 / 3.0), (0.0, 0.0, 800.0, 600.0));
+        // Already on the ratio: unchanged
+        assert_eq!(snap(region(5.0, 5.0, 1920.0, 1080.0), 16.0 / 9.0), (5.0, 5.0, 1920.0, 1080.0));
+    }
+
+    #[test]
+    fn snap_to_aspect_rounds_to_whole_points() {
+        // 1000 / (16/9) = 562.5 → 563; the origin rounds too
+        assert_eq!(
+            snap(region(10.4, 20.6, 1000.0, 1000.0), 16.0 / 9.0),
+            (10.0, 21.0, 1000.0, 563.0)
+        );
+        // Fractional drag sizes are rounded before the ratio is applied
+        assert_eq!(snap(region(0.0, 0.0, 99.6, 300.2), 1.0), (0.0, 0.0, 100.0, 100.0));
+    }
+
+    #[test]
+    fn snap_to_aspect_keeps_the_minimum_edge() {
+        // A 1pt-wide drag can't go below the 2pt minimum on either side
+        assert_eq!(snap(region(0.0, 0.0, 1.0, 100.0), 16.0 / 9.0), (0.0, 0.0, 2.0, 2.0));
+        assert_eq!(snap(region(0.0, 0.0, 300.0, 100.0), 1000.0), (0.0, 0.0, 300.0, 2.0));
+    }
+
+    #[test]
+    fn snap_to_aspect_ignores_bad_input() {
+        let r = region(1.5, 2.5, 300.0, 200.0);
+        for ratio in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert_eq!(parts(&snap_region_to_aspect(&r, ratio)), parts(&r));
+        }
+        let empty = region(1.5, 2.5, 0.0, 200.0);
+        assert_eq!(parts(&snap_region_to_aspect(&empty, 16.0 / 9.0)), parts(&empty));
+    }
+
+    fn clamp(r: CaptureRegion, bounds: &CaptureRegion) -> (f64, f64, f64, f64) {
+        parts(&clamp_region_to_display(&r, bounds))
+    }
+
+    #[test]
+    fn clamp_keeps_an_on_screen_region() {
+        let display = region(0.0, 0.0, 1440.0, 900.0);
+        assert_eq!(clamp(region(100.0, 50.0, 640.0, 480.0), &display), (100.0, 50.0, 640.0, 480.0));
+        // Exactly the display
+        assert_eq!(clamp(region(0.0, 0.0, 1440.0, 900.0), &display), (0.0, 0.0, 1440.0, 900.0));
+    }
+
+    #[test]
+    fn clamp_moves_a_partly_off_screen_region_back() {
+        let display = region(0.0, 0.0, 1440.0, 900.0);
+        // Past the right and bottom edges: slid back, size kept
+        assert_eq!(clamp(region(1000.0, 700.0, 640.0, 480.0), &display), (800.0, 420.0, 640.0, 480.0));
+        // Past the left and top edges
+        assert_eq!(clamp(region(-50.0, -20.0, 640.0, 480.0), &display), (0.0, 0.0, 640.0, 480.0));
+    }
+
+    #[test]
+    fn clamp_pulls_an_off_screen_region_onto_the_display() {
+        let display = region(0.0, 0.0, 1440.0, 900.0);
+        assert_eq!(clamp(region(5000.0, -3000.0, 200.0, 100.0), &display), (1240.0, 0.0, 200.0, 100.0));
+        // Bigger than the display: capped to it
+        assert_eq!(clamp(region(-10.0, -10.0, 3000.0, 2000.0), &display), (0.0, 0.0, 1440.0, 900.0));
+        // Degenerate sizes come back at the 2pt minimum
+        assert_eq!(clamp(region(10.0, 10.0, 0.0, -5.0), &display), (10.0, 10.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn clamp_handles_offset_and_fractional_displays() {
+        // A 2560px display at 1.5x next to a 1512pt Retina one: 1706.67pt
+        // wide, starting at x = 1512 in global points
+        let external = region(1512.0, 0.0, 2560.0 / 1.5, 1440.0 / 1.5);
+        assert_eq!(clamp(region(3000.0, 100.0, 400.0, 300.0), &external), (2818.0, 100.0, 400.0, 300.0));
+        assert_eq!(clamp(region(1400.0, 100.0, 400.0, 300.0), &external), (1512.0, 100.0, 400.0, 300.0));
+        // The size is capped to whole points inside the fractional width
+        assert_eq!(clamp(region(1512.0, 0.0, 2000.0, 2000.0), &external), (1512.0, 0.0, 1706.0, 960.0));
+        // A display above the main one (negative origin)
+        let above = region(-200.0, -1080.0, 1920.0, 1080.0);
+        assert_eq!(clamp(region(0.0, -100.0, 800.0, 600.0), &above), (0.0, -600.0, 800.0, 600.0));
+    }
+
+    #[test]
+    fn validate_rejects_zero_tiny_and_non_finite_regions() {
+        let display = region(0.0, 0.0, 1440.0, 900.0);
+        for bad in [
+            region(10.0, 10.0, 0.0, 100.0),
+            region(10.0, 10.0, 100.0, 0.0),
+            region(10.0, 10.0, 1.9, 100.0),
+            region(10.0, 10.0, -50.0, 100.0),
+            region(f64::NAN, 10.0, 100.0, 100.0),
+            region(10.0, 10.0, f64::INFINITY, 100.0),
+        ] {
+            assert!(validate_region(&bad, &display).is_err(), "accepted {:?}", bad);
+        }
+    }
+
+    #[test]
+    fn validate_accepts_the_minimum_size() {
+        let display = region(0.0, 0.0, 1440.0, 900.0);
+        let r = validate_region(&region(10.0, 10.0, 2.0, 2.0), &display).unwrap();
+        assert_eq!(parts(&r), (10.0, 10.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn validate_cuts_a_region_to_its_on_screen_part() {
+        let display = region(0.0, 0.0, 1440.0, 900.0);
+        let r = validate_region(&region(1240.0, -100.0, 400.0, 300.0), &display).unwrap();
+        assert_eq!(parts(&r), (1240.0, 0.0, 200.0, 200.0));
+        // Off-screen, or on-screen by less than the minimum: rejected
+        assert!(validate_region(&region(2000.0, 0.0, 400.0, 300.0), &display).is_err());
+        assert!(validate_region(&region(1439.0, 0.0, 400.0, 300.0), &display).is_err());
+    }
+
+    #[test]
+    fn even_pixels_rounds_to_even_sizes() {
+        // Odd pixel counts round down to even
+        assert_eq!(even_pixels(101.0, 1.0), 100);
+        assert_eq!(even_pixels(100.5, 2.0), 200);
+        assert_eq!(even_pixels(333.0, 1.5), 500); // 499.5 → 500
+        assert_eq!(even_pixels(331.0, 1.5), 496); // 496.5 → 497 → 496
+        // Fractional points land on the nearest pixel first
+        assert_eq!(even_pixels(99.8, 1.0), 100);
+        assert_eq!(even_pixels(99.2, 1.0), 98);
+    }
+
+    #[test]
+    fn even_pixels_never_goes_below_two() {
+        assert_eq!(even_pixels(0.0, 2.0), 2);
+        assert_eq!(even_pixels(1.0, 1.0), 2);
+        assert_eq!(even_pixels(-10.0, 2.0), 2);
+        assert_eq!(even_pixels(f64::NAN, 2.0), 2);
+        assert_eq!(even_dimension(3), 2);
+        assert_eq!(even_dimension(0), 2);
+    }
+
+    #[test]
+    fn menu_bar_crop_without_notch() {
+        // 1440×900 @2x, 24pt menu bar
+        let (w, h, src) = menu_bar_crop(1440.0, 900.0, 2880, 24.0, 2.0);
+        assert_eq!((w, h), (2880, 1752));
+        assert_eq!(parts(&src), (0.0, 24.0, 1440.0, 876.0));
+    }
+
+    #[test]
+    fn menu_bar_crop_with_notch() {
+        // 14" MacBook Pro, 1512×982 @2x, 37pt notched menu bar: 945pt left
+        let (w, h, src) = menu_bar_crop(1512.0, 982.0, 3024, 37.0, 2.0);
+        assert_eq!((w, h), (3024, 1890));
+        assert_eq!(parts(&src), (0.0, 37.0, 1512.0, 945.0));
+    }
+
+    #[test]
+    fn menu_bar_crop_on_scaled_displays() {
+        // 1x external display, 25pt menu bar: 1055 rows, made even
+        let (w, h, src) = menu_bar_crop(1920.0, 1080.0, 1920, 25.0, 1.0);
+        assert_eq!((w, h), (1920, 1054));
+        assert_eq!(parts(&src), (0.0, 25.0, 1920.0, 1054.0));
+
+        // "More Space" scaling: 1800×1169pt on a 3024-pixel-wide panel
+        let scale = 3024.0 / 1800.0;
+        let (w, h, src) = menu_bar_crop(1800.0, 1169.0, 3024, 37.0, scale);
+        assert_eq!((w, h), (3024, 1900)); // 1132pt × 1.68 = 1901.76
+        // The source rect maps exactly onto the output rows
+        assert!((src.height * scale - h as f64).abs() < 1e-9);
+        assert!(src.height <= 1169.0 - 37.0);
+    }
+
+    #[test]
+    fn menu_bar_crop_handles_hidden_and_oversized_menu_bars() {
+        // Auto-hidden menu bar: the whole display
+        let (w, h, src) = menu_bar_crop(1440.0, 900.0, 2880, 0.0, 2.0);
+        assert_eq!((w, h), (2880, 1800));
+        assert_eq!(parts(&src), (0.0, 0.0, 1440.0, 900.0));
+
+        // Nonsense heights never leave less than the minimum, or go negative
+        let (_, h, src) = menu_bar_crop(1440.0, 900.0, 2880, 5000.0, 2.0);
+        assert_eq!((h, src.y), (4, 898.0));
+        let (_, h, src) = menu_bar_crop(1440.0, 900.0, 2880, -10.0, 2.0);
+        assert_eq!((h, src.y), (1800, 0.0));
+        let (_, h, _) = menu_bar_crop(1440.0, 900.0, 2880, f64::NAN, 2.0);
+        assert_eq!(h, 1800);
+    }
+}
@@ -0,0 +1,141 @@
+//! Overlap detection for scrolling screenshots, shared by the macOS and
+//! Linux stitchers: where does a new frame continue the stitched image?
+//!
+//! Takes the bottom strip of the stitched image and slides it down the new
+//! frame, comparing by SAD (sum of absolute differences). Pixels are 4-byte
+//! (BGRA or RGBA — only equality matters); rows may be padded.
+
+/// Rows at the bottom of the stitched image matched against each new frame
+const STRIP_ROWS: usize = 40;
+/// Fewer rows than this can't be matched reliably
+const MIN_STRIP_ROWS: usize = 4;
+/// Compare every 4th pixel across a row (16 bytes) for speed
+const SAMPLE_STEP_BYTES: usize = 16;
+/// Mean absolute difference per sample above which the strip isn't found
+const MATCH_THRESHOLD: u64 = 12;
+
+/// Number of rows at the top of `new` that repeat the bottom of `acc`, or
+/// `None` when the strip isn't found (a jump past a whole frame, content
+/// that changed under the capture) or either image is too short to match.
+///
+/// `acc_bpr` / `new_bpr` are the bytes per row of each buffer;
+/// `compare_width_bytes` is how much of each row holds pixels.
+pub fn find_overlap(
+    acc: &[u8],
+    acc_height: usize,
+    acc_bpr: usize,
+    new: &[u8],
+    new_height: usize,
+    new_bpr: usize,
+    compare_width_bytes: usize,
+) -> Option<usize> {
+    let strip_height = STRIP_ROWS.min(acc_height / 2).min(new_height / 2);
+    if strip_height < MIN_STRIP_ROWS || compare_width_bytes == 0 {
+        return None;
+    }
+    let strip = &acc[(acc_height - strip_height) * acc_bpr..];
+    let step = SAMPLE_STEP_BYTES.min(compare_width_bytes);
+
+    let mut best_pos = 0usize;
+    let mut best_sad = u64::MAX;
+    for pos in 0..=new_height - strip_height {
+        let mut sad: u64 = 0;
+        for row in 0..strip_height {
+            let a = &strip[row * acc_bpr..row * acc_bpr + compare_width_bytes];
+            let b = &new[(pos + row) * new_bpr..(pos + row) * new_bpr + compare_width_bytes];
+            sad += a
+                .iter()
+                .step_by(step)
+                .zip(b.iter().step_by(step))
+                .map(|(p, q)| p.abs_diff(*q) as u64)
+                .sum::<u64>();
+            if sad >= best_sad {
+                break;
+            }
+        }
+        if sad < best_sad {
+            best_sad = sad;
+            best_pos = pos;
+        }
+    }
+
+    let samples = (strip_height * compare_width_bytes.div_ceil(step)) as u64;
+    (best_sad <= samples * MATCH_THRESHOLD).then_some(best_pos + strip_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: usize = 32;
+
+    /// A page whose rows are all distinct (pseudo-random bytes).
+    fn page(height: usize) -> Vec<u8> {
+        let mut state: u32 = 0x9e37_79b9;
+        (0..height * WIDTH * 4)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    /// Rows `top..top + height` of `page`, each padded to `bpr` bytes.
+    fn viewport(page: &[u8], top: usize, height: usize, bpr: usize) -> Vec<u8> {
+        let row_bytes = WIDTH * 4;
+        let mut out = Vec::with_capacity(height * bpr);
+        for row in top..top + height {
+            out.extend_from_slice(&page[row * row_bytes..(row + 1) * row_bytes]);
+            out.resize(out.len() + bpr - row_bytes, 0);
+        }
+        out
+    }
+
+    #[test]
+    fn finds_scrolled_overlap() {
+        let page = page(400);
+        let bpr = WIDTH * 4;
+        let first = viewport(&page, 0, 200, bpr);
+        let second = viewport(&page, 70, 200, bpr);
+        // Rows 70..200 of the page are in both
+        assert_eq!(find_overlap(&first, 200, bpr, &second, 200, bpr, bpr), Some(130));
+    }
+
+    #[test]
+    fn unchanged_frame_overlaps_completely() {
+        let page = page(200);
+        let bpr = WIDTH * 4;
+        let frame = viewport(&page, 0, 200, bpr);
+        assert_eq!(find_overlap(&frame, 200, bpr, &frame, 200, bpr, bpr), Some(200));
+    }
+
+    #[test]
+    fn padded_rows_match_across_strides() {
+        let page = page(400);
+        let first = viewport(&page, 0, 200, WIDTH * 4 + 64);
+        let second = viewport(&page, 150, 200, WIDTH * 4 + 32);
+        assert_eq!(
+            find_overlap(&first, 200, WIDTH * 4 + 64, &second, 200, WIDTH * 4 + 32, WIDTH * 4),
+            Some(50)
+        );
+    }
+
+    #[test]
+    fn jump_past_the_frame_is_no_match() {
+        let page = page(600);
+        let bpr = WIDTH * 4;
+        let first = viewport(&page, 0, 200, bpr);
+        let second = viewport(&page, 300, 200, bpr);
+        assert_eq!(find_overlap(&first, 200, bpr, &second, 200, bpr, bpr), None);
+    }
+
+    #[test]
+    fn short_frames_are_no_match() {
+        let page = page(10);
+        let bpr = WIDTH * 4;
+        let frame = viewport(&page, 0, 7, bpr);
+        assert_eq!(find_overlap(&frame, 7, bpr, &frame, 7, bpr, bpr), None);
+    }
+}
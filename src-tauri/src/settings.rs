@@ -0,0 +1,119 @@
+//! Persistent user settings stored as `settings.json` in the app config dir.
+//!
+//! Values are kept as loose JSON keys so new options can be added without a
+//! migration; every getter takes the default to use when a key is missing.
+
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+fn settings_path(app: &AppHandle) -> PathBuf {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    let _ = std::fs::create_dir_all(&dir);
+    dir.join("settings.json")
+}
+
+/// Load the full settings object (empty object if missing or unreadable).
+pub fn load(app: &AppHandle) -> serde_json::Value {
+    std::fs::read_to_string(settings_path(app))
+        .ok()
+        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        .filter(|v| v.is_object())
+        .unwrap_or_else(|| serde_json::json!({}))
+}
+
+/// Held across every read-modify-write of the settings file.
+static WRITE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Write a single key, preserving all other settings.
+pub fn set(app: &AppHandle, key: &str, value: serde_json::Value) {
+    update(app, key, |_| value);
+}
+
+/// Replace a key with `f(current value)` (null if unset) as one step:
+/// no other write can land in between. Returns the new value.
+pub fn update(
+    app: &AppHandle,
+    key: &str,
+    f: impl FnOnce(&serde_json::Value) -> serde_json::Value,
+) -> serde_json::Value {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = settings_path(app);
+    let mut settings = load(app);
+    let value = f(&settings[key]);
+    settings[key] = value.clone();
+    if let Err(e) = std::fs::write(&path, serde_json::to_string_pretty(&settings).unwrap()) {
+        eprintln!("[zureshot] Failed to save settings ({}): {}", key, e);
+    }
+    value
+}
+
+/// Read and deserialize a key (e.g. an enum stored by name).
+pub fn get<T: serde::de::DeserializeOwned>(app: &AppHandle, key: &str) -> Option<T> {
+    serde_json::from_value(load(app).get(key)?.clone()).ok()
+}
+
+pub fn get_bool(app: &AppHandle, key: &str, default: bool) -> bool {
+    load(app)[key].as_bool().unwrap_or(default)
+}
+
+pub fn get_f64(app: &AppHandle, key: &str, default: f64) -> f64 {
+    load(app)[key].as_f64().unwrap_or(default)
+}
+
+/// Default folder for recordings and screenshots: `~/Downloads/Zureshot`.
+fn default_recordings_dir() -> PathBuf {
+    dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("Zureshot")
+}
+
+/// Create `dir` if missing and check a file can be written into it. Writes
+/// (and removes) a probe file, which touches the folder's mtime: for when
+/// the user picks a folder, not for every lookup (see `check_usable_dir`).
+pub fn check_writable_dir(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    let probe = dir.join(format!(".zureshot_write_test_{}", std::process::id()));
+    std::fs::write(&probe, b"").map_err(|e| format!("{} is not writable: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Create `dir` if missing and check from its metadata that it's a folder
+/// that isn't read-only. Writes nothing, so it's cheap enough for every
+/// lookup; `check_writable_dir` already proved the folder when it was chosen.
+fn check_usable_dir(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    let meta = std::fs::metadata(dir).map_err(|e| format!("Cannot read {}: {}", dir.display(), e))?;
+    if !meta.is_dir() {
+        return Err(format!("{} is not a folder", dir.display()));
+    }
+    if meta.permissions().readonly() {
+        return Err(format!("{} is read-only", dir.display()));
+    }
+    Ok(())
+}
+
+/// Folder new recordings and screenshots are saved to (created if missing).
+///
+/// Read fresh on every call, so a change via `set_recordings_dir` applies to
+/// the next capture without restarting. A configured folder that is gone or
+/// read-only (unmounted drive, revoked permission) falls back to the default.
+pub fn recordings_dir(app: &AppHandle) -> PathBuf {
+    let configured = load(app)["recordings_dir"]
+        .as_str()
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from);
+    if let Some(dir) = configured {
+        match check_usable_dir(&dir) {
+            Ok(()) => return dir,
+            Err(e) => eprintln!("[zureshot] Recordings folder unusable, using the default: {}", e),
+        }
+    }
+    let dir = default_recordings_dir();
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
@@ -2,7 +2,7 @@
 
 use tauri::{
     image::Image,
-    menu::{CheckMenuItem, Menu, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     AppHandle, Emitter, Manager,
 };
@@ -10,12 +10,24 @@ use tauri_plugin_updater::UpdaterExt;
 
 use crate::commands;
 use crate::commands::RecordingState;
-use std::path::PathBuf;
+use crate::platform::VideoCodec;
+use crate::settings;
 use std::sync::Mutex;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 const TRAY_ID: &str = "zureshot-tray";
 
+/// Menu id prefix of the "Record Window…" entries (`record_window:<id>`).
+const RECORD_WINDOW_PREFIX: &str = "record_window:";
+/// Prefix of the "Hide From Recordings" items; the bundle id follows
+const PRIVACY_APP_PREFIX: &str = "privacy_app:";
+/// Prefix of the "Recent Recordings" items; the file path follows
+const RECENT_RECORDING_PREFIX: &str = "recent_recording:";
+/// Recordings listed under "Recent Recordings"
+const RECENT_MENU_ITEMS: usize = 8;
+/// Longest window title shown in "Record Window…" before it is cut off.
+const WINDOW_TITLE_MAX_CHARS: usize = 60;
+
 /// Guard against double-quit: once set, further quit requests are ignored.
 static QUITTING: AtomicBool = AtomicBool::new(false);
 
@@ -23,6 +35,17 @@ static QUITTING: AtomicBool = AtomicBool::new(false);
 /// whether to show the menu or directly stop recording.
 static IS_RECORDING: AtomicBool = AtomicBool::new(false);
 
+/// Whether the primary recording is paused (tooltip only).
+static IS_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// The "Record Window…" and "Recent Recordings" submenus. Listing blocks
+/// (on SCK, on ffprobe), so `refresh_menu_data` fills these on a worker
+/// thread and `build_menu` only reads them.
+static WINDOWS: Mutex<Vec<crate::platform::WindowInfo>> = Mutex::new(Vec::new());
+static RECENT: Mutex<Vec<commands::RecentRecording>> = Mutex::new(Vec::new());
+/// A `refresh_menu_data` worker is running
+static MENU_DATA_REFRESHING: AtomicBool = AtomicBool::new(false);
+
 /// Load tray icon from bundled resources
 fn load_tray_icon(app: &AppHandle, recording: bool) -> Result<Image<'static>, Box<dyn std::error::Error>> {
     let filename = if recording { "tray-recording.png" } else { "tray.png" };
@@ -90,52 +113,42 @@ fn update_tray_icon(app: &AppHandle, recording: bool) {
 
 // ── Settings persistence ──────────────────────────────────────────────
 
-fn settings_path(app: &AppHandle) -> PathBuf {
-    let dir = app
-        .path()
-        .app_config_dir()
-        .unwrap_or_else(|_| PathBuf::from("."));
-    let _ = std::fs::create_dir_all(&dir);
-    dir.join("settings.json")
-}
-
 fn get_auto_update_enabled(app: &AppHandle) -> bool {
-    let path = settings_path(app);
-    std::fs::read_to_string(&path)
-        .ok()
-        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
-        .and_then(|v| v["auto_update"].as_bool())
-        .unwrap_or(true)
+    settings::get_bool(app, "auto_update", true)
 }
 
 fn set_auto_update_enabled(app: &AppHandle, enabled: bool) {
-    let path = settings_path(app);
-    let mut settings: serde_json::Value = std::fs::read_to_string(&path)
-        .ok()
-        .and_then(|s| serde_json::from_str(&s).ok())
-        .unwrap_or_else(|| serde_json::json!({}));
-    settings["auto_update"] = serde_json::json!(enabled);
-    let _ = std::fs::write(&path, serde_json::to_string_pretty(&settings).unwrap());
+    settings::set(app, "auto_update", serde_json::json!(enabled));
+}
+
+/// Seconds to wait after launch before the automatic update check.
+const DEFAULT_UPDATE_CHECK_DELAY_SECS: u64 = 5;
+/// Minimum hours between automatic update checks (across relaunches).
+const DEFAULT_UPDATE_CHECK_INTERVAL_HOURS: f64 = 24.0;
+
+pub fn get_update_check_delay_secs(app: &AppHandle) -> u64 {
+    settings::get::<u64>(app, "update_check_delay_secs").unwrap_or(DEFAULT_UPDATE_CHECK_DELAY_SECS)
+}
+
+pub fn get_update_check_interval_hours(app: &AppHandle) -> f64 {
+    settings::get_f64(app, "update_check_interval_hours", DEFAULT_UPDATE_CHECK_INTERVAL_HOURS)
+}
+
+/// Unix timestamp (seconds) of the last completed update check, 0 if never.
+fn get_last_update_check(app: &AppHandle) -> i64 {
+    settings::get::<i64>(app, "last_update_check").unwrap_or(0)
+}
+
+fn mark_update_checked(app: &AppHandle) {
+    settings::set(app, "last_update_check", serde_json::json!(chrono::Utc::now().timestamp()));
 }
 
 fn is_first_run(app: &AppHandle) -> bool {
-    let path = settings_path(app);
-    let done = std::fs::read_to_string(&path)
-        .ok()
-        .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
-        .and_then(|v| v["first_run_done"].as_bool())
-        .unwrap_or(false);
-    !done
+    !settings::get_bool(app, "first_run_done", false)
 }
 
 fn mark_first_run_done(app: &AppHandle) {
-    let path = settings_path(app);
-    let mut settings: serde_json::Value = std::fs::read_to_string(&path)
-        .ok()
-        .and_then(|s| serde_json::from_str(&s).ok())
-        .unwrap_or_else(|| serde_json::json!({}));
-    settings["first_run_done"] = serde_json::json!(true);
-    let _ = std::fs::write(&path, serde_json::to_string_pretty(&settings).unwrap());
+    settings::set(app, "first_run_done", serde_json::json!(true));
 }
 
 // ── Native dialogs (delegated to platform layer) ────────────────────
@@ -174,6 +187,149 @@ fn build_menu(app: &AppHandle, is_recording: bool) -> Result<Menu<tauri::Wry>, B
         !is_recording,
         Some("CmdOrCtrl+Shift+R"),
     )?;
+    let record_fullscreen = MenuItem::with_id(
+        app,
+        "record_fullscreen",
+        "Record Full Screen",
+        !is_recording,
+        None::<&str>,
+    )?;
+    let record_all_displays = MenuItem::with_id(
+        app,
+        "record_all_displays",
+        "Record All Displays",
+        !is_recording,
+        None::<&str>,
+    )?;
+    let record_audio = MenuItem::with_id(
+        app,
+        "record_audio",
+        "Record Audio Only",
+        !is_recording,
+        None::<&str>,
+    )?;
+    let record_focused_window = MenuItem::with_id(
+        app,
+        "record_focused_window",
+        "Record Focused Window",
+        !is_recording,
+        None::<&str>,
+    )?;
+    // Refreshed whenever the pointer enters the tray icon
+    let windows = if is_recording {
+        Vec::new()
+    } else {
+        WINDOWS.lock().map(|w| w.clone()).unwrap_or_default()
+    };
+    let record_window = Submenu::with_id(
+        app,
+        "record_window",
+        "Record Window…",
+        // Linux lists no windows; the portal dialog entry is always there
+        !is_recording && (!windows.is_empty() || cfg!(target_os = "linux")),
+    )?;
+    for window in &windows {
+        let mut title: String = window.title.chars().take(WINDOW_TITLE_MAX_CHARS).collect();
+        if title.len() < window.title.len() {
+            title.push('…');
+        }
+        record_window.append(&MenuItem::with_id(
+            app,
+            format!("{}{}", RECORD_WINDOW_PREFIX, window.id),
+            format!("{} — {}", window.app_name, title),
+            true,
+            None::<&str>,
+        )?)?;
+    }
+    // The portal can't list windows — its dialog offers them instead
+    #[cfg(target_os = "linux")]
+    record_window.append(&MenuItem::with_id(
+        app,
+        format!("{}{}", RECORD_WINDOW_PREFIX, crate::platform::linux::PICKED_WINDOW_ID),
+        "Choose in System Dialog…",
+        true,
+        None::<&str>,
+    )?)?;
+    // Apps with a window on screen, plus hidden ones that aren't running
+    let privacy = commands::privacy_exclusions(app);
+    let mut privacy_apps: Vec<(String, String)> = Vec::new();
+    for window in &windows {
+        if let Some(ref bundle_id) = window.bundle_id {
+            if !privacy_apps.iter().any(|(id, _)| id == bundle_id) {
+                privacy_apps.push((bundle_id.clone(), window.app_name.clone()));
+            }
+        }
+    }
+    for bundle_id in &privacy.bundle_ids {
+        if !privacy_apps.iter().any(|(id, _)| id == bundle_id) {
+            privacy_apps.push((bundle_id.clone(), bundle_id.clone()));
+        }
+    }
+    let hide_from_recordings = Submenu::with_id(
+        app,
+        "hide_from_recordings",
+        "Hide From Recordings",
+        cfg!(target_os = "macos") && !privacy_apps.is_empty(),
+    )?;
+    for (bundle_id, name) in &privacy_apps {
+        hide_from_recordings.append(&CheckMenuItem::with_id(
+            app,
+            format!("{}{}", PRIVACY_APP_PREFIX, bundle_id),
+            name,
+            true,
+            privacy.bundle_ids.contains(bundle_id),
+            None::<&str>,
+        )?)?;
+    }
+    let record_as_gif = CheckMenuItem::with_id(
+        app,
+        "record_as_gif",
+        "Record as GIF",
+        true,
+        commands::default_format(app) == "gif",
+        None::<&str>,
+    )?;
+    let show_cursor = CheckMenuItem::with_id(
+        app,
+        "show_cursor",
+        "Show Cursor",
+        true,
+        commands::show_cursor(app),
+        None::<&str>,
+    )?;
+    let codec = commands::video_codec(app);
+    let video_codec = Submenu::with_id_and_items(
+        app,
+        "video_codec",
+        "Video Codec",
+        true,
+        &[
+            &CheckMenuItem::with_id(
+                app,
+                "codec_hevc",
+                "HEVC (Smaller Files)",
+                true,
+                codec == VideoCodec::Hevc,
+                None::<&str>,
+            )?,
+            &CheckMenuItem::with_id(
+                app,
+                "codec_h264",
+                "H.264 (Plays Everywhere)",
+                true,
+                codec == VideoCodec::H264,
+                None::<&str>,
+            )?,
+            &CheckMenuItem::with_id(
+                app,
+                "codec_prores",
+                "ProRes 422 (Editing, Large Files)",
+                cfg!(target_os = "macos"),
+                codec == VideoCodec::ProRes422,
+                None::<&str>,
+            )?,
+        ],
+    )?;
     let stop_recording = MenuItem::with_id(
         app,
         "stop",
@@ -182,6 +338,28 @@ fn build_menu(app: &AppHandle, is_recording: bool) -> Result<Menu<tauri::Wry>, B
         Some("CmdOrCtrl+Shift+S"),
     )?;
     let separator = MenuItem::with_id(app, "sep1", "────────────", false, None::<&str>)?;
+    let recent = RECENT.lock().map(|r| r.clone()).unwrap_or_default();
+    let recent_recordings = Submenu::with_id(app, "recent_recordings", "Recent Recordings", !recent.is_empty())?;
+    for recording in &recent {
+        let name = std::path::Path::new(&recording.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| recording.path.clone());
+        let label = match recording.duration_secs {
+            Some(secs) => {
+                let secs = secs.round() as u64;
+                format!("{} ({}:{:02})", name, secs / 60, secs % 60)
+            }
+            None => name,
+        };
+        recent_recordings.append(&MenuItem::with_id(
+            app,
+            format!("{}{}", RECENT_RECORDING_PREFIX, recording.path),
+            label,
+            true,
+            None::<&str>,
+        )?)?;
+    }
     let open_recordings = MenuItem::with_id(
         app,
         "open_folder",
@@ -189,6 +367,13 @@ fn build_menu(app: &AppHandle, is_recording: bool) -> Result<Menu<tauri::Wry>, B
         true,
         None::<&str>,
     )?;
+    let set_recordings_folder = MenuItem::with_id(
+        app,
+        "set_recordings_folder",
+        "Set Recordings Folder…",
+        true,
+        None::<&str>,
+    )?;
     let check_update = MenuItem::with_id(
         app,
         "check_update",
@@ -222,9 +407,20 @@ fn build_menu(app: &AppHandle, is_recording: bool) -> Result<Menu<tauri::Wry>, B
             &scroll_screenshot,
             &separator_screenshots,
             &record_region,
+            &record_fullscreen,
+            &record_all_displays,
+            &record_audio,
+            &record_focused_window,
+            &record_window,
+            &hide_from_recordings,
+            &record_as_gif,
+            &show_cursor,
+            &video_codec,
             &stop_recording,
             &separator,
+            &recent_recordings,
             &open_recordings,
+            &set_recordings_folder,
             &check_update,
             &auto_update,
             &launch_at_login,
@@ -275,7 +471,7 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
                         IS_RECORDING.store(false, Ordering::SeqCst);
                         // Stop recording on background thread
                         std::thread::spawn(move || {
-                            match commands::do_stop_recording(&app) {
+                            match commands::do_stop_recording(&app, None) {
                                 Ok(result) => {
                                     println!(
                                         "[zureshot] Stopped via tray click: {} ({:.1}s)",
@@ -287,10 +483,15 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
                         });
                     }
                 }
+                TrayIconEvent::Enter { .. } if !IS_RECORDING.load(Ordering::SeqCst) => {
+                    // Keep the "Record Window…" and recent lists current
+                    refresh_menu_data(tray.app_handle());
+                }
                 _ => {}
             }
         })
         .build(app)?;
+    refresh_menu_data(app);
 
     // Show first-run permission guide (Linux only — macOS uses system dialogs)
     if is_first_run(app) {
@@ -317,7 +518,9 @@ fn update_menu_state(app: &AppHandle, is_recording: bool) {
                 // When idle: left click opens menu as usual
                 let _ = tray.set_show_menu_on_left_click(!is_recording);
                 // Update tooltip to show state
-                let tooltip = if is_recording {
+                let tooltip = if is_recording && IS_PAUSED.load(Ordering::SeqCst) {
+                    "Zureshot - ⏸ Paused"
+                } else if is_recording {
                     "Zureshot - ⏹ Click to Stop Recording"
                 } else {
                     "Zureshot - Screen Recorder"
@@ -329,12 +532,58 @@ fn update_menu_state(app: &AppHandle, is_recording: bool) {
     }
 }
 
+/// Store `value` in `slot`; whether it changed.
+fn replace_if_changed<T: PartialEq>(slot: &Mutex<T>, value: T) -> bool {
+    match slot.lock() {
+        Ok(mut current) if *current != value => {
+            *current = value;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Re-list the windows and recent recordings off the main thread and
+/// rebuild the menu if either changed. Windows aren't listed while
+/// recording (the submenu is disabled then).
+fn refresh_menu_data(app: &AppHandle) {
+    if MENU_DATA_REFRESHING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let windows = if IS_RECORDING.load(Ordering::SeqCst) {
+            Vec::new()
+        } else {
+            crate::platform::imp::list_windows().unwrap_or_default()
+        };
+        let recent = commands::recent_recordings(&app, RECENT_MENU_ITEMS);
+        let windows_changed = replace_if_changed(&WINDOWS, windows);
+        let recent_changed = replace_if_changed(&RECENT, recent);
+        MENU_DATA_REFRESHING.store(false, Ordering::SeqCst);
+        if windows_changed || recent_changed {
+            refresh_menu(&app);
+        }
+    });
+}
+
+/// Rebuild the tray menu after a setting shown in it changed elsewhere.
+pub fn refresh_menu(app: &AppHandle) {
+    let is_recording = {
+        let state = app.state::<Mutex<RecordingState>>();
+        state.lock().map(|r| r.is_recording()).unwrap_or(false)
+    };
+    update_menu_state(app, is_recording);
+}
+
 /// Called from commands.rs when recording stops (e.g. via the recording bar).
 /// Resets tray icon and menu to idle state.
 pub fn notify_recording_stopped(app: &AppHandle) {
     IS_RECORDING.store(false, Ordering::SeqCst);
+    IS_PAUSED.store(false, Ordering::SeqCst);
     update_menu_state(app, false);
     update_tray_icon(app, false);
+    refresh_menu_data(app);
 }
 
 /// Called from commands.rs when recording starts.
@@ -346,6 +595,19 @@ pub fn notify_recording_started(app: &AppHandle) {
     update_tray_icon(app, true);
 }
 
+/// Called from commands.rs when the primary recording is paused.
+/// Switches the tooltip to "Paused"; left click still stops.
+pub fn notify_recording_paused(app: &AppHandle) {
+    IS_PAUSED.store(true, Ordering::SeqCst);
+    update_menu_state(app, true);
+}
+
+/// Called from commands.rs when the primary recording resumes.
+pub fn notify_recording_resumed(app: &AppHandle) {
+    IS_PAUSED.store(false, Ordering::SeqCst);
+    update_menu_state(app, true);
+}
+
 /// Check for updates.
 ///
 /// * `interactive` – `true` when the user clicks "Check for Updates\u2026".
@@ -357,8 +619,10 @@ async fn check_for_updates(
     interactive: bool,
 ) -> Result<Option<String>, Box<dyn std::error::Error>> {
     let updater = app.updater_builder().build()?;
+    let result = updater.check().await?;
+    mark_update_checked(app);
 
-    match updater.check().await? {
+    match result {
         Some(update) => {
             let version = update.version.clone();
             println!("[zureshot] Update found: v{}", version);
@@ -426,16 +690,32 @@ async fn check_for_updates(
 }
 
 /// Auto-check for updates on app startup (respects user preference).
+///
+/// Waits `update_check_delay_secs` after launch, and skips the check if the
+/// last one (automatic or manual) was less than `update_check_interval_hours`
+/// ago, so repeated relaunches don't hammer the update endpoint.
+/// "Check for Updates…" is never throttled.
 pub fn auto_check_update(app: &AppHandle) {
     if !get_auto_update_enabled(app) {
         println!("[zureshot] Auto-update check disabled by user");
         return;
     }
 
+    let interval_secs = (get_update_check_interval_hours(app).max(0.0) * 3600.0) as i64;
+    let since_last = chrono::Utc::now().timestamp() - get_last_update_check(app);
+    if since_last >= 0 && since_last < interval_secs {
+        println!(
+            "[zureshot] Auto-update check skipped: last check {}m ago (interval {}h)",
+            since_last / 60,
+            interval_secs / 3600
+        );
+        return;
+    }
+
+    let delay = get_update_check_delay_secs(app);
     let app = app.clone();
     tauri::async_runtime::spawn(async move {
-        // Wait 5 seconds after startup before checking
-        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        tokio::time::sleep(std::time::Duration::from_secs(delay)).await;
         match check_for_updates(&app, false).await {
             Ok(Some(v)) => println!("[zureshot] Auto-check: update v{} available", v),
             Ok(None) => println!("[zureshot] Auto-check: up to date"),
@@ -465,6 +745,104 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
                 Err(e) => eprintln!("[zureshot] Region selection error: {}", e),
             }
         }
+        "record_fullscreen" => {
+            // Start blocks on GCD completion handlers — keep off the main thread
+            let app = app.clone();
+            std::thread::spawn(move || match commands::do_start_fullscreen_recording(&app, None) {
+                Ok(path) => println!("[zureshot] Full-screen recording started: {}", path),
+                Err(e) => eprintln!("[zureshot] Full-screen recording error: {}", e),
+            });
+        }
+        "record_all_displays" => {
+            // Start blocks on GCD completion handlers — keep off the main thread
+            let app = app.clone();
+            std::thread::spawn(move || match commands::do_start_all_displays_recording(&app) {
+                Ok(path) => println!("[zureshot] All-displays recording started: {}", path),
+                Err(e) => eprintln!("[zureshot] All-displays recording error: {}", e),
+            });
+        }
+        "record_audio" => {
+            // System audio, plus the microphone if the last recording used it
+            let microphone = settings::get::<commands::LastRecording>(app, "last_recording")
+                .is_some_and(|l| l.microphone);
+            let app = app.clone();
+            std::thread::spawn(move || match commands::do_start_audio_recording(&app, true, microphone) {
+                Ok(path) => println!("[zureshot] Audio-only recording started: {}", path),
+                Err(e) => eprintln!("[zureshot] Audio-only recording error: {}", e),
+            });
+        }
+        "record_focused_window" => {
+            // Start blocks on GCD completion handlers — keep off the main thread
+            let app = app.clone();
+            std::thread::spawn(move || {
+                let (quality, system_audio, microphone) = commands::last_recording_settings(&app);
+                match commands::do_record_focused_window(&app, quality, system_audio, microphone) {
+                    Ok(path) => println!("[zureshot] Focused-window recording started: {}", path),
+                    Err(e) => eprintln!("[zureshot] Focused-window recording error: {}", e),
+                }
+            });
+        }
+        id if id.starts_with(RECENT_RECORDING_PREFIX) => {
+            let path = &id[RECENT_RECORDING_PREFIX.len()..];
+            if let Err(e) = commands::do_open_recording(app, path) {
+                eprintln!("[zureshot] Failed to open {}: {}", path, e);
+            }
+        }
+        id if id.starts_with(RECORD_WINDOW_PREFIX) => {
+            let Ok(window_id) = id[RECORD_WINDOW_PREFIX.len()..].parse::<u32>() else { return };
+            // Start blocks on GCD completion handlers — keep off the main thread
+            let app = app.clone();
+            std::thread::spawn(move || {
+                if !crate::platform::imp::window_exists(window_id) {
+                    eprintln!("[zureshot] Window {} closed before recording started", window_id);
+                    refresh_menu_data(&app);
+                    return;
+                }
+                let (quality, system_audio, microphone) = commands::last_recording_settings(&app);
+                match commands::do_record_window(&app, Some(window_id), quality, system_audio, microphone) {
+                    Ok(path) => println!("[zureshot] Window recording started: {}", path),
+                    Err(e) => eprintln!("[zureshot] Window recording error: {}", e),
+                }
+            });
+        }
+        id if id.starts_with(PRIVACY_APP_PREFIX) => {
+            let bundle_id = &id[PRIVACY_APP_PREFIX.len()..];
+            let mut privacy = commands::privacy_exclusions(app);
+            if privacy.bundle_ids.iter().any(|b| b == bundle_id) {
+                privacy.bundle_ids.retain(|b| b != bundle_id);
+            } else {
+                privacy.bundle_ids.push(bundle_id.to_string());
+            }
+            // Re-filters a running recording — keep off the main thread
+            let app = app.clone();
+            std::thread::spawn(move || {
+                commands::do_set_privacy_exclusions(&app, privacy);
+                refresh_menu(&app);
+            });
+        }
+        "codec_hevc" | "codec_h264" | "codec_prores" => {
+            // Only affects the next recording; a running one keeps its codec
+            let codec = match id {
+                "codec_h264" => VideoCodec::H264,
+                "codec_prores" => VideoCodec::ProRes422,
+                _ => VideoCodec::Hevc,
+            };
+            commands::do_set_video_codec(app, codec);
+            refresh_menu(app);
+        }
+        "record_as_gif" => {
+            // Only affects the next recording; a running one keeps its format
+            let format = if commands::default_format(app) == "gif" { "video" } else { "gif" };
+            if let Err(e) = commands::do_set_default_format(app, format) {
+                eprintln!("[zureshot] Default format error: {}", e);
+            }
+            refresh_menu(app);
+        }
+        "show_cursor" => {
+            // Only affects the next recording
+            commands::set_show_cursor(app.clone(), !commands::show_cursor(app));
+            refresh_menu(app);
+        }
         "stop" => {
             // CRITICAL: Must run on background thread!
             // finishWritingWithCompletionHandler and stopCaptureWithCompletionHandler
@@ -477,7 +855,7 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
             update_tray_icon(app, false);
             let app = app.clone();
             std::thread::spawn(move || {
-                match commands::do_stop_recording(&app) {
+                match commands::do_stop_recording(&app, None) {
                     Ok(result) => {
                         println!(
                             "[zureshot] Stopped via menu: {} ({:.1}s, {:.1} MB)",
@@ -491,13 +869,26 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
             });
         }
         "open_folder" => {
-            let base = dirs::download_dir()
-                .or_else(dirs::home_dir)
-                .unwrap_or_else(|| std::path::PathBuf::from("."));
-            let zureshot_dir = base.join("Zureshot");
-            let _ = std::fs::create_dir_all(&zureshot_dir);
+            let zureshot_dir = settings::recordings_dir(app);
             let _ = crate::platform::imp::open_folder(&zureshot_dir.to_string_lossy());
         }
+        "set_recordings_folder" => {
+            // The picker blocks until the user chooses — keep it off the main thread
+            let app = app.clone();
+            std::thread::spawn(move || {
+                let current = settings::recordings_dir(&app);
+                let Some(path) = crate::platform::imp::pick_folder(
+                    "Choose where Zureshot saves recordings",
+                    &current.to_string_lossy(),
+                ) else {
+                    return;
+                };
+                if let Err(e) = commands::do_set_recordings_dir(&app, &path) {
+                    eprintln!("[zureshot] {}", e);
+                    crate::platform::imp::show_info_dialog("Zureshot", &e);
+                }
+            });
+        }
         "check_update" => {
             let app = app.clone();
             tauri::async_runtime::spawn(async move {
@@ -525,7 +916,7 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
             // Rebuild menu so the checkmark reflects the new state
             let is_recording = {
                 let state = app.state::<Mutex<RecordingState>>();
-                state.lock().map(|r| r.is_recording).unwrap_or(false)
+                state.lock().map(|r| r.is_recording()).unwrap_or(false)
             };
             update_menu_state(app, is_recording);
         }
@@ -540,7 +931,7 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
             // Rebuild menu so the checkmark reflects the new state
             let is_recording = {
                 let state = app.state::<Mutex<RecordingState>>();
-                state.lock().map(|r| r.is_recording).unwrap_or(false)
+                state.lock().map(|r| r.is_recording()).unwrap_or(false)
             };
             update_menu_state(app, is_recording);
         }
@@ -564,23 +955,40 @@ fn handle_menu_event(app: &AppHandle, id: &str) {
             // may need the main run loop to deliver callbacks.
             let is_recording = {
                 let state = app.state::<Mutex<RecordingState>>();
-                state.lock().map(|r| r.is_recording).unwrap_or(false)
+                state.lock().map(|r| r.is_recording()).unwrap_or(false)
             };
             if is_recording {
                 let app = app.clone();
                 std::thread::spawn(move || {
                     // Use catch_unwind so app.exit(0) always runs even if
                     // do_stop_recording panics (e.g. mutex poisoned).
-                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                        commands::do_stop_recording(&app)
-                    }));
-                    match result {
-                        Ok(Ok(r)) => println!(
-                            "[zureshot] Recording finalized before quit: {} ({:.1}s)",
-                            r.path, r.duration_secs
-                        ),
-                        Ok(Err(e)) => eprintln!("[zureshot] Error finalizing on quit: {}", e),
-                        Err(_) => eprintln!("[zureshot] PANIC during finalize on quit"),
+                    // Stopping the primary promotes the next one, so this
+                    // loop finalizes every simultaneous recording.
+                    loop {
+                        let any_left = {
+                            let state = app.state::<Mutex<RecordingState>>();
+                            state.lock().map(|r| r.is_recording()).unwrap_or(false)
+                        };
+                        if !any_left {
+                            break;
+                        }
+                        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                            commands::do_stop_recording(&app, None)
+                        }));
+                        match result {
+                            Ok(Ok(r)) => println!(
+                                "[zureshot] Recording finalized before quit: {} ({:.1}s)",
+                                r.path, r.duration_secs
+                            ),
+                            Ok(Err(e)) => {
+                                eprintln!("[zureshot] Error finalizing on quit: {}", e);
+                                break;
+                            }
+                            Err(_) => {
+                                eprintln!("[zureshot] PANIC during finalize on quit");
+                                break;
+                            }
+                        }
                     }
                     crate::SHOULD_EXIT.store(true, std::sync::atomic::Ordering::SeqCst);
                     app.exit(0);
@@ -0,0 +1,12 @@
+import ClickOverlay from './lib/ClickOverlay.svelte';
+import { mount } from 'svelte';
+
+try {
+  const target = document.getElementById('app');
+  if (!target) throw new Error('#app element not found');
+  target.innerHTML = '';
+  mount(ClickOverlay, { target });
+  console.log('[click-overlay] Component mounted');
+} catch (e) {
+  console.error('[click-overlay] Mount error:', e);
+}
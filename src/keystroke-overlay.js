@@ -0,0 +1,12 @@
+import KeystrokeOverlay from './lib/KeystrokeOverlay.svelte';
+import { mount } from 'svelte';
+
+try {
+  const target = document.getElementById('app');
+  if (!target) throw new Error('#app element not found');
+  target.innerHTML = '';
+  mount(KeystrokeOverlay, { target });
+  console.log('[keystroke-overlay] Component mounted');
+} catch (e) {
+  console.error('[keystroke-overlay] Mount error:', e);
+}
@@ -28,6 +28,8 @@ export default defineConfig({
         'recording-overlay': resolve(__dirname, 'recording-overlay.html'),
         'camera-overlay': resolve(__dirname, 'camera-overlay.html'),
         countdown: resolve(__dirname, 'countdown.html'),
+        'keystroke-overlay': resolve(__dirname, 'keystroke-overlay.html'),
+        'click-overlay': resolve(__dirname, 'click-overlay.html'),
         'screenshot-preview': resolve(__dirname, 'screenshot-preview.html'),
         'pinned-screenshot': resolve(__dirname, 'pinned-screenshot.html'),
         'scroll-capture-bar': resolve(__dirname, 'scroll-capture-bar.html'),